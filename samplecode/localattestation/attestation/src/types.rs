@@ -55,7 +55,7 @@ pub struct Callback {
 pub enum DhSessionStatus {
     Closed,
     InProgress(SgxDhResponder),
-    Active(sgx_align_key_128bit_t),
+    Active(SgxKey128Secret),
 }
 
 impl Default for DhSessionStatus {
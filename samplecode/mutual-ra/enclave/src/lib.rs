@@ -29,17 +29,13 @@ extern crate sgx_tse;
 #[macro_use]
 extern crate sgx_tstd as std;
 extern crate sgx_rand;
+extern crate sgx_ra_tls;
 
 extern crate rustls;
 extern crate webpki;
 extern crate itertools;
 extern crate base64;
 extern crate httparse;
-extern crate yasna;
-extern crate bit_vec;
-extern crate num_bigint;
-extern crate serde_json;
-extern crate chrono;
 extern crate webpki_roots;
 
 use std::backtrace::{self, PrintFormat};
@@ -48,6 +44,7 @@ use sgx_tse::*;
 //use sgx_trts::trts::{rsgx_raw_is_outside_enclave, rsgx_lfence};
 use sgx_tcrypto::*;
 use sgx_rand::*;
+use sgx_ra_tls::{EnclavePolicy, Evidence, RaTlsCert, RaTlsVerifier};
 
 use std::prelude::v1::*;
 use std::sync::Arc;
@@ -61,13 +58,12 @@ use std::untrusted::fs;
 use std::vec::Vec;
 use itertools::Itertools;
 
-mod cert;
 mod hex;
 
 pub const DEV_HOSTNAME:&'static str = "api.trustedservices.intel.com";
 pub const SIGRL_SUFFIX:&'static str = "/sgx/dev/attestation/v3/sigrl/";
 pub const REPORT_SUFFIX:&'static str = "/sgx/dev/attestation/v3/report";
-pub const CERTEXPIRYDAYS: i64 = 90i64;
+pub const IAS_REPORT_CA: &[u8] = include_bytes!("../AttestationReportSigningCACert.pem");
 
 extern "C" {
     pub fn ocall_sgx_init_quote ( ret_val : *mut sgx_status_t,
@@ -134,7 +130,7 @@ fn parse_response_attn_report(resp : &[u8]) -> (String, String, String){
 
     // Remove %0A from cert, and only obtain the signing cert
     cert = cert.replace("%0A", "");
-    cert = cert::percent_decode(cert);
+    cert = percent_decode(cert);
     let v: Vec<&str> = cert.split("-----").collect();
     let sig_cert = v[2].to_string();
 
@@ -149,6 +145,19 @@ fn parse_response_attn_report(resp : &[u8]) -> (String, String, String){
     (attn_report, sig, sig_cert)
 }
 
+fn percent_decode(orig: String) -> String {
+    let v:Vec<&str> = orig.split("%").collect();
+    let mut ret = String::new();
+    ret.push_str(v[0]);
+    if v.len() > 1 {
+        for s in v[1..].iter() {
+            ret.push(u8::from_str_radix(&s[0..2], 16).unwrap() as char);
+            ret.push_str(&s[2..]);
+        }
+    }
+    ret
+}
+
 
 fn parse_response_sigrl(resp : &[u8]) -> Vec<u8> {
     println!("parse_response_sigrl");
@@ -341,14 +350,8 @@ pub fn create_attestation_report(pub_k: &sgx_ec256_public_t, sign_type: sgx_quot
     let sigrl_vec : Vec<u8> = get_sigrl_from_intel(ias_sock, eg_num);
 
     // (2) Generate the report
-    // Fill ecc256 public key into report_data
-    let mut report_data: sgx_report_data_t = sgx_report_data_t::default();
-    let mut pub_k_gx = pub_k.gx.clone();
-    pub_k_gx.reverse();
-    let mut pub_k_gy = pub_k.gy.clone();
-    pub_k_gy.reverse();
-    report_data.d[..32].clone_from_slice(&pub_k_gx);
-    report_data.d[32..].clone_from_slice(&pub_k_gy);
+    // Bind the ecc256 public key of the certificate into report_data
+    let report_data = sgx_ra_tls::report_data(pub_k)?;
 
     let rep = match rsgx_create_report(&ti, &report_data) {
         Ok(r) =>{
@@ -504,79 +507,31 @@ fn get_ias_api_key() -> String {
     key.trim_end().to_owned()
 }
 
-struct ClientAuth {
-    outdated_ok: bool,
-}
-
-impl ClientAuth {
-    fn new(outdated_ok: bool) -> ClientAuth {
-        ClientAuth{ outdated_ok : outdated_ok }
-    }
-}
-
-impl rustls::ClientCertVerifier for ClientAuth {
-    fn client_auth_root_subjects(&self, _sni: Option<&webpki::DNSName>) -> Option<rustls::DistinguishedNames> {
-        Some(rustls::DistinguishedNames::new())
-    }
+fn make_evidence(pub_k: &sgx_ec256_public_t, sign_type: sgx_quote_sign_type_t) -> Result<Evidence, sgx_status_t> {
+    let (attn_report, sig, cert) = create_attestation_report(pub_k, sign_type)?;
+    let signature = base64::decode(&sig).map_err(|_| sgx_status_t::SGX_ERROR_UNEXPECTED)?;
+    let signing_cert = base64::decode(&cert).map_err(|_| sgx_status_t::SGX_ERROR_UNEXPECTED)?;
 
-    fn verify_client_cert(&self, _certs: &[rustls::Certificate], _sni: Option<&webpki::DNSName>)
-    -> Result<rustls::ClientCertVerified, rustls::TLSError> {
-        println!("client cert: {:?}", _certs);
-            // This call will automatically verify cert is properly signed
-            match cert::verify_mra_cert(&_certs[0].0) {
-                Ok(()) => {
-                    return Ok(rustls::ClientCertVerified::assertion());
-                }
-                Err(sgx_status_t::SGX_ERROR_UPDATE_NEEDED) => {
-                    if self.outdated_ok {
-                        println!("outdated_ok is set, overriding outdated error");
-                        return Ok(rustls::ClientCertVerified::assertion());
-                    } else {
-                        return Err(rustls::TLSError::WebPKIError(webpki::Error::ExtensionValueInvalid));
-                    }
-                }
-                Err(_) => {
-                    return Err(rustls::TLSError::WebPKIError(webpki::Error::ExtensionValueInvalid));
-                }
-            }
-    }
+    Ok(Evidence::Epid {
+        report: attn_report.into_bytes(),
+        signature: signature,
+        signing_cert: signing_cert,
+    })
 }
 
-struct ServerAuth {
-    outdated_ok: bool
-}
-
-impl ServerAuth {
-    fn new(outdated_ok: bool) -> ServerAuth {
-        ServerAuth{ outdated_ok : outdated_ok }
-    }
-}
-
-impl rustls::ServerCertVerifier for ServerAuth {
-    fn verify_server_cert(&self,
-              _roots: &rustls::RootCertStore,
-              _certs: &[rustls::Certificate],
-              _hostname: webpki::DNSNameRef,
-              _ocsp: &[u8]) -> Result<rustls::ServerCertVerified, rustls::TLSError> {
-    println!("server cert: {:?}", _certs);
-        // This call will automatically verify cert is properly signed
-        match cert::verify_mra_cert(&_certs[0].0) {
-            Ok(()) => {
-                return Ok(rustls::ServerCertVerified::assertion());
-            }
-            Err(sgx_status_t::SGX_ERROR_UPDATE_NEEDED) => {
-                if self.outdated_ok {
-                    println!("outdated_ok is set, overriding outdated error");
-                    return Ok(rustls::ServerCertVerified::assertion());
-                } else {
-                    return Err(rustls::TLSError::WebPKIError(webpki::Error::ExtensionValueInvalid));
-                }
-            }
-            Err(_) => {
-                return Err(rustls::TLSError::WebPKIError(webpki::Error::ExtensionValueInvalid));
-            }
-        }
-    }
+// Both ends run this very enclave, so the peer must be signed by the same
+// key. Debug enclaves and outdated platforms are accepted to keep the
+// sample runnable on development machines; do not do this in production.
+fn make_verifier() -> RaTlsVerifier {
+    let policy = EnclavePolicy::new()
+        .mr_signer(rsgx_self_report().body.mr_signer)
+        .allow_debug(true)
+        .tcb_status(sgx_ql_qv_result_t::SGX_QL_QV_RESULT_OUT_OF_DATE)
+        .tcb_status(sgx_ql_qv_result_t::SGX_QL_QV_RESULT_CONFIG_NEEDED)
+        .tcb_status(sgx_ql_qv_result_t::SGX_QL_QV_RESULT_SW_HARDENING_NEEDED)
+        .tcb_status(sgx_ql_qv_result_t::SGX_QL_QV_RESULT_CONFIG_AND_SW_HARDENING_NEEDED);
+
+    RaTlsVerifier::new(policy).ias(IAS_REPORT_CA).expect("the IAS report CA certificate is malformed")
 }
 
 #[no_mangle]
@@ -588,7 +543,7 @@ pub extern "C" fn run_server(socket_fd : c_int, sign_type: sgx_quote_sign_type_t
     let _result = ecc_handle.open();
     let (prv_k, pub_k) = ecc_handle.create_key_pair().unwrap();
 
-    let (attn_report, sig, cert) = match create_attestation_report(&pub_k, sign_type) {
+    let evidence = match make_evidence(&pub_k, sign_type) {
         Ok(r) => r,
         Err(e) => {
             println!("Error in create_attestation_report: {:?}", e);
@@ -596,21 +551,20 @@ pub extern "C" fn run_server(socket_fd : c_int, sign_type: sgx_quote_sign_type_t
         }
     };
 
-    let payload = attn_report + "|" + &sig + "|" + &cert;
-    let (key_der, cert_der) = match cert::gen_ecc_cert(payload, &prv_k, &pub_k, &ecc_handle) {
+    let ra_cert = match RaTlsCert::new(&prv_k, &pub_k, &evidence) {
         Ok(r) => r,
         Err(e) => {
-            println!("Error in gen_ecc_cert: {:?}", e);
+            println!("Error in RaTlsCert::new: {:?}", e);
             return e;
         }
     };
     let _result = ecc_handle.close();
 
 
-    let mut cfg = rustls::ServerConfig::new(Arc::new(ClientAuth::new(true)));
+    let mut cfg = rustls::ServerConfig::new(Arc::new(make_verifier()));
     let mut certs = Vec::new();
-    certs.push(rustls::Certificate(cert_der));
-    let privkey = rustls::PrivateKey(key_der);
+    certs.push(ra_cert.certificate());
+    let privkey = ra_cert.private_key();
 
     cfg.set_single_cert_with_ocsp_and_sct(certs, privkey, vec![], vec![]).unwrap();
 
@@ -642,7 +596,7 @@ pub extern "C" fn run_client(socket_fd : c_int, sign_type: sgx_quote_sign_type_t
     ecc_handle.open().unwrap();
     let (prv_k, pub_k) = ecc_handle.create_key_pair().unwrap();
 
-    let (attn_report, sig, cert) = match create_attestation_report(&pub_k, sign_type) {
+    let evidence = match make_evidence(&pub_k, sign_type) {
         Ok(r) => r,
         Err(e) => {
             println!("Error in create_attestation_report: {:?}", e);
@@ -650,12 +604,10 @@ pub extern "C" fn run_client(socket_fd : c_int, sign_type: sgx_quote_sign_type_t
         }
     };
 
-    let payload = attn_report + "|" + &sig + "|" + &cert;
-
-    let (key_der, cert_der) = match cert::gen_ecc_cert(payload, &prv_k, &pub_k, &ecc_handle) {
+    let ra_cert = match RaTlsCert::new(&prv_k, &pub_k, &evidence) {
         Ok(r) => r,
         Err(e) => {
-            println!("Error in gen_ecc_cert: {:?}", e);
+            println!("Error in RaTlsCert::new: {:?}", e);
             return e;
         }
     };
@@ -664,11 +616,11 @@ pub extern "C" fn run_client(socket_fd : c_int, sign_type: sgx_quote_sign_type_t
 
     let mut cfg = rustls::ClientConfig::new();
     let mut certs = Vec::new();
-    certs.push(rustls::Certificate(cert_der));
-    let privkey = rustls::PrivateKey(key_der);
+    certs.push(ra_cert.certificate());
+    let privkey = ra_cert.private_key();
 
     cfg.set_single_client_cert(certs, privkey).unwrap();
-    cfg.dangerous().set_certificate_verifier(Arc::new(ServerAuth::new(true)));
+    cfg.dangerous().set_certificate_verifier(Arc::new(make_verifier()));
     cfg.versions.clear();
     cfg.versions.push(rustls::ProtocolVersion::TLSv1_2);
 
@@ -168,7 +168,7 @@ fn verify_att_result_mac(context: sgx_ra_context_t,
         return sgx_status_t::SGX_ERROR_INVALID_PARAMETER;
     }
 
-    let mk_key: sgx_ec_key_128bit_t = match rsgx_ra_get_keys(context, sgx_ra_key_type_t::SGX_RA_KEY_MK) {
+    let mk_key: SgxKey128Secret = match rsgx_ra_get_keys(context, sgx_ra_key_type_t::SGX_RA_KEY_MK) {
         Ok(k) => k,
         Err(x) => return x,
     };
@@ -203,7 +203,7 @@ fn verify_secret_data(context: sgx_ra_context_t,
         return sgx_status_t::SGX_ERROR_UNEXPECTED;
     }
 
-    let sk_key: sgx_ec_key_128bit_t = match rsgx_ra_get_keys(context, sgx_ra_key_type_t::SGX_RA_KEY_SK) {
+    let sk_key: SgxKey128Secret = match rsgx_ra_get_keys(context, sgx_ra_key_type_t::SGX_RA_KEY_SK) {
         Ok(key) => key,
         Err(x) => return x,
     };
@@ -267,7 +267,7 @@ fn add_hash_data(id: u32,
         return sgx_status_t::SGX_ERROR_INVALID_PARAMETER;
     }
 
-    let sk_key: sgx_ec_key_128bit_t = match rsgx_ra_get_keys(context, sgx_ra_key_type_t::SGX_RA_KEY_SK) {
+    let sk_key: SgxKey128Secret = match rsgx_ra_get_keys(context, sgx_ra_key_type_t::SGX_RA_KEY_SK) {
         Ok(key) => key,
         Err(x) => return x
     };
@@ -378,7 +378,7 @@ fn get_result(id: u32,
         0
     };
 
-    let sk_key: sgx_ec_key_128bit_t = match rsgx_ra_get_keys(context, sgx_ra_key_type_t::SGX_RA_KEY_SK) {
+    let sk_key: SgxKey128Secret = match rsgx_ra_get_keys(context, sgx_ra_key_type_t::SGX_RA_KEY_SK) {
         Ok(key) => key,
         Err(x) => return x,
     };
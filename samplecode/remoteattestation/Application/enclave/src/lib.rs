@@ -85,7 +85,7 @@ fn verify_att_result_mac(context : sgx_ra_context_t,
                          mac_size: size_t) -> sgx_status_t {
 
     let ret:sgx_status_t;
-    let mk_key:sgx_ec_key_128bit_t;
+    let mk_key: SgxKey128Secret;
     let mac_slice;
     let message_slice;
     let mac_result:sgx_cmac_128bit_tag_t;
@@ -133,7 +133,7 @@ fn verify_secret_data(context : sgx_ra_context_t,
                       p_ret   : & mut [u8;16]) -> sgx_status_t {
 
     let ret:sgx_status_t;
-    let sk_key:sgx_ec_key_128bit_t;
+    let sk_key: SgxKey128Secret;
 
     match rsgx_ra_get_keys(context, sgx_ra_key_type_t::SGX_RA_KEY_SK) {
         Ok(key) => sk_key = key,
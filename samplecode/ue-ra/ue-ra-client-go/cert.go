@@ -1,8 +1,9 @@
 package main
 
 import (
-	"bytes"
+	"crypto/sha256"
 	"crypto/x509"
+	"encoding/asn1"
 	"encoding/base64"
 	"encoding/hex"
 	"encoding/json"
@@ -12,21 +13,32 @@ import (
 	"time"
 )
 
+// OIDs of the RA-TLS certificate extensions carrying the EPID evidence
+var (
+	iasReportOid      = asn1.ObjectIdentifier{1, 2, 840, 113741, 1337, 2}
+	iasSigningCertOid = asn1.ObjectIdentifier{1, 2, 840, 113741, 1337, 4}
+	iasSignatureOid   = asn1.ObjectIdentifier{1, 2, 840, 113741, 1337, 5}
+)
+
 func verify_mra_cert(rawCerts [][]byte, verifiedChains [][]*x509.Certificate) error {
 	printCert(rawCerts[0])
 
-	// get the pubkey and payload from raw data
-	pub_k, payload := unmarshalCert(rawCerts[0])
+	// get the expected report data and the evidence from raw data
+	report_data, attn_report_raw, sig, sig_cert, err := unmarshalCert(rawCerts[0])
+	if err != nil {
+		log.Fatalln(err)
+		return err
+	}
 
 	// Load Intel CA, Verify Cert and Signature
-	attn_report_raw, err := verifyCert(payload)
+	err = verifyCert(attn_report_raw, sig, sig_cert)
 	if err != nil {
 		log.Fatalln(err)
 		return err
 	}
 
 	// Verify attestation report
-	err = verifyAttReport(attn_report_raw, pub_k)
+	err = verifyAttReport(attn_report_raw, report_data)
 	if err != nil {
 		log.Fatalln(err)
 		return err
@@ -35,72 +47,44 @@ func verify_mra_cert(rawCerts [][]byte, verifiedChains [][]*x509.Certificate) er
 	return nil
 }
 
-func unmarshalCert(rawbyte []byte) ([]byte, []byte) {
-	// Search for Public Key prime256v1 OID
-	prime256v1_oid := []byte{0x06, 0x08, 0x2A, 0x86, 0x48, 0xCE, 0x3D, 0x03, 0x01, 0x07}
-	offset := uint(bytes.Index(rawbyte, prime256v1_oid))
-	offset += 11 // 10 + TAG (0x03)
-
-	// Obtain Public Key length
-	length := uint(rawbyte[offset])
-	if length > 0x80 {
-		length = uint(rawbyte[offset+1])*uint(0x100) + uint(rawbyte[offset+2])
-		offset += 2
-	}
-
-	// Obtain Public Key
-	offset += 1
-	pub_k := rawbyte[offset+2 : offset+length] // skip "00 04"
-
-	// Search for Netscape Comment OID
-	ns_cmt_oid := []byte{0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x86, 0xF8, 0x42, 0x01, 0x0D}
-	offset = uint(bytes.Index(rawbyte, ns_cmt_oid))
-	offset += 12 // 11 + TAG (0x04)
-
-	// Obtain Netscape Comment length
-	length = uint(rawbyte[offset])
-	if length > 0x80 {
-		length = uint(rawbyte[offset+1])*uint(0x100) + uint(rawbyte[offset+2])
-		offset += 2
-	}
-
-	// Obtain Netscape Comment
-	offset += 1
-	payload := rawbyte[offset : offset+length]
-	return pub_k, payload
-}
-
-func verifyCert(payload []byte) ([]byte, error) {
-	// Extract each field
-	pl_split := bytes.Split(payload, []byte{0x7C})
-	attn_report_raw := pl_split[0]
-	sig_raw := pl_split[1]
-
-	var sig, sig_cert_dec []byte
-	sig, err := base64.StdEncoding.DecodeString(string(sig_raw))
+func unmarshalCert(rawbyte []byte) ([]byte, []byte, []byte, []byte, error) {
+	cert, err := x509.ParseCertificate(rawbyte)
 	if err != nil {
-		log.Fatalln(err)
-		return nil, err
+		return nil, nil, nil, nil, err
+	}
+
+	// The enclave binds the SHA-256 hash of the SubjectPublicKeyInfo into
+	// the first half of its report data
+	hash := sha256.Sum256(cert.RawSubjectPublicKeyInfo)
+	report_data := append(hash[:], make([]byte, 32)...)
+
+	var attn_report_raw, sig, sig_cert []byte
+	for _, ext := range cert.Extensions {
+		switch {
+		case ext.Id.Equal(iasReportOid):
+			attn_report_raw = ext.Value
+		case ext.Id.Equal(iasSignatureOid):
+			sig = ext.Value
+		case ext.Id.Equal(iasSigningCertOid):
+			sig_cert = ext.Value
+		}
 	}
-
-	sig_cert_raw := pl_split[2]
-	sig_cert_dec, err = base64.StdEncoding.DecodeString(string(sig_cert_raw))
-	if err != nil {
-		log.Fatalln(err)
-		return nil, err
+	if attn_report_raw == nil || sig == nil || sig_cert == nil {
+		return nil, nil, nil, nil, errors.New("Failed to find the EPID evidence in the certificate")
 	}
+	return report_data, attn_report_raw, sig, sig_cert, nil
+}
 
-	certServer, err := x509.ParseCertificate(sig_cert_dec)
+func verifyCert(attn_report_raw []byte, sig []byte, sig_cert []byte) error {
+	certServer, err := x509.ParseCertificate(sig_cert)
 	if err != nil {
-		log.Fatalln(err)
-		return nil, err
+		return err
 	}
 
 	roots := x509.NewCertPool()
 	cacert, err := readFile("./../../cert/AttestationReportSigningCACert.pem")
 	if err != nil {
-		log.Fatalln(err)
-		return nil, err
+		return err
 	}
 	ok := roots.AppendCertsFromPEM([]byte(cacert))
 	if !ok {
@@ -112,8 +96,7 @@ func verifyCert(payload []byte) ([]byte, error) {
 	}
 
 	if _, err := certServer.Verify(opts); err != nil {
-		log.Fatalln(err)
-		return nil, err
+		return err
 	} else {
 		fmt.Println("Cert is good")
 	}
@@ -121,15 +104,14 @@ func verifyCert(payload []byte) ([]byte, error) {
 	// Verify the signature against the signing cert
 	err = certServer.CheckSignature(certServer.SignatureAlgorithm, attn_report_raw, sig)
 	if err != nil {
-		log.Fatalln(err)
-		return nil, err
+		return err
 	} else {
 		fmt.Println("Signature good")
 	}
-	return attn_report_raw, nil
+	return nil
 }
 
-func verifyAttReport(attn_report_raw []byte, pub_k []byte) error {
+func verifyAttReport(attn_report_raw []byte, report_data []byte) error {
 	var qr QuoteReport
 	err := json.Unmarshal(attn_report_raw, &qr)
 	if err != nil {
@@ -186,14 +168,14 @@ func verifyAttReport(attn_report_raw []byte, pub_k []byte) error {
 			return err
 		}
 
-		var quoteBytes, quoteHex, pubHex string
+		var quoteBytes, quoteHex, reportDataHex string
 		for _, b := range qb {
 			quoteBytes += fmt.Sprint(int(b), ", ")
 			quoteHex += fmt.Sprintf("%02x", int(b))
 		}
 
-		for _, b := range pub_k {
-			pubHex += fmt.Sprintf("%02x", int(b))
+		for _, b := range report_data {
+			reportDataHex += fmt.Sprintf("%02x", int(b))
 		}
 
 		qrData := parseReport(qb, quoteHex)
@@ -204,11 +186,12 @@ func verifyAttReport(attn_report_raw []byte, pub_k []byte) error {
 		fmt.Println("sgx quote report_data = ", qrData.reportBody.reportData)
 		fmt.Println("sgx quote mr_enclave = ", qrData.reportBody.mrEnclave)
 		fmt.Println("sgx quote mr_signer = ", qrData.reportBody.mrSigner)
-		fmt.Println("Anticipated public key = ", pubHex)
+		fmt.Println("Anticipated report_data = ", reportDataHex)
 
-		if qrData.reportBody.reportData == pubHex {
-			fmt.Println("ue RA done!")
+		if qrData.reportBody.reportData != reportDataHex {
+			return errors.New("Quote report_data does not match the certificate public key")
 		}
+		fmt.Println("ue RA done!")
 	} else {
 		err := errors.New("Failed to fetch isvEnclaveQuoteBody from attestation report")
 		return err
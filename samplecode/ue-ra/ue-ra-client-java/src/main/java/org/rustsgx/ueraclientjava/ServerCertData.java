@@ -1,13 +1,15 @@
 package org.rustsgx.ueraclientjava;
 
-import java.util.List;
-
 public class ServerCertData {
-    public List<Byte> payload;
-    public byte[] pub_k;
+    public byte[] attnReport;
+    public byte[] sig;
+    public byte[] sigCert;
+    public byte[] reportData;
 
-    public ServerCertData(List<Byte> payload, byte[] pub_k) {
-        this.payload = payload;
-        this.pub_k = pub_k;
+    public ServerCertData(byte[] attnReport, byte[] sig, byte[] sigCert, byte[] reportData) {
+        this.attnReport = attnReport;
+        this.sig = sig;
+        this.sigCert = sigCert;
+        this.reportData = reportData;
     }
-}
\ No newline at end of file
+}
@@ -4,7 +4,6 @@ import java.io.File;
 import java.security.KeyStore;
 import java.security.PrivateKey;
 import java.security.cert.Certificate;
-import java.util.ArrayList;
 import java.util.List;
 import javax.net.ssl.KeyManagerFactory;
 import javax.net.ssl.TrustManager;
@@ -46,22 +45,17 @@ public class SgxCertVerifier {
 
                     public void checkServerTrusted(X509Certificate[] certs, String authType) throws CertificateException {
                         CommonUtils.printCert(certs[0].getEncoded());
-                        List<Byte> byteArray = new ArrayList<Byte>();
-                        for (int i = 0; i < certs[0].getEncoded().length; i++) {
-                            byteArray.add(certs[0].getEncoded()[i]);
-                        }
-                        // get the pubkey and payload from raw data
-                        ServerCertData certData = VerifyMraCert.unmarshalByte(byteArray);
-
                         try {
+                            // get the expected report data and the evidence from the cert
+                            ServerCertData certData = VerifyMraCert.unmarshalCert(certs[0]);
+
                             // Load Intel CA, Verify Cert and Signature
-                            byte[] attnReportRaw = VerifyMraCert.verifyCert(certData.payload);
+                            byte[] attnReportRaw = VerifyMraCert.verifyCert(certData);
 
                             // Verify attestation report
-                            VerifyMraCert.verifyAtteReport(attnReportRaw, certData.pub_k);
+                            VerifyMraCert.verifyAtteReport(attnReportRaw, certData.reportData);
                         } catch (Exception e) {
-                            System.out.println(e.toString());
-                            System.exit(0);
+                            throw new CertificateException(e);
                         }
                     }
                 }
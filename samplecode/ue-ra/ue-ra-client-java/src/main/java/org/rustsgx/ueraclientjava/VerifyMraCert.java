@@ -2,6 +2,7 @@ package org.rustsgx.ueraclientjava;
 
 import com.google.gson.Gson;
 import com.sun.org.apache.xerces.internal.impl.dv.util.HexBin;
+import org.bouncycastle.asn1.ASN1OctetString;
 import org.bouncycastle.cert.X509CertificateHolder;
 import org.bouncycastle.cert.jcajce.JcaX509CertificateConverter;
 import org.bouncycastle.openssl.PEMParser;
@@ -11,66 +12,43 @@ import org.joda.time.Seconds;
 
 import java.io.ByteArrayInputStream;
 import java.io.FileReader;
+import java.security.MessageDigest;
 import java.security.Signature;
 import java.security.cert.CertificateFactory;
 import java.security.cert.X509Certificate;
 import java.util.Arrays;
 import java.util.Base64;
-import java.util.List;
 
 public class VerifyMraCert {
-    public static ServerCertData unmarshalByte(List<Byte> certList) {
-        // Search for Public Key prime256v1 OID
-        String[] prime256v1_oid_string = new String[]{"0x06",
-                "0x08", "0x2a", "0x86", "0x48", "0xce", "0x3d", "0x03", "0x01", "0x07"};
-
-        List<Byte> prime256v1_oid = CommonUtils.string2BytesList(prime256v1_oid_string);
-        int offset = CommonUtils.getIndexOf(certList, prime256v1_oid);
-        offset += 11; // 10 + TAG (0x03)
-
-        // Obtain Public Key length
-        int length = Byte.toUnsignedInt(certList.get(offset));
-        if (length > Byte.toUnsignedInt(CommonUtils.hexToByte("80"))) {
-            length = Byte.toUnsignedInt(certList.get(offset + 1)) * 256 +
-                    Byte.toUnsignedInt(certList.get(offset + 2));
-            offset += 2;
-        }
-
-        // Obtain Public Key
-        offset += 1;
-        byte[] pub_k = CommonUtils.list2array(certList.subList(offset + 2, offset + length)); // skip "00 04"
-
-        String[] ns_cmt_oid_string = new String[]{"0x06",
-                "0x09", "0x60", "0x86", "0x48", "0x01", "0x86", "0xf8", "0x42", "0x01", "0x0d"};
-        List<Byte> ns_cmt_oid = CommonUtils.string2BytesList(ns_cmt_oid_string);
-        offset = CommonUtils.getIndexOf(certList, ns_cmt_oid);
-        offset += 12; // 10 + TAG (0x03)
-
-
-        // Obtain Netscape Comment length
-        length = Byte.toUnsignedInt(certList.get(offset));
-        if (length > Byte.toUnsignedInt(CommonUtils.hexToByte("80"))) {
-            length = Byte.toUnsignedInt(certList.get(offset + 1)) * 256 +
-                    Byte.toUnsignedInt(certList.get(offset + 2));
-            offset += 2;
+    // OIDs of the RA-TLS certificate extensions carrying the EPID evidence
+    private static final String IAS_REPORT_OID = "1.2.840.113741.1337.2";
+    private static final String IAS_SIGNING_CERT_OID = "1.2.840.113741.1337.4";
+    private static final String IAS_SIGNATURE_OID = "1.2.840.113741.1337.5";
+
+    private static byte[] extensionValue(X509Certificate cert, String oid) throws Exception {
+        byte[] value = cert.getExtensionValue(oid);
+        if (value == null) {
+            throw new Exception("Failed to find the EPID evidence in the certificate");
         }
+        return ASN1OctetString.getInstance(value).getOctets();
+    }
 
-        offset += 1;
-        List<Byte> payload = certList.subList(offset, offset + length);
+    public static ServerCertData unmarshalCert(X509Certificate cert) throws Exception {
+        // The enclave binds the SHA-256 hash of the SubjectPublicKeyInfo into
+        // the first half of its report data
+        byte[] spki = new X509CertificateHolder(cert.getEncoded()).getSubjectPublicKeyInfo().getEncoded();
+        byte[] reportData = Arrays.copyOf(MessageDigest.getInstance("SHA-256").digest(spki), 64);
 
-        return new ServerCertData(payload, pub_k);
+        return new ServerCertData(extensionValue(cert, IAS_REPORT_OID),
+                extensionValue(cert, IAS_SIGNATURE_OID),
+                extensionValue(cert, IAS_SIGNING_CERT_OID),
+                reportData);
     }
 
-    public static byte[] verifyCert(List<Byte> payload) throws Exception {
-        Base64.Decoder decoder = Base64.getDecoder();
-
-        int startIndex = payload.indexOf(CommonUtils.hexToByte("7c"));
-        int endIndex = payload.lastIndexOf(CommonUtils.hexToByte("7c"));
-        byte[] attnReportRaw = CommonUtils.list2array(payload.subList(0, startIndex));
-        byte[] sigRaw = CommonUtils.list2array(payload.subList(startIndex + 1, endIndex));
-        byte[] sig = decoder.decode(sigRaw);
-        byte[] sigCertRaw = CommonUtils.list2array(payload.subList(endIndex + 1, payload.size()));
-        byte[] sigCert = decoder.decode(sigCertRaw);
+    public static byte[] verifyCert(ServerCertData certData) throws Exception {
+        byte[] attnReportRaw = certData.attnReport;
+        byte[] sig = certData.sig;
+        byte[] sigCert = certData.sigCert;
         X509Certificate server, provider;
 
         try {
@@ -102,7 +80,7 @@ public class VerifyMraCert {
         return attnReportRaw;
     }
 
-    public static void verifyAtteReport(byte[] attnReportRaw, byte[] pubK) throws Exception {
+    public static void verifyAtteReport(byte[] attnReportRaw, byte[] reportData) throws Exception {
         //extract data from attReportJson
         Gson gson = new Gson();
         String attReportJson = new String();
@@ -160,13 +138,13 @@ public class VerifyMraCert {
             byte[] qb = decoder.decode(sgxQr.getIsvEnclaveQuoteBody());
             String qbString = new String();
             String qbBytes = new String();
-            String pubKeyString = new String();
+            String reportDataString = new String();
             for (int i = 0; i < qb.length; i++) {
                 qbBytes += String.format("%d, ", Byte.toUnsignedInt(qb[i]));
                 qbString += String.format("%02x", qb[i]);
             }
-            for (int i = 0; i < pubK.length; i++) {
-                pubKeyString += String.format("%02x", pubK[i]);
+            for (int i = 0; i < reportData.length; i++) {
+                reportDataString += String.format("%02x", reportData[i]);
             }
 
             QuoteReportData quoteReportData = new QuoteReportData();
@@ -177,11 +155,12 @@ public class VerifyMraCert {
             System.out.printf("sgx quote report_data = %s\n", quoteReportData.getQuoteReportBody().getReportData());
             System.out.printf("sgx quote mr_enclave = %s\n", quoteReportData.getQuoteReportBody().getMrEnclave());
             System.out.printf("sgx quote mr_signer = %s\n", quoteReportData.getQuoteReportBody().getMrSigner());
-            System.out.printf("Anticipated public key = %s\n", pubKeyString);
+            System.out.printf("Anticipated report_data = %s\n", reportDataString);
 
-            if (pubKeyString.equals(quoteReportData.getQuoteReportBody().getReportData())) {
-                System.out.println("ue RA done!");
+            if (!reportDataString.equals(quoteReportData.getQuoteReportBody().getReportData())) {
+                throw new Exception("Quote report_data does not match the certificate public key");
             }
+            System.out.println("ue RA done!");
         } else {
             throw new Exception("Failed to fetch isvEnclaveQuoteBody from attestation report");
         }
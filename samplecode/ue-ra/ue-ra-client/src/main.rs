@@ -1,64 +1,47 @@
-extern crate chrono;
 extern crate webpki;
 extern crate rustls;
-extern crate base64;
-extern crate itertools;
-extern crate serde_json;
-extern crate num_bigint;
-extern crate bit_vec;
 extern crate hex;
 extern crate sgx_types;
+extern crate sgx_ra_tls;
 
 use sgx_types::*;
+use sgx_ra_tls::{EnclavePolicy, RaTlsVerifier};
 
+use std::env;
 use std::io::{self, Write, Read, BufReader};
 use std::sync::Arc;
 use std::str;
 use std::net::TcpStream;
 
-mod cert;
-mod pib;
-
 const SERVERADDR: &str = "localhost:3443";
-
-struct ServerAuth {
-    outdated_ok: bool
-}
-
-impl ServerAuth {
-    fn new(outdated_ok: bool) -> ServerAuth {
-        ServerAuth{ outdated_ok }
-    }
+const IAS_REPORT_CA: &[u8] = include_bytes!("../../cert/AttestationReportSigningCACert.pem");
+
+// The server enclave is trusted if it is signed by the key whose hash is
+// `mr_signer`. Debug enclaves and outdated platforms are accepted to keep the
+// sample runnable on development machines; do not do this in production.
+fn make_verifier(mr_signer: sgx_measurement_t) -> RaTlsVerifier {
+    let policy = EnclavePolicy::new()
+        .mr_signer(mr_signer)
+        .allow_debug(true)
+        .tcb_status(sgx_ql_qv_result_t::SGX_QL_QV_RESULT_OUT_OF_DATE)
+        .tcb_status(sgx_ql_qv_result_t::SGX_QL_QV_RESULT_CONFIG_NEEDED)
+        .tcb_status(sgx_ql_qv_result_t::SGX_QL_QV_RESULT_SW_HARDENING_NEEDED)
+        .tcb_status(sgx_ql_qv_result_t::SGX_QL_QV_RESULT_CONFIG_AND_SW_HARDENING_NEEDED);
+
+    RaTlsVerifier::new(policy).ias(IAS_REPORT_CA).expect("the IAS report CA certificate is malformed")
 }
 
-impl rustls::ServerCertVerifier for ServerAuth {
-    fn verify_server_cert(&self,
-              _roots: &rustls::RootCertStore,
-              _certs: &[rustls::Certificate],
-              _hostname: webpki::DNSNameRef,
-              _ocsp: &[u8]) -> Result<rustls::ServerCertVerified, rustls::TLSError> {
-        println!("--received-server cert: {:?}", _certs);
-        // This call will automatically verify cert is properly signed
-        match cert::verify_mra_cert(&_certs[0].0) {
-            Ok(()) => {
-                Ok(rustls::ServerCertVerified::assertion())
-            }
-            Err(sgx_status_t::SGX_ERROR_UPDATE_NEEDED) => {
-                if self.outdated_ok {
-                    println!("outdated_ok is set, overriding outdated error");
-                    Ok(rustls::ServerCertVerified::assertion())
-                } else {
-                    Err(rustls::TLSError::WebPKIError(webpki::Error::ExtensionValueInvalid))
-                }
-            }
-            Err(_) => {
-                Err(rustls::TLSError::WebPKIError(webpki::Error::ExtensionValueInvalid))
-            }
-        }
+fn parse_mr_signer(arg: &str) -> Option<sgx_measurement_t> {
+    let bytes = hex::decode(arg.trim()).ok()?;
+    if bytes.len() != SGX_HASH_SIZE {
+        return None;
     }
+    let mut mr_signer = sgx_measurement_t::default();
+    mr_signer.m.copy_from_slice(&bytes);
+    Some(mr_signer)
 }
 
-fn make_config() -> rustls::ClientConfig {
+fn make_config(mr_signer: sgx_measurement_t) -> rustls::ClientConfig {
     let mut config = rustls::ClientConfig::new();
 
     let client_cert = include_bytes!("../../cert/client.crt");
@@ -72,7 +55,7 @@ fn make_config() -> rustls::ClientConfig {
 
     config.set_single_client_cert(certs, privk.unwrap()[0].clone()).unwrap();
 
-    config.dangerous().set_certificate_verifier(Arc::new(ServerAuth::new(true)));
+    config.dangerous().set_certificate_verifier(Arc::new(make_verifier(mr_signer)));
     config.versions.clear();
     config.versions.push(rustls::ProtocolVersion::TLSv1_2);
 
@@ -82,9 +65,17 @@ fn make_config() -> rustls::ClientConfig {
 fn main() {
     println!("Starting ue-ra-client");
 
+    let mr_signer = match env::args().nth(1).as_ref().and_then(|arg| parse_mr_signer(arg)) {
+        Some(m) => m,
+        None => {
+            println!("Usage: ue-ra-client <MRSIGNER of the server enclave in hex>");
+            return;
+        }
+    };
+
     println!("Connecting to {}", SERVERADDR);
 
-    let client_config = make_config();
+    let client_config = make_config(mr_signer);
     let dns_name = webpki::DNSNameRef::try_from_ascii_str("localhost").unwrap();
     let mut sess = rustls::ClientSession::new(&Arc::new(client_config), dns_name);
 
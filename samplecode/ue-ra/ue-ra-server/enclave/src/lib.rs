@@ -30,6 +30,7 @@ extern crate sgx_tse;
 #[macro_use]
 extern crate sgx_tstd as std;
 extern crate sgx_rand;
+extern crate sgx_ra_tls;
 
 extern crate rustls;
 extern crate webpki;
@@ -37,15 +38,12 @@ extern crate webpki_roots;
 extern crate itertools;
 extern crate base64;
 extern crate httparse;
-extern crate yasna;
-extern crate bit_vec;
-extern crate num_bigint;
-extern crate chrono;
 
 use sgx_types::*;
 use sgx_tse::*;
 use sgx_tcrypto::*;
 use sgx_rand::*;
+use sgx_ra_tls::{Evidence, RaTlsCert};
 
 use std::prelude::v1::*;
 use std::sync::Arc;
@@ -58,13 +56,11 @@ use std::untrusted::fs;
 use std::vec::Vec;
 use itertools::Itertools;
 
-mod cert;
 mod hex;
 
 pub const DEV_HOSTNAME:&'static str = "api.trustedservices.intel.com";
 pub const SIGRL_SUFFIX:&'static str = "/sgx/dev/attestation/v3/sigrl/";
 pub const REPORT_SUFFIX:&'static str = "/sgx/dev/attestation/v3/report";
-pub const CERTEXPIRYDAYS: i64 = 90i64;
 
 extern "C" {
     pub fn ocall_sgx_init_quote ( ret_val : *mut sgx_status_t,
@@ -131,7 +127,7 @@ fn parse_response_attn_report(resp : &[u8]) -> (String, String, String){
 
     // Remove %0A from cert, and only obtain the signing cert
     cert = cert.replace("%0A", "");
-    cert = cert::percent_decode(cert);
+    cert = percent_decode(cert);
     let v: Vec<&str> = cert.split("-----").collect();
     let sig_cert = v[2].to_string();
 
@@ -146,6 +142,19 @@ fn parse_response_attn_report(resp : &[u8]) -> (String, String, String){
     (attn_report, sig, sig_cert)
 }
 
+fn percent_decode(orig: String) -> String {
+    let v:Vec<&str> = orig.split("%").collect();
+    let mut ret = String::new();
+    ret.push_str(v[0]);
+    if v.len() > 1 {
+        for s in v[1..].iter() {
+            ret.push(u8::from_str_radix(&s[0..2], 16).unwrap() as char);
+            ret.push_str(&s[2..]);
+        }
+    }
+    ret
+}
+
 
 fn parse_response_sigrl(resp : &[u8]) -> Vec<u8> {
     println!("parse_response_sigrl");
@@ -337,14 +346,8 @@ pub fn create_attestation_report(pub_k: &sgx_ec256_public_t, sign_type: sgx_quot
     let sigrl_vec : Vec<u8> = get_sigrl_from_intel(ias_sock, eg_num);
 
     // (2) Generate the report
-    // Fill ecc256 public key into report_data
-    let mut report_data: sgx_report_data_t = sgx_report_data_t::default();
-    let mut pub_k_gx = pub_k.gx.clone();
-    pub_k_gx.reverse();
-    let mut pub_k_gy = pub_k.gy.clone();
-    pub_k_gy.reverse();
-    report_data.d[..32].clone_from_slice(&pub_k_gx);
-    report_data.d[32..].clone_from_slice(&pub_k_gy);
+    // Bind the ecc256 public key of the certificate into report_data
+    let report_data = sgx_ra_tls::report_data(pub_k)?;
 
     let rep = match rsgx_create_report(&ti, &report_data) {
         Ok(r) =>{
@@ -498,6 +501,18 @@ fn get_ias_api_key() -> String {
     key.trim_end().to_owned()
 }
 
+fn make_evidence(pub_k: &sgx_ec256_public_t, sign_type: sgx_quote_sign_type_t) -> Result<Evidence, sgx_status_t> {
+    let (attn_report, sig, cert) = create_attestation_report(pub_k, sign_type)?;
+    let signature = base64::decode(&sig).map_err(|_| sgx_status_t::SGX_ERROR_UNEXPECTED)?;
+    let signing_cert = base64::decode(&cert).map_err(|_| sgx_status_t::SGX_ERROR_UNEXPECTED)?;
+
+    Ok(Evidence::Epid {
+        report: attn_report.into_bytes(),
+        signature: signature,
+        signing_cert: signing_cert,
+    })
+}
+
 #[no_mangle]
 pub extern "C" fn run_server(socket_fd : c_int, sign_type: sgx_quote_sign_type_t) -> sgx_status_t {
     // Generate Keypair
@@ -505,7 +520,7 @@ pub extern "C" fn run_server(socket_fd : c_int, sign_type: sgx_quote_sign_type_t
     let _result = ecc_handle.open();
     let (prv_k, pub_k) = ecc_handle.create_key_pair().unwrap();
 
-    let (attn_report, sig, cert) = match create_attestation_report(&pub_k, sign_type) {
+    let evidence = match make_evidence(&pub_k, sign_type) {
         Ok(r) => r,
         Err(e) => {
             println!("Error in create_attestation_report: {:?}", e);
@@ -513,11 +528,10 @@ pub extern "C" fn run_server(socket_fd : c_int, sign_type: sgx_quote_sign_type_t
         }
     };
 
-    let payload = attn_report + "|" + &sig + "|" + &cert;
-    let (key_der, cert_der) = match cert::gen_ecc_cert(payload, &prv_k, &pub_k, &ecc_handle) {
+    let ra_cert = match RaTlsCert::new(&prv_k, &pub_k, &evidence) {
         Ok(r) => r,
         Err(e) => {
-            println!("Error in gen_ecc_cert: {:?}", e);
+            println!("Error in RaTlsCert::new: {:?}", e);
             return e;
         }
     };
@@ -533,8 +547,8 @@ pub extern "C" fn run_server(socket_fd : c_int, sign_type: sgx_quote_sign_type_t
     let authenticator = rustls::AllowAnyAuthenticatedClient::new(rc_store);
     let mut cfg = rustls::ServerConfig::new(authenticator);
     let mut certs = Vec::new();
-    certs.push(rustls::Certificate(cert_der));
-    let privkey = rustls::PrivateKey(key_der);
+    certs.push(ra_cert.certificate());
+    let privkey = ra_cert.private_key();
 
     cfg.set_single_cert_with_ocsp_and_sct(certs, privkey, vec![], vec![]).unwrap();
 
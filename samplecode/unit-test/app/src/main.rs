@@ -16,10 +16,13 @@
 // under the License..
 
 extern crate sgx_types;
+extern crate sgx_ucrypto;
 extern crate sgx_urts;
 use sgx_types::*;
 use sgx_urts::SgxEnclave;
 
+mod ra;
+
 static ENCLAVE_FILE: &'static str = "enclave.signed.so";
 
 extern {
@@ -69,6 +72,8 @@ fn main() {
     }
     assert_eq!(retval, 0);
 
+    ra::test_ra_session(enclave.geteid());
+
     println!("[+] unit_test ended!");
 
     enclave.destroy();
@@ -0,0 +1,129 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+// The app plays the service provider of a remote attestation with the
+// enclave: it answers msg1 with a msg2 signed by its own key, checks the msg3
+// produced by sgx_urts::ra, and asks the enclave to compare the keys of its
+// RaSession with the ones derived here.
+
+use sgx_types::*;
+use sgx_ucrypto::*;
+use sgx_urts::ra::*;
+use std::mem;
+use std::slice;
+
+// The fixed part of msg2 covered by its MAC: g_b, spid, quote_type, kdf_id
+// and sign_gb_ga.
+const MSG2_MAC_SIZE: usize = 148;
+// The report data in sgx_quote_t.
+const QUOTE_REPORT_DATA_OFFSET: usize = 368;
+
+extern {
+    fn test_ra_session_open(eid: sgx_enclave_id_t, retval: *mut sgx_status_t,
+                            sp_pub_key: *const sgx_ec256_public_t,
+                            context: *mut sgx_ra_context_t) -> sgx_status_t;
+    fn test_ra_session_keys(eid: sgx_enclave_id_t, retval: *mut sgx_status_t,
+                            sk: *const uint8_t, mk: *const uint8_t) -> sgx_status_t;
+    fn test_ra_session_close(eid: sgx_enclave_id_t, retval: *mut sgx_status_t) -> sgx_status_t;
+
+    fn sgx_ra_proc_msg2_trusted(eid: sgx_enclave_id_t, retval: *mut sgx_status_t,
+                                context: sgx_ra_context_t,
+                                p_msg2: *const sgx_ra_msg2_t,
+                                p_qe_target: *const sgx_target_info_t,
+                                p_report: *mut sgx_report_t,
+                                nonce: *mut sgx_quote_nonce_t) -> sgx_status_t;
+    fn sgx_ra_get_msg3_trusted(eid: sgx_enclave_id_t, retval: *mut sgx_status_t,
+                               context: sgx_ra_context_t,
+                               quote_size: uint32_t,
+                               qe_report: *mut sgx_report_t,
+                               p_msg3: *mut sgx_ra_msg3_t,
+                               msg3_size: uint32_t) -> sgx_status_t;
+}
+
+fn ecall(f: impl FnOnce(*mut sgx_status_t) -> sgx_status_t) -> SgxError {
+    let mut retval = sgx_status_t::SGX_SUCCESS;
+    match f(&mut retval) {
+        sgx_status_t::SGX_SUCCESS => (),
+        ret => return Err(ret),
+    }
+    match retval {
+        sgx_status_t::SGX_SUCCESS => Ok(()),
+        _ => Err(retval),
+    }
+}
+
+// The default key derivation of the key exchange (kdf_id 1).
+fn derive_key(shared: &sgx_ec256_dh_shared_t, label: &[u8]) -> sgx_key_128bit_t {
+    let kdk = rsgx_rijndael128_cmac_slice(&[0; 16], &shared.s).unwrap();
+    let mut derivation = vec![0x01];
+    derivation.extend_from_slice(label);
+    derivation.extend_from_slice(&[0x00, 0x80, 0x00]);
+    rsgx_rijndael128_cmac_slice(&kdk, &derivation).unwrap()
+}
+
+fn as_bytes<T>(value: &T) -> &[u8] {
+    unsafe { slice::from_raw_parts(value as *const T as *const u8, mem::size_of::<T>()) }
+}
+
+pub fn test_ra_session(eid: sgx_enclave_id_t) {
+    let ecc = SgxEccHandle::new();
+    ecc.open().unwrap();
+    let (sp_priv, sp_pub) = ecc.create_key_pair().unwrap();
+
+    let mut context: sgx_ra_context_t = 0;
+    ecall(|retval| unsafe { test_ra_session_open(eid, retval, &sp_pub, &mut context) }).unwrap();
+
+    let msg1 = rsgx_ra_get_msg1(context, eid, sgx_ra_get_ga).unwrap();
+
+    let (b_priv, g_b) = ecc.create_key_pair().unwrap();
+    let shared = ecc.compute_shared_dhkey(&b_priv, &msg1.g_a).unwrap();
+    let smk = derive_key(&shared, b"SMK");
+    let sk = derive_key(&shared, b"SK");
+    let mk = derive_key(&shared, b"MK");
+    let vk = derive_key(&shared, b"VK");
+
+    let mut header = sgx_ra_msg2_t::default();
+    header.g_b = g_b;
+    header.quote_type = 0; // unlinkable
+    header.kdf_id = 1;
+    header.sign_gb_ga = ecc.ecdsa_sign_slice(&[g_b, msg1.g_a], &sp_priv).unwrap();
+    header.mac = rsgx_rijndael128_cmac_slice(&smk, &as_bytes(&header)[..MSG2_MAC_SIZE]).unwrap();
+    let msg2 = RaMsg2::parse(as_bytes(&header)).unwrap();
+
+    let msg3 = rsgx_ra_proc_msg2(context, eid, sgx_ra_proc_msg2_trusted, sgx_ra_get_msg3_trusted, &msg2).unwrap();
+    assert_eq!(msg3.g_a().gx, msg1.g_a.gx);
+    assert_eq!(msg3.g_a().gy, msg1.g_a.gy);
+    let mac_size = mem::size_of::<sgx_mac_t>();
+    assert_eq!(msg3.mac(), rsgx_rijndael128_cmac_slice(&smk, &msg3.as_bytes()[mac_size..]).unwrap());
+
+    // The quote binds g_a, g_b and VK.
+    let mut binding = Vec::new();
+    binding.extend_from_slice(as_bytes(&msg1.g_a));
+    binding.extend_from_slice(as_bytes(&g_b));
+    binding.extend_from_slice(&vk);
+    let report_data = &msg3.quote()[QUOTE_REPORT_DATA_OFFSET..QUOTE_REPORT_DATA_OFFSET + 32];
+    assert_eq!(report_data, &rsgx_sha256_slice(&binding).unwrap()[..]);
+
+    // The enclave holds the keys derived here, and no other.
+    ecall(|retval| unsafe { test_ra_session_keys(eid, retval, sk.as_ptr(), mk.as_ptr()) }).unwrap();
+    assert_eq!(ecall(|retval| unsafe { test_ra_session_keys(eid, retval, mk.as_ptr(), sk.as_ptr()) }),
+               Err(sgx_status_t::SGX_ERROR_MAC_MISMATCH));
+
+    ecall(|retval| unsafe { test_ra_session_close(eid, retval) }).unwrap();
+    ecc.close().unwrap();
+    println!("[+] test_ra_session passed!");
+}
@@ -31,6 +31,7 @@ extern crate sgx_types;
 #[macro_use]
 extern crate sgx_tstd as std;
 extern crate sgx_tcrypto;
+extern crate sgx_tkey_exchange;
 #[macro_use]
 extern crate sgx_tunittest;
 extern crate sgx_trts;
@@ -47,6 +48,7 @@ pub use sgx_serialize::*;
 extern crate sgx_serialize_derive;
 extern crate sgx_signal;
 extern crate sgx_libc;
+extern crate sgx_enclave_policy;
 
 pub use sgx_serialize::*;
 use sgx_types::*;
@@ -110,6 +112,13 @@ use test_signal::*;
 
 mod test_exception;
 use test_exception::*;
+
+mod test_policy;
+use test_policy::*;
+
+// ECALLs of the remote attestation test, driven by the app.
+mod test_ra;
+
 #[no_mangle]
 pub extern "C"
 fn test_main_entrance() -> size_t {
@@ -117,6 +126,18 @@ fn test_main_entrance() -> size_t {
                     // tcrypto
                     test_rsgx_sha256_slice,
                     test_rsgx_sha256_handle,
+                    test_rsgx_sha384_sha512,
+                    test_rsgx_hmac_sha384_sha512,
+                    test_rsgx_hkdf,
+                    test_rsgx_kdf_ctr_cmac,
+                    test_rsgx_ed25519,
+                    test_rsgx_x25519,
+                    test_rsgx_ecdsa_der_raw,
+                    test_rsgx_rsa_pss_pkcs1_oaep,
+                    test_rsgx_aes256gcm,
+                    test_rsgx_chacha20_poly1305,
+                    test_rsgx_aead_handle,
+                    test_rsgx_aes_dec_handle,
                     // assert
                     foo_panic,
                     foo_should,
@@ -145,6 +166,7 @@ fn test_main_entrance() -> size_t {
                     // rts::memchr
                     test_rts_memchr_memchr,
                     test_rts_memchr_memrchr,
+                    test_rts_ct,
                     test_ascii,
                     // rts::c_str
                     test_cstr,
@@ -154,6 +176,11 @@ fn test_main_entrance() -> size_t {
                     test_array_sealing,         // Thanks to @silvanegli
                     test_mac_aadata_slice,
                     test_mac_aadata_number,
+                    test_sealed_data_bytes,
+                    test_sealed_object,
+                    test_seal_stream,
+                    test_seal_policy,
+                    test_seal_rollback,
                     // rand
                     test_rand_os_sgxrng,
                     test_rand_distributions,
@@ -166,6 +193,12 @@ fn test_main_entrance() -> size_t {
                     test_serialize_enum,
                     // std::sgxfs
                     test_sgxfs,
+                    test_sgxfs_rename_truncate,
+                    test_sgxfs_vfs,
+                    test_sgxfs_vfs_rollback,
+                    test_sgxfs_key_provider,
+                    test_sgxfs_integrity_only,
+                    test_sgxfs_integrity_only_rename,
                     // std::fs
                     test_fs,
                     // std::fs untrusted mode
@@ -177,6 +210,9 @@ fn test_main_entrance() -> size_t {
                     // types
                     check_metadata_size,
                     check_version,
+                    check_report_quote_views,
+                    // policy
+                    test_enclave_policy,
                     // env
                     test_env_vars_os,
                     test_env_self_exe_path,
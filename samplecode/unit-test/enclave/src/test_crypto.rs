@@ -18,6 +18,7 @@
 use utils::*;
 use std::string::String;
 use sgx_tcrypto::*;
+use sgx_types::*;
 
 static HASH_TEST_VEC: &'static [&'static str] = &[
     &"abc",
@@ -31,6 +32,18 @@ static HASH_SHA256_TRUTH: &'static [&'static str] = &[
     &"cf5b16a778af8380036ce59e7b0492370b249b11e8f07a51afac45037afee9d1",
 ];
 
+static HASH_SHA384_TRUTH: &'static [&'static str] = &[
+    &"cb00753f45a35e8bb5a03d699ac65007272c32ab0eded1631a8b605a43ff5bed8086072ba1e7cc2358baeca134c825a7",
+    &"3391fdddfc8dc7393707a65b1b4709397cf8b1d162af05abfe8f450de5f36bc6b0455a8520bc4e6f5fe95b1fe3c8452b",
+    &"09330c33f71147e83d192fc782cd1b4753111b173b3b05d22fa08086e3b0f712fcc7c71a557e2db966c3e9fa91746039",
+];
+
+static HASH_SHA512_TRUTH: &'static [&'static str] = &[
+    &"ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f",
+    &"204a8fc6dda82f0a0ced7beb8e08a41657c16ef468b228a8279be331a703c33596fd15c13b1b07f9aa1d3bea57789ca031ad85c7a71dd70354ec631238ca3445",
+    &"8e959b75dae313da8cf4f72814fc143f8f7779c6eb9f7fa17299aeadb6889018501d289e4900f7e4331b99dec4b5433ac7d329eeb6dd26545e96e55b874be909",
+];
+
 pub fn test_rsgx_sha256_slice() {
     let test_size = HASH_TEST_VEC.len();
     for i in 0..test_size {
@@ -53,3 +66,359 @@ pub fn test_rsgx_sha256_handle(){
     }
 }
 
+
+static AES256GCM_KEY: &'static str = "feffe9928665731c6d6a8f9467308308feffe9928665731c6d6a8f9467308308";
+static AES256GCM_IV: &'static str = "cafebabefacedbaddecaf888";
+static AES256GCM_AAD: &'static str = "feedfacedeadbeeffeedfacedeadbeefabaddad2";
+static AES256GCM_PT: &'static str = "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a721c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39";
+static AES256GCM_CT: &'static str = "522dc1f099567d07f47f37a32a84427d643a8cdcbfe5c0c97598a2bd2555d1aa8cb08e48590dbb3da7b08b1056828838c5f61e6393ba7a0abcc9f662";
+static AES256GCM_TAG: &'static str = "76fc6ece0f4e1768cddf8853bb2d551b";
+
+static CHACHAPOLY_KEY: &'static str = "808182838485868788898a8b8c8d8e8f909192939495969798999a9b9c9d9e9f";
+static CHACHAPOLY_IV: &'static str = "070000004041424344454647";
+static CHACHAPOLY_AAD: &'static str = "50515253c0c1c2c3c4c5c6c7";
+static CHACHAPOLY_PT: &'static str = "Ladies and Gentlemen of the class of '99: If I could offer you only one tip for the future, sunscreen would be it.";
+static CHACHAPOLY_CT: &'static str = "d31a8d34648e60db7b86afbc53ef7ec2a4aded51296e08fea9e2b5a736ee62d63dbea45e8ca9671282fafb69da92728b1a71de0a9e060b2905d6a5b67ecd3b3692ddbd7f2d778b8c9803aee328091b58fab324e4fad675945585808b4831d7bc3ff4def08e4b7a9de576d26586cec64b6116";
+static CHACHAPOLY_TAG: &'static str = "1ae10b594f09e26a7e902ecbd0600691";
+
+fn to_key_256bit(bytes: &[u8]) -> sgx_key_256bit_t {
+    let mut key = sgx_key_256bit_t::default();
+    key.copy_from_slice(bytes);
+    key
+}
+
+pub fn test_rsgx_aes256gcm() {
+    let key = to_key_256bit(&hex_to_bytes(AES256GCM_KEY));
+    let iv = hex_to_bytes(AES256GCM_IV);
+    let aad = hex_to_bytes(AES256GCM_AAD);
+    let pt = hex_to_bytes(AES256GCM_PT);
+
+    let mut ct = vec![0_u8; pt.len()];
+    let mut mac = sgx_aes_gcm_256bit_tag_t::default();
+    rsgx_aes256GCM_encrypt(&key, &pt, &iv, &aad, &mut ct, &mut mac).unwrap();
+    assert_eq!(hex_to_bytes(AES256GCM_CT), ct);
+    assert_eq!(hex_to_bytes(AES256GCM_TAG), mac);
+
+    let mut out = vec![0_u8; ct.len()];
+    rsgx_aes256GCM_decrypt(&key, &ct, &iv, &aad, &mac, &mut out).unwrap();
+    assert_eq!(pt, out);
+
+    mac[0] ^= 1;
+    assert_eq!(rsgx_aes256GCM_decrypt(&key, &ct, &iv, &aad, &mac, &mut out),
+               Err(sgx_status_t::SGX_ERROR_MAC_MISMATCH));
+}
+
+pub fn test_rsgx_chacha20_poly1305() {
+    let key = to_key_256bit(&hex_to_bytes(CHACHAPOLY_KEY));
+    let iv = hex_to_bytes(CHACHAPOLY_IV);
+    let aad = hex_to_bytes(CHACHAPOLY_AAD);
+    let pt = CHACHAPOLY_PT.as_bytes();
+
+    let mut ct = vec![0_u8; pt.len()];
+    let mut mac = sgx_chacha20_poly1305_tag_t::default();
+    rsgx_chacha20_poly1305_encrypt(&key, pt, &iv, &aad, &mut ct, &mut mac).unwrap();
+    assert_eq!(hex_to_bytes(CHACHAPOLY_CT), ct);
+    assert_eq!(hex_to_bytes(CHACHAPOLY_TAG), mac);
+
+    let mut out = vec![0_u8; ct.len()];
+    rsgx_chacha20_poly1305_decrypt(&key, &ct, &iv, &aad, &mac, &mut out).unwrap();
+    assert_eq!(pt, &out[..]);
+
+    ct[0] ^= 1;
+    assert_eq!(rsgx_chacha20_poly1305_decrypt(&key, &ct, &iv, &aad, &mac, &mut out),
+               Err(sgx_status_t::SGX_ERROR_MAC_MISMATCH));
+}
+
+pub fn test_rsgx_aead_handle() {
+    let key = to_key_256bit(&hex_to_bytes(AES256GCM_KEY));
+    let iv = hex_to_bytes(AES256GCM_IV);
+    let aad = hex_to_bytes(AES256GCM_AAD);
+    let pt = hex_to_bytes(AES256GCM_PT);
+
+    for alg in [sgx_aead_algorithm_t::SGX_AEAD_AES_256_GCM,
+                sgx_aead_algorithm_t::SGX_AEAD_CHACHA20_POLY1305].iter() {
+        let mut expected = vec![0_u8; pt.len()];
+        let mut expected_mac = sgx_mac_128bit_t::default();
+        rsgx_aead_encrypt(*alg, &key, &pt, &iv, &aad, &mut expected, &mut expected_mac).unwrap();
+
+        let handle = SgxAeadHandle::new();
+        handle.init(*alg, &key, &iv, &aad).unwrap();
+        let mut ct = vec![0_u8; pt.len()];
+        for (src, dst) in pt.chunks(7).zip(ct.chunks_mut(7)) {
+            handle.update(src, dst).unwrap();
+        }
+        assert_eq!(expected, ct);
+        assert_eq!(expected_mac, handle.get_mac().unwrap());
+        handle.close().unwrap();
+    }
+}
+
+pub fn test_rsgx_aes_dec_handle() {
+    let key: sgx_aes_gcm_128bit_key_t = [0xfe, 0xff, 0xe9, 0x92, 0x86, 0x65, 0x73, 0x1c,
+                                         0x6d, 0x6a, 0x8f, 0x94, 0x67, 0x30, 0x83, 0x08];
+    let iv = hex_to_bytes(AES256GCM_IV);
+    let aad = hex_to_bytes(AES256GCM_AAD);
+    let pt = hex_to_bytes(AES256GCM_PT);
+
+    let mut ct = vec![0_u8; pt.len()];
+    let mut mac = sgx_aes_gcm_128bit_tag_t::default();
+    rsgx_rijndael128GCM_encrypt(&key, &pt, &iv, &aad, &mut ct, &mut mac).unwrap();
+
+    let handle = SgxAesDecHandle::new();
+    handle.init(&key, &iv, &aad).unwrap();
+    let mut out = vec![0_u8; ct.len()];
+    for (src, dst) in ct.chunks(5).zip(out.chunks_mut(5)) {
+        handle.update(src, dst).unwrap();
+    }
+    handle.verify_mac(&mac).unwrap();
+    assert_eq!(pt, out);
+
+    mac[15] ^= 0x80;
+    assert_eq!(handle.verify_mac(&mac), Err(sgx_status_t::SGX_ERROR_MAC_MISMATCH));
+    handle.close().unwrap();
+}
+
+pub fn test_rsgx_sha384_sha512() {
+    for i in 0..HASH_TEST_VEC.len() {
+        let input = HASH_TEST_VEC[i].as_bytes();
+        assert_eq!(hex_to_bytes(HASH_SHA384_TRUTH[i]), &rsgx_sha384_slice(input).unwrap()[..]);
+        assert_eq!(hex_to_bytes(HASH_SHA512_TRUTH[i]), &rsgx_sha512_slice(input).unwrap()[..]);
+
+        let sha384 = SgxSha384Handle::new();
+        let sha512 = SgxSha512Handle::new();
+        sha384.init().unwrap();
+        sha512.init().unwrap();
+        for chunk in input.chunks(5) {
+            sha384.update_slice(chunk).unwrap();
+            sha512.update_slice(chunk).unwrap();
+        }
+        assert_eq!(hex_to_bytes(HASH_SHA384_TRUTH[i]), &sha384.get_hash().unwrap()[..]);
+        assert_eq!(hex_to_bytes(HASH_SHA512_TRUTH[i]), &sha512.get_hash().unwrap()[..]);
+    }
+}
+
+// RFC 4231 test case 2
+pub fn test_rsgx_hmac_sha384_sha512() {
+    let key = b"Jefe";
+    let data = b"what do ya want for nothing?";
+    let hmac384 = "af45d2e376484031617f78d2b58a6b1b9c7ef464f5a01b47e42ec3736322445e8e2240ca5e69e2c78b3239ecfab21649";
+    let hmac512 = "164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea2505549758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737";
+
+    assert_eq!(hex_to_bytes(hmac384), &rsgx_hmac_sha384_slice(key, data).unwrap()[..]);
+    assert_eq!(hex_to_bytes(hmac512), &rsgx_hmac_sha512_slice(key, data).unwrap()[..]);
+
+    let handle = SgxHmac512Handle::new();
+    handle.init(key).unwrap();
+    handle.update_slice(&data[..10]).unwrap();
+    handle.update_slice(&data[10..]).unwrap();
+    assert_eq!(hex_to_bytes(hmac512), &handle.get_hash().unwrap()[..]);
+    handle.close().unwrap();
+}
+
+// RFC 5869 test case 1, and the same inputs with HKDF-SHA384
+pub fn test_rsgx_hkdf() {
+    let ikm = [0x0b_u8; 22];
+    let salt = hex_to_bytes("000102030405060708090a0b0c");
+    let info = hex_to_bytes("f0f1f2f3f4f5f6f7f8f9");
+
+    let prk = rsgx_hkdf_sha256_extract(&salt, &ikm).unwrap();
+    assert_eq!(hex_to_bytes("077709362c2e32df0ddc3f0dc47bba6390b6c73bb50f9c3122ec844ad7c2b3e5"), prk.as_bytes());
+    let okm: SgxSecret<[u8; 42]> = rsgx_hkdf_sha256_expand(&prk, &info).unwrap();
+    assert_eq!(hex_to_bytes("3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865"), okm.as_bytes());
+    let key: SgxSecret<sgx_key_128bit_t> = rsgx_hkdf_sha256(&salt, &ikm, &info).unwrap();
+    assert_eq!(&okm.as_bytes()[..16], key.as_bytes());
+
+    let prk = rsgx_hkdf_sha384_extract(&salt, &ikm).unwrap();
+    assert_eq!(hex_to_bytes("704b39990779ce1dc548052c7dc39f303570dd13fb39f7acc564680bef80e8dec70ee9a7e1f3e293ef68eceb072a5ade"), prk.as_bytes());
+    let okm: SgxSecret<[u8; 42]> = rsgx_hkdf_sha384_expand(&prk, &info).unwrap();
+    assert_eq!(hex_to_bytes("9b5097a86038b805309076a44b3a9f38063e25b516dcbf369f394cfab43685f748b6457763e4f0204fc5"), okm.as_bytes());
+
+    // at most 255 * HashLen bytes can be derived
+    let prk = rsgx_hkdf_sha256_extract(&[], &ikm).unwrap();
+    assert!(rsgx_hkdf_sha256_expand::<[[[u8; 32]; 64]; 4]>(&prk, &info).is_err());
+}
+
+pub fn test_rsgx_kdf_ctr_cmac() {
+    let key: sgx_cmac_128bit_key_t = [0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6,
+                                      0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf, 0x4f, 0x3c];
+    let label = b"label";
+    let context = b"context";
+
+    let derived: SgxSecret<[u8; 32]> = rsgx_kdf_ctr_cmac(&key, label, context).unwrap();
+    // counter(i) || label || 0x00 || context || L = 256 bits
+    let mut fixed = [0_u8; 4 + 5 + 1 + 7 + 4];
+    fixed[4..9].copy_from_slice(label);
+    fixed[10..17].copy_from_slice(context);
+    fixed[19] = 0x01;
+    for i in 0..2 {
+        fixed[3] = i as u8 + 1;
+        let block = rsgx_rijndael128_cmac_slice(&key, &fixed).unwrap();
+        assert_eq!(&derived.as_bytes()[i * 16..(i + 1) * 16], &block[..]);
+    }
+
+    let from_fixed: SgxSecret<[u8; 32]> = rsgx_kdf_ctr_cmac_fixed(&key, &fixed[4..]).unwrap();
+    assert_eq!(derived.as_bytes(), from_fixed.as_bytes());
+}
+
+fn to_array_32(v: &[u8]) -> [u8; 32] {
+    let mut a = [0_u8; 32];
+    a.copy_from_slice(v);
+    a
+}
+
+// RFC 8032 section 7.1, test 2
+pub fn test_rsgx_ed25519() {
+    let private = sgx_ed25519_private_t {
+        seed: to_array_32(&hex_to_bytes("4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb")),
+    };
+    let public = rsgx_ed25519_calculate_public(&private);
+    assert_eq!(hex_to_bytes("3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c"), &public.key[..]);
+
+    let msg = [0x72_u8];
+    let signature = rsgx_ed25519_sign_slice(&msg, &private).unwrap();
+    assert_eq!(hex_to_bytes("92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da"), &signature.r[..]);
+    assert_eq!(hex_to_bytes("085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00"), &signature.s[..]);
+    assert_eq!(rsgx_ed25519_verify_slice(&msg, &public, &signature), Ok(true));
+
+    let mut bad = signature;
+    bad.s[0] ^= 1;
+    assert_eq!(rsgx_ed25519_verify_slice(&msg, &public, &bad), Ok(false));
+
+    let (private, public) = rsgx_ed25519_create_key_pair().unwrap();
+    let data = [0x5a_u8; 64];
+    let signature = rsgx_ed25519_sign_msg(&data, &private).unwrap();
+    assert_eq!(rsgx_ed25519_verify_msg(&data, &public, &signature), Ok(true));
+}
+
+// RFC 7748 section 6.1
+pub fn test_rsgx_x25519() {
+    let alice = sgx_x25519_private_t {
+        k: to_array_32(&hex_to_bytes("77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a")),
+    };
+    let bob = sgx_x25519_private_t {
+        k: to_array_32(&hex_to_bytes("5dab087e624a8a4b79e17f8b83800ee66f3bb1292618b6fd1c2f8b27ff88e0eb")),
+    };
+    let alice_public = rsgx_x25519_calculate_public(&alice);
+    let bob_public = rsgx_x25519_calculate_public(&bob);
+    assert_eq!(hex_to_bytes("8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a"), &alice_public.u[..]);
+    assert_eq!(hex_to_bytes("de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f"), &bob_public.u[..]);
+
+    let shared = hex_to_bytes("4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742");
+    assert_eq!(shared, &rsgx_x25519_compute_shared_key(&alice, &bob_public).unwrap().s[..]);
+    assert_eq!(shared, &rsgx_x25519_compute_shared_key(&bob, &alice_public).unwrap().s[..]);
+
+    let (private, public) = rsgx_x25519_create_key_pair().unwrap();
+    assert_eq!(
+        rsgx_x25519_compute_shared_key(&private, &bob_public).unwrap().s,
+        rsgx_x25519_compute_shared_key(&bob, &public).unwrap().s
+    );
+    assert!(rsgx_x25519_compute_shared_key(&private, &sgx_x25519_public_t::default()).is_err());
+}
+
+pub fn test_rsgx_ecdsa_der_raw() {
+    let ecc_handle = SgxEccHandle::new();
+    ecc_handle.open().unwrap();
+    let (private, public) = ecc_handle.create_key_pair().unwrap();
+    let data = [0x5a_u8; 64];
+
+    let signature = ecc_handle.ecdsa_sign_slice(&data, &private).unwrap();
+    let raw = rsgx_ec256_signature_to_raw(&signature);
+    let der = rsgx_ec256_signature_to_der(&signature);
+    assert_eq!(der.as_bytes()[0], 0x30);
+    assert_eq!(der.as_bytes()[1] as usize, der.len() - 2);
+
+    let from_raw = rsgx_ec256_signature_from_raw(&raw).unwrap();
+    let from_der = rsgx_ec256_signature_from_der(der.as_bytes()).unwrap();
+    assert_eq!(from_raw.x, signature.x);
+    assert_eq!(from_raw.y, signature.y);
+    assert_eq!(from_der.x, signature.x);
+    assert_eq!(from_der.y, signature.y);
+    assert_eq!(ecc_handle.ecdsa_verify_slice_raw(&data, &public, &raw), Ok(true));
+    assert_eq!(ecc_handle.ecdsa_verify_slice_der(&data, &public, der.as_bytes()), Ok(true));
+
+    let der = ecc_handle.ecdsa_sign_msg_der(&data, &private).unwrap();
+    assert_eq!(ecc_handle.ecdsa_verify_msg_der(&data, &public, der.as_bytes()), Ok(true));
+    assert_eq!(ecc_handle.ecdsa_verify_msg_der(&data, &public, &der.as_bytes()[1..]), Ok(false));
+    let raw = ecc_handle.ecdsa_sign_msg_raw(&data, &private).unwrap();
+    assert_eq!(ecc_handle.ecdsa_verify_msg_raw(&data, &public, &raw), Ok(true));
+
+    let sec1 = rsgx_ec256_public_to_sec1(&public);
+    assert_eq!(sec1[0], 0x04);
+    assert_eq!(sec1[1], public.gx[SGX_ECP256_KEY_SIZE - 1]);
+    let decoded = rsgx_ec256_public_from_sec1(&sec1).unwrap();
+    assert_eq!(decoded.gx, public.gx);
+    assert_eq!(decoded.gy, public.gy);
+    assert!(rsgx_ec256_public_from_sec1(&sec1[..33]).is_err());
+
+    let (other, _) = ecc_handle.create_key_pair().unwrap();
+    assert!(private != other);
+    assert!(private == SgxEc256PrivateSecret::new(*private));
+    assert_eq!(format!("{:?}", private), "SgxSecret([REDACTED])");
+    ecc_handle.close().unwrap();
+}
+
+pub fn test_rsgx_rsa_pss_pkcs1_oaep() {
+    let hashes = [
+        sgx_hash_algorithm_t::SGX_HASH_SHA256,
+        sgx_hash_algorithm_t::SGX_HASH_SHA384,
+        sgx_hash_algorithm_t::SGX_HASH_SHA512,
+    ];
+    let data = [0x5a_u8; 100];
+
+    for &mod_size in [SGX_RSA2048_KEY_SIZE, SGX_RSA3072_KEY_SIZE, SGX_RSA4096_KEY_SIZE].iter() {
+        let mut n = vec![0_u8; mod_size];
+        let mut d = vec![0_u8; mod_size];
+        let mut e = vec![1_u8, 0, 1, 0];
+        let mut p = vec![0_u8; mod_size / 2];
+        let mut q = vec![0_u8; mod_size / 2];
+        let mut dmp1 = vec![0_u8; mod_size / 2];
+        let mut dmq1 = vec![0_u8; mod_size / 2];
+        let mut iqmp = vec![0_u8; mod_size / 2];
+        rsgx_create_rsa_key_pair(
+            mod_size as i32, 4,
+            &mut n, &mut d, &mut e, &mut p, &mut q, &mut dmp1, &mut dmq1, &mut iqmp,
+        ).unwrap();
+
+        let privkey = SgxRsaPrivKey::new();
+        privkey.create(mod_size as i32, 4, &e, &p, &q, &dmp1, &dmq1, &iqmp).unwrap();
+        let pubkey = SgxRsaPubKey::new();
+        pubkey.create(mod_size as i32, 4, &n, &e).unwrap();
+
+        for &hash in hashes.iter() {
+            let mut signature = vec![0_u8; mod_size];
+            privkey.sign_pss_slice(hash, &data, &mut signature).unwrap();
+            assert_eq!(pubkey.verify_pss_slice(hash, &data, &signature), Ok(true));
+            assert_eq!(pubkey.verify_pss_slice(hash, &data[1..], &signature), Ok(false));
+            assert_eq!(pubkey.verify_pkcs1_v15_slice(hash, &data, &signature), Ok(false));
+
+            privkey.sign_pkcs1_v15_slice(hash, &data, &mut signature).unwrap();
+            assert_eq!(pubkey.verify_pkcs1_v15_slice(hash, &data, &signature), Ok(true));
+            signature[0] ^= 1;
+            assert_eq!(pubkey.verify_pkcs1_v15_slice(hash, &data, &signature), Ok(false));
+
+            let mut ct_len = 0_usize;
+            pubkey.encrypt_oaep(hash, b"label", &mut [], &mut ct_len, &data).unwrap();
+            assert_eq!(ct_len, mod_size);
+            let mut ciphertext = vec![0_u8; ct_len];
+            pubkey.encrypt_oaep(hash, b"label", &mut ciphertext, &mut ct_len, &data).unwrap();
+            let mut plaintext = vec![0_u8; mod_size];
+            let mut pt_len = plaintext.len();
+            privkey.decrypt_oaep(hash, b"label", &mut plaintext, &mut pt_len, &ciphertext).unwrap();
+            assert_eq!(&plaintext[..pt_len], &data[..]);
+            let mut pt_len = plaintext.len();
+            assert_eq!(
+                privkey.decrypt_oaep(hash, b"other", &mut plaintext, &mut pt_len, &ciphertext),
+                Err(sgx_status_t::SGX_ERROR_UNEXPECTED)
+            );
+        }
+
+        // encrypt_sha256 is OAEP with SHA-256 and an empty label.
+        let mut ciphertext = vec![0_u8; mod_size];
+        let mut ct_len = ciphertext.len();
+        pubkey.encrypt_sha256(&mut ciphertext, &mut ct_len, &data).unwrap();
+        let mut plaintext = vec![0_u8; mod_size];
+        let mut pt_len = plaintext.len();
+        privkey.decrypt_oaep(sgx_hash_algorithm_t::SGX_HASH_SHA256, &[], &mut plaintext, &mut pt_len, &ciphertext).unwrap();
+        assert_eq!(&plaintext[..pt_len], &data[..]);
+    }
+}
@@ -20,6 +20,8 @@ use std::sgxfs::{self, SgxFile};
 use std::untrusted::fs::File;
 use std::untrusted::fs::remove_file;
 use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::prelude::v1::*;
 use std::string::*;
 
 pub fn test_sgxfs() {
@@ -87,6 +89,36 @@ pub fn test_sgxfs() {
     }
 }
 
+pub fn test_sgxfs_rename_truncate() {
+    let dir = "sgxfs_dir";
+    let _ = std::untrusted::fs::create_dir(dir);
+    let data: Vec<u8> = (0..10_000_u32).map(|i| i as u8).collect();
+    sgxfs::write("sgxfs_dir/a", &data).unwrap();
+    assert_eq!(sgxfs::metadata("sgxfs_dir/a").unwrap().len(), data.len() as u64);
+
+    sgxfs::rename("sgxfs_dir/a", "sgxfs_dir/b").unwrap();
+    assert!(SgxFile::open("sgxfs_dir/a").is_err());
+    assert_eq!(sgxfs::read("sgxfs_dir/b").unwrap(), data);
+
+    sgxfs::truncate("sgxfs_dir/b", 100).unwrap();
+    assert_eq!(sgxfs::read("sgxfs_dir/b").unwrap(), &data[..100]);
+    sgxfs::truncate("sgxfs_dir/b", 200).unwrap();
+    let grown = sgxfs::read("sgxfs_dir/b").unwrap();
+    assert_eq!(&grown[..100], &data[..100]);
+    assert!(grown[100..].iter().all(|&b| b == 0));
+    assert_eq!(SgxFile::open("sgxfs_dir/b").unwrap().metadata().unwrap().len(), 200);
+
+    let mut plain = File::create("sgxfs_dir/plain").unwrap();
+    plain.write_all(b"not protected").unwrap();
+    drop(plain);
+    let files = sgxfs::read_dir(dir).unwrap();
+    assert_eq!(files, vec![PathBuf::from("sgxfs_dir/b")]);
+
+    sgxfs::remove("sgxfs_dir/b").unwrap();
+    remove_file("sgxfs_dir/plain").unwrap();
+    std::untrusted::fs::remove_dir(dir).unwrap();
+}
+
 pub fn test_fs () {
     {
         let f = File::create("foo.txt");
@@ -129,3 +161,172 @@ pub fn test_fs_untrusted_fs_feature_enabled() {
         assert!(f.is_ok());
     }
 }
+
+pub fn test_sgxfs_vfs() {
+    use sgx_tseal::SgxMemoryCounter;
+    use std::sgxfs::vfs::ProtectedFs;
+
+    let dir = "sgxfs_vfs";
+    let key = [0x42_u8; 16];
+    let mut counter = SgxMemoryCounter::new();
+    let mut fs = ProtectedFs::create_ex(dir, &key, &mut counter).unwrap();
+    fs.create_dir_all("a/b").unwrap();
+    fs.write("a/b/f", b"first").unwrap();
+    fs.write("a/b/f", b"second").unwrap();
+    fs.rename("a/b", "c").unwrap();
+    assert_eq!(fs.metadata("c/f").unwrap().version(), 2);
+    let entries = fs.read_dir("/").unwrap();
+    let paths: Vec<&str> = entries.iter().map(|e| e.path().to_str().unwrap()).collect();
+    assert_eq!(paths, vec!["a", "c"]);
+    let version = fs.version();
+    drop(fs);
+
+    assert!(ProtectedFs::open_ex(dir, &[0_u8; 16], SgxMemoryCounter::new()).is_err());
+    let mut fs = ProtectedFs::open_ex(dir, &key, &mut counter).unwrap();
+    assert_eq!(fs.version(), version);
+    assert_eq!(fs.read("c/f").unwrap(), b"second");
+
+    fs.remove_file("c/f").unwrap();
+    fs.remove_dir("c").unwrap();
+    fs.remove_dir("a").unwrap();
+    drop(fs);
+    std::untrusted::fs::remove_dir_all(dir).unwrap();
+}
+
+pub fn test_sgxfs_vfs_rollback() {
+    use sgx_tseal::{MonotonicCounter, SgxMemoryCounter};
+    use std::sgxfs::vfs::ProtectedFs;
+    use std::untrusted::fs;
+
+    let dir = "sgxfs_vfs_rollback";
+    let old = "sgxfs_vfs_rollback.old";
+    let key = [0x42_u8; 16];
+    let mut counter = SgxMemoryCounter::new();
+    let mut pfs = ProtectedFs::create_ex(dir, &key, &mut counter).unwrap();
+    pfs.write("f", b"old").unwrap();
+    drop(pfs);
+
+    // Keep a copy of the root and of all the objects of the tree.
+    fs::create_dir(old).unwrap();
+    for entry in fs::read_dir(dir).unwrap() {
+        let entry = entry.unwrap();
+        if entry.file_type().unwrap().is_file() {
+            fs::copy(entry.path(), std::path::Path::new(old).join(entry.file_name())).unwrap();
+        }
+    }
+
+    let mut pfs = ProtectedFs::open_ex(dir, &key, &mut counter).unwrap();
+    pfs.write("f", b"new").unwrap();
+    let version = pfs.version();
+    drop(pfs);
+    assert_eq!(counter.read().unwrap(), version);
+
+    // Put the old tree back as a whole.
+    fs::remove_dir_all(dir).unwrap();
+    fs::rename(old, dir).unwrap();
+    let err = ProtectedFs::open_ex(dir, &key, &mut counter).err().unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    fs::remove_dir_all(dir).unwrap();
+}
+
+pub fn test_sgxfs_key_provider() {
+    use sgx_types::SgxKey128Secret;
+    use std::sgxfs::DerivedKeyProvider;
+
+    let master = SgxKey128Secret::new([0x11_u8; 16]);
+    let old = DerivedKeyProvider::new(master.clone(), b"tenant-a").unwrap();
+    let new = DerivedKeyProvider::new(master, b"tenant-b").unwrap();
+
+    let data = b"rotate me";
+    SgxFile::create_with("sgx_file_kp", &old).unwrap().write_all(data).unwrap();
+    sgxfs::rekey("sgx_file_kp", &old, &new).unwrap();
+    assert!(SgxFile::open_with("sgx_file_kp", &old).is_err());
+
+    let mut read_data = Vec::new();
+    SgxFile::open_with("sgx_file_kp", &new).unwrap().read_to_end(&mut read_data).unwrap();
+    assert_eq!(read_data, data);
+
+    let raw_key = [0x22_u8; 16];
+    sgxfs::rekey("sgx_file_kp", &new, &raw_key).unwrap();
+    assert!(SgxFile::open_ex("sgx_file_kp", &raw_key).is_ok());
+    sgxfs::remove("sgx_file_kp").unwrap();
+}
+
+pub fn test_sgxfs_integrity_only() {
+    use std::io::{Seek, SeekFrom};
+    use std::sgxfs::OpenOptions;
+
+    let key = [0x3c_u8; 16];
+    let data: Vec<u8> = (0..3 * 4096 + 100).map(|i| (i % 251) as u8).collect();
+    {
+        let mut file = OpenOptions::new().write(true).integrity_only(true).open_ex("sgx_file_mac", &key).unwrap();
+        file.write_all(&data).unwrap();
+    }
+    assert_eq!(std::untrusted::fs::read("sgx_file_mac").unwrap(), data);
+    assert!(OpenOptions::new().read(true).integrity_only(true).open("sgx_file_mac").is_err());
+
+    let mut file = OpenOptions::new().read(true).integrity_only(true).open_ex("sgx_file_mac", &key).unwrap();
+    let mut buf = [0_u8; 16];
+    file.seek(SeekFrom::Start(8190)).unwrap();
+    file.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf[..], &data[8190..8206]);
+    drop(file);
+
+    let mut tampered = data.clone();
+    tampered[5000] ^= 1;
+    std::untrusted::fs::write("sgx_file_mac", &tampered).unwrap();
+    let mut file = OpenOptions::new().read(true).integrity_only(true).open_ex("sgx_file_mac", &key).unwrap();
+    file.read_exact(&mut buf).unwrap();
+    file.seek(SeekFrom::Start(5000)).unwrap();
+    assert!(file.read_exact(&mut buf).is_err());
+    drop(file);
+    assert!(OpenOptions::new().read(true).integrity_only(true).open_ex("sgx_file_mac", &[0_u8; 16]).is_err());
+
+    let public = sgxfs::integrity_public_key(&key).unwrap();
+    let again = sgxfs::integrity_public_key(&key).unwrap();
+    let other = sgxfs::integrity_public_key(&[0_u8; 16]).unwrap();
+    assert_eq!((public.gx, public.gy), (again.gx, again.gy));
+    assert_ne!(public.gx, other.gx);
+
+    std::untrusted::fs::write("sgx_file_mac", &data).unwrap();
+    let mut tags = std::untrusted::fs::read("sgx_file_mac.mht").unwrap();
+    tags[24] ^= 1;
+    std::untrusted::fs::write("sgx_file_mac.mht", &tags).unwrap();
+    assert!(OpenOptions::new().read(true).integrity_only(true).open_ex("sgx_file_mac", &key).is_err());
+
+    remove_file("sgx_file_mac").unwrap();
+    remove_file("sgx_file_mac.mht").unwrap();
+}
+
+pub fn test_sgxfs_integrity_only_rename() {
+    use std::sgxfs::OpenOptions;
+    use std::untrusted::fs::rename;
+
+    let key = [0x3c_u8; 16];
+    for (name, content) in [("sgx_file_mac_a", &b"first"[..]), ("sgx_file_mac_b", &b"second"[..])].iter() {
+        let mut file = OpenOptions::new().write(true).integrity_only(true).open_ex(name, &key).unwrap();
+        file.write_all(content).unwrap();
+    }
+    let open = |name: &str| OpenOptions::new().read(true).integrity_only(true).open_ex(name, &key);
+
+    rename("sgx_file_mac_a", "sgx_file_mac_c").unwrap();
+    rename("sgx_file_mac_a.mht", "sgx_file_mac_c.mht").unwrap();
+    assert!(open("sgx_file_mac_c").is_err());
+    rename("sgx_file_mac_c", "sgx_file_mac_a").unwrap();
+    rename("sgx_file_mac_c.mht", "sgx_file_mac_a.mht").unwrap();
+    assert!(open("sgx_file_mac_a").is_ok());
+
+    rename("sgx_file_mac_a", "sgx_file_mac_c").unwrap();
+    rename("sgx_file_mac_a.mht", "sgx_file_mac_c.mht").unwrap();
+    rename("sgx_file_mac_b", "sgx_file_mac_a").unwrap();
+    rename("sgx_file_mac_b.mht", "sgx_file_mac_a.mht").unwrap();
+    rename("sgx_file_mac_c", "sgx_file_mac_b").unwrap();
+    rename("sgx_file_mac_c.mht", "sgx_file_mac_b.mht").unwrap();
+    assert!(open("sgx_file_mac_a").is_err());
+    assert!(open("sgx_file_mac_b").is_err());
+
+    for name in ["sgx_file_mac_a", "sgx_file_mac_b"].iter() {
+        remove_file(name).unwrap();
+        remove_file(format!("{}.mht", name)).unwrap();
+    }
+}
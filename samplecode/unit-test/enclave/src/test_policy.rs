@@ -0,0 +1,69 @@
+use sgx_types::metadata::*;
+use sgx_types::*;
+
+use core::mem;
+use std::prelude::v1::*;
+
+pub fn check_metadata_size() {
+    assert_eq!(mem::size_of::<layout_group_t>(), 32);
+    assert_eq!(mem::size_of::<layout_entry_t>(), 32);
+    assert_eq!(mem::size_of::<layout_t>(), 32);
+    assert_eq!(mem::size_of::<css_header_t>(), 128);
+    assert_eq!(mem::size_of::<css_key_t>(), 772);
+    assert_eq!(mem::size_of::<css_body_t>(), 128);
+    assert_eq!(mem::size_of::<css_buffer_t>(), 780);
+    assert_eq!(mem::size_of::<enclave_css_t>(), 1808);
+    assert_eq!(mem::size_of::<metadata_t>(), METADATA_SIZE);
+
+use sgx_enclave_policy::*;
+use sgx_types::*;
+
+pub fn test_enclave_policy() {
+    let mut body = sgx_report_body_t::default();
+    body.mr_enclave.m = [0xee; 32];
+    body.mr_signer.m = [0x55; 32];
+    body.isv_prod_id = 1;
+    body.isv_svn = 3;
+    body.attributes.flags = SGX_FLAGS_INITTED | SGX_FLAGS_MODE64BIT;
+
+    assert_eq!(EnclavePolicy::new().check(&body), Err(vec![PolicyViolation::Unconstrained]));
+
+    let signer = sgx_measurement_t { m: [0x55; 32] };
+    let policy = EnclavePolicy::new().mr_signer(signer).min_isv_svn(1, 3);
+    assert!(policy.check(&body).is_ok());
+    assert!(policy.check(&SgxReportBody::from_raw(&body)).is_ok());
+
+    body.isv_svn = 2;
+    body.attributes.flags |= SGX_FLAGS_DEBUG;
+    assert_eq!(
+        policy.check(&body),
+        Err(vec![
+            PolicyViolation::IsvSvn { isv_prod_id: 1, isv_svn: 2, min_isv_svn: 3 },
+            PolicyViolation::Debug,
+        ])
+    );
+    assert!(policy.clone().allow_debug(true).min_isv_svn(1, 2).check(&body).is_ok());
+
+    body.isv_prod_id = 2;
+    body.mr_signer.m = [0x66; 32];
+    let policy = policy.allow_debug(true).mr_enclave(sgx_measurement_t { m: [0xee; 32] });
+    assert_eq!(
+        policy.check(&body),
+        Err(vec![PolicyViolation::MrSigner([0x66; 32]), PolicyViolation::IsvProdId(2)])
+    );
+
+    // KSS configurations are not carried by local attestation identities.
+    let mut identity = sgx_dh_session_enclave_identity_t::default();
+    identity.mr_enclave.m = [0xee; 32];
+    let policy = EnclavePolicy::new()
+        .mr_enclave(identity.mr_enclave)
+        .config_id([7; 64])
+        .allow_debug(true);
+    assert_eq!(policy.check(&identity), Err(vec![PolicyViolation::ConfigId]));
+    body.config_id = [7; 64];
+    assert!(policy.check(&body).is_ok());
+
+    let status = sgx_ql_qv_result_t::SGX_QL_QV_RESULT_SW_HARDENING_NEEDED;
+    assert_eq!(policy.check_with_tcb_status(&body, status), Err(vec![PolicyViolation::TcbStatus(status)]));
+    assert!(policy.tcb_status(status).check_with_tcb_status(&body, status).is_ok());
+}
@@ -0,0 +1,82 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+// The enclave half of the remote attestation test. The app plays the service
+// provider and drives msg1, msg2 and msg3 through the ECALLs of
+// sgx_tkey_exchange.edl, then checks the keys of the session with the ECALLs
+// below, so that nothing but the comparison leaves the enclave.
+
+use sgx_tkey_exchange::RaSession;
+use sgx_trts::ct::ct_eq;
+use sgx_types::*;
+use std::boxed::Box;
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, Ordering};
+
+static SESSION: AtomicPtr<RaSession> = AtomicPtr::new(ptr::null_mut());
+
+#[no_mangle]
+pub extern "C"
+fn test_ra_session_open(sp_pub_key: &sgx_ec256_public_t, context: &mut sgx_ra_context_t) -> sgx_status_t {
+    let session = match RaSession::new(sp_pub_key) {
+        Ok(session) => session,
+        Err(e) => return e,
+    };
+    // No key before msg2.
+    match session.keys() {
+        Err(sgx_status_t::SGX_ERROR_INVALID_STATE) => (),
+        _ => return sgx_status_t::SGX_ERROR_UNEXPECTED,
+    }
+
+    *context = session.context();
+    let previous = SESSION.swap(Box::into_raw(Box::new(session)), Ordering::SeqCst);
+    if !previous.is_null() {
+        drop(unsafe { Box::from_raw(previous) });
+    }
+    sgx_status_t::SGX_SUCCESS
+}
+
+#[no_mangle]
+pub extern "C"
+fn test_ra_session_keys(sk: &sgx_key_128bit_t, mk: &sgx_key_128bit_t) -> sgx_status_t {
+    let session = SESSION.load(Ordering::SeqCst);
+    if session.is_null() {
+        return sgx_status_t::SGX_ERROR_INVALID_STATE;
+    }
+    let keys = match unsafe { &*session }.keys() {
+        Ok(keys) => keys,
+        Err(e) => return e,
+    };
+    if ct_eq(keys.sk.expose(), sk) && ct_eq(keys.mk.expose(), mk) {
+        sgx_status_t::SGX_SUCCESS
+    } else {
+        sgx_status_t::SGX_ERROR_MAC_MISMATCH
+    }
+}
+
+#[no_mangle]
+pub extern "C"
+fn test_ra_session_close() -> sgx_status_t {
+    let session = SESSION.swap(ptr::null_mut(), Ordering::SeqCst);
+    if session.is_null() {
+        return sgx_status_t::SGX_ERROR_INVALID_STATE;
+    }
+    match unsafe { Box::from_raw(session) }.close() {
+        Ok(()) => sgx_status_t::SGX_SUCCESS,
+        Err(e) => e,
+    }
+}
@@ -25,6 +25,7 @@ use sgx_trts::ascii::AsciiExt;
 use sgx_trts::c_str::*;
 use sgx_trts::error;
 use sgx_trts::memchr;
+use sgx_trts::ct::{self, ConstantTimeEq};
 use sgx_trts::libc;
 use sgx_trts::enclave::*;
 
@@ -276,6 +277,27 @@ pub fn test_rts_memchr_memrchr(){
     assert_eq!(memchr::memrchr(needle, test_str), None);
 }
 
+// ct
+pub fn test_rts_ct(){
+    let tag: sgx_cmac_128bit_tag_t = [0x5a; SGX_CMAC_MAC_SIZE];
+    let mut other = tag;
+    assert!(tag.ct_eq(&other));
+    other[SGX_CMAC_MAC_SIZE - 1] ^= 1;
+    assert!(tag.ct_ne(&other));
+    assert!(ct::ct_eq(&tag[..], &tag));
+    assert!(!ct::ct_eq(&tag[..], &tag[1..]));
+
+    assert_eq!(ct::ct_select(true, 1_u32, 2_u32), 1);
+    assert_eq!(ct::ct_select(false, 1_u32, 2_u32), 2);
+    assert_eq!(ct::ct_select(true, -1_i64, 7_i64), -1);
+
+    let mut dst = [0_u8; 4];
+    ct::ct_copy_if(false, &mut dst, &[1, 2, 3, 4]);
+    assert_eq!(dst, [0, 0, 0, 0]);
+    ct::ct_copy_if(true, &mut dst, &[1, 2, 3, 4]);
+    assert_eq!(dst, [1, 2, 3, 4]);
+}
+
 // ascii
 pub fn test_ascii(){
     assert_eq!("café".to_ascii_uppercase(), "CAFÉ");
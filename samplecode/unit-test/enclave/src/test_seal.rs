@@ -100,3 +100,120 @@ pub fn test_mac_aadata_slice() {
     let inner_slice = unsafe {slice::from_raw_parts(inner as *mut u8, 10)};
     assert_eq!(inner_slice, aad_data);
 }
+
+pub fn test_sealed_data_bytes() {
+    let data: [u8; 10] = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
+    let aad: [u8; 4] = [0xaa, 0xbb, 0xcc, 0xdd];
+    let sealed_data = SgxSealedData::<[u8]>::seal_data(&aad, &data).expect("error while sealing array");
+
+    let bytes = sealed_data.to_bytes();
+    assert_eq!(&bytes[0..4], b"SGXS");
+    let sealed_data = SgxSealedData::<[u8]>::from_bytes(&bytes).expect("error while parsing bytes");
+    assert_eq!(sealed_data.get_additional_txt(), &aad);
+    let unsealed_data = sealed_data.unseal_data().expect("error while unsealing array");
+    assert_eq!(unsealed_data.get_decrypt_txt(), &data);
+
+    assert_eq!(SgxSealedData::<[u8]>::from_bytes(&bytes[..bytes.len() - 1]).err(),
+               Some(sgx_status_t::SGX_ERROR_INVALID_PARAMETER));
+    let mut trailing = bytes.clone();
+    trailing.push(0);
+    assert_eq!(SgxSealedData::<[u8]>::from_bytes(&trailing).err(),
+               Some(sgx_status_t::SGX_ERROR_INVALID_PARAMETER));
+    let mut version = bytes.clone();
+    version[4] = 2;
+    assert_eq!(SgxSealedData::<[u8]>::from_bytes(&version).err(),
+               Some(sgx_status_t::SGX_ERROR_INVALID_VERSION));
+
+    let mmac = SgxMacAadata::<[u8]>::mac_aadata(&aad).expect("error while mac data");
+    let mmac = SgxMacAadata::<[u8]>::from_bytes(&mmac.to_bytes()).expect("error while parsing bytes");
+    assert_eq!(&*mmac.unmac_aadata().expect("error when unmac data"), &aad);
+}
+
+pub fn test_sealed_object() {
+    use std::collections::HashMap;
+
+    #[derive(Serializable, DeSerializable, PartialEq, Debug)]
+    struct Account {
+        name: String,
+        tags: Vec<String>,
+        balances: HashMap<u32, u64>,
+    }
+
+    let mut balances = HashMap::new();
+    balances.insert(1, 100);
+    balances.insert(2, 200);
+    let account = Account {
+        name: "alice".to_string(),
+        tags: vec!["admin".to_string(), "ops".to_string()],
+        balances: balances,
+    };
+
+    let aad = b"account v1";
+    let sealed = SgxSealedObject::<Account>::seal(aad, &account).expect("error while sealing object");
+    let sealed = SgxSealedObject::<Account>::from_bytes(&sealed.to_bytes()).expect("error while parsing bytes");
+    assert_eq!(sealed.unseal().expect("error while unsealing object"), account);
+    assert_eq!(sealed.get_additional_txt(), aad);
+}
+
+pub fn test_seal_stream() {
+    use std::io::{ErrorKind, Read, Write};
+
+    let data: Vec<u8> = (0..100_000_u32).map(|i| i as u8).collect();
+    let (key_policy, attribute_mask) = (SGX_KEYPOLICY_MRSIGNER,
+                                        sgx_attributes_t { flags: TSEAL_DEFAULT_FLAGSMASK, xfrm: 0 });
+    let mut writer = SgxSealWriter::with_policy(Vec::new(), key_policy, attribute_mask,
+                                                TSEAL_DEFAULT_MISCMASK, 4096).unwrap();
+    for part in data.chunks(1000) {
+        writer.write_all(part).unwrap();
+    }
+    let sealed = writer.finish().unwrap();
+
+    let mut unsealed = Vec::new();
+    SgxUnsealReader::new(&sealed[..]).unwrap().read_to_end(&mut unsealed).unwrap();
+    assert_eq!(unsealed, data);
+
+    let mut unsealed = Vec::new();
+    let truncated = &sealed[..sealed.len() - 1];
+    let err = SgxUnsealReader::new(truncated).unwrap().read_to_end(&mut unsealed).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+}
+
+pub fn test_seal_policy() {
+    let data = [0x5a_u8; 64];
+    let aad = b"policy";
+    let policy = SealPolicy::new(SealIdentity::MrEnclave);
+    let sealed = SgxSealedData::<[u8]>::seal_data_with_policy(&policy, aad, &data).unwrap();
+    assert_eq!(sealed.get_key_request().key_policy, SGX_KEYPOLICY_MRENCLAVE);
+    assert!(!sealed.needs_reseal());
+
+    let resealed = sealed.reseal(&SealPolicy::default()).unwrap();
+    assert_eq!(resealed.get_key_request().key_policy, SGX_KEYPOLICY_MRSIGNER);
+    assert_eq!(resealed.get_additional_txt(), aad);
+    assert!(!resealed.needs_reseal());
+    let unsealed = resealed.unseal_data().unwrap();
+    assert_eq!(unsealed.get_decrypt_txt(), &data[..]);
+
+    let current = sealed.get_key_request().isv_svn;
+    let err = SgxSealedData::<[u8]>::seal_data_with_policy(&policy.isv_svn(current + 1), aad, &data);
+    assert_eq!(err.err(), Some(sgx_status_t::SGX_ERROR_INVALID_ISVSVN));
+}
+
+pub fn test_seal_rollback() {
+    let data = [0x33_u8; 48];
+    let mut guard = SgxRollbackGuard::new(SgxMemoryCounter::new());
+    let old = guard.seal_data(b"state", &data).unwrap();
+    let unsealed = guard.unseal_data(&old).unwrap();
+    assert_eq!(unsealed.get_decrypt_txt(), &data[..]);
+    assert_eq!(unsealed.get_additional_txt(), b"state");
+
+    let new = guard.seal_data(b"state", &data).unwrap();
+    assert_eq!(guard.unseal_data(&old).err(), Some(sgx_status_t::SGX_ERROR_INVALID_STATE));
+    assert!(guard.unseal_data(&new).is_ok());
+
+    let mut reset = SgxRollbackGuard::new(SgxMemoryCounter::new());
+    assert_eq!(reset.unseal_data(&new).err(), Some(sgx_status_t::SGX_ERROR_MC_NOT_FOUND));
+
+    let mut other = SgxRollbackGuard::new(SgxMemoryCounter::with_value(2));
+    assert_eq!(other.get_mut().read().unwrap(), guard.get_mut().read().unwrap());
+    assert_eq!(other.unseal_data(&new).err(), Some(sgx_status_t::SGX_ERROR_MC_NOT_FOUND));
+}
@@ -2,6 +2,7 @@ use sgx_types::metadata::*;
 use sgx_types::*;
 
 use core::mem;
+use std::prelude::v1::*;
 
 pub fn check_metadata_size() {
     assert_eq!(mem::size_of::<layout_group_t>(), 32);
@@ -22,3 +23,61 @@ pub fn check_version() {
     assert_eq!(major_version_of_metadata!(curr_version), MAJOR_VERSION as u64);
     assert_eq!(minor_version_of_metadata!(curr_version), MINOR_VERSION as u64);
 }
+
+pub fn check_report_quote_views() {
+    let mut body = sgx_report_body_t::default();
+    body.misc_select = 0x1234_5678;
+    body.attributes.flags = SGX_FLAGS_INITTED | SGX_FLAGS_DEBUG | SGX_FLAGS_MODE64BIT;
+    body.attributes.xfrm = 0x3;
+    body.mr_enclave.m = [0xee; 32];
+    body.mr_signer.m = [0x55; 32];
+    body.isv_prod_id = 7;
+    body.isv_svn = 0x0102;
+    body.report_data.d[63] = 0xab;
+
+    let view = SgxReportBody::from_raw(&body);
+    assert_eq!(view.as_bytes().len(), SGX_REPORT_BODY_SIZE);
+    assert_eq!(view.misc_select(), 0x1234_5678);
+    assert!(view.attributes().debug());
+    assert!(!view.attributes().provision_key());
+    assert_eq!(view.attributes().xfrm, 0x3);
+    assert_eq!(view.mr_enclave().m, [0xee; 32]);
+    assert_eq!(view.mr_signer().m, [0x55; 32]);
+    assert_eq!(view.isv_prod_id(), 7);
+    assert_eq!(view.isv_svn(), 0x0102);
+    assert_eq!(view.report_data().d[63], 0xab);
+    assert_eq!(view.to_raw().isv_svn, 0x0102);
+    assert!(SgxReportBody::parse(&view.as_bytes()[1..]).is_err());
+
+    // An IAS quote body, then a full quote with its signature.
+    let mut quote = vec![0_u8; SGX_QUOTE_BODY_SIZE];
+    quote[0] = 2;
+    quote[2] = 1;
+    quote[10] = 9;
+    quote[48..SGX_QUOTE_BODY_SIZE].copy_from_slice(view.as_bytes());
+    let epid = SgxQuote::parse(&quote).unwrap();
+    assert_eq!(epid.version(), 2);
+    assert_eq!(epid.sign_type(), sgx_quote_sign_type_t::SGX_LINKABLE_SIGNATURE as u16);
+    assert_eq!(epid.pce_svn(), 9);
+    assert_eq!(epid.report_body().mr_enclave().m, [0xee; 32]);
+    assert!(epid.signature().is_empty());
+    quote.extend_from_slice(&[3, 0, 0, 0, 1, 2, 3]);
+    assert_eq!(SgxQuote::parse(&quote).unwrap().signature(), &[1, 2, 3]);
+    assert!(SgxQuote::parse(&quote[..quote.len() - 1]).is_err());
+    assert!(SgxQuote::parse(&quote[..SGX_QUOTE_BODY_SIZE - 1]).is_err());
+
+    let mut quote3 = vec![0_u8; SGX_QUOTE3_HEADER_SIZE];
+    quote3[0] = 3;
+    quote3[2] = 2;
+    quote3[12] = 0x93;
+    quote3.extend_from_slice(view.as_bytes());
+    quote3.extend_from_slice(&[2, 0, 0, 0, 0xaa, 0xbb, 0xcc]);
+    let ecdsa = SgxQuote3::parse(&quote3).unwrap();
+    assert_eq!(ecdsa.version(), 3);
+    assert_eq!(ecdsa.att_key_type(), 2);
+    assert_eq!(ecdsa.qe_vendor_id()[0], 0x93);
+    assert_eq!(ecdsa.report_body().isv_prod_id(), 7);
+    assert_eq!(ecdsa.signature_data(), &[0xaa, 0xbb]);
+    assert_eq!(ecdsa.signed_bytes().len(), SGX_QUOTE3_HEADER_SIZE + SGX_REPORT_BODY_SIZE);
+    assert!(SgxQuote3::parse(&quote3[..SGX_QUOTE3_HEADER_SIZE + SGX_REPORT_BODY_SIZE + 5]).is_err());
+}
@@ -20,7 +20,8 @@
 //! This crate provides helper functions to simplify key distribution and
 //! encryption/decryption. It utilizes sgx_tcrypto and sgx_ucrypto to provide
 //! a uniform interface to both enclave and untrusted app. It provides key
-//! serialization/deserialization by serde.
+//! serialization/deserialization by serde, and import/export in the PKCS#1,
+//! PKCS#8 and SubjectPublicKeyInfo DER/PEM formats.
 //!
 //! The Intel(R) Software Guard Extensions SDK includes a trusted cryptography
 //! library named sgx_tcrypto. It includes the cryptographic functions used by
@@ -67,6 +68,78 @@ pub trait RsaKeyPair {
     fn decrypt_buffer(self, ciphertext: &[u8], plaintext: &mut Vec<u8>) -> SgxResult<usize>;
 }
 
+/// A trait to express the ability to import and export a RSA private key in
+/// the PKCS#1 (RSAPrivateKey) and PKCS#8 (PrivateKeyInfo) formats, either DER
+/// encoded or PEM armored. Only unencrypted keys are supported. The encodings
+/// are returned in buffers which are wiped when dropped.
+pub trait RsaPrivKeyEncoding {
+    /// Encode the key as a DER RSAPrivateKey structure.
+    fn to_pkcs1_der(&self) -> SecretBytes;
+    /// Decode a DER RSAPrivateKey structure. The modulus must match the key size.
+    fn from_pkcs1_der(der: &[u8]) -> SgxResult<Self> where Self: std::marker::Sized;
+
+    /// Encode the key as a DER PrivateKeyInfo structure.
+    fn to_pkcs8_der(&self) -> SecretBytes {
+        pkcs::encode_pkcs8_private(&self.to_pkcs1_der())
+    }
+    /// Decode a DER PrivateKeyInfo structure holding a RSA key.
+    fn from_pkcs8_der(der: &[u8]) -> SgxResult<Self> where Self: std::marker::Sized {
+        Self::from_pkcs1_der(pkcs::decode_pkcs8_private(der)?)
+    }
+    /// Encode the key as a "RSA PRIVATE KEY" PEM block.
+    fn to_pkcs1_pem(&self) -> SecretString {
+        pkcs::pem_encode(pkcs::PEM_RSA_PRIVATE_KEY, &self.to_pkcs1_der())
+    }
+    /// Decode a "RSA PRIVATE KEY" PEM block.
+    fn from_pkcs1_pem(pem: &str) -> SgxResult<Self> where Self: std::marker::Sized {
+        Self::from_pkcs1_der(&pkcs::pem_decode(pkcs::PEM_RSA_PRIVATE_KEY, pem)?)
+    }
+    /// Encode the key as a "PRIVATE KEY" PEM block.
+    fn to_pkcs8_pem(&self) -> SecretString {
+        pkcs::pem_encode(pkcs::PEM_PRIVATE_KEY, &self.to_pkcs8_der())
+    }
+    /// Decode a "PRIVATE KEY" PEM block.
+    fn from_pkcs8_pem(pem: &str) -> SgxResult<Self> where Self: std::marker::Sized {
+        Self::from_pkcs8_der(&pkcs::pem_decode(pkcs::PEM_PRIVATE_KEY, pem)?)
+    }
+}
+
+/// A trait to express the ability to import and export a RSA public key in
+/// the PKCS#1 (RSAPublicKey) and X.509 SubjectPublicKeyInfo formats, either
+/// DER encoded or PEM armored.
+pub trait RsaPubKeyEncoding {
+    /// Encode the key as a DER RSAPublicKey structure.
+    fn to_pkcs1_der(&self) -> Vec<u8>;
+    /// Decode a DER RSAPublicKey structure. The modulus must match the key size.
+    fn from_pkcs1_der(der: &[u8]) -> SgxResult<Self> where Self: std::marker::Sized;
+
+    /// Encode the key as a DER SubjectPublicKeyInfo structure.
+    fn to_spki_der(&self) -> Vec<u8> {
+        pkcs::encode_spki(&self.to_pkcs1_der())
+    }
+    /// Decode a DER SubjectPublicKeyInfo structure holding a RSA key.
+    fn from_spki_der(der: &[u8]) -> SgxResult<Self> where Self: std::marker::Sized {
+        Self::from_pkcs1_der(pkcs::decode_spki(der)?)
+    }
+    /// Encode the key as a "RSA PUBLIC KEY" PEM block.
+    fn to_pkcs1_pem(&self) -> String {
+        pkcs::pem_encode(pkcs::PEM_RSA_PUBLIC_KEY, &self.to_pkcs1_der()).to_string()
+    }
+    /// Decode a "RSA PUBLIC KEY" PEM block.
+    fn from_pkcs1_pem(pem: &str) -> SgxResult<Self> where Self: std::marker::Sized {
+        Self::from_pkcs1_der(&pkcs::pem_decode(pkcs::PEM_RSA_PUBLIC_KEY, pem)?)
+    }
+    /// Encode the key as a "PUBLIC KEY" PEM block, as written by
+    /// `openssl rsa -pubout`.
+    fn to_spki_pem(&self) -> String {
+        pkcs::pem_encode(pkcs::PEM_PUBLIC_KEY, &self.to_spki_der()).to_string()
+    }
+    /// Decode a "PUBLIC KEY" PEM block.
+    fn from_spki_pem(pem: &str) -> SgxResult<Self> where Self: std::marker::Sized {
+        Self::from_spki_der(&pkcs::pem_decode(pkcs::PEM_PUBLIC_KEY, pem)?)
+    }
+}
+
 #[cfg(any(feature = "mesalock_sgx", target_env = "sgx"))]
 extern crate serde_sgx;
 #[cfg(not(any(feature = "mesalock_sgx", target_env = "sgx")))]
@@ -84,5 +157,7 @@ extern crate serde_big_array_sgx as serde_big_array;
 #[macro_use]
 extern crate serde_big_array;
 
+mod pkcs;
+pub use pkcs::{SecretBytes, SecretString};
 pub mod rsa2048;
 pub mod rsa3072;
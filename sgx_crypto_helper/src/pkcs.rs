@@ -0,0 +1,527 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//! DER and PEM encodings of RSA keys.
+//!
+//! The Intel SGX SDK keeps RSA key components as little endian byte arrays,
+//! while PKCS#1 (RFC 8017), PKCS#8 (RFC 5208) and SubjectPublicKeyInfo
+//! (RFC 5280) store them as big endian ASN.1 INTEGERs. This module contains
+//! the minimal DER reader/writer, PEM armor and base64 code needed to convert
+//! between the two.
+//!
+//! The encodings of private keys are written into buffers allocated at their
+//! final size, so that no copy is left behind by a reallocation, and are
+//! returned as `SecretBytes` or `SecretString`, which wipe them when dropped.
+
+use std::prelude::v1::*;
+use std::fmt;
+use std::ops::Deref;
+use sgx_types::{sgx_status_t, zeroize_bytes, SgxError, SgxResult};
+
+const TAG_INTEGER: u8 = 0x02;
+const TAG_BIT_STRING: u8 = 0x03;
+const TAG_OCTET_STRING: u8 = 0x04;
+const TAG_NULL: u8 = 0x05;
+const TAG_OID: u8 = 0x06;
+const TAG_SEQUENCE: u8 = 0x30;
+
+// 1.2.840.113549.1.1.1
+const OID_RSA_ENCRYPTION: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x01];
+
+pub(crate) const PEM_RSA_PRIVATE_KEY: &str = "RSA PRIVATE KEY";
+pub(crate) const PEM_PRIVATE_KEY: &str = "PRIVATE KEY";
+pub(crate) const PEM_RSA_PUBLIC_KEY: &str = "RSA PUBLIC KEY";
+pub(crate) const PEM_PUBLIC_KEY: &str = "PUBLIC KEY";
+
+/// Little endian components of an RSA private key, as used by sgx_tcrypto.
+pub(crate) struct RsaPrivateKeyRef<'a> {
+    pub n: &'a [u8],
+    pub e: &'a [u8],
+    pub d: &'a [u8],
+    pub p: &'a [u8],
+    pub q: &'a [u8],
+    pub dmp1: &'a [u8],
+    pub dmq1: &'a [u8],
+    pub iqmp: &'a [u8],
+}
+
+/// Output buffers for the little endian components of an RSA private key.
+pub(crate) struct RsaPrivateKeyMut<'a> {
+    pub n: &'a mut [u8],
+    pub e: &'a mut [u8],
+    pub d: &'a mut [u8],
+    pub p: &'a mut [u8],
+    pub q: &'a mut [u8],
+    pub dmp1: &'a mut [u8],
+    pub dmq1: &'a mut [u8],
+    pub iqmp: &'a mut [u8],
+}
+
+/// Bytes holding private key material, wiped when dropped.
+pub struct SecretBytes(Vec<u8>);
+
+impl Deref for SecretBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl AsRef<[u8]> for SecretBytes {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl fmt::Debug for SecretBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SecretBytes([REDACTED; {}])", self.0.len())
+    }
+}
+
+impl Drop for SecretBytes {
+    fn drop(&mut self) {
+        let capacity = self.0.capacity();
+        self.0.resize(capacity, 0);
+        zeroize_bytes(&mut self.0);
+    }
+}
+
+/// Text holding private key material, such as a PEM block, wiped when
+/// dropped.
+pub struct SecretString(SecretBytes);
+
+impl Deref for SecretString {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        // only ever built from ASCII
+        std::str::from_utf8(&self.0).unwrap_or_default()
+    }
+}
+
+impl AsRef<str> for SecretString {
+    fn as_ref(&self) -> &str {
+        self
+    }
+}
+
+impl fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SecretString([REDACTED; {}])", self.0.len())
+    }
+}
+
+fn invalid<T>() -> SgxResult<T> {
+    Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER)
+}
+
+fn header_len(len: usize) -> usize {
+    if len < 0x80 {
+        2
+    } else {
+        2 + 4 - (len as u32).leading_zeros() as usize / 8
+    }
+}
+
+fn tlv_len(len: usize) -> usize {
+    header_len(len) + len
+}
+
+/// The length of the value of a little endian unsigned integer written as a
+/// DER INTEGER, and whether it needs a leading zero.
+fn le_integer_len(le: &[u8]) -> (usize, bool) {
+    let magnitude = le.len() - le.iter().rev().take_while(|b| **b == 0).count();
+    if magnitude == 0 {
+        (1, true)
+    } else {
+        let pad = le[magnitude - 1] & 0x80 != 0;
+        (magnitude + pad as usize, pad)
+    }
+}
+
+fn write_header(tag: u8, len: usize, out: &mut Vec<u8>) {
+    out.push(tag);
+    if len < 0x80 {
+        out.push(len as u8);
+    } else {
+        let bytes = (len as u32).to_be_bytes();
+        let skip = bytes.iter().take_while(|b| **b == 0).count();
+        out.push(0x80 | (4 - skip) as u8);
+        out.extend_from_slice(&bytes[skip..]);
+    }
+}
+
+fn write_tlv(tag: u8, value: &[u8], out: &mut Vec<u8>) {
+    write_header(tag, value.len(), out);
+    out.extend_from_slice(value);
+}
+
+/// Writes a little endian unsigned integer as a DER INTEGER, without a
+/// temporary copy.
+fn write_le_integer(le: &[u8], out: &mut Vec<u8>) {
+    let (len, pad) = le_integer_len(le);
+    write_header(TAG_INTEGER, len, out);
+    if pad {
+        out.push(0);
+    }
+    out.extend(le[..len - pad as usize].iter().rev());
+}
+
+const RSA_ALGORITHM_LEN: usize = 2 + 2 + OID_RSA_ENCRYPTION.len() + 2;
+
+fn write_rsa_algorithm(out: &mut Vec<u8>) {
+    write_header(TAG_SEQUENCE, RSA_ALGORITHM_LEN - 2, out);
+    write_tlv(TAG_OID, OID_RSA_ENCRYPTION, out);
+    write_tlv(TAG_NULL, &[], out);
+}
+
+/// A cursor over DER encoded data.
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Reader<'a> {
+        Reader { data }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    fn peek_tag(&self) -> Option<u8> {
+        self.data.first().cloned()
+    }
+
+    fn read(&mut self, tag: u8) -> SgxResult<&'a [u8]> {
+        if self.data.len() < 2 || self.data[0] != tag {
+            return invalid();
+        }
+        let (len, header) = match self.data[1] {
+            l if l < 0x80 => (l as usize, 2),
+            l => {
+                let count = (l & 0x7f) as usize;
+                if count == 0 || count > 4 || self.data.len() < 2 + count {
+                    return invalid();
+                }
+                let len = self.data[2..2 + count]
+                    .iter()
+                    .fold(0_usize, |acc, b| (acc << 8) | *b as usize);
+                // DER requires the shortest length encoding
+                if len < 0x80 || self.data[2] == 0 {
+                    return invalid();
+                }
+                (len, 2 + count)
+            }
+        };
+        if self.data.len() - header < len {
+            return invalid();
+        }
+        let value = &self.data[header..header + len];
+        self.data = &self.data[header + len..];
+        Ok(value)
+    }
+
+    fn read_sequence(&mut self) -> SgxResult<Reader<'a>> {
+        self.read(TAG_SEQUENCE).map(Reader::new)
+    }
+
+    /// Reads a non-negative INTEGER and returns its big endian magnitude
+    /// without leading zeros.
+    fn read_unsigned(&mut self) -> SgxResult<&'a [u8]> {
+        let value = self.read(TAG_INTEGER)?;
+        if value.is_empty() || value[0] & 0x80 != 0 {
+            return invalid();
+        }
+        if value.len() > 1 && value[0] == 0 && value[1] & 0x80 == 0 {
+            return invalid();
+        }
+        let skip = value.iter().take_while(|b| **b == 0).count();
+        Ok(&value[skip..])
+    }
+
+    fn read_small(&mut self) -> SgxResult<u32> {
+        let value = self.read_unsigned()?;
+        if value.len() > 4 {
+            return invalid();
+        }
+        Ok(value.iter().fold(0, |acc, b| (acc << 8) | *b as u32))
+    }
+
+    fn finish(&self) -> SgxError {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            invalid()
+        }
+    }
+}
+
+fn read_rsa_algorithm(reader: &mut Reader<'_>) -> SgxError {
+    let mut alg = reader.read_sequence()?;
+    if alg.read(TAG_OID)? != OID_RSA_ENCRYPTION {
+        return invalid();
+    }
+    // the parameters must be NULL, but some encoders omit them
+    if !alg.is_empty() && !alg.read(TAG_NULL)?.is_empty() {
+        return invalid();
+    }
+    alg.finish()
+}
+
+/// Stores a big endian magnitude into a little endian buffer.
+fn be_to_le(be: &[u8], out: &mut [u8]) -> SgxError {
+    if be.len() > out.len() {
+        return invalid();
+    }
+    for b in out.iter_mut() {
+        *b = 0;
+    }
+    for (o, b) in out.iter_mut().zip(be.iter().rev()) {
+        *o = *b;
+    }
+    Ok(())
+}
+
+/// Encodes an RSAPrivateKey structure (PKCS#1).
+pub(crate) fn encode_pkcs1_private(key: &RsaPrivateKeyRef<'_>) -> SecretBytes {
+    let components = [key.n, key.e, key.d, key.p, key.q, key.dmp1, key.dmq1, key.iqmp];
+    let body_len = components
+        .iter()
+        .fold(tlv_len(1), |len, component| len + tlv_len(le_integer_len(component).0));
+    let mut out = SecretBytes(Vec::with_capacity(tlv_len(body_len)));
+    write_header(TAG_SEQUENCE, body_len, &mut out.0);
+    write_tlv(TAG_INTEGER, &[0], &mut out.0);
+    for component in components.iter() {
+        write_le_integer(component, &mut out.0);
+    }
+    out
+}
+
+/// Decodes an RSAPrivateKey structure (PKCS#1). The modulus must have exactly
+/// the size of `key.n`, and every other component must fit its buffer.
+pub(crate) fn decode_pkcs1_private(der: &[u8], key: &mut RsaPrivateKeyMut<'_>) -> SgxError {
+    let mut outer = Reader::new(der);
+    let mut seq = outer.read_sequence()?;
+    outer.finish()?;
+
+    // version 1 (multi-prime) keys are not supported
+    if seq.read_small()? != 0 {
+        return invalid();
+    }
+    let n = seq.read_unsigned()?;
+    if n.len() != key.n.len() {
+        return invalid();
+    }
+    be_to_le(n, key.n)?;
+    be_to_le(seq.read_unsigned()?, key.e)?;
+    be_to_le(seq.read_unsigned()?, key.d)?;
+    be_to_le(seq.read_unsigned()?, key.p)?;
+    be_to_le(seq.read_unsigned()?, key.q)?;
+    be_to_le(seq.read_unsigned()?, key.dmp1)?;
+    be_to_le(seq.read_unsigned()?, key.dmq1)?;
+    be_to_le(seq.read_unsigned()?, key.iqmp)?;
+    seq.finish()
+}
+
+/// Wraps an RSAPrivateKey into a PrivateKeyInfo structure (PKCS#8).
+pub(crate) fn encode_pkcs8_private(pkcs1: &[u8]) -> SecretBytes {
+    let body_len = tlv_len(1) + RSA_ALGORITHM_LEN + tlv_len(pkcs1.len());
+    let mut out = SecretBytes(Vec::with_capacity(tlv_len(body_len)));
+    write_header(TAG_SEQUENCE, body_len, &mut out.0);
+    write_tlv(TAG_INTEGER, &[0], &mut out.0);
+    write_rsa_algorithm(&mut out.0);
+    write_tlv(TAG_OCTET_STRING, pkcs1, &mut out.0);
+    out
+}
+
+/// Extracts the RSAPrivateKey from a PrivateKeyInfo or OneAsymmetricKey
+/// structure (PKCS#8, RFC 5958). Optional attributes are ignored.
+pub(crate) fn decode_pkcs8_private(der: &[u8]) -> SgxResult<&[u8]> {
+    let mut outer = Reader::new(der);
+    let mut seq = outer.read_sequence()?;
+    outer.finish()?;
+
+    if seq.read_small()? > 1 {
+        return invalid();
+    }
+    read_rsa_algorithm(&mut seq)?;
+    let pkcs1 = seq.read(TAG_OCTET_STRING)?;
+    while let Some(tag) = seq.peek_tag() {
+        // [0] attributes, [1] publicKey
+        if tag & 0xc0 != 0x80 {
+            return invalid();
+        }
+        seq.read(tag)?;
+    }
+    Ok(pkcs1)
+}
+
+/// Encodes an RSAPublicKey structure (PKCS#1).
+pub(crate) fn encode_pkcs1_public(n: &[u8], e: &[u8]) -> Vec<u8> {
+    let mut body = Vec::new();
+    write_le_integer(n, &mut body);
+    write_le_integer(e, &mut body);
+    let mut out = Vec::new();
+    write_tlv(TAG_SEQUENCE, &body, &mut out);
+    out
+}
+
+/// Decodes an RSAPublicKey structure (PKCS#1). The modulus must have exactly
+/// the size of `n`.
+pub(crate) fn decode_pkcs1_public(der: &[u8], n: &mut [u8], e: &mut [u8]) -> SgxError {
+    let mut outer = Reader::new(der);
+    let mut seq = outer.read_sequence()?;
+    outer.finish()?;
+
+    let modulus = seq.read_unsigned()?;
+    if modulus.len() != n.len() {
+        return invalid();
+    }
+    be_to_le(modulus, n)?;
+    be_to_le(seq.read_unsigned()?, e)?;
+    seq.finish()
+}
+
+/// Wraps an RSAPublicKey into a SubjectPublicKeyInfo structure.
+pub(crate) fn encode_spki(pkcs1: &[u8]) -> Vec<u8> {
+    let mut bits = Vec::with_capacity(pkcs1.len() + 1);
+    bits.push(0);
+    bits.extend_from_slice(pkcs1);
+
+    let mut body = Vec::new();
+    write_rsa_algorithm(&mut body);
+    write_tlv(TAG_BIT_STRING, &bits, &mut body);
+    let mut out = Vec::new();
+    write_tlv(TAG_SEQUENCE, &body, &mut out);
+    out
+}
+
+/// Extracts the RSAPublicKey from a SubjectPublicKeyInfo structure.
+pub(crate) fn decode_spki(der: &[u8]) -> SgxResult<&[u8]> {
+    let mut outer = Reader::new(der);
+    let mut seq = outer.read_sequence()?;
+    outer.finish()?;
+
+    read_rsa_algorithm(&mut seq)?;
+    let bits = seq.read(TAG_BIT_STRING)?;
+    seq.finish()?;
+    match bits.split_first() {
+        Some((0, pkcs1)) => Ok(pkcs1),
+        _ => invalid(),
+    }
+}
+
+const BASE64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Writes `data` in base64, with a line break after every 64 characters and
+/// at the end.
+fn base64_encode_lines(data: &[u8], out: &mut Vec<u8>) {
+    for (i, chunk) in data.chunks(3).enumerate() {
+        base64_encode_chunk(chunk, out);
+        if i % 16 == 15 || (i + 1) * 3 >= data.len() {
+            out.push(b'\n');
+        }
+    }
+}
+
+fn base64_encode_chunk(chunk: &[u8], out: &mut Vec<u8>) {
+    let b = [
+        chunk[0],
+        chunk.get(1).cloned().unwrap_or(0),
+        chunk.get(2).cloned().unwrap_or(0),
+    ];
+    let v = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+    for i in 0..4 {
+        if i <= chunk.len() {
+            out.push(BASE64_ALPHABET[(v >> (18 - 6 * i) & 0x3f) as usize]);
+        } else {
+            out.push(b'=');
+        }
+    }
+}
+
+fn base64_decode(text: &str) -> SgxResult<SecretBytes> {
+    // every character carries at most 6 bits
+    let mut out = SecretBytes(Vec::with_capacity(text.len() / 4 * 3 + 3));
+    let mut acc = 0_u32;
+    let mut bits = 0;
+    let mut padding = 0;
+    for c in text.bytes().filter(|c| !c.is_ascii_whitespace()) {
+        let v = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            b'=' => {
+                padding += 1;
+                continue;
+            }
+            _ => return invalid(),
+        };
+        if padding > 0 {
+            return invalid();
+        }
+        acc = (acc << 6) | v as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.0.push((acc >> bits) as u8);
+        }
+    }
+    if padding > 2 || bits >= 6 {
+        return invalid();
+    }
+    Ok(out)
+}
+
+/// Armors DER data with the given PEM label (RFC 7468).
+pub(crate) fn pem_encode(label: &str, der: &[u8]) -> SecretString {
+    let body_len = (der.len() + 2) / 3 * 4;
+    let len = "-----BEGIN -----\n".len()
+        + "-----END -----\n".len()
+        + 2 * label.len()
+        + body_len
+        + (body_len + 63) / 64;
+    let mut pem = SecretBytes(Vec::with_capacity(len));
+    for part in ["-----BEGIN ", label, "-----\n"].iter() {
+        pem.0.extend_from_slice(part.as_bytes());
+    }
+    base64_encode_lines(der, &mut pem.0);
+    for part in ["-----END ", label, "-----\n"].iter() {
+        pem.0.extend_from_slice(part.as_bytes());
+    }
+    SecretString(pem)
+}
+
+/// Extracts the DER data of the first PEM block with the given label.
+pub(crate) fn pem_decode(label: &str, pem: &str) -> SgxResult<SecretBytes> {
+    let begin = format!("-----BEGIN {}-----", label);
+    let end = format!("-----END {}-----", label);
+    let start = match pem.find(&begin) {
+        Some(pos) => pos + begin.len(),
+        None => return invalid(),
+    };
+    let stop = match pem[start..].find(&end) {
+        Some(pos) => start + pos,
+        None => return invalid(),
+    };
+    base64_decode(&pem[start..stop])
+}
@@ -0,0 +1,716 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//!
+//! Authenticated Encryption with Associated Data
+//!
+//! libsgx_tcrypto.a only offers AES-GCM with 128-bit keys, and only in the
+//! encryption direction for streams. This module adds AES-256-GCM and
+//! ChaCha20-Poly1305, implemented in Rust, behind the same one-shot and
+//! Init, Update … Update, Final interfaces as the rsgx_rijndael128GCM_*
+//! functions and SgxAesHandle, and the SgxAesDecHandle streaming decryptor.
+//! sgx_tcrypto and sgx_ucrypto are built from this same source, so data can
+//! be encrypted on one side of the enclave boundary and decrypted on the
+//! other.
+//!
+
+use crate::primitive::aes::aesni_available;
+use crate::primitive::chacha20_poly1305::ChaCha20Poly1305;
+use crate::primitive::ct_eq;
+use crate::primitive::gcm::AesGcm;
+use core::cell::RefCell;
+use core::ops::Drop;
+use sgx_types::*;
+
+enum AeadState {
+    AesGcm(AesGcm),
+    ChaCha20Poly1305(ChaCha20Poly1305),
+}
+
+impl AeadState {
+    fn new(alg: sgx_aead_algorithm_t, key: &[u8], iv: &[u8], aad: &[u8]) -> SgxResult<AeadState> {
+        let state = match alg {
+            sgx_aead_algorithm_t::SGX_AEAD_AES_256_GCM => {
+                check_aesni()?;
+                AesGcm::new(key, iv, aad).map(AeadState::AesGcm)
+            }
+            sgx_aead_algorithm_t::SGX_AEAD_CHACHA20_POLY1305 => {
+                ChaCha20Poly1305::new(key, iv, aad).map(AeadState::ChaCha20Poly1305)
+            }
+        };
+        state.ok_or(sgx_status_t::SGX_ERROR_INVALID_PARAMETER)
+    }
+
+    fn encrypt_update(&mut self, src: &[u8], dst: &mut [u8]) -> bool {
+        match self {
+            AeadState::AesGcm(s) => s.encrypt_update(src, dst),
+            AeadState::ChaCha20Poly1305(s) => s.encrypt_update(src, dst),
+        }
+    }
+
+    fn decrypt_update(&mut self, src: &[u8], dst: &mut [u8]) -> bool {
+        match self {
+            AeadState::AesGcm(s) => s.decrypt_update(src, dst),
+            AeadState::ChaCha20Poly1305(s) => s.decrypt_update(src, dst),
+        }
+    }
+
+    fn authenticate(&mut self, src: &[u8]) -> bool {
+        match self {
+            AeadState::AesGcm(s) => s.authenticate(src),
+            AeadState::ChaCha20Poly1305(s) => s.authenticate(src),
+        }
+    }
+
+    fn decrypt_authenticated(&mut self, src: &[u8], dst: &mut [u8]) {
+        match self {
+            AeadState::AesGcm(s) => s.decrypt_authenticated(src, dst),
+            AeadState::ChaCha20Poly1305(s) => s.decrypt_authenticated(src, dst),
+        }
+    }
+
+    fn tag(&self) -> sgx_mac_128bit_t {
+        match self {
+            AeadState::AesGcm(s) => s.tag(),
+            AeadState::ChaCha20Poly1305(s) => s.tag(),
+        }
+    }
+}
+
+fn check_aesni() -> SgxError {
+    if !aesni_available() {
+        return Err(sgx_status_t::SGX_ERROR_FEATURE_NOT_SUPPORTED);
+    }
+    Ok(())
+}
+
+fn check_iv(iv: &[u8]) -> SgxError {
+    if iv.len() != SGX_AESGCM_IV_SIZE {
+        return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+    }
+    Ok(())
+}
+
+///
+/// rsgx_aead_encrypt performs an AEAD encryption operation with a 256-bit key.
+///
+/// # Description
+///
+/// Both supported algorithms take a 256-bit key and a 96-bit IV (nonce), and
+/// produce a 128-bit MAC computed over the ciphertext and the additional
+/// authentication data. An IV must never be reused with the same key.
+///
+/// # Parameters
+///
+/// **alg**
+///
+/// The AEAD algorithm, SGX_AEAD_AES_256_GCM or SGX_AEAD_CHACHA20_POLY1305.
+///
+/// **key**
+///
+/// A pointer to the 256-bit key to be used in the encryption operation.
+///
+/// **src**
+///
+/// A pointer to the input data stream to be encrypted. Buffer content could be empty.
+///
+/// **iv**
+///
+/// A pointer to the initialization vector. The size must be 96 bits (12 bytes).
+///
+/// **aad**
+///
+/// A pointer to an optional additional authentication data buffer which is used in the MAC calculation.
+/// The data in this buffer will not be encrypted. The field is optional and content could be empty.
+///
+/// **dst**
+///
+/// A pointer to the output encrypted data buffer. It must be at least as large as src.
+///
+/// **mac**
+///
+/// This is the output MAC computed over the encrypted data as well as the additional authentication data.
+///
+/// # Errors
+///
+/// **SGX_ERROR_INVALID_PARAMETER**
+///
+/// If IV Length is not equal to 12 (bytes).
+///
+/// If dst is smaller than src, or src is longer than the algorithm allows for a single IV.
+///
+/// **SGX_ERROR_FEATURE_NOT_SUPPORTED**
+///
+/// The algorithm is AES-256-GCM and the processor does not implement AES-NI.
+///
+pub fn rsgx_aead_encrypt(
+    alg: sgx_aead_algorithm_t,
+    key: &sgx_key_256bit_t,
+    src: &[u8],
+    iv: &[u8],
+    aad: &[u8],
+    dst: &mut [u8],
+    mac: &mut sgx_mac_128bit_t,
+) -> SgxError {
+    check_iv(iv)?;
+    if dst.len() < src.len() {
+        return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+    }
+
+    let mut state = AeadState::new(alg, key, iv, aad)?;
+    if !state.encrypt_update(src, dst) {
+        return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+    }
+    *mac = state.tag();
+    Ok(())
+}
+
+///
+/// rsgx_aead_decrypt performs an AEAD decryption operation with a 256-bit key.
+///
+/// # Description
+///
+/// The MAC is verified over the whole ciphertext before anything is written to
+/// dst; if verification fails, dst is left untouched.
+///
+/// # Parameters
+///
+/// **alg**
+///
+/// The AEAD algorithm that was used for the encryption.
+///
+/// **key**
+///
+/// A pointer to the 256-bit key to be used in the decryption operation.
+///
+/// **src**
+///
+/// A pointer to the input data stream to be decrypted. Buffer content could be empty.
+///
+/// **iv**
+///
+/// A pointer to the initialization vector. The size must be 96 bits (12 bytes).
+///
+/// **aad**
+///
+/// A pointer to the additional authentication data that was provided when encrypting.
+///
+/// **mac**
+///
+/// The MAC produced by the encryption operation.
+///
+/// **dst**
+///
+/// A pointer to the output decrypted data buffer. It must be at least as large as src.
+///
+/// # Errors
+///
+/// **SGX_ERROR_INVALID_PARAMETER**
+///
+/// If IV Length is not equal to 12 (bytes).
+///
+/// If dst is smaller than src, or src is longer than the algorithm allows for a single IV.
+///
+/// **SGX_ERROR_FEATURE_NOT_SUPPORTED**
+///
+/// The algorithm is AES-256-GCM and the processor does not implement AES-NI.
+///
+/// **SGX_ERROR_MAC_MISMATCH**
+///
+/// The input MAC does not match the MAC calculated.
+///
+pub fn rsgx_aead_decrypt(
+    alg: sgx_aead_algorithm_t,
+    key: &sgx_key_256bit_t,
+    src: &[u8],
+    iv: &[u8],
+    aad: &[u8],
+    mac: &sgx_mac_128bit_t,
+    dst: &mut [u8],
+) -> SgxError {
+    check_iv(iv)?;
+    if dst.len() < src.len() {
+        return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+    }
+
+    let mut state = AeadState::new(alg, key, iv, aad)?;
+    if !state.authenticate(src) {
+        return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+    }
+    if !ct_eq(&state.tag(), mac) {
+        return Err(sgx_status_t::SGX_ERROR_MAC_MISMATCH);
+    }
+    state.decrypt_authenticated(src, dst);
+    Ok(())
+}
+
+///
+/// rsgx_aes256GCM_encrypt performs an AES-GCM encryption operation with a 256-bit key.
+///
+/// See rsgx_aead_encrypt for a description of the parameters.
+///
+pub fn rsgx_aes256GCM_encrypt(
+    key: &sgx_aes_gcm_256bit_key_t,
+    src: &[u8],
+    iv: &[u8],
+    aad: &[u8],
+    dst: &mut [u8],
+    mac: &mut sgx_aes_gcm_256bit_tag_t,
+) -> SgxError {
+    rsgx_aead_encrypt(
+        sgx_aead_algorithm_t::SGX_AEAD_AES_256_GCM,
+        key,
+        src,
+        iv,
+        aad,
+        dst,
+        mac,
+    )
+}
+
+///
+/// rsgx_aes256GCM_decrypt performs an AES-GCM decryption operation with a 256-bit key.
+///
+/// See rsgx_aead_decrypt for a description of the parameters.
+///
+pub fn rsgx_aes256GCM_decrypt(
+    key: &sgx_aes_gcm_256bit_key_t,
+    src: &[u8],
+    iv: &[u8],
+    aad: &[u8],
+    mac: &sgx_aes_gcm_256bit_tag_t,
+    dst: &mut [u8],
+) -> SgxError {
+    rsgx_aead_decrypt(
+        sgx_aead_algorithm_t::SGX_AEAD_AES_256_GCM,
+        key,
+        src,
+        iv,
+        aad,
+        mac,
+        dst,
+    )
+}
+
+///
+/// rsgx_chacha20_poly1305_encrypt performs a ChaCha20-Poly1305 encryption operation (RFC 8439).
+///
+/// See rsgx_aead_encrypt for a description of the parameters.
+///
+pub fn rsgx_chacha20_poly1305_encrypt(
+    key: &sgx_chacha20_poly1305_key_t,
+    src: &[u8],
+    iv: &[u8],
+    aad: &[u8],
+    dst: &mut [u8],
+    mac: &mut sgx_chacha20_poly1305_tag_t,
+) -> SgxError {
+    rsgx_aead_encrypt(
+        sgx_aead_algorithm_t::SGX_AEAD_CHACHA20_POLY1305,
+        key,
+        src,
+        iv,
+        aad,
+        dst,
+        mac,
+    )
+}
+
+///
+/// rsgx_chacha20_poly1305_decrypt performs a ChaCha20-Poly1305 decryption operation (RFC 8439).
+///
+/// See rsgx_aead_decrypt for a description of the parameters.
+///
+pub fn rsgx_chacha20_poly1305_decrypt(
+    key: &sgx_chacha20_poly1305_key_t,
+    src: &[u8],
+    iv: &[u8],
+    aad: &[u8],
+    mac: &sgx_chacha20_poly1305_tag_t,
+    dst: &mut [u8],
+) -> SgxError {
+    rsgx_aead_decrypt(
+        sgx_aead_algorithm_t::SGX_AEAD_CHACHA20_POLY1305,
+        key,
+        src,
+        iv,
+        aad,
+        mac,
+        dst,
+    )
+}
+
+///
+/// AEAD encryption context state.
+///
+/// This is the AES-256-GCM / ChaCha20-Poly1305 counterpart of SgxAesHandle: it
+/// encrypts a message that arrives in several pieces. The pieces may have any
+/// length, and the concatenated output equals that of rsgx_aead_encrypt.
+///
+pub struct SgxAeadHandle {
+    state: RefCell<Option<AeadState>>,
+}
+
+impl SgxAeadHandle {
+    ///
+    /// Constructs a new, empty SgxAeadHandle.
+    ///
+    pub fn new() -> SgxAeadHandle {
+        SgxAeadHandle {
+            state: RefCell::new(None),
+        }
+    }
+
+    ///
+    /// init sets up the key, the IV and the additional authentication data.
+    ///
+    /// Calling init on a handle which is already initialized has no effect.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_PARAMETER**
+    ///
+    /// If IV Length is not equal to 12 (bytes).
+    ///
+    /// **SGX_ERROR_FEATURE_NOT_SUPPORTED**
+    ///
+    /// The algorithm is AES-256-GCM and the processor does not implement AES-NI.
+    ///
+    pub fn init(
+        &self,
+        alg: sgx_aead_algorithm_t,
+        key: &sgx_key_256bit_t,
+        iv: &[u8],
+        aad: &[u8],
+    ) -> SgxError {
+        if self.state.borrow().is_some() {
+            return Ok(());
+        }
+        check_iv(iv)?;
+        *self.state.borrow_mut() = Some(AeadState::new(alg, key, iv, aad)?);
+        Ok(())
+    }
+
+    ///
+    /// update encrypts the next piece of the message into dst.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_PARAMETER**
+    ///
+    /// If dst is smaller than src, or the total message length exceeds what the algorithm allows.
+    ///
+    /// **SGX_ERROR_INVALID_STATE**
+    ///
+    /// The handle is not initialized.
+    ///
+    pub fn update(&self, src: &[u8], dst: &mut [u8]) -> SgxError {
+        let mut state = self.state.borrow_mut();
+        let state = state.as_mut().ok_or(sgx_status_t::SGX_ERROR_INVALID_STATE)?;
+        if dst.len() < src.len() {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+        if !state.encrypt_update(src, dst) {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+        Ok(())
+    }
+
+    ///
+    /// get_mac returns the MAC over the data encrypted so far.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_STATE**
+    ///
+    /// The handle is not initialized.
+    ///
+    pub fn get_mac(&self) -> SgxResult<sgx_mac_128bit_t> {
+        let state = self.state.borrow();
+        let state = state.as_ref().ok_or(sgx_status_t::SGX_ERROR_INVALID_STATE)?;
+        Ok(state.tag())
+    }
+
+    pub fn get_align_mac(&self) -> SgxResult<sgx_align_mac_128bit_t> {
+        let mut align_mac = sgx_align_mac_128bit_t::default();
+        align_mac.mac = self.get_mac()?;
+        Ok(align_mac)
+    }
+
+    ///
+    /// close wipes the key material held by the handle.
+    ///
+    pub fn close(&self) -> SgxError {
+        *self.state.borrow_mut() = None;
+        Ok(())
+    }
+}
+
+impl Default for SgxAeadHandle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for SgxAeadHandle {
+    fn drop(&mut self) {
+        let _ = self.close();
+    }
+}
+
+///
+/// AES-GCM decryption context state.
+///
+/// This is the decryption counterpart of SgxAesHandle. It decrypts a ciphertext
+/// that arrives in pieces of any length, so the whole ciphertext never has to
+/// be resident in enclave memory at once, and checks the MAC at the end.
+///
+/// The plaintext returned by update is not authenticated until verify_mac
+/// succeeds. Callers must not act on it, and must discard it if verification
+/// fails.
+///
+pub struct SgxAesDecHandle {
+    state: RefCell<Option<AesGcm>>,
+}
+
+impl SgxAesDecHandle {
+    ///
+    /// Constructs a new, empty SgxAesDecHandle.
+    ///
+    pub fn new() -> SgxAesDecHandle {
+        SgxAesDecHandle {
+            state: RefCell::new(None),
+        }
+    }
+
+    ///
+    /// init sets up the key, the IV and the additional authentication data.
+    ///
+    /// Calling init on a handle which is already initialized has no effect.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_PARAMETER**
+    ///
+    /// If IV Length is not equal to 12 (bytes).
+    ///
+    /// **SGX_ERROR_FEATURE_NOT_SUPPORTED**
+    ///
+    /// The processor does not implement AES-NI.
+    ///
+    pub fn init(&self, key: &sgx_aes_gcm_128bit_key_t, iv: &[u8], aad: &[u8]) -> SgxError {
+        if self.state.borrow().is_some() {
+            return Ok(());
+        }
+        check_aesni()?;
+        let state = AesGcm::new(key, iv, aad).ok_or(sgx_status_t::SGX_ERROR_INVALID_PARAMETER)?;
+        *self.state.borrow_mut() = Some(state);
+        Ok(())
+    }
+
+    ///
+    /// update decrypts the next piece of the ciphertext into dst.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_PARAMETER**
+    ///
+    /// If dst is smaller than src, or the total ciphertext exceeds the GCM limit of 2^39 - 256 bits.
+    ///
+    /// **SGX_ERROR_INVALID_STATE**
+    ///
+    /// The handle is not initialized.
+    ///
+    pub fn update(&self, src: &[u8], dst: &mut [u8]) -> SgxError {
+        let mut state = self.state.borrow_mut();
+        let state = state.as_mut().ok_or(sgx_status_t::SGX_ERROR_INVALID_STATE)?;
+        if dst.len() < src.len() {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+        if !state.decrypt_update(src, dst) {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+        Ok(())
+    }
+
+    ///
+    /// verify_mac compares, in constant time, the expected MAC with the one
+    /// computed over all the ciphertext passed to update.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_STATE**
+    ///
+    /// The handle is not initialized.
+    ///
+    /// **SGX_ERROR_MAC_MISMATCH**
+    ///
+    /// The input MAC does not match the MAC calculated.
+    ///
+    pub fn verify_mac(&self, mac: &sgx_aes_gcm_128bit_tag_t) -> SgxError {
+        let state = self.state.borrow();
+        let state = state.as_ref().ok_or(sgx_status_t::SGX_ERROR_INVALID_STATE)?;
+        if ct_eq(&state.tag(), mac) {
+            Ok(())
+        } else {
+            Err(sgx_status_t::SGX_ERROR_MAC_MISMATCH)
+        }
+    }
+
+    pub fn verify_align_mac(&self, mac: &sgx_align_mac_128bit_t) -> SgxError {
+        self.verify_mac(&mac.mac)
+    }
+
+    ///
+    /// close wipes the key material held by the handle.
+    ///
+    pub fn close(&self) -> SgxError {
+        *self.state.borrow_mut() = None;
+        Ok(())
+    }
+}
+
+impl Default for SgxAesDecHandle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for SgxAesDecHandle {
+    fn drop(&mut self) {
+        let _ = self.close();
+    }
+}
+
+///
+/// AEAD decryption context state.
+///
+/// The decryption counterpart of SgxAeadHandle. As with SgxAesDecHandle, the
+/// plaintext returned by update must not be trusted until verify_mac succeeds.
+///
+pub struct SgxAeadDecHandle {
+    state: RefCell<Option<AeadState>>,
+}
+
+impl SgxAeadDecHandle {
+    ///
+    /// Constructs a new, empty SgxAeadDecHandle.
+    ///
+    pub fn new() -> SgxAeadDecHandle {
+        SgxAeadDecHandle {
+            state: RefCell::new(None),
+        }
+    }
+
+    ///
+    /// init sets up the key, the IV and the additional authentication data.
+    ///
+    /// Calling init on a handle which is already initialized has no effect.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_PARAMETER**
+    ///
+    /// If IV Length is not equal to 12 (bytes).
+    ///
+    /// **SGX_ERROR_FEATURE_NOT_SUPPORTED**
+    ///
+    /// The algorithm is AES-256-GCM and the processor does not implement AES-NI.
+    ///
+    pub fn init(
+        &self,
+        alg: sgx_aead_algorithm_t,
+        key: &sgx_key_256bit_t,
+        iv: &[u8],
+        aad: &[u8],
+    ) -> SgxError {
+        if self.state.borrow().is_some() {
+            return Ok(());
+        }
+        check_iv(iv)?;
+        *self.state.borrow_mut() = Some(AeadState::new(alg, key, iv, aad)?);
+        Ok(())
+    }
+
+    ///
+    /// update decrypts the next piece of the ciphertext into dst.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_PARAMETER**
+    ///
+    /// If dst is smaller than src, or the total ciphertext length exceeds what the algorithm allows.
+    ///
+    /// **SGX_ERROR_INVALID_STATE**
+    ///
+    /// The handle is not initialized.
+    ///
+    pub fn update(&self, src: &[u8], dst: &mut [u8]) -> SgxError {
+        let mut state = self.state.borrow_mut();
+        let state = state.as_mut().ok_or(sgx_status_t::SGX_ERROR_INVALID_STATE)?;
+        if dst.len() < src.len() {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+        if !state.decrypt_update(src, dst) {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+        Ok(())
+    }
+
+    ///
+    /// verify_mac checks, in constant time, the MAC over all the ciphertext passed to update.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_STATE**
+    ///
+    /// The handle is not initialized.
+    ///
+    /// **SGX_ERROR_MAC_MISMATCH**
+    ///
+    /// The input MAC does not match the MAC calculated.
+    ///
+    pub fn verify_mac(&self, mac: &sgx_mac_128bit_t) -> SgxError {
+        let state = self.state.borrow();
+        let state = state.as_ref().ok_or(sgx_status_t::SGX_ERROR_INVALID_STATE)?;
+        if ct_eq(&state.tag(), mac) {
+            Ok(())
+        } else {
+            Err(sgx_status_t::SGX_ERROR_MAC_MISMATCH)
+        }
+    }
+
+    ///
+    /// close wipes the key material held by the handle.
+    ///
+    pub fn close(&self) -> SgxError {
+        *self.state.borrow_mut() = None;
+        Ok(())
+    }
+}
+
+impl Default for SgxAeadDecHandle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for SgxAeadDecHandle {
+    fn drop(&mut self) {
+        let _ = self.close();
+    }
+}
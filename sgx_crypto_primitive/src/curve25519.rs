@@ -0,0 +1,314 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//!
+//! Ed25519 signatures and X25519 key agreement
+//!
+//! SgxEccHandle only covers NIST P-256. The functions below implement the
+//! Curve25519 based schemes of RFC 8032 and RFC 7748 in Rust. They need no
+//! handle since there are no domain parameters to set up, and the key and
+//! signature types are plain C structures like sgx_ec256_*, so they can be
+//! sealed with sgx_tseal.
+//!
+
+use crate::primitive::curve25519::{x25519, X25519_BASEPOINT};
+use crate::primitive::ed25519;
+use core::mem;
+use core::slice;
+use sgx_types::marker::ContiguousMemory;
+use sgx_types::*;
+
+fn read_rand(buf: &mut [u8]) -> SgxError {
+    let ret = unsafe { sgx_read_rand(buf.as_mut_ptr(), buf.len()) };
+    match ret {
+        sgx_status_t::SGX_SUCCESS => Ok(()),
+        _ => Err(ret),
+    }
+}
+
+fn signature_bytes(signature: &sgx_ed25519_signature_t) -> [u8; 64] {
+    let mut sig = [0_u8; 64];
+    sig[..SGX_ED25519_KEY_SIZE].copy_from_slice(&signature.r);
+    sig[SGX_ED25519_KEY_SIZE..].copy_from_slice(&signature.s);
+    sig
+}
+
+fn ed25519_sign(data: &[u8], private: &sgx_ed25519_private_t) -> sgx_ed25519_signature_t {
+    let sig = ed25519::sign(&private.seed, data);
+    let mut signature = sgx_ed25519_signature_t::default();
+    signature.r.copy_from_slice(&sig[..SGX_ED25519_KEY_SIZE]);
+    signature.s.copy_from_slice(&sig[SGX_ED25519_KEY_SIZE..]);
+    signature
+}
+
+///
+/// rsgx_ed25519_create_key_pair generates an Ed25519 private/public key pair.
+///
+/// # Description
+///
+/// The private key is a 32-byte seed read from sgx_read_rand, and the public
+/// key is the encoded point [s]B derived from it as specified in RFC 8032.
+///
+/// # Requirements
+///
+/// Library: libsgx_tcrypto.a
+///
+/// # Return value
+///
+/// The private and the public key. The private key is overwritten with
+/// zeros when it is dropped.
+///
+/// # Errors
+///
+/// **SGX_ERROR_UNEXPECTED**
+///
+/// The random number generator failed.
+///
+pub fn rsgx_ed25519_create_key_pair() -> SgxResult<(SgxEd25519PrivateSecret, sgx_ed25519_public_t)> {
+    let mut private = SgxEd25519PrivateSecret::zeroed();
+    read_rand(&mut private.seed)?;
+    let public = rsgx_ed25519_calculate_public(&private);
+    Ok((private, public))
+}
+
+///
+/// rsgx_ed25519_calculate_public derives the public key of an Ed25519 private key.
+///
+pub fn rsgx_ed25519_calculate_public(private: &sgx_ed25519_private_t) -> sgx_ed25519_public_t {
+    sgx_ed25519_public_t {
+        key: ed25519::public_key(&private.seed),
+    }
+}
+
+///
+/// rsgx_ed25519_sign_msg computes an Ed25519 signature over an input dataset.
+///
+/// # Description
+///
+/// Ed25519 signatures are deterministic: signing the same data with the same
+/// key always produces the same signature, and no random numbers are needed.
+///
+/// # Parameters
+///
+/// **data**
+///
+/// The dataset to sign.
+///
+/// **private**
+///
+/// The private key used to compute the signature.
+///
+/// # Requirements
+///
+/// Library: libsgx_tcrypto.a
+///
+/// # Return value
+///
+/// The signature (R, S).
+///
+/// # Errors
+///
+/// **SGX_ERROR_INVALID_PARAMETER**
+///
+/// The size of T is zero.
+///
+pub fn rsgx_ed25519_sign_msg<T>(data: &T, private: &sgx_ed25519_private_t) -> SgxResult<sgx_ed25519_signature_t>
+where
+    T: Copy + ContiguousMemory,
+{
+    let size = mem::size_of::<T>();
+    if size == 0 {
+        return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+    }
+    let data = unsafe { slice::from_raw_parts(data as *const _ as *const u8, size) };
+    Ok(ed25519_sign(data, private))
+}
+
+///
+/// rsgx_ed25519_sign_slice computes an Ed25519 signature over an input dataset.
+///
+/// Unlike the other slice functions of this crate, an empty slice is a valid
+/// message. See rsgx_ed25519_sign_msg.
+///
+pub fn rsgx_ed25519_sign_slice<T>(data: &[T], private: &sgx_ed25519_private_t) -> SgxResult<sgx_ed25519_signature_t>
+where
+    T: Copy + ContiguousMemory,
+{
+    let data = unsafe { slice::from_raw_parts(data.as_ptr() as *const u8, mem::size_of_val(data)) };
+    Ok(ed25519_sign(data, private))
+}
+
+///
+/// rsgx_ed25519_verify_msg verifies an Ed25519 signature over an input dataset.
+///
+/// # Description
+///
+/// The signature is rejected if S is not reduced modulo the group order or if
+/// the public key is not a canonical encoding of a curve point.
+///
+/// # Parameters
+///
+/// **data**
+///
+/// The signed dataset to verify.
+///
+/// **public**
+///
+/// The public key of the signer.
+///
+/// **signature**
+///
+/// The signature to be verified.
+///
+/// # Requirements
+///
+/// Library: libsgx_tcrypto.a
+///
+/// # Return value
+///
+/// **true**
+///
+/// Digital signature is valid.
+///
+/// **false**
+///
+/// Digital signature is not valid.
+///
+/// # Errors
+///
+/// **SGX_ERROR_INVALID_PARAMETER**
+///
+/// The size of T is zero.
+///
+pub fn rsgx_ed25519_verify_msg<T>(
+    data: &T,
+    public: &sgx_ed25519_public_t,
+    signature: &sgx_ed25519_signature_t,
+) -> SgxResult<bool>
+where
+    T: Copy + ContiguousMemory,
+{
+    let size = mem::size_of::<T>();
+    if size == 0 {
+        return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+    }
+    let data = unsafe { slice::from_raw_parts(data as *const _ as *const u8, size) };
+    Ok(ed25519::verify(&public.key, data, &signature_bytes(signature)))
+}
+
+///
+/// rsgx_ed25519_verify_slice verifies an Ed25519 signature over an input dataset.
+///
+/// An empty slice is a valid message. See rsgx_ed25519_verify_msg.
+///
+pub fn rsgx_ed25519_verify_slice<T>(
+    data: &[T],
+    public: &sgx_ed25519_public_t,
+    signature: &sgx_ed25519_signature_t,
+) -> SgxResult<bool>
+where
+    T: Copy + ContiguousMemory,
+{
+    let data = unsafe { slice::from_raw_parts(data.as_ptr() as *const u8, mem::size_of_val(data)) };
+    Ok(ed25519::verify(&public.key, data, &signature_bytes(signature)))
+}
+
+///
+/// rsgx_x25519_create_key_pair generates an X25519 private/public key pair.
+///
+/// # Description
+///
+/// The private key is 32 bytes read from sgx_read_rand. The public key is
+/// X25519(k, 9) as specified in RFC 7748.
+///
+/// # Requirements
+///
+/// Library: libsgx_tcrypto.a
+///
+/// # Return value
+///
+/// The private and the public key. The private key is overwritten with
+/// zeros when it is dropped.
+///
+/// # Errors
+///
+/// **SGX_ERROR_UNEXPECTED**
+///
+/// The random number generator failed.
+///
+pub fn rsgx_x25519_create_key_pair() -> SgxResult<(SgxX25519PrivateSecret, sgx_x25519_public_t)> {
+    let mut private = SgxX25519PrivateSecret::zeroed();
+    read_rand(&mut private.k)?;
+    let public = rsgx_x25519_calculate_public(&private);
+    Ok((private, public))
+}
+
+///
+/// rsgx_x25519_calculate_public derives the public key of an X25519 private key.
+///
+pub fn rsgx_x25519_calculate_public(private: &sgx_x25519_private_t) -> sgx_x25519_public_t {
+    sgx_x25519_public_t {
+        u: x25519(&private.k, &X25519_BASEPOINT),
+    }
+}
+
+///
+/// rsgx_x25519_compute_shared_key computes the X25519 shared secret.
+///
+/// # Description
+///
+/// The function runs in constant time with respect to the private key. The
+/// raw shared secret should be passed through a key derivation function,
+/// e.g. rsgx_hkdf_sha256, before being used as a key.
+///
+/// # Parameters
+///
+/// **private**
+///
+/// The local private key.
+///
+/// **peer_public**
+///
+/// The public key of the peer.
+///
+/// # Requirements
+///
+/// Library: libsgx_tcrypto.a
+///
+/// # Return value
+///
+/// The shared secret. It is overwritten with zeros when it is dropped.
+///
+/// # Errors
+///
+/// **SGX_ERROR_INVALID_PARAMETER**
+///
+/// The peer public key is a low order point and the shared secret would be
+/// all zeros.
+///
+pub fn rsgx_x25519_compute_shared_key(
+    private: &sgx_x25519_private_t,
+    peer_public: &sgx_x25519_public_t,
+) -> SgxResult<SgxX25519SharedSecret> {
+    let mut shared = SgxX25519SharedSecret::zeroed();
+    shared.s = x25519(&private.k, &peer_public.u);
+    let zero = shared.s.iter().fold(0, |acc, b| acc | b) == 0;
+    if zero {
+        return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+    }
+    Ok(shared)
+}
@@ -0,0 +1,254 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//!
+//! Interoperable encodings of P-256 keys and ECDSA signatures
+//!
+//! libsgx_tcrypto.a stores the coordinates of sgx_ec256_public_t and the
+//! components of sgx_ec256_signature_t in little endian order. Other
+//! libraries (OpenSSL, webpki, X.509) expect big endian integers: the SEC1
+//! uncompressed point encoding 0x04 || X || Y for public keys, and either the
+//! fixed-size r || s form or the DER encoded ECDSA-Sig-Value structure for
+//! signatures. The functions below convert between these representations.
+//!
+
+use sgx_types::*;
+
+const DER_SEQUENCE: u8 = 0x30;
+const DER_INTEGER: u8 = 0x02;
+const SEC1_UNCOMPRESSED: u8 = 0x04;
+
+///
+/// A DER encoded ECDSA signature, SEQUENCE { r INTEGER, s INTEGER }.
+///
+#[derive(Clone, Copy)]
+pub struct SgxEc256DerSignature {
+    buf: [u8; SGX_EC256_DER_SIGNATURE_MAX_SIZE],
+    len: usize,
+}
+
+impl SgxEc256DerSignature {
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl AsRef<[u8]> for SgxEc256DerSignature {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+fn limbs_to_be(limbs: &[u32; SGX_NISTP_ECP256_KEY_SIZE], out: &mut [u8]) {
+    for (i, limb) in limbs.iter().enumerate() {
+        let end = SGX_ECP256_KEY_SIZE - 4 * i;
+        out[end - 4..end].copy_from_slice(&limb.to_be_bytes());
+    }
+}
+
+fn be_to_limbs(be: &[u8]) -> [u32; SGX_NISTP_ECP256_KEY_SIZE] {
+    let mut limbs = [0_u32; SGX_NISTP_ECP256_KEY_SIZE];
+    for (i, limb) in limbs.iter_mut().enumerate() {
+        let end = SGX_ECP256_KEY_SIZE - 4 * i;
+        *limb = u32::from_be_bytes([be[end - 4], be[end - 3], be[end - 2], be[end - 1]]);
+    }
+    limbs
+}
+
+fn reversed(src: &[u8], out: &mut [u8]) {
+    for (o, b) in out.iter_mut().zip(src.iter().rev()) {
+        *o = *b;
+    }
+}
+
+fn write_der_integer(be: &[u8], out: &mut [u8]) -> usize {
+    let mut value = be;
+    while value.len() > 1 && value[0] == 0 {
+        value = &value[1..];
+    }
+    let pad = (value[0] & 0x80) != 0;
+    let len = value.len() + pad as usize;
+
+    out[0] = DER_INTEGER;
+    out[1] = len as u8;
+    let start = 2 + pad as usize;
+    if pad {
+        out[2] = 0;
+    }
+    out[start..start + value.len()].copy_from_slice(value);
+    2 + len
+}
+
+/// Parses a DER INTEGER holding a non-negative value of at most 32 bytes and
+/// returns it left-padded to 32 bytes, together with the rest of the input.
+fn read_der_integer(der: &[u8]) -> Option<([u8; SGX_ECP256_KEY_SIZE], &[u8])> {
+    if der.len() < 2 || der[0] != DER_INTEGER {
+        return None;
+    }
+    let len = der[1] as usize;
+    if len == 0 || len > 0x7f || der.len() < 2 + len {
+        return None;
+    }
+    let mut value = &der[2..2 + len];
+    let rest = &der[2 + len..];
+
+    // negative numbers and non-minimal encodings are not valid DER here
+    if value[0] & 0x80 != 0 {
+        return None;
+    }
+    if value.len() > 1 && value[0] == 0 {
+        if value[1] & 0x80 == 0 {
+            return None;
+        }
+        value = &value[1..];
+    }
+    if value.len() > SGX_ECP256_KEY_SIZE {
+        return None;
+    }
+
+    let mut be = [0_u8; SGX_ECP256_KEY_SIZE];
+    be[SGX_ECP256_KEY_SIZE - value.len()..].copy_from_slice(value);
+    Some((be, rest))
+}
+
+///
+/// rsgx_ec256_signature_to_raw encodes a signature as r || s, each a 32-byte
+/// big endian integer, as used by JWS/COSE and PKCS#11.
+///
+pub fn rsgx_ec256_signature_to_raw(signature: &sgx_ec256_signature_t) -> sgx_ec256_raw_signature_t {
+    let mut raw = [0_u8; SGX_EC256_RAW_SIGNATURE_SIZE];
+    limbs_to_be(&signature.x, &mut raw[..SGX_ECP256_KEY_SIZE]);
+    limbs_to_be(&signature.y, &mut raw[SGX_ECP256_KEY_SIZE..]);
+    raw
+}
+
+///
+/// rsgx_ec256_signature_from_raw decodes a signature encoded as r || s.
+///
+/// # Errors
+///
+/// **SGX_ERROR_INVALID_PARAMETER**
+///
+/// The input is not 64 bytes long.
+///
+pub fn rsgx_ec256_signature_from_raw(raw: &[u8]) -> SgxResult<sgx_ec256_signature_t> {
+    if raw.len() != SGX_EC256_RAW_SIGNATURE_SIZE {
+        return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+    }
+    Ok(sgx_ec256_signature_t {
+        x: be_to_limbs(&raw[..SGX_ECP256_KEY_SIZE]),
+        y: be_to_limbs(&raw[SGX_ECP256_KEY_SIZE..]),
+    })
+}
+
+///
+/// rsgx_ec256_signature_to_der encodes a signature as the DER encoding of
+/// the ECDSA-Sig-Value structure of RFC 3279, as used in X.509 and TLS.
+///
+pub fn rsgx_ec256_signature_to_der(signature: &sgx_ec256_signature_t) -> SgxEc256DerSignature {
+    let raw = rsgx_ec256_signature_to_raw(signature);
+    let mut der = SgxEc256DerSignature {
+        buf: [0_u8; SGX_EC256_DER_SIGNATURE_MAX_SIZE],
+        len: 0,
+    };
+
+    let mut pos = 2;
+    pos += write_der_integer(&raw[..SGX_ECP256_KEY_SIZE], &mut der.buf[pos..]);
+    pos += write_der_integer(&raw[SGX_ECP256_KEY_SIZE..], &mut der.buf[pos..]);
+    der.buf[0] = DER_SEQUENCE;
+    der.buf[1] = (pos - 2) as u8;
+    der.len = pos;
+    der
+}
+
+///
+/// rsgx_ec256_signature_from_der decodes a DER encoded ECDSA-Sig-Value.
+///
+/// # Description
+///
+/// Only the strict DER form is accepted: minimal length and integer
+/// encodings, no negative integers and no trailing data.
+///
+/// # Errors
+///
+/// **SGX_ERROR_INVALID_PARAMETER**
+///
+/// The input is not a valid DER encoded P-256 signature.
+///
+pub fn rsgx_ec256_signature_from_der(der: &[u8]) -> SgxResult<sgx_ec256_signature_t> {
+    let parse = || {
+        if der.len() < 2 || der[0] != DER_SEQUENCE || der[1] as usize != der.len() - 2 {
+            return None;
+        }
+        let (r, rest) = read_der_integer(&der[2..])?;
+        let (s, rest) = read_der_integer(rest)?;
+        if !rest.is_empty() {
+            return None;
+        }
+        Some(sgx_ec256_signature_t {
+            x: be_to_limbs(&r),
+            y: be_to_limbs(&s),
+        })
+    };
+    parse().ok_or(sgx_status_t::SGX_ERROR_INVALID_PARAMETER)
+}
+
+///
+/// rsgx_ec256_public_to_sec1 encodes a public key as an uncompressed SEC1
+/// point, 0x04 || X || Y with big endian coordinates. This is the content of
+/// the subjectPublicKey BIT STRING of an X.509 certificate.
+///
+pub fn rsgx_ec256_public_to_sec1(public: &sgx_ec256_public_t) -> sgx_ec256_sec1_public_t {
+    let mut sec1 = [0_u8; SGX_EC256_SEC1_PUBLIC_SIZE];
+    sec1[0] = SEC1_UNCOMPRESSED;
+    reversed(&public.gx, &mut sec1[1..1 + SGX_ECP256_KEY_SIZE]);
+    reversed(&public.gy, &mut sec1[1 + SGX_ECP256_KEY_SIZE..]);
+    sec1
+}
+
+///
+/// rsgx_ec256_public_from_sec1 decodes an uncompressed SEC1 point.
+///
+/// # Description
+///
+/// The function only checks the encoding. Use SgxEccHandle::check_point to
+/// verify that a public key received from a peer is on the curve.
+///
+/// # Errors
+///
+/// **SGX_ERROR_INVALID_PARAMETER**
+///
+/// The input is not a 65-byte uncompressed point. Compressed points are not
+/// supported.
+///
+pub fn rsgx_ec256_public_from_sec1(sec1: &[u8]) -> SgxResult<sgx_ec256_public_t> {
+    if sec1.len() != SGX_EC256_SEC1_PUBLIC_SIZE || sec1[0] != SEC1_UNCOMPRESSED {
+        return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+    }
+    let mut public = sgx_ec256_public_t::default();
+    reversed(&sec1[1..1 + SGX_ECP256_KEY_SIZE], &mut public.gx);
+    reversed(&sec1[1 + SGX_ECP256_KEY_SIZE..], &mut public.gy);
+    Ok(public)
+}
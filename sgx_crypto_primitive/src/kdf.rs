@@ -0,0 +1,520 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//!
+//! Key derivation functions
+//!
+//! HKDF (RFC 5869) over HMAC-SHA256 and HMAC-SHA384, and the NIST SP800-108
+//! KDF in counter mode with AES-CMAC as the PRF. All derived keys are
+//! returned as SgxSecret so they are cleared from memory when dropped.
+//!
+//! The output type `T` of the derivation functions determines how many bytes
+//! are derived, e.g. `SgxSecret<sgx_key_128bit_t>` yields 16 bytes.
+//!
+
+use crate::primitive::sha512::{HmacSha512, Sha512Variant};
+use core::mem;
+use core::ops::Drop;
+use core::ptr;
+use sgx_types::marker::ContiguousMemory;
+use sgx_types::*;
+
+const HKDF_MAX_HASH_SIZE: usize = SGX_SHA384_HASH_SIZE;
+
+struct HmacSha256 {
+    handle: sgx_hmac_state_handle_t,
+}
+
+impl HmacSha256 {
+    fn new(key: &[u8]) -> SgxResult<HmacSha256> {
+        if key.is_empty() || key.len() > i32::MAX as usize {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+
+        let mut handle: sgx_hmac_state_handle_t = ptr::null_mut();
+        let ret = unsafe { sgx_hmac256_init(key.as_ptr(), key.len() as i32, &mut handle) };
+        match ret {
+            sgx_status_t::SGX_SUCCESS => Ok(HmacSha256 { handle }),
+            _ => Err(ret),
+        }
+    }
+
+    fn update(&mut self, src: &[u8]) -> SgxError {
+        if src.is_empty() {
+            return Ok(());
+        }
+        if src.len() > i32::MAX as usize {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+
+        let ret = unsafe { sgx_hmac256_update(src.as_ptr(), src.len() as i32, self.handle) };
+        match ret {
+            sgx_status_t::SGX_SUCCESS => Ok(()),
+            _ => Err(ret),
+        }
+    }
+
+    fn finalize_into(self, out: &mut [u8]) -> SgxError {
+        let ret = unsafe { sgx_hmac256_final(out.as_mut_ptr(), out.len() as i32, self.handle) };
+        match ret {
+            sgx_status_t::SGX_SUCCESS => Ok(()),
+            _ => Err(ret),
+        }
+    }
+}
+
+impl Drop for HmacSha256 {
+    fn drop(&mut self) {
+        if !self.handle.is_null() {
+            unsafe { sgx_hmac256_close(self.handle) };
+        }
+    }
+}
+
+struct Cmac128 {
+    handle: sgx_cmac_state_handle_t,
+}
+
+impl Cmac128 {
+    fn new(key: &sgx_cmac_128bit_key_t) -> SgxResult<Cmac128> {
+        let mut handle: sgx_cmac_state_handle_t = ptr::null_mut();
+        let ret = unsafe { sgx_cmac128_init(key as *const sgx_cmac_128bit_key_t, &mut handle) };
+        match ret {
+            sgx_status_t::SGX_SUCCESS => Ok(Cmac128 { handle }),
+            _ => Err(ret),
+        }
+    }
+
+    fn update(&mut self, src: &[u8]) -> SgxError {
+        if src.is_empty() {
+            return Ok(());
+        }
+        if src.len() > u32::MAX as usize {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+
+        let ret = unsafe { sgx_cmac128_update(src.as_ptr(), src.len() as u32, self.handle) };
+        match ret {
+            sgx_status_t::SGX_SUCCESS => Ok(()),
+            _ => Err(ret),
+        }
+    }
+
+    fn finalize(self) -> SgxResult<sgx_cmac_128bit_tag_t> {
+        let mut tag = sgx_cmac_128bit_tag_t::default();
+        let ret = unsafe { sgx_cmac128_final(self.handle, &mut tag as *mut sgx_cmac_128bit_tag_t) };
+        match ret {
+            sgx_status_t::SGX_SUCCESS => Ok(tag),
+            _ => Err(ret),
+        }
+    }
+}
+
+impl Drop for Cmac128 {
+    fn drop(&mut self) {
+        if !self.handle.is_null() {
+            unsafe { sgx_cmac128_close(self.handle) };
+        }
+    }
+}
+
+fn hmac_sha256(key: &[u8], parts: &[&[u8]], out: &mut [u8]) -> SgxError {
+    let mut hmac = HmacSha256::new(key)?;
+    for part in parts {
+        hmac.update(part)?;
+    }
+    hmac.finalize_into(out)
+}
+
+fn hmac_sha384(key: &[u8], parts: &[&[u8]], out: &mut [u8]) -> SgxError {
+    let mut hmac = HmacSha512::new(Sha512Variant::Sha384, key);
+    for part in parts {
+        hmac.update(part);
+    }
+    hmac.finalize_into(out);
+    Ok(())
+}
+
+fn hkdf_extract<F>(hash_len: usize, salt: &[u8], ikm: &[u8], prk: &mut [u8], hmac: F) -> SgxError
+where
+    F: Fn(&[u8], &[&[u8]], &mut [u8]) -> SgxError,
+{
+    // RFC 5869: if the salt is not provided, it is set to a string of
+    // HashLen zeros.
+    let zero_salt = [0_u8; HKDF_MAX_HASH_SIZE];
+    let salt = if salt.is_empty() {
+        &zero_salt[..hash_len]
+    } else {
+        salt
+    };
+    hmac(salt, &[ikm], prk)
+}
+
+fn hkdf_expand<F>(hash_len: usize, prk: &[u8], info: &[u8], okm: &mut [u8], hmac: F) -> SgxError
+where
+    F: Fn(&[u8], &[&[u8]], &mut [u8]) -> SgxError,
+{
+    if okm.is_empty() || okm.len() > 255 * hash_len {
+        return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+    }
+
+    let mut prev = [0_u8; HKDF_MAX_HASH_SIZE];
+    let mut block = [0_u8; HKDF_MAX_HASH_SIZE];
+    let mut prev_len = 0;
+    let mut result = Ok(());
+    for (i, chunk) in okm.chunks_mut(hash_len).enumerate() {
+        let counter = [(i + 1) as u8];
+        result = hmac(prk, &[&prev[..prev_len], info, &counter], &mut block[..hash_len]);
+        if result.is_err() {
+            break;
+        }
+        chunk.copy_from_slice(&block[..chunk.len()]);
+        prev[..hash_len].copy_from_slice(&block[..hash_len]);
+        prev_len = hash_len;
+    }
+
+    zeroize_bytes(&mut prev);
+    zeroize_bytes(&mut block);
+    if result.is_err() {
+        zeroize_bytes(okm);
+    }
+    result
+}
+
+fn kdf_ctr_cmac(key: &sgx_cmac_128bit_key_t, fixed_input: &[&[u8]], okm: &mut [u8]) -> SgxError {
+    for (i, chunk) in okm.chunks_mut(SGX_CMAC_MAC_SIZE).enumerate() {
+        let counter = (i as u32 + 1).to_be_bytes();
+        let mut cmac = Cmac128::new(key)?;
+        cmac.update(&counter)?;
+        for part in fixed_input {
+            cmac.update(part)?;
+        }
+        let mut block = cmac.finalize()?;
+        chunk.copy_from_slice(&block[..chunk.len()]);
+        zeroize_bytes(&mut block);
+    }
+    Ok(())
+}
+
+fn output_secret<T>() -> SgxResult<SgxSecret<T>>
+where
+    T: Copy + ContiguousMemory,
+{
+    if mem::size_of::<T>() == 0 {
+        return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+    }
+    Ok(SgxSecret::zeroed())
+}
+
+///
+/// rsgx_hkdf_sha256_extract performs the HKDF-Extract step with HMAC-SHA256.
+///
+/// # Description
+///
+/// Computes the pseudorandom key PRK = HMAC-SHA256(salt, ikm). An empty salt
+/// is replaced by 32 zero bytes as specified in RFC 5869.
+///
+/// # Parameters
+///
+/// **salt**
+///
+/// Optional, non-secret random value.
+///
+/// **ikm**
+///
+/// The input keying material.
+///
+/// # Requirements
+///
+/// Library: libsgx_tcrypto.a
+///
+/// # Return value
+///
+/// The 256-bit pseudorandom key.
+///
+/// # Errors
+///
+/// **SGX_ERROR_INVALID_PARAMETER**
+///
+/// The salt or input keying material is too large.
+///
+/// **SGX_ERROR_OUT_OF_MEMORY**
+///
+/// Not enough memory is available to complete this operation.
+///
+/// **SGX_ERROR_UNEXPECTED**
+///
+/// An internal cryptography library failure occurred.
+///
+pub fn rsgx_hkdf_sha256_extract(salt: &[u8], ikm: &[u8]) -> SgxResult<SgxSecret<sgx_hmac_256bit_tag_t>> {
+    let mut prk = SgxSecret::<sgx_hmac_256bit_tag_t>::zeroed();
+    hkdf_extract(SGX_HMAC256_MAC_SIZE, salt, ikm, prk.as_bytes_mut(), hmac_sha256)?;
+    Ok(prk)
+}
+
+///
+/// rsgx_hkdf_sha256_expand performs the HKDF-Expand step with HMAC-SHA256.
+///
+/// # Description
+///
+/// Expands the pseudorandom key into `size_of::<T>()` bytes of output
+/// keying material bound to `info`.
+///
+/// # Parameters
+///
+/// **prk**
+///
+/// The pseudorandom key, usually the output of rsgx_hkdf_sha256_extract.
+///
+/// **info**
+///
+/// Optional context and application specific information.
+///
+/// # Requirements
+///
+/// Library: libsgx_tcrypto.a
+///
+/// # Return value
+///
+/// The output keying material.
+///
+/// # Errors
+///
+/// **SGX_ERROR_INVALID_PARAMETER**
+///
+/// `T` is zero-sized or larger than 255 * 32 bytes, or info is too large.
+///
+/// **SGX_ERROR_OUT_OF_MEMORY**
+///
+/// Not enough memory is available to complete this operation.
+///
+/// **SGX_ERROR_UNEXPECTED**
+///
+/// An internal cryptography library failure occurred.
+///
+pub fn rsgx_hkdf_sha256_expand<T>(prk: &SgxSecret<sgx_hmac_256bit_tag_t>, info: &[u8]) -> SgxResult<SgxSecret<T>>
+where
+    T: Copy + ContiguousMemory,
+{
+    let mut okm = output_secret::<T>()?;
+    hkdf_expand(SGX_HMAC256_MAC_SIZE, prk.as_bytes(), info, okm.as_bytes_mut(), hmac_sha256)?;
+    Ok(okm)
+}
+
+///
+/// rsgx_hkdf_sha256 derives a key with HKDF-SHA256 (extract then expand).
+///
+/// # Parameters
+///
+/// **salt**
+///
+/// Optional, non-secret random value.
+///
+/// **ikm**
+///
+/// The input keying material.
+///
+/// **info**
+///
+/// Optional context and application specific information.
+///
+/// # Requirements
+///
+/// Library: libsgx_tcrypto.a
+///
+/// # Return value
+///
+/// The output keying material, `size_of::<T>()` bytes long.
+///
+/// # Errors
+///
+/// **SGX_ERROR_INVALID_PARAMETER**
+///
+/// `T` is zero-sized or larger than 255 * 32 bytes, or an input is too large.
+///
+/// **SGX_ERROR_OUT_OF_MEMORY**
+///
+/// Not enough memory is available to complete this operation.
+///
+/// **SGX_ERROR_UNEXPECTED**
+///
+/// An internal cryptography library failure occurred.
+///
+pub fn rsgx_hkdf_sha256<T>(salt: &[u8], ikm: &[u8], info: &[u8]) -> SgxResult<SgxSecret<T>>
+where
+    T: Copy + ContiguousMemory,
+{
+    let prk = rsgx_hkdf_sha256_extract(salt, ikm)?;
+    rsgx_hkdf_sha256_expand(&prk, info)
+}
+
+///
+/// rsgx_hkdf_sha384_extract performs the HKDF-Extract step with HMAC-SHA384.
+///
+/// An empty salt is replaced by 48 zero bytes. See rsgx_hkdf_sha256_extract.
+///
+pub fn rsgx_hkdf_sha384_extract(salt: &[u8], ikm: &[u8]) -> SgxResult<SgxSecret<sgx_hmac_384bit_tag_t>> {
+    let mut prk = SgxSecret::<sgx_hmac_384bit_tag_t>::zeroed();
+    hkdf_extract(SGX_HMAC384_MAC_SIZE, salt, ikm, prk.as_bytes_mut(), hmac_sha384)?;
+    Ok(prk)
+}
+
+///
+/// rsgx_hkdf_sha384_expand performs the HKDF-Expand step with HMAC-SHA384.
+///
+/// At most 255 * 48 bytes can be derived. See rsgx_hkdf_sha256_expand.
+///
+pub fn rsgx_hkdf_sha384_expand<T>(prk: &SgxSecret<sgx_hmac_384bit_tag_t>, info: &[u8]) -> SgxResult<SgxSecret<T>>
+where
+    T: Copy + ContiguousMemory,
+{
+    let mut okm = output_secret::<T>()?;
+    hkdf_expand(SGX_HMAC384_MAC_SIZE, prk.as_bytes(), info, okm.as_bytes_mut(), hmac_sha384)?;
+    Ok(okm)
+}
+
+///
+/// rsgx_hkdf_sha384 derives a key with HKDF-SHA384 (extract then expand).
+///
+/// See rsgx_hkdf_sha256.
+///
+pub fn rsgx_hkdf_sha384<T>(salt: &[u8], ikm: &[u8], info: &[u8]) -> SgxResult<SgxSecret<T>>
+where
+    T: Copy + ContiguousMemory,
+{
+    let prk = rsgx_hkdf_sha384_extract(salt, ikm)?;
+    rsgx_hkdf_sha384_expand(&prk, info)
+}
+
+///
+/// rsgx_kdf_ctr_cmac_fixed derives a key with the NIST SP800-108 KDF in
+/// counter mode, using AES-128-CMAC as the PRF.
+///
+/// # Description
+///
+/// Each 128-bit block of output is computed as
+/// K(i) = CMAC(key, [i]32 || fixed_input), where [i]32 is the block counter,
+/// starting at 1, encoded as a 32-bit big-endian integer placed before the
+/// fixed input data. This is the layout of the NIST CAVP KDF test vectors with
+/// CTRLOCATION=BEFORE_FIXED and RLEN=32_BITS.
+///
+/// Use rsgx_kdf_ctr_cmac to build the fixed input data from a label and a
+/// context.
+///
+/// # Parameters
+///
+/// **key**
+///
+/// The key derivation key.
+///
+/// **fixed_input**
+///
+/// The fixed input data. It must encode the output length for the
+/// derivation to be secure.
+///
+/// # Requirements
+///
+/// Library: libsgx_tcrypto.a
+///
+/// # Return value
+///
+/// The derived key, `size_of::<T>()` bytes long.
+///
+/// # Errors
+///
+/// **SGX_ERROR_INVALID_PARAMETER**
+///
+/// `T` is zero-sized or the fixed input data is too large.
+///
+/// **SGX_ERROR_OUT_OF_MEMORY**
+///
+/// Not enough memory is available to complete this operation.
+///
+/// **SGX_ERROR_UNEXPECTED**
+///
+/// An internal cryptography library failure occurred.
+///
+pub fn rsgx_kdf_ctr_cmac_fixed<T>(key: &sgx_cmac_128bit_key_t, fixed_input: &[u8]) -> SgxResult<SgxSecret<T>>
+where
+    T: Copy + ContiguousMemory,
+{
+    let mut okm = output_secret::<T>()?;
+    kdf_ctr_cmac(key, &[fixed_input], okm.as_bytes_mut())?;
+    Ok(okm)
+}
+
+///
+/// rsgx_kdf_ctr_cmac derives a key with the NIST SP800-108 KDF in counter
+/// mode, using AES-128-CMAC as the PRF.
+///
+/// # Description
+///
+/// The fixed input data is Label || 0x00 || Context || [L]32, where [L]32 is
+/// the output length in bits encoded as a 32-bit big-endian integer. See
+/// rsgx_kdf_ctr_cmac_fixed for the layout of each PRF input.
+///
+/// # Parameters
+///
+/// **key**
+///
+/// The key derivation key.
+///
+/// **label**
+///
+/// Identifies the purpose of the derived key.
+///
+/// **context**
+///
+/// Information related to the derived key, e.g. the identities of the
+/// parties or a nonce.
+///
+/// # Requirements
+///
+/// Library: libsgx_tcrypto.a
+///
+/// # Return value
+///
+/// The derived key, `size_of::<T>()` bytes long.
+///
+/// # Errors
+///
+/// **SGX_ERROR_INVALID_PARAMETER**
+///
+/// `T` is zero-sized or too large, or the label or the context is too large.
+///
+/// **SGX_ERROR_OUT_OF_MEMORY**
+///
+/// Not enough memory is available to complete this operation.
+///
+/// **SGX_ERROR_UNEXPECTED**
+///
+/// An internal cryptography library failure occurred.
+///
+pub fn rsgx_kdf_ctr_cmac<T>(key: &sgx_cmac_128bit_key_t, label: &[u8], context: &[u8]) -> SgxResult<SgxSecret<T>>
+where
+    T: Copy + ContiguousMemory,
+{
+    let bits = mem::size_of::<T>()
+        .checked_mul(8)
+        .filter(|bits| *bits <= u32::MAX as usize)
+        .ok_or(sgx_status_t::SGX_ERROR_INVALID_PARAMETER)?;
+
+    let mut okm = output_secret::<T>()?;
+    let length = (bits as u32).to_be_bytes();
+    kdf_ctr_cmac(key, &[label, &[0_u8], context, &length], okm.as_bytes_mut())?;
+    Ok(okm)
+}
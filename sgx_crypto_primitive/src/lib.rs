@@ -0,0 +1,61 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//! # Portable Cryptographic Primitives
+//!
+//! Pure Rust implementations of the algorithms libsgx_tcrypto.a does not
+//! provide, and the interoperable key and signature encodings built on them.
+//! sgx_tcrypto and sgx_ucrypto both depend on this crate and re-export the
+//! items of its modules, so it only depends on `core` and `sgx_types`, and
+//! only calls into libsgx_tcrypto.a through the FFI declared in `sgx_types`.
+//!
+//! The `ucrypto` feature is enabled by sgx_ucrypto. It checks for AES-NI at
+//! runtime, which must not be done in an enclave.
+//!
+
+#![no_std]
+#![cfg_attr(target_env = "sgx", feature(rustc_private))]
+#![allow(non_camel_case_types)]
+#![allow(non_snake_case)]
+#![allow(clippy::too_many_arguments)]
+
+#[cfg(feature = "ucrypto")]
+extern crate std;
+
+extern crate sgx_types;
+
+pub mod aead;
+pub mod curve25519;
+pub mod ecc;
+pub mod kdf;
+pub mod rsa;
+pub mod sha512;
+
+pub mod primitive;
+
+// The untrusted host may be any x86_64 processor, so the AES primitive is
+// only used once the CPU is known to implement AES-NI.
+#[cfg(feature = "ucrypto")]
+fn has_aesni() -> bool {
+    std::is_x86_feature_detected!("aes")
+}
+
+// Every SGX capable processor implements AES-NI.
+#[cfg(not(feature = "ucrypto"))]
+fn has_aesni() -> bool {
+    true
+}
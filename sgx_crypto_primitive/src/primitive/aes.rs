@@ -0,0 +1,177 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//!
+//! AES block encryption for 128-bit and 256-bit keys.
+//!
+//! Every SGX capable processor implements the AES-NI instruction set, so the
+//! cipher is built on it rather than on lookup tables. This keeps the block
+//! function free of secret dependent memory accesses. Untrusted hosts need
+//! not have it, so the crate root decides whether it may be used, and Aes
+//! refuses to run without it.
+//!
+
+use super::zeroize;
+use core::arch::x86_64::*;
+
+pub const AES_BLOCK_SIZE: usize = 16;
+
+const AES_MAX_ROUNDS: usize = 14;
+
+pub struct Aes {
+    round_keys: [[u8; AES_BLOCK_SIZE]; AES_MAX_ROUNDS + 1],
+    rounds: usize,
+}
+
+macro_rules! expand_key_128 {
+    ($key:expr, $rcon:expr) => {{
+        let mut key = $key;
+        let tmp = _mm_shuffle_epi32(_mm_aeskeygenassist_si128(key, $rcon), 0xff);
+        key = _mm_xor_si128(key, _mm_slli_si128(key, 4));
+        key = _mm_xor_si128(key, _mm_slli_si128(key, 4));
+        key = _mm_xor_si128(key, _mm_slli_si128(key, 4));
+        _mm_xor_si128(key, tmp)
+    }};
+}
+
+macro_rules! expand_key_256_even {
+    ($key0:expr, $key1:expr, $rcon:expr) => {{
+        let mut key = $key0;
+        let tmp = _mm_shuffle_epi32(_mm_aeskeygenassist_si128($key1, $rcon), 0xff);
+        key = _mm_xor_si128(key, _mm_slli_si128(key, 4));
+        key = _mm_xor_si128(key, _mm_slli_si128(key, 4));
+        key = _mm_xor_si128(key, _mm_slli_si128(key, 4));
+        _mm_xor_si128(key, tmp)
+    }};
+}
+
+macro_rules! expand_key_256_odd {
+    ($key0:expr, $key1:expr) => {{
+        let mut key = $key1;
+        let tmp = _mm_shuffle_epi32(_mm_aeskeygenassist_si128($key0, 0x00), 0xaa);
+        key = _mm_xor_si128(key, _mm_slli_si128(key, 4));
+        key = _mm_xor_si128(key, _mm_slli_si128(key, 4));
+        key = _mm_xor_si128(key, _mm_slli_si128(key, 4));
+        _mm_xor_si128(key, tmp)
+    }};
+}
+
+///
+/// Whether the processor implements the AES-NI instructions Aes is built on.
+///
+pub fn aesni_available() -> bool {
+    crate::has_aesni()
+}
+
+impl Aes {
+    ///
+    /// Expands a 16-byte or 32-byte key. Returns None for any other key
+    /// length, or if the processor does not implement AES-NI.
+    ///
+    pub fn new(key: &[u8]) -> Option<Aes> {
+        if !aesni_available() {
+            return None;
+        }
+        let mut aes = Aes {
+            round_keys: [[0_u8; AES_BLOCK_SIZE]; AES_MAX_ROUNDS + 1],
+            rounds: 0,
+        };
+        match key.len() {
+            16 => {
+                unsafe { aes.expand_128(key) };
+                aes.rounds = 10;
+            }
+            32 => {
+                unsafe { aes.expand_256(key) };
+                aes.rounds = 14;
+            }
+            _ => return None,
+        }
+        Some(aes)
+    }
+
+    #[target_feature(enable = "aes,sse2")]
+    unsafe fn expand_128(&mut self, key: &[u8]) {
+        let mut rk = [_mm_setzero_si128(); 11];
+        rk[0] = _mm_loadu_si128(key.as_ptr() as *const __m128i);
+        rk[1] = expand_key_128!(rk[0], 0x01);
+        rk[2] = expand_key_128!(rk[1], 0x02);
+        rk[3] = expand_key_128!(rk[2], 0x04);
+        rk[4] = expand_key_128!(rk[3], 0x08);
+        rk[5] = expand_key_128!(rk[4], 0x10);
+        rk[6] = expand_key_128!(rk[5], 0x20);
+        rk[7] = expand_key_128!(rk[6], 0x40);
+        rk[8] = expand_key_128!(rk[7], 0x80);
+        rk[9] = expand_key_128!(rk[8], 0x1b);
+        rk[10] = expand_key_128!(rk[9], 0x36);
+        for (dst, src) in self.round_keys.iter_mut().zip(rk.iter_mut()) {
+            _mm_storeu_si128(dst.as_mut_ptr() as *mut __m128i, *src);
+            *src = _mm_setzero_si128();
+        }
+    }
+
+    #[target_feature(enable = "aes,sse2")]
+    unsafe fn expand_256(&mut self, key: &[u8]) {
+        let mut rk = [_mm_setzero_si128(); 15];
+        rk[0] = _mm_loadu_si128(key.as_ptr() as *const __m128i);
+        rk[1] = _mm_loadu_si128(key.as_ptr().add(16) as *const __m128i);
+        rk[2] = expand_key_256_even!(rk[0], rk[1], 0x01);
+        rk[3] = expand_key_256_odd!(rk[2], rk[1]);
+        rk[4] = expand_key_256_even!(rk[2], rk[3], 0x02);
+        rk[5] = expand_key_256_odd!(rk[4], rk[3]);
+        rk[6] = expand_key_256_even!(rk[4], rk[5], 0x04);
+        rk[7] = expand_key_256_odd!(rk[6], rk[5]);
+        rk[8] = expand_key_256_even!(rk[6], rk[7], 0x08);
+        rk[9] = expand_key_256_odd!(rk[8], rk[7]);
+        rk[10] = expand_key_256_even!(rk[8], rk[9], 0x10);
+        rk[11] = expand_key_256_odd!(rk[10], rk[9]);
+        rk[12] = expand_key_256_even!(rk[10], rk[11], 0x20);
+        rk[13] = expand_key_256_odd!(rk[12], rk[11]);
+        rk[14] = expand_key_256_even!(rk[12], rk[13], 0x40);
+        for (dst, src) in self.round_keys.iter_mut().zip(rk.iter_mut()) {
+            _mm_storeu_si128(dst.as_mut_ptr() as *mut __m128i, *src);
+            *src = _mm_setzero_si128();
+        }
+    }
+
+    ///
+    /// Encrypts a single block in place.
+    ///
+    pub fn encrypt_block(&self, block: &mut [u8; AES_BLOCK_SIZE]) {
+        unsafe { self.encrypt_block_aesni(block) }
+    }
+
+    #[target_feature(enable = "aes,sse2")]
+    unsafe fn encrypt_block_aesni(&self, block: &mut [u8; AES_BLOCK_SIZE]) {
+        let rk = |i: usize| _mm_loadu_si128(self.round_keys[i].as_ptr() as *const __m128i);
+        let mut b = _mm_loadu_si128(block.as_ptr() as *const __m128i);
+        b = _mm_xor_si128(b, rk(0));
+        for i in 1..self.rounds {
+            b = _mm_aesenc_si128(b, rk(i));
+        }
+        b = _mm_aesenclast_si128(b, rk(self.rounds));
+        _mm_storeu_si128(block.as_mut_ptr() as *mut __m128i, b);
+    }
+}
+
+impl Drop for Aes {
+    fn drop(&mut self) {
+        for rk in self.round_keys.iter_mut() {
+            zeroize(rk);
+        }
+    }
+}
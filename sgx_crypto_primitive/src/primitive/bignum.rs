@@ -0,0 +1,355 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//!
+//! Fixed capacity unsigned integers and Montgomery arithmetic, sized for
+//! moduli of up to 4096 bits.
+//!
+//! Numbers are little endian arrays of 64-bit limbs. Reductions,
+//! exponentiation and table lookups do not branch on secret values; only
+//! the limb counts of the operands influence the control flow.
+//!
+
+use core::ptr;
+use core::sync::atomic::{self, Ordering};
+
+pub const MAX_LIMBS: usize = 64;
+const WINDOW_BITS: usize = 4;
+const TABLE_SIZE: usize = 1 << WINDOW_BITS;
+
+pub type Limbs = [u64; MAX_LIMBS];
+
+/// Overwrites `limbs` with zeros in a way the optimizer will not elide.
+pub fn zeroize_limbs(limbs: &mut [u64]) {
+    for w in limbs.iter_mut() {
+        unsafe { ptr::write_volatile(w, 0) };
+    }
+    atomic::compiler_fence(Ordering::SeqCst);
+}
+
+/// Number of limbs needed to hold `bytes` bytes.
+pub fn limbs_for(bytes: usize) -> usize {
+    (bytes + 7) / 8
+}
+
+/// Loads a little endian byte string. Returns false if it does not fit.
+pub fn from_le_bytes(bytes: &[u8], out: &mut [u64]) -> bool {
+    if limbs_for(bytes.len()) > out.len() {
+        return false;
+    }
+    for w in out.iter_mut() {
+        *w = 0;
+    }
+    for (i, b) in bytes.iter().enumerate() {
+        out[i / 8] |= u64::from(*b) << ((i % 8) * 8);
+    }
+    true
+}
+
+/// Loads a big endian byte string. Returns false if it does not fit.
+pub fn from_be_bytes(bytes: &[u8], out: &mut [u64]) -> bool {
+    if limbs_for(bytes.len()) > out.len() {
+        return false;
+    }
+    for w in out.iter_mut() {
+        *w = 0;
+    }
+    for (i, b) in bytes.iter().rev().enumerate() {
+        out[i / 8] |= u64::from(*b) << ((i % 8) * 8);
+    }
+    true
+}
+
+/// Stores `a` as a big endian byte string filling `out`. Returns false if
+/// the value does not fit.
+pub fn to_be_bytes(a: &[u64], out: &mut [u8]) -> bool {
+    if bit_len(a) > out.len() * 8 {
+        return false;
+    }
+    for (i, b) in out.iter_mut().rev().enumerate() {
+        *b = if i / 8 < a.len() { (a[i / 8] >> ((i % 8) * 8)) as u8 } else { 0 };
+    }
+    true
+}
+
+/// Number of significant bits of `a`.
+pub fn bit_len(a: &[u64]) -> usize {
+    for i in (0..a.len()).rev() {
+        if a[i] != 0 {
+            return i * 64 + 64 - a[i].leading_zeros() as usize;
+        }
+    }
+    0
+}
+
+pub fn is_zero(a: &[u64]) -> bool {
+    a.iter().fold(0, |acc, w| acc | w) == 0
+}
+
+/// Returns true if a < b, where both have the same number of limbs.
+pub fn lt(a: &[u64], b: &[u64]) -> bool {
+    let mut borrow = 0_u64;
+    for (x, y) in a.iter().zip(b.iter()) {
+        let (d, b1) = x.overflowing_sub(*y);
+        let (_, b2) = d.overflowing_sub(borrow);
+        borrow = (b1 | b2) as u64;
+    }
+    borrow == 1
+}
+
+/// out = a * b, schoolbook. `out` must hold a.len() + b.len() limbs.
+pub fn mul(a: &[u64], b: &[u64], out: &mut [u64]) {
+    for w in out.iter_mut() {
+        *w = 0;
+    }
+    for (i, x) in a.iter().enumerate() {
+        let mut carry = 0_u64;
+        for (j, y) in b.iter().enumerate() {
+            let t = u128::from(out[i + j]) + u128::from(*x) * u128::from(*y) + u128::from(carry);
+            out[i + j] = t as u64;
+            carry = (t >> 64) as u64;
+        }
+        out[i + b.len()] = carry;
+    }
+}
+
+/// a += b, returns the carry. `b` may be shorter than `a`.
+pub fn add_assign(a: &mut [u64], b: &[u64]) -> u64 {
+    let mut carry = 0_u64;
+    for (i, x) in a.iter_mut().enumerate() {
+        let y = if i < b.len() { b[i] } else { 0 };
+        let t = u128::from(*x) + u128::from(y) + u128::from(carry);
+        *x = t as u64;
+        carry = (t >> 64) as u64;
+    }
+    carry
+}
+
+/// a -= b over a.len() limbs, returns the borrow.
+fn sub_assign(a: &mut [u64], b: &[u64]) -> u64 {
+    let mut borrow = 0_u64;
+    for (x, y) in a.iter_mut().zip(b.iter()) {
+        let (d, b1) = x.overflowing_sub(*y);
+        let (d, b2) = d.overflowing_sub(borrow);
+        *x = d;
+        borrow = (b1 | b2) as u64;
+    }
+    borrow
+}
+
+/// dst = mask ? src : dst, with mask either 0 or all ones.
+fn select_assign(dst: &mut [u64], src: &[u64], mask: u64) {
+    for (d, s) in dst.iter_mut().zip(src.iter()) {
+        *d ^= mask & (*d ^ *s);
+    }
+}
+
+///
+/// An odd modulus with its Montgomery constants.
+///
+pub struct Modulus {
+    m: Limbs,
+    rr: Limbs,
+    n0: u64,
+    len: usize,
+}
+
+impl Modulus {
+    /// Prepares `m`, given in little endian limbs. `m` must be odd and
+    /// greater than one.
+    pub fn new(m: &[u64]) -> Option<Modulus> {
+        let mut len = m.len();
+        while len > 0 && m[len - 1] == 0 {
+            len -= 1;
+        }
+        if len == 0 || len > MAX_LIMBS || m[0] & 1 == 0 || (len == 1 && m[0] == 1) {
+            return None;
+        }
+
+        let mut modulus = Modulus {
+            m: [0_u64; MAX_LIMBS],
+            rr: [0_u64; MAX_LIMBS],
+            n0: 0,
+            len,
+        };
+        modulus.m[..len].copy_from_slice(&m[..len]);
+
+        // Newton iteration for m[0]^-1 mod 2^64.
+        let mut inv = 1_u64;
+        for _ in 0..6 {
+            inv = inv.wrapping_mul(2_u64.wrapping_sub(m[0].wrapping_mul(inv)));
+        }
+        modulus.n0 = inv.wrapping_neg();
+
+        // R^2 mod m by doubling 1 a total of 2 * 64 * len times.
+        let mut r = [0_u64; MAX_LIMBS];
+        r[0] = 1;
+        for _ in 0..2 * 64 * len {
+            modulus.shift_in(&mut r[..len], 0);
+        }
+        modulus.rr = r;
+        Some(modulus)
+    }
+
+    pub fn limbs(&self) -> &[u64] {
+        &self.m[..self.len]
+    }
+
+    /// r = 2r + bit mod m, for r < m and bit in {0, 1}.
+    fn shift_in(&self, r: &mut [u64], bit: u64) {
+        let len = self.len;
+        let top = r[len - 1] >> 63;
+        for i in (1..len).rev() {
+            r[i] = (r[i] << 1) | (r[i - 1] >> 63);
+        }
+        r[0] = (r[0] << 1) | bit;
+        self.finish_reduce(r, top);
+    }
+
+    /// Given t = r + top * 2^(64 * len) < 2m, stores t mod m in r.
+    fn finish_reduce(&self, r: &mut [u64], top: u64) {
+        let len = self.len;
+        let mut d = [0_u64; MAX_LIMBS];
+        d[..len].copy_from_slice(&r[..len]);
+        let borrow = sub_assign(&mut d[..len], &self.m[..len]);
+        // Keep the difference when it did not underflow or the top bit absorbs it.
+        let mask = (top | (borrow ^ 1)).wrapping_neg();
+        select_assign(&mut r[..len], &d[..len], mask);
+        zeroize_limbs(&mut d[..len]);
+    }
+
+    /// out = a * b * R^-1 mod m, for a, b < m.
+    #[allow(clippy::many_single_char_names)]
+    pub fn mont_mul(&self, a: &[u64], b: &[u64], out: &mut [u64]) {
+        let len = self.len;
+        let mut t = [0_u64; MAX_LIMBS + 2];
+        for &ai in a.iter().take(len) {
+            let mut carry = 0_u64;
+            for j in 0..len {
+                let s = u128::from(t[j]) + u128::from(ai) * u128::from(b[j]) + u128::from(carry);
+                t[j] = s as u64;
+                carry = (s >> 64) as u64;
+            }
+            let s = u128::from(t[len]) + u128::from(carry);
+            t[len] = s as u64;
+            t[len + 1] = (s >> 64) as u64;
+
+            let q = t[0].wrapping_mul(self.n0);
+            let s = u128::from(t[0]) + u128::from(q) * u128::from(self.m[0]);
+            let mut carry = (s >> 64) as u64;
+            for j in 1..len {
+                let s = u128::from(t[j]) + u128::from(q) * u128::from(self.m[j]) + u128::from(carry);
+                t[j - 1] = s as u64;
+                carry = (s >> 64) as u64;
+            }
+            let s = u128::from(t[len]) + u128::from(carry);
+            t[len - 1] = s as u64;
+            t[len] = t[len + 1] + (s >> 64) as u64;
+        }
+        let top = t[len];
+        self.finish_reduce(&mut t[..len], top);
+        out[..len].copy_from_slice(&t[..len]);
+        zeroize_limbs(&mut t);
+    }
+
+    /// out = a mod m, for a of any length.
+    pub fn reduce(&self, a: &[u64], out: &mut [u64]) {
+        let len = self.len;
+        let mut r = [0_u64; MAX_LIMBS];
+        for i in (0..a.len() * 64).rev() {
+            self.shift_in(&mut r[..len], (a[i / 64] >> (i % 64)) & 1);
+        }
+        out[..len].copy_from_slice(&r[..len]);
+        zeroize_limbs(&mut r);
+    }
+
+    /// out = a * b mod m, for a, b < m.
+    pub fn mod_mul(&self, a: &[u64], b: &[u64], out: &mut [u64]) {
+        let mut t = [0_u64; MAX_LIMBS];
+        self.mont_mul(a, b, &mut t);
+        self.mont_mul(&t, &self.rr, out);
+        zeroize_limbs(&mut t);
+    }
+
+    /// out = a - b mod m, for a, b < m.
+    pub fn mod_sub(&self, a: &[u64], b: &[u64], out: &mut [u64]) {
+        let len = self.len;
+        let mut d = [0_u64; MAX_LIMBS];
+        d[..len].copy_from_slice(&a[..len]);
+        let borrow = sub_assign(&mut d[..len], &b[..len]);
+        let mut s = d;
+        add_assign(&mut s[..len], &self.m[..len]);
+        select_assign(&mut d[..len], &s[..len], borrow.wrapping_neg());
+        out[..len].copy_from_slice(&d[..len]);
+        zeroize_limbs(&mut d);
+        zeroize_limbs(&mut s);
+    }
+
+    /// out = base ^ exp mod m, for base < m. Uses a fixed window over all
+    /// exp.len() * 64 exponent bits with constant time table lookups.
+    pub fn mod_exp(&self, base: &[u64], exp: &[u64], out: &mut [u64]) {
+        let len = self.len;
+        let mut one = [0_u64; MAX_LIMBS];
+        one[0] = 1;
+
+        let mut table = [[0_u64; MAX_LIMBS]; TABLE_SIZE];
+        // table[i] = base^i in Montgomery form.
+        self.mont_mul(&one, &self.rr, &mut table[0]);
+        self.mont_mul(base, &self.rr, &mut table[1]);
+        for i in 2..TABLE_SIZE {
+            let (lo, hi) = table.split_at_mut(i);
+            self.mont_mul(&lo[i - 1], &lo[1], &mut hi[0]);
+        }
+
+        let mut acc = table[0];
+        let mut tmp = [0_u64; MAX_LIMBS];
+        let mut sel = [0_u64; MAX_LIMBS];
+        let windows = exp.len() * 64 / WINDOW_BITS;
+        for w in (0..windows).rev() {
+            for _ in 0..WINDOW_BITS {
+                self.mont_mul(&acc, &acc, &mut tmp);
+                acc = tmp;
+            }
+            let bit = w * WINDOW_BITS;
+            let idx = ((exp[bit / 64] >> (bit % 64)) as usize) & (TABLE_SIZE - 1);
+            for w in sel.iter_mut() {
+                *w = 0;
+            }
+            for (i, entry) in table.iter().enumerate() {
+                let mask = ((((i ^ idx) as u64).wrapping_sub(1)) >> 63).wrapping_neg();
+                select_assign(&mut sel[..len], &entry[..len], mask);
+            }
+            self.mont_mul(&acc, &sel, &mut tmp);
+            acc = tmp;
+        }
+        self.mont_mul(&acc, &one, out);
+
+        for entry in table.iter_mut() {
+            zeroize_limbs(entry);
+        }
+        zeroize_limbs(&mut acc);
+        zeroize_limbs(&mut tmp);
+        zeroize_limbs(&mut sel);
+    }
+}
+
+impl Drop for Modulus {
+    fn drop(&mut self) {
+        zeroize_limbs(&mut self.m);
+        zeroize_limbs(&mut self.rr);
+    }
+}
@@ -0,0 +1,124 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//!
+//! ChaCha20 stream cipher as specified in RFC 8439 (96-bit nonce, 32-bit
+//! block counter).
+//!
+
+use super::{zeroize, zeroize_u32};
+
+pub const CHACHA20_KEY_SIZE: usize = 32;
+pub const CHACHA20_NONCE_SIZE: usize = 12;
+pub const CHACHA20_BLOCK_SIZE: usize = 64;
+
+pub struct ChaCha20 {
+    state: [u32; 16],
+    keystream: [u8; CHACHA20_BLOCK_SIZE],
+    ks_pos: usize,
+}
+
+#[inline(always)]
+#[allow(clippy::many_single_char_names)]
+fn quarter_round(x: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    x[a] = x[a].wrapping_add(x[b]);
+    x[d] = (x[d] ^ x[a]).rotate_left(16);
+    x[c] = x[c].wrapping_add(x[d]);
+    x[b] = (x[b] ^ x[c]).rotate_left(12);
+    x[a] = x[a].wrapping_add(x[b]);
+    x[d] = (x[d] ^ x[a]).rotate_left(8);
+    x[c] = x[c].wrapping_add(x[d]);
+    x[b] = (x[b] ^ x[c]).rotate_left(7);
+}
+
+fn load_le32(b: &[u8]) -> u32 {
+    u32::from_le_bytes([b[0], b[1], b[2], b[3]])
+}
+
+impl ChaCha20 {
+    pub fn new(
+        key: &[u8; CHACHA20_KEY_SIZE],
+        nonce: &[u8; CHACHA20_NONCE_SIZE],
+        counter: u32,
+    ) -> ChaCha20 {
+        let mut state = [0_u32; 16];
+        state[0] = 0x6170_7865;
+        state[1] = 0x3320_646e;
+        state[2] = 0x7962_2d32;
+        state[3] = 0x6b20_6574;
+        for i in 0..8 {
+            state[4 + i] = load_le32(&key[i * 4..]);
+        }
+        state[12] = counter;
+        for i in 0..3 {
+            state[13 + i] = load_le32(&nonce[i * 4..]);
+        }
+        ChaCha20 {
+            state,
+            keystream: [0_u8; CHACHA20_BLOCK_SIZE],
+            ks_pos: CHACHA20_BLOCK_SIZE,
+        }
+    }
+
+    ///
+    /// Produces the keystream block for the current counter and advances it.
+    ///
+    pub fn next_block(&mut self, out: &mut [u8; CHACHA20_BLOCK_SIZE]) {
+        let mut x = self.state;
+        for _ in 0..10 {
+            quarter_round(&mut x, 0, 4, 8, 12);
+            quarter_round(&mut x, 1, 5, 9, 13);
+            quarter_round(&mut x, 2, 6, 10, 14);
+            quarter_round(&mut x, 3, 7, 11, 15);
+            quarter_round(&mut x, 0, 5, 10, 15);
+            quarter_round(&mut x, 1, 6, 11, 12);
+            quarter_round(&mut x, 2, 7, 8, 13);
+            quarter_round(&mut x, 3, 4, 9, 14);
+        }
+        for i in 0..16 {
+            let w = x[i].wrapping_add(self.state[i]);
+            out[i * 4..i * 4 + 4].copy_from_slice(&w.to_le_bytes());
+        }
+        zeroize_u32(&mut x);
+        self.state[12] = self.state[12].wrapping_add(1);
+    }
+
+    ///
+    /// XORs the keystream into src and writes the result to dst. The keystream
+    /// position carries over between calls.
+    ///
+    pub fn apply_keystream(&mut self, src: &[u8], dst: &mut [u8]) {
+        for (s, d) in src.iter().zip(dst.iter_mut()) {
+            if self.ks_pos == CHACHA20_BLOCK_SIZE {
+                let mut block = [0_u8; CHACHA20_BLOCK_SIZE];
+                self.next_block(&mut block);
+                self.keystream = block;
+                zeroize(&mut block);
+                self.ks_pos = 0;
+            }
+            *d = *s ^ self.keystream[self.ks_pos];
+            self.ks_pos += 1;
+        }
+    }
+}
+
+impl Drop for ChaCha20 {
+    fn drop(&mut self) {
+        zeroize_u32(&mut self.state);
+        zeroize(&mut self.keystream);
+    }
+}
@@ -0,0 +1,116 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//!
+//! ChaCha20-Poly1305 AEAD construction (RFC 8439), usable incrementally.
+//!
+
+use super::chacha20::{ChaCha20, CHACHA20_BLOCK_SIZE, CHACHA20_KEY_SIZE, CHACHA20_NONCE_SIZE};
+use super::poly1305::{Poly1305, POLY1305_KEY_SIZE, POLY1305_TAG_SIZE};
+use super::zeroize;
+
+/// The block counter starts at 1 and must not wrap: (2^32 - 1) blocks.
+const CHACHA20_POLY1305_MAX_TEXT_LEN: u64 = ((1 << 32) - 1) * CHACHA20_BLOCK_SIZE as u64;
+
+pub struct ChaCha20Poly1305 {
+    cipher: ChaCha20,
+    poly: Poly1305,
+    aad_len: u64,
+    text_len: u64,
+}
+
+impl ChaCha20Poly1305 {
+    pub fn new(key: &[u8], nonce: &[u8], aad: &[u8]) -> Option<ChaCha20Poly1305> {
+        if key.len() != CHACHA20_KEY_SIZE || nonce.len() != CHACHA20_NONCE_SIZE {
+            return None;
+        }
+        let mut k = [0_u8; CHACHA20_KEY_SIZE];
+        k.copy_from_slice(key);
+        let mut n = [0_u8; CHACHA20_NONCE_SIZE];
+        n.copy_from_slice(nonce);
+
+        let mut cipher = ChaCha20::new(&k, &n, 0);
+        zeroize(&mut k);
+        let mut block = [0_u8; CHACHA20_BLOCK_SIZE];
+        cipher.next_block(&mut block);
+        let mut poly_key = [0_u8; POLY1305_KEY_SIZE];
+        poly_key.copy_from_slice(&block[..POLY1305_KEY_SIZE]);
+        zeroize(&mut block);
+        let mut poly = Poly1305::new(&poly_key);
+        zeroize(&mut poly_key);
+
+        poly.update(aad);
+        poly.pad();
+
+        Some(ChaCha20Poly1305 {
+            cipher,
+            poly,
+            aad_len: aad.len() as u64,
+            text_len: 0,
+        })
+    }
+
+    fn reserve(&mut self, len: usize) -> bool {
+        match self.text_len.checked_add(len as u64) {
+            Some(total) if total <= CHACHA20_POLY1305_MAX_TEXT_LEN => {
+                self.text_len = total;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn authenticate(&mut self, ciphertext: &[u8]) -> bool {
+        if !self.reserve(ciphertext.len()) {
+            return false;
+        }
+        self.poly.update(ciphertext);
+        true
+    }
+
+    pub fn decrypt_authenticated(&mut self, src: &[u8], dst: &mut [u8]) {
+        self.cipher.apply_keystream(src, &mut dst[..src.len()]);
+    }
+
+    pub fn encrypt_update(&mut self, src: &[u8], dst: &mut [u8]) -> bool {
+        if !self.reserve(src.len()) {
+            return false;
+        }
+        let dst = &mut dst[..src.len()];
+        self.cipher.apply_keystream(src, dst);
+        self.poly.update(dst);
+        true
+    }
+
+    pub fn decrypt_update(&mut self, src: &[u8], dst: &mut [u8]) -> bool {
+        if !self.authenticate(src) {
+            return false;
+        }
+        self.decrypt_authenticated(src, dst);
+        true
+    }
+
+    pub fn tag(&self) -> [u8; POLY1305_TAG_SIZE] {
+        let mut poly = self.poly.clone();
+        poly.pad();
+        let mut lengths = [0_u8; 16];
+        lengths[..8].copy_from_slice(&self.aad_len.to_le_bytes());
+        lengths[8..].copy_from_slice(&self.text_len.to_le_bytes());
+        poly.update(&lengths);
+        poly.finalize()
+    }
+}
@@ -0,0 +1,285 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//!
+//! Arithmetic in GF(2^255 - 19) with 51-bit limbs, and X25519 (RFC 7748).
+//!
+
+use super::zeroize;
+
+const MASK51: u64 = (1 << 51) - 1;
+
+// p - 2, little endian
+const P_MINUS_2: [u8; 32] = [
+    0xeb, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f,
+];
+
+// (p - 5) / 8, little endian
+const P_MINUS_5_DIV_8: [u8; 32] = [
+    0xfd, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x0f,
+];
+
+/// A field element, not necessarily fully reduced.
+#[derive(Clone, Copy)]
+pub struct Fe([u64; 5]);
+
+impl Fe {
+    pub const ZERO: Fe = Fe([0, 0, 0, 0, 0]);
+    pub const ONE: Fe = Fe([1, 0, 0, 0, 0]);
+
+    /// Decodes 32 little endian bytes, ignoring the most significant bit.
+    pub fn from_bytes(s: &[u8; 32]) -> Fe {
+        let load = |i: usize| {
+            let mut w = [0_u8; 8];
+            w.copy_from_slice(&s[i..i + 8]);
+            u64::from_le_bytes(w)
+        };
+        Fe([
+            load(0) & MASK51,
+            (load(6) >> 3) & MASK51,
+            (load(12) >> 6) & MASK51,
+            (load(19) >> 1) & MASK51,
+            (load(24) >> 12) & MASK51,
+        ])
+    }
+
+    /// Encodes the canonical representative as 32 little endian bytes.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        let mut l = Fe::carry(self.0);
+
+        // l < 2p here; subtract p if l >= p by computing the carry of l + 19
+        let mut q = (l[0] + 19) >> 51;
+        q = (l[1] + q) >> 51;
+        q = (l[2] + q) >> 51;
+        q = (l[3] + q) >> 51;
+        q = (l[4] + q) >> 51;
+
+        l[0] += 19 * q;
+        l[1] += l[0] >> 51;
+        l[0] &= MASK51;
+        l[2] += l[1] >> 51;
+        l[1] &= MASK51;
+        l[3] += l[2] >> 51;
+        l[2] &= MASK51;
+        l[4] += l[3] >> 51;
+        l[3] &= MASK51;
+        l[4] &= MASK51;
+
+        let mut out = [0_u8; 32];
+        let mut acc: u128 = 0;
+        let mut bits = 0;
+        let mut pos = 0;
+        for limb in l.iter() {
+            acc |= (*limb as u128) << bits;
+            bits += 51;
+            while bits >= 8 && pos < 32 {
+                out[pos] = acc as u8;
+                acc >>= 8;
+                bits -= 8;
+                pos += 1;
+            }
+        }
+        if pos < 32 {
+            out[pos] = acc as u8;
+        }
+        out
+    }
+
+    fn carry(mut l: [u64; 5]) -> [u64; 5] {
+        let c0 = l[0] >> 51;
+        let c1 = l[1] >> 51;
+        let c2 = l[2] >> 51;
+        let c3 = l[3] >> 51;
+        let c4 = l[4] >> 51;
+        l[0] &= MASK51;
+        l[1] &= MASK51;
+        l[2] &= MASK51;
+        l[3] &= MASK51;
+        l[4] &= MASK51;
+        l[0] += c4 * 19;
+        l[1] += c0;
+        l[2] += c1;
+        l[3] += c2;
+        l[4] += c3;
+        l
+    }
+
+    pub fn add(&self, b: &Fe) -> Fe {
+        let a = &self.0;
+        let b = &b.0;
+        Fe(Fe::carry([a[0] + b[0], a[1] + b[1], a[2] + b[2], a[3] + b[3], a[4] + b[4]]))
+    }
+
+    pub fn sub(&self, b: &Fe) -> Fe {
+        // add 16p so the limbs never underflow
+        let a = &self.0;
+        let b = &b.0;
+        Fe(Fe::carry([
+            (a[0] + 36_028_797_018_963_664) - b[0],
+            (a[1] + 36_028_797_018_963_952) - b[1],
+            (a[2] + 36_028_797_018_963_952) - b[2],
+            (a[3] + 36_028_797_018_963_952) - b[3],
+            (a[4] + 36_028_797_018_963_952) - b[4],
+        ]))
+    }
+
+    pub fn neg(&self) -> Fe {
+        Fe::ZERO.sub(self)
+    }
+
+    pub fn mul(&self, b: &Fe) -> Fe {
+        let m = |x: u64, y: u64| (x as u128) * (y as u128);
+        let a = &self.0;
+        let b = &b.0;
+        let b1_19 = b[1] * 19;
+        let b2_19 = b[2] * 19;
+        let b3_19 = b[3] * 19;
+        let b4_19 = b[4] * 19;
+
+        let c0 = m(a[0], b[0]) + m(a[4], b1_19) + m(a[3], b2_19) + m(a[2], b3_19) + m(a[1], b4_19);
+        let mut c1 = m(a[1], b[0]) + m(a[0], b[1]) + m(a[4], b2_19) + m(a[3], b3_19) + m(a[2], b4_19);
+        let mut c2 = m(a[2], b[0]) + m(a[1], b[1]) + m(a[0], b[2]) + m(a[4], b3_19) + m(a[3], b4_19);
+        let mut c3 = m(a[3], b[0]) + m(a[2], b[1]) + m(a[1], b[2]) + m(a[0], b[3]) + m(a[4], b4_19);
+        let mut c4 = m(a[4], b[0]) + m(a[3], b[1]) + m(a[2], b[2]) + m(a[1], b[3]) + m(a[0], b[4]);
+
+        let mut l = [0_u64; 5];
+        c1 += c0 >> 51;
+        l[0] = (c0 as u64) & MASK51;
+        c2 += c1 >> 51;
+        l[1] = (c1 as u64) & MASK51;
+        c3 += c2 >> 51;
+        l[2] = (c2 as u64) & MASK51;
+        c4 += c3 >> 51;
+        l[3] = (c3 as u64) & MASK51;
+        let carry = (c4 >> 51) as u64;
+        l[4] = (c4 as u64) & MASK51;
+
+        l[0] += carry * 19;
+        l[1] += l[0] >> 51;
+        l[0] &= MASK51;
+        Fe(l)
+    }
+
+    pub fn square(&self) -> Fe {
+        self.mul(self)
+    }
+
+    /// Raises to a public exponent given as 32 little endian bytes.
+    fn pow(&self, e: &[u8; 32]) -> Fe {
+        let mut r = Fe::ONE;
+        for i in (0..256).rev() {
+            r = r.square();
+            if (e[i / 8] >> (i % 8)) & 1 == 1 {
+                r = r.mul(self);
+            }
+        }
+        r
+    }
+
+    pub fn invert(&self) -> Fe {
+        self.pow(&P_MINUS_2)
+    }
+
+    pub fn pow_p58(&self) -> Fe {
+        self.pow(&P_MINUS_5_DIV_8)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        let s = self.to_bytes();
+        s.iter().fold(0, |acc, b| acc | b) == 0
+    }
+
+    pub fn is_negative(&self) -> u8 {
+        self.to_bytes()[0] & 1
+    }
+
+    pub fn ct_eq(&self, b: &Fe) -> bool {
+        super::ct_eq(&self.to_bytes(), &b.to_bytes())
+    }
+
+    /// Returns `b` if `choice` is 1 and `a` if it is 0.
+    pub fn select(a: &Fe, b: &Fe, choice: u8) -> Fe {
+        let mask = 0_u64.wrapping_sub(choice as u64);
+        let mut l = [0_u64; 5];
+        for (i, x) in l.iter_mut().enumerate() {
+            *x = a.0[i] ^ (mask & (a.0[i] ^ b.0[i]));
+        }
+        Fe(l)
+    }
+
+    /// Swaps `a` and `b` if `choice` is 1.
+    pub fn cswap(a: &mut Fe, b: &mut Fe, choice: u8) {
+        let mask = 0_u64.wrapping_sub(choice as u64);
+        for i in 0..5 {
+            let t = mask & (a.0[i] ^ b.0[i]);
+            a.0[i] ^= t;
+            b.0[i] ^= t;
+        }
+    }
+}
+
+pub const X25519_KEY_SIZE: usize = 32;
+
+pub const X25519_BASEPOINT: [u8; X25519_KEY_SIZE] = [
+    9, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+];
+
+/// Computes the X25519 function of RFC 7748 section 5, in constant time.
+#[allow(clippy::many_single_char_names)]
+pub fn x25519(scalar: &[u8; X25519_KEY_SIZE], u: &[u8; X25519_KEY_SIZE]) -> [u8; X25519_KEY_SIZE] {
+    let mut k = *scalar;
+    k[0] &= 248;
+    k[31] &= 127;
+    k[31] |= 64;
+
+    let a24 = Fe([121_665, 0, 0, 0, 0]);
+    let x1 = Fe::from_bytes(u);
+    let mut x2 = Fe::ONE;
+    let mut z2 = Fe::ZERO;
+    let mut x3 = x1;
+    let mut z3 = Fe::ONE;
+    let mut swap = 0_u8;
+
+    for t in (0..255).rev() {
+        let k_t = (k[t / 8] >> (t % 8)) & 1;
+        swap ^= k_t;
+        Fe::cswap(&mut x2, &mut x3, swap);
+        Fe::cswap(&mut z2, &mut z3, swap);
+        swap = k_t;
+
+        let a = x2.add(&z2);
+        let aa = a.square();
+        let b = x2.sub(&z2);
+        let bb = b.square();
+        let e = aa.sub(&bb);
+        let c = x3.add(&z3);
+        let d = x3.sub(&z3);
+        let da = d.mul(&a);
+        let cb = c.mul(&b);
+        x3 = da.add(&cb).square();
+        z3 = x1.mul(&da.sub(&cb).square());
+        x2 = aa.mul(&bb);
+        z2 = e.mul(&aa.add(&a24.mul(&e)));
+    }
+    Fe::cswap(&mut x2, &mut x3, swap);
+    Fe::cswap(&mut z2, &mut z3, swap);
+    zeroize(&mut k);
+
+    x2.mul(&z2.invert()).to_bytes()
+}
@@ -0,0 +1,352 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//!
+//! Ed25519 signatures (RFC 8032), on top of the field arithmetic of the
+//! curve25519 module.
+//!
+
+use super::curve25519::Fe;
+use super::sha512::{Sha512, Sha512Variant};
+use super::{ct_eq, zeroize};
+
+pub const ED25519_SEED_SIZE: usize = 32;
+pub const ED25519_PUBLIC_KEY_SIZE: usize = 32;
+pub const ED25519_SIGNATURE_SIZE: usize = 64;
+
+const D: [u8; 32] = [
+    0xa3, 0x78, 0x59, 0x13, 0xca, 0x4d, 0xeb, 0x75, 0xab, 0xd8, 0x41, 0x41, 0x4d, 0x0a, 0x70, 0x00,
+    0x98, 0xe8, 0x79, 0x77, 0x79, 0x40, 0xc7, 0x8c, 0x73, 0xfe, 0x6f, 0x2b, 0xee, 0x6c, 0x03, 0x52,
+];
+
+const D2: [u8; 32] = [
+    0x59, 0xf1, 0xb2, 0x26, 0x94, 0x9b, 0xd6, 0xeb, 0x56, 0xb1, 0x83, 0x82, 0x9a, 0x14, 0xe0, 0x00,
+    0x30, 0xd1, 0xf3, 0xee, 0xf2, 0x80, 0x8e, 0x19, 0xe7, 0xfc, 0xdf, 0x56, 0xdc, 0xd9, 0x06, 0x24,
+];
+
+const SQRT_M1: [u8; 32] = [
+    0xb0, 0xa0, 0x0e, 0x4a, 0x27, 0x1b, 0xee, 0xc4, 0x78, 0xe4, 0x2f, 0xad, 0x06, 0x18, 0x43, 0x2f,
+    0xa7, 0xd7, 0xfb, 0x3d, 0x99, 0x00, 0x4d, 0x2b, 0x0b, 0xdf, 0xc1, 0x4f, 0x80, 0x24, 0x83, 0x2b,
+];
+
+// Encoding of the base point B, y = 4/5 with x positive
+const BASE_POINT: [u8; 32] = [
+    0x58, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66,
+    0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66,
+];
+
+// Group order L = 2^252 + 27742317777372353535851937790883648493
+const L: [u64; 4] = [0x5812_631a_5cf5_d3ed, 0x14de_f9de_a2f7_9cd6, 0, 0x1000_0000_0000_0000];
+
+/// A point in extended twisted Edwards coordinates (X:Y:Z:T), x = X/Z,
+/// y = Y/Z, xy = T/Z.
+#[derive(Clone, Copy)]
+struct EdwardsPoint {
+    x: Fe,
+    y: Fe,
+    z: Fe,
+    t: Fe,
+}
+
+impl EdwardsPoint {
+    fn identity() -> EdwardsPoint {
+        EdwardsPoint {
+            x: Fe::ZERO,
+            y: Fe::ONE,
+            z: Fe::ONE,
+            t: Fe::ZERO,
+        }
+    }
+
+    fn base() -> EdwardsPoint {
+        EdwardsPoint::decompress(&BASE_POINT).unwrap()
+    }
+
+    /// Unified addition (add-2008-hwcd-3), also valid for doubling.
+    #[allow(clippy::many_single_char_names)]
+    fn add(&self, q: &EdwardsPoint) -> EdwardsPoint {
+        let a = self.y.sub(&self.x).mul(&q.y.sub(&q.x));
+        let b = self.y.add(&self.x).mul(&q.y.add(&q.x));
+        let c = self.t.mul(&Fe::from_bytes(&D2)).mul(&q.t);
+        let d = self.z.add(&self.z).mul(&q.z);
+        let e = b.sub(&a);
+        let f = d.sub(&c);
+        let g = d.add(&c);
+        let h = b.add(&a);
+        EdwardsPoint {
+            x: e.mul(&f),
+            y: g.mul(&h),
+            z: f.mul(&g),
+            t: e.mul(&h),
+        }
+    }
+
+    fn neg(&self) -> EdwardsPoint {
+        EdwardsPoint {
+            x: self.x.neg(),
+            y: self.y,
+            z: self.z,
+            t: self.t.neg(),
+        }
+    }
+
+    fn select(a: &EdwardsPoint, b: &EdwardsPoint, choice: u8) -> EdwardsPoint {
+        EdwardsPoint {
+            x: Fe::select(&a.x, &b.x, choice),
+            y: Fe::select(&a.y, &b.y, choice),
+            z: Fe::select(&a.z, &b.z, choice),
+            t: Fe::select(&a.t, &b.t, choice),
+        }
+    }
+
+    /// Computes [s]P with a fixed sequence of operations.
+    fn mul(&self, s: &[u8; 32]) -> EdwardsPoint {
+        let mut r = EdwardsPoint::identity();
+        for i in (0..256).rev() {
+            r = r.add(&r);
+            let sum = r.add(self);
+            r = EdwardsPoint::select(&r, &sum, (s[i / 8] >> (i % 8)) & 1);
+        }
+        r
+    }
+
+    fn compress(&self) -> [u8; 32] {
+        let zinv = self.z.invert();
+        let x = self.x.mul(&zinv);
+        let y = self.y.mul(&zinv);
+        let mut s = y.to_bytes();
+        s[31] ^= x.is_negative() << 7;
+        s
+    }
+
+    /// Decodes a point as specified in RFC 8032 section 5.1.3.
+    #[allow(clippy::many_single_char_names)]
+    fn decompress(s: &[u8; 32]) -> Option<EdwardsPoint> {
+        let sign = s[31] >> 7;
+        let y = Fe::from_bytes(s);
+        let mut canonical = y.to_bytes();
+        canonical[31] |= sign << 7;
+        if canonical != *s {
+            return None;
+        }
+
+        let d = Fe::from_bytes(&D);
+        let yy = y.square();
+        let u = yy.sub(&Fe::ONE);
+        let v = d.mul(&yy).add(&Fe::ONE);
+        let v3 = v.square().mul(&v);
+        let v7 = v3.square().mul(&v);
+        let mut x = u.mul(&v3).mul(&u.mul(&v7).pow_p58());
+
+        let vxx = v.mul(&x.square());
+        if !vxx.ct_eq(&u) {
+            if vxx.ct_eq(&u.neg()) {
+                x = x.mul(&Fe::from_bytes(&SQRT_M1));
+            } else {
+                return None;
+            }
+        }
+        if x.is_zero() && sign == 1 {
+            return None;
+        }
+        if x.is_negative() != sign {
+            x = x.neg();
+        }
+
+        Some(EdwardsPoint {
+            x,
+            y,
+            z: Fe::ONE,
+            t: x.mul(&y),
+        })
+    }
+}
+
+/// Reduces a 512-bit little endian integer modulo L.
+fn scalar_reduce(x: &[u8; 64]) -> [u8; 32] {
+    let mut r = [0_u64; 4];
+    for i in (0..512).rev() {
+        // r = 2r + bit; r < 2L < 2^254 so no limb overflows
+        let bit = ((x[i / 8] >> (i % 8)) & 1) as u64;
+        r[3] = (r[3] << 1) | (r[2] >> 63);
+        r[2] = (r[2] << 1) | (r[1] >> 63);
+        r[1] = (r[1] << 1) | (r[0] >> 63);
+        r[0] = (r[0] << 1) | bit;
+
+        // r -= L if r >= L
+        let mut t = [0_u64; 4];
+        let mut borrow = 0_u64;
+        for j in 0..4 {
+            let (d1, b1) = r[j].overflowing_sub(L[j]);
+            let (d2, b2) = d1.overflowing_sub(borrow);
+            t[j] = d2;
+            borrow = (b1 | b2) as u64;
+        }
+        let mask = borrow.wrapping_sub(1);
+        for j in 0..4 {
+            r[j] = (t[j] & mask) | (r[j] & !mask);
+        }
+    }
+
+    let mut out = [0_u8; 32];
+    for (j, limb) in r.iter().enumerate() {
+        out[j * 8..j * 8 + 8].copy_from_slice(&limb.to_le_bytes());
+    }
+    out
+}
+
+/// Computes (a * b + c) mod L.
+#[allow(clippy::many_single_char_names)]
+fn scalar_mul_add(a: &[u8; 32], b: &[u8; 32], c: &[u8; 32]) -> [u8; 32] {
+    let load = |s: &[u8; 32]| {
+        let mut l = [0_u64; 4];
+        for (j, limb) in l.iter_mut().enumerate() {
+            let mut w = [0_u8; 8];
+            w.copy_from_slice(&s[j * 8..j * 8 + 8]);
+            *limb = u64::from_le_bytes(w);
+        }
+        l
+    };
+    let a = load(a);
+    let b = load(b);
+    let c = load(c);
+
+    let mut prod = [0_u64; 8];
+    prod[..4].copy_from_slice(&c);
+    for i in 0..4 {
+        let mut carry: u128 = 0;
+        for j in 0..4 {
+            let t = (a[i] as u128) * (b[j] as u128) + prod[i + j] as u128 + carry;
+            prod[i + j] = t as u64;
+            carry = t >> 64;
+        }
+        let mut k = i + 4;
+        while carry != 0 && k < 8 {
+            let t = prod[k] as u128 + carry;
+            prod[k] = t as u64;
+            carry = t >> 64;
+            k += 1;
+        }
+    }
+
+    let mut wide = [0_u8; 64];
+    for (j, limb) in prod.iter().enumerate() {
+        wide[j * 8..j * 8 + 8].copy_from_slice(&limb.to_le_bytes());
+    }
+    let out = scalar_reduce(&wide);
+    zeroize(&mut wide);
+    out
+}
+
+fn is_canonical_scalar(s: &[u8; 32]) -> bool {
+    for j in (0..4).rev() {
+        let mut w = [0_u8; 8];
+        w.copy_from_slice(&s[j * 8..j * 8 + 8]);
+        let limb = u64::from_le_bytes(w);
+        if limb != L[j] {
+            return limb < L[j];
+        }
+    }
+    false
+}
+
+fn hash_to_scalar(parts: &[&[u8]]) -> [u8; 32] {
+    let mut h = Sha512::new(Sha512Variant::Sha512);
+    for part in parts {
+        h.update(part);
+    }
+    let mut digest = [0_u8; 64];
+    h.finalize_into(&mut digest);
+    let s = scalar_reduce(&digest);
+    zeroize(&mut digest);
+    s
+}
+
+/// The secret scalar and nonce prefix derived from a seed.
+struct ExpandedKey {
+    scalar: [u8; 32],
+    prefix: [u8; 32],
+}
+
+impl ExpandedKey {
+    fn new(seed: &[u8; ED25519_SEED_SIZE]) -> ExpandedKey {
+        let mut h = [0_u8; 64];
+        Sha512::digest(Sha512Variant::Sha512, seed, &mut h);
+        let mut key = ExpandedKey {
+            scalar: [0_u8; 32],
+            prefix: [0_u8; 32],
+        };
+        key.scalar.copy_from_slice(&h[..32]);
+        key.prefix.copy_from_slice(&h[32..]);
+        zeroize(&mut h);
+        key.scalar[0] &= 248;
+        key.scalar[31] &= 127;
+        key.scalar[31] |= 64;
+        key
+    }
+}
+
+impl Drop for ExpandedKey {
+    fn drop(&mut self) {
+        zeroize(&mut self.scalar);
+        zeroize(&mut self.prefix);
+    }
+}
+
+/// Derives the public key A = [s]B of a seed.
+pub fn public_key(seed: &[u8; ED25519_SEED_SIZE]) -> [u8; ED25519_PUBLIC_KEY_SIZE] {
+    let key = ExpandedKey::new(seed);
+    EdwardsPoint::base().mul(&key.scalar).compress()
+}
+
+/// Signs `msg` with the key derived from `seed`.
+pub fn sign(seed: &[u8; ED25519_SEED_SIZE], msg: &[u8]) -> [u8; ED25519_SIGNATURE_SIZE] {
+    let key = ExpandedKey::new(seed);
+    let base = EdwardsPoint::base();
+    let public = base.mul(&key.scalar).compress();
+
+    let mut r = hash_to_scalar(&[&key.prefix, msg]);
+    let big_r = base.mul(&r).compress();
+    let k = hash_to_scalar(&[&big_r, &public, msg]);
+    let s = scalar_mul_add(&k, &key.scalar, &r);
+    zeroize(&mut r);
+
+    let mut sig = [0_u8; ED25519_SIGNATURE_SIZE];
+    sig[..32].copy_from_slice(&big_r);
+    sig[32..].copy_from_slice(&s);
+    sig
+}
+
+/// Verifies a signature over `msg`.
+pub fn verify(public: &[u8; ED25519_PUBLIC_KEY_SIZE], msg: &[u8], sig: &[u8; ED25519_SIGNATURE_SIZE]) -> bool {
+    let mut big_r = [0_u8; 32];
+    let mut s = [0_u8; 32];
+    big_r.copy_from_slice(&sig[..32]);
+    s.copy_from_slice(&sig[32..]);
+    if !is_canonical_scalar(&s) {
+        return false;
+    }
+    let a = match EdwardsPoint::decompress(public) {
+        Some(a) => a,
+        None => return false,
+    };
+    let k = hash_to_scalar(&[&big_r, public, msg]);
+
+    // check [S]B - [k]A == R
+    let check = EdwardsPoint::base().mul(&s).add(&a.neg().mul(&k));
+    ct_eq(&check.compress(), &big_r)
+}
@@ -0,0 +1,175 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//!
+//! AES-GCM with a 96-bit IV (NIST SP 800-38D), usable incrementally.
+//!
+
+use super::aes::{Aes, AES_BLOCK_SIZE};
+use super::ghash::GHash;
+use super::zeroize;
+
+pub const GCM_IV_SIZE: usize = 12;
+pub const GCM_TAG_SIZE: usize = 16;
+
+/// Largest plaintext GCM can protect under one IV: 2^39 - 256 bits.
+const GCM_MAX_TEXT_LEN: u64 = (1 << 36) - 32;
+
+pub struct AesGcm {
+    aes: Aes,
+    ghash: GHash,
+    counter: [u8; AES_BLOCK_SIZE],
+    tag_mask: [u8; AES_BLOCK_SIZE],
+    keystream: [u8; AES_BLOCK_SIZE],
+    ks_pos: usize,
+    aad_len: u64,
+    text_len: u64,
+}
+
+impl AesGcm {
+    ///
+    /// Sets up the key schedule, hashes the AAD and prepares the counter.
+    /// Returns None if the key is neither 128 nor 256 bits long or the IV is not 96 bits.
+    ///
+    pub fn new(key: &[u8], iv: &[u8], aad: &[u8]) -> Option<AesGcm> {
+        if iv.len() != GCM_IV_SIZE {
+            return None;
+        }
+        let aes = Aes::new(key)?;
+
+        let mut h = [0_u8; AES_BLOCK_SIZE];
+        aes.encrypt_block(&mut h);
+        let mut ghash = GHash::new(&h);
+        zeroize(&mut h);
+        ghash.update(aad);
+        ghash.pad();
+
+        let mut counter = [0_u8; AES_BLOCK_SIZE];
+        counter[..GCM_IV_SIZE].copy_from_slice(iv);
+        counter[AES_BLOCK_SIZE - 1] = 1;
+        let mut tag_mask = counter;
+        aes.encrypt_block(&mut tag_mask);
+
+        Some(AesGcm {
+            aes,
+            ghash,
+            counter,
+            tag_mask,
+            keystream: [0_u8; AES_BLOCK_SIZE],
+            ks_pos: AES_BLOCK_SIZE,
+            aad_len: aad.len() as u64,
+            text_len: 0,
+        })
+    }
+
+    fn increment_counter(&mut self) {
+        let mut ctr = [0_u8; 4];
+        ctr.copy_from_slice(&self.counter[GCM_IV_SIZE..]);
+        let ctr = u32::from_be_bytes(ctr).wrapping_add(1);
+        self.counter[GCM_IV_SIZE..].copy_from_slice(&ctr.to_be_bytes());
+    }
+
+    fn apply_keystream(&mut self, src: &[u8], dst: &mut [u8]) {
+        for (s, d) in src.iter().zip(dst.iter_mut()) {
+            if self.ks_pos == AES_BLOCK_SIZE {
+                self.increment_counter();
+                self.keystream = self.counter;
+                self.aes.encrypt_block(&mut self.keystream);
+                self.ks_pos = 0;
+            }
+            *d = *s ^ self.keystream[self.ks_pos];
+            self.ks_pos += 1;
+        }
+    }
+
+    ///
+    /// Accounts for `len` more bytes of text. Returns false if the total would
+    /// exceed what GCM allows under a single IV.
+    ///
+    fn reserve(&mut self, len: usize) -> bool {
+        match self.text_len.checked_add(len as u64) {
+            Some(total) if total <= GCM_MAX_TEXT_LEN => {
+                self.text_len = total;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    ///
+    /// Hashes ciphertext without decrypting it, so the tag can be checked
+    /// before any plaintext is released.
+    ///
+    pub fn authenticate(&mut self, ciphertext: &[u8]) -> bool {
+        if !self.reserve(ciphertext.len()) {
+            return false;
+        }
+        self.ghash.update(ciphertext);
+        true
+    }
+
+    ///
+    /// Decrypts ciphertext that has already been passed to authenticate.
+    ///
+    pub fn decrypt_authenticated(&mut self, src: &[u8], dst: &mut [u8]) {
+        self.apply_keystream(src, &mut dst[..src.len()]);
+    }
+
+    pub fn encrypt_update(&mut self, src: &[u8], dst: &mut [u8]) -> bool {
+        if !self.reserve(src.len()) {
+            return false;
+        }
+        let dst = &mut dst[..src.len()];
+        self.apply_keystream(src, dst);
+        self.ghash.update(dst);
+        true
+    }
+
+    pub fn decrypt_update(&mut self, src: &[u8], dst: &mut [u8]) -> bool {
+        if !self.authenticate(src) {
+            return false;
+        }
+        self.decrypt_authenticated(src, dst);
+        true
+    }
+
+    ///
+    /// Computes the tag over everything processed so far. The state is left
+    /// untouched, so more text may still follow.
+    ///
+    pub fn tag(&self) -> [u8; GCM_TAG_SIZE] {
+        let mut ghash = self.ghash.clone();
+        ghash.pad();
+        let mut lengths = [0_u8; 16];
+        lengths[..8].copy_from_slice(&(self.aad_len * 8).to_be_bytes());
+        lengths[8..].copy_from_slice(&(self.text_len * 8).to_be_bytes());
+        ghash.update(&lengths);
+        let mut tag = ghash.finalize();
+        for (t, m) in tag.iter_mut().zip(self.tag_mask.iter()) {
+            *t ^= *m;
+        }
+        tag
+    }
+}
+
+impl Drop for AesGcm {
+    fn drop(&mut self) {
+        zeroize(&mut self.counter);
+        zeroize(&mut self.tag_mask);
+        zeroize(&mut self.keystream);
+    }
+}
@@ -0,0 +1,197 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//!
+//! GHASH universal hash used by AES-GCM (NIST SP 800-38D).
+//!
+//! The multiplication in GF(2^128) follows the constant-time "ctmul64"
+//! approach: carry-less products are emulated with integer multiplications
+//! on operands whose bits are spread out so that carries never reach a
+//! significant bit.
+//!
+
+use super::zeroize;
+
+pub const GHASH_BLOCK_SIZE: usize = 16;
+
+#[derive(Clone)]
+pub struct GHash {
+    h0: u64,
+    h1: u64,
+    y0: u64,
+    y1: u64,
+    buf: [u8; GHASH_BLOCK_SIZE],
+    buf_len: usize,
+}
+
+fn bmul64(x: u64, y: u64) -> u64 {
+    let x0 = x & 0x1111_1111_1111_1111;
+    let x1 = x & 0x2222_2222_2222_2222;
+    let x2 = x & 0x4444_4444_4444_4444;
+    let x3 = x & 0x8888_8888_8888_8888;
+    let y0 = y & 0x1111_1111_1111_1111;
+    let y1 = y & 0x2222_2222_2222_2222;
+    let y2 = y & 0x4444_4444_4444_4444;
+    let y3 = y & 0x8888_8888_8888_8888;
+    let mut z0 = x0.wrapping_mul(y0) ^ x1.wrapping_mul(y3) ^ x2.wrapping_mul(y2) ^ x3.wrapping_mul(y1);
+    let mut z1 = x0.wrapping_mul(y1) ^ x1.wrapping_mul(y0) ^ x2.wrapping_mul(y3) ^ x3.wrapping_mul(y2);
+    let mut z2 = x0.wrapping_mul(y2) ^ x1.wrapping_mul(y1) ^ x2.wrapping_mul(y0) ^ x3.wrapping_mul(y3);
+    let mut z3 = x0.wrapping_mul(y3) ^ x1.wrapping_mul(y2) ^ x2.wrapping_mul(y1) ^ x3.wrapping_mul(y0);
+    z0 &= 0x1111_1111_1111_1111;
+    z1 &= 0x2222_2222_2222_2222;
+    z2 &= 0x4444_4444_4444_4444;
+    z3 &= 0x8888_8888_8888_8888;
+    z0 | z1 | z2 | z3
+}
+
+fn rev64(mut x: u64) -> u64 {
+    x = ((x & 0x5555_5555_5555_5555) << 1) | ((x >> 1) & 0x5555_5555_5555_5555);
+    x = ((x & 0x3333_3333_3333_3333) << 2) | ((x >> 2) & 0x3333_3333_3333_3333);
+    x = ((x & 0x0F0F_0F0F_0F0F_0F0F) << 4) | ((x >> 4) & 0x0F0F_0F0F_0F0F_0F0F);
+    x = ((x & 0x00FF_00FF_00FF_00FF) << 8) | ((x >> 8) & 0x00FF_00FF_00FF_00FF);
+    x = ((x & 0x0000_FFFF_0000_FFFF) << 16) | ((x >> 16) & 0x0000_FFFF_0000_FFFF);
+    (x << 32) | (x >> 32)
+}
+
+fn load_be64(b: &[u8]) -> u64 {
+    let mut v = [0_u8; 8];
+    v.copy_from_slice(&b[..8]);
+    u64::from_be_bytes(v)
+}
+
+impl GHash {
+    pub fn new(h: &[u8; GHASH_BLOCK_SIZE]) -> GHash {
+        GHash {
+            h0: load_be64(&h[8..]),
+            h1: load_be64(&h[..8]),
+            y0: 0,
+            y1: 0,
+            buf: [0_u8; GHASH_BLOCK_SIZE],
+            buf_len: 0,
+        }
+    }
+
+    fn mul_block(&mut self, block: &[u8]) {
+        let h0 = self.h0;
+        let h1 = self.h1;
+        let h2 = h0 ^ h1;
+        let h0r = rev64(h0);
+        let h1r = rev64(h1);
+        let h2r = h0r ^ h1r;
+
+        let y1 = self.y1 ^ load_be64(&block[..8]);
+        let y0 = self.y0 ^ load_be64(&block[8..]);
+        let y2 = y0 ^ y1;
+        let y0r = rev64(y0);
+        let y1r = rev64(y1);
+        let y2r = y0r ^ y1r;
+
+        let z0 = bmul64(y0, h0);
+        let z1 = bmul64(y1, h1);
+        let mut z2 = bmul64(y2, h2);
+        let mut z0h = bmul64(y0r, h0r);
+        let mut z1h = bmul64(y1r, h1r);
+        let mut z2h = bmul64(y2r, h2r);
+        z2 ^= z0 ^ z1;
+        z2h ^= z0h ^ z1h;
+        z0h = rev64(z0h) >> 1;
+        z1h = rev64(z1h) >> 1;
+        z2h = rev64(z2h) >> 1;
+
+        let mut v0 = z0;
+        let mut v1 = z0h ^ z2;
+        let mut v2 = z1 ^ z2h;
+        let mut v3 = z1h;
+
+        v3 = (v3 << 1) | (v2 >> 63);
+        v2 = (v2 << 1) | (v1 >> 63);
+        v1 = (v1 << 1) | (v0 >> 63);
+        v0 <<= 1;
+
+        v2 ^= v0 ^ (v0 >> 1) ^ (v0 >> 2) ^ (v0 >> 7);
+        v1 ^= (v0 << 63) ^ (v0 << 62) ^ (v0 << 57);
+        v3 ^= v1 ^ (v1 >> 1) ^ (v1 >> 2) ^ (v1 >> 7);
+        v2 ^= (v1 << 63) ^ (v1 << 62) ^ (v1 << 57);
+
+        self.y0 = v2;
+        self.y1 = v3;
+    }
+
+    ///
+    /// Absorbs data. Input that does not fill a whole block is buffered until
+    /// more data arrives or pad is called.
+    ///
+    pub fn update(&mut self, mut data: &[u8]) {
+        if self.buf_len > 0 {
+            let n = core::cmp::min(GHASH_BLOCK_SIZE - self.buf_len, data.len());
+            self.buf[self.buf_len..self.buf_len + n].copy_from_slice(&data[..n]);
+            self.buf_len += n;
+            data = &data[n..];
+            if self.buf_len < GHASH_BLOCK_SIZE {
+                return;
+            }
+            let block = self.buf;
+            self.mul_block(&block);
+            self.buf_len = 0;
+        }
+        while data.len() >= GHASH_BLOCK_SIZE {
+            self.mul_block(&data[..GHASH_BLOCK_SIZE]);
+            data = &data[GHASH_BLOCK_SIZE..];
+        }
+        if !data.is_empty() {
+            self.buf[..data.len()].copy_from_slice(data);
+            self.buf_len = data.len();
+        }
+    }
+
+    ///
+    /// Zero pads and absorbs a pending partial block, if any.
+    ///
+    pub fn pad(&mut self) {
+        if self.buf_len > 0 {
+            for b in self.buf[self.buf_len..].iter_mut() {
+                *b = 0;
+            }
+            let block = self.buf;
+            self.mul_block(&block);
+            self.buf_len = 0;
+        }
+    }
+
+    ///
+    /// Pads the pending input and returns the current hash value.
+    ///
+    pub fn finalize(&mut self) -> [u8; GHASH_BLOCK_SIZE] {
+        self.pad();
+        let mut out = [0_u8; GHASH_BLOCK_SIZE];
+        out[..8].copy_from_slice(&self.y1.to_be_bytes());
+        out[8..].copy_from_slice(&self.y0.to_be_bytes());
+        out
+    }
+}
+
+impl Drop for GHash {
+    fn drop(&mut self) {
+        unsafe {
+            core::ptr::write_volatile(&mut self.h0, 0);
+            core::ptr::write_volatile(&mut self.h1, 0);
+            core::ptr::write_volatile(&mut self.y0, 0);
+            core::ptr::write_volatile(&mut self.y1, 0);
+        }
+        zeroize(&mut self.buf);
+    }
+}
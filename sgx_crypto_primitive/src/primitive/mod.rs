@@ -0,0 +1,55 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//!
+//! Pure Rust implementations of the primitives which are not provided by
+//! libsgx_tcrypto.a.
+//!
+
+use core::ptr;
+use core::sync::atomic::{self, Ordering};
+
+pub mod aes;
+pub mod bignum;
+pub mod chacha20;
+pub mod chacha20_poly1305;
+pub mod curve25519;
+pub mod ed25519;
+pub mod gcm;
+pub mod ghash;
+pub mod poly1305;
+pub mod rsa;
+pub mod sha512;
+
+/// Overwrites `buf` with zeros in a way the optimizer will not elide.
+pub fn zeroize(buf: &mut [u8]) {
+    for b in buf.iter_mut() {
+        unsafe { ptr::write_volatile(b, 0) };
+    }
+    atomic::compiler_fence(Ordering::SeqCst);
+}
+
+/// Overwrites `buf` with zeros in a way the optimizer will not elide.
+pub fn zeroize_u32(buf: &mut [u32]) {
+    for w in buf.iter_mut() {
+        unsafe { ptr::write_volatile(w, 0) };
+    }
+    atomic::compiler_fence(Ordering::SeqCst);
+}
+
+/// Compares two buffers in time that only depends on their length.
+pub use sgx_types::ct_eq_bytes as ct_eq;
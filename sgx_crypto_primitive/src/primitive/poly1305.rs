@@ -0,0 +1,231 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//!
+//! Poly1305 one-time authenticator (RFC 8439), using 26-bit limbs.
+//!
+
+use super::{zeroize, zeroize_u32};
+
+pub const POLY1305_KEY_SIZE: usize = 32;
+pub const POLY1305_BLOCK_SIZE: usize = 16;
+pub const POLY1305_TAG_SIZE: usize = 16;
+
+#[derive(Clone)]
+pub struct Poly1305 {
+    r: [u32; 5],
+    h: [u32; 5],
+    pad: [u32; 4],
+    buf: [u8; POLY1305_BLOCK_SIZE],
+    buf_len: usize,
+}
+
+fn load_le32(b: &[u8]) -> u32 {
+    u32::from_le_bytes([b[0], b[1], b[2], b[3]])
+}
+
+impl Poly1305 {
+    pub fn new(key: &[u8; POLY1305_KEY_SIZE]) -> Poly1305 {
+        Poly1305 {
+            r: [
+                load_le32(&key[0..]) & 0x03ff_ffff,
+                (load_le32(&key[3..]) >> 2) & 0x03ff_ff03,
+                (load_le32(&key[6..]) >> 4) & 0x03ff_c0ff,
+                (load_le32(&key[9..]) >> 6) & 0x03f0_3fff,
+                (load_le32(&key[12..]) >> 8) & 0x000f_ffff,
+            ],
+            h: [0_u32; 5],
+            pad: [
+                load_le32(&key[16..]),
+                load_le32(&key[20..]),
+                load_le32(&key[24..]),
+                load_le32(&key[28..]),
+            ],
+            buf: [0_u8; POLY1305_BLOCK_SIZE],
+            buf_len: 0,
+        }
+    }
+
+    fn block(&mut self, m: &[u8], hibit: u32) {
+        let [r0, r1, r2, r3, r4] = self.r;
+        let s1 = r1 * 5;
+        let s2 = r2 * 5;
+        let s3 = r3 * 5;
+        let s4 = r4 * 5;
+
+        let mut h0 = self.h[0] + (load_le32(&m[0..]) & 0x03ff_ffff);
+        let mut h1 = self.h[1] + ((load_le32(&m[3..]) >> 2) & 0x03ff_ffff);
+        let mut h2 = self.h[2] + ((load_le32(&m[6..]) >> 4) & 0x03ff_ffff);
+        let mut h3 = self.h[3] + ((load_le32(&m[9..]) >> 6) & 0x03ff_ffff);
+        let mut h4 = self.h[4] + ((load_le32(&m[12..]) >> 8) | hibit);
+
+        let m64 = |a: u32, b: u32| u64::from(a) * u64::from(b);
+        let d0 = m64(h0, r0) + m64(h1, s4) + m64(h2, s3) + m64(h3, s2) + m64(h4, s1);
+        let mut d1 = m64(h0, r1) + m64(h1, r0) + m64(h2, s4) + m64(h3, s3) + m64(h4, s2);
+        let mut d2 = m64(h0, r2) + m64(h1, r1) + m64(h2, r0) + m64(h3, s4) + m64(h4, s3);
+        let mut d3 = m64(h0, r3) + m64(h1, r2) + m64(h2, r1) + m64(h3, r0) + m64(h4, s4);
+        let mut d4 = m64(h0, r4) + m64(h1, r3) + m64(h2, r2) + m64(h3, r1) + m64(h4, r0);
+
+        let mut c = (d0 >> 26) as u32;
+        h0 = d0 as u32 & 0x03ff_ffff;
+        d1 += u64::from(c);
+        c = (d1 >> 26) as u32;
+        h1 = d1 as u32 & 0x03ff_ffff;
+        d2 += u64::from(c);
+        c = (d2 >> 26) as u32;
+        h2 = d2 as u32 & 0x03ff_ffff;
+        d3 += u64::from(c);
+        c = (d3 >> 26) as u32;
+        h3 = d3 as u32 & 0x03ff_ffff;
+        d4 += u64::from(c);
+        c = (d4 >> 26) as u32;
+        h4 = d4 as u32 & 0x03ff_ffff;
+        h0 += c * 5;
+        c = h0 >> 26;
+        h0 &= 0x03ff_ffff;
+        h1 += c;
+
+        self.h = [h0, h1, h2, h3, h4];
+    }
+
+    ///
+    /// Absorbs message data, buffering any trailing partial block.
+    ///
+    pub fn update(&mut self, mut data: &[u8]) {
+        if self.buf_len > 0 {
+            let n = core::cmp::min(POLY1305_BLOCK_SIZE - self.buf_len, data.len());
+            self.buf[self.buf_len..self.buf_len + n].copy_from_slice(&data[..n]);
+            self.buf_len += n;
+            data = &data[n..];
+            if self.buf_len < POLY1305_BLOCK_SIZE {
+                return;
+            }
+            let block = self.buf;
+            self.block(&block, 1 << 24);
+            self.buf_len = 0;
+        }
+        while data.len() >= POLY1305_BLOCK_SIZE {
+            self.block(&data[..POLY1305_BLOCK_SIZE], 1 << 24);
+            data = &data[POLY1305_BLOCK_SIZE..];
+        }
+        if !data.is_empty() {
+            self.buf[..data.len()].copy_from_slice(data);
+            self.buf_len = data.len();
+        }
+    }
+
+    ///
+    /// Absorbs zero bytes up to the next block boundary, as the AEAD
+    /// construction requires after the AAD and the ciphertext.
+    ///
+    pub fn pad(&mut self) {
+        if self.buf_len > 0 {
+            let zeros = [0_u8; POLY1305_BLOCK_SIZE];
+            let n = POLY1305_BLOCK_SIZE - self.buf_len;
+            self.update(&zeros[..n]);
+        }
+    }
+
+    pub fn finalize(&mut self) -> [u8; POLY1305_TAG_SIZE] {
+        if self.buf_len > 0 {
+            let mut block = [0_u8; POLY1305_BLOCK_SIZE];
+            block[..self.buf_len].copy_from_slice(&self.buf[..self.buf_len]);
+            block[self.buf_len] = 1;
+            self.block(&block, 0);
+            self.buf_len = 0;
+        }
+
+        let [mut h0, mut h1, mut h2, mut h3, mut h4] = self.h;
+
+        let mut c = h1 >> 26;
+        h1 &= 0x03ff_ffff;
+        h2 += c;
+        c = h2 >> 26;
+        h2 &= 0x03ff_ffff;
+        h3 += c;
+        c = h3 >> 26;
+        h3 &= 0x03ff_ffff;
+        h4 += c;
+        c = h4 >> 26;
+        h4 &= 0x03ff_ffff;
+        h0 += c * 5;
+        c = h0 >> 26;
+        h0 &= 0x03ff_ffff;
+        h1 += c;
+
+        // compute h + -p
+        let mut g0 = h0.wrapping_add(5);
+        c = g0 >> 26;
+        g0 &= 0x03ff_ffff;
+        let mut g1 = h1.wrapping_add(c);
+        c = g1 >> 26;
+        g1 &= 0x03ff_ffff;
+        let mut g2 = h2.wrapping_add(c);
+        c = g2 >> 26;
+        g2 &= 0x03ff_ffff;
+        let mut g3 = h3.wrapping_add(c);
+        c = g3 >> 26;
+        g3 &= 0x03ff_ffff;
+        let mut g4 = h4.wrapping_add(c).wrapping_sub(1 << 26);
+
+        // select h if h < p, or h + -p if h >= p
+        let mut mask = (g4 >> 31).wrapping_sub(1);
+        g0 &= mask;
+        g1 &= mask;
+        g2 &= mask;
+        g3 &= mask;
+        g4 &= mask;
+        mask = !mask;
+        h0 = (h0 & mask) | g0;
+        h1 = (h1 & mask) | g1;
+        h2 = (h2 & mask) | g2;
+        h3 = (h3 & mask) | g3;
+        h4 = (h4 & mask) | g4;
+
+        // h = h % (2^128)
+        let h0 = h0 | (h1 << 26);
+        let h1 = (h1 >> 6) | (h2 << 20);
+        let h2 = (h2 >> 12) | (h3 << 14);
+        let h3 = (h3 >> 18) | (h4 << 8);
+
+        // mac = (h + pad) % (2^128)
+        let mut f = u64::from(h0) + u64::from(self.pad[0]);
+        let t0 = f as u32;
+        f = u64::from(h1) + u64::from(self.pad[1]) + (f >> 32);
+        let t1 = f as u32;
+        f = u64::from(h2) + u64::from(self.pad[2]) + (f >> 32);
+        let t2 = f as u32;
+        f = u64::from(h3) + u64::from(self.pad[3]) + (f >> 32);
+        let t3 = f as u32;
+
+        let mut tag = [0_u8; POLY1305_TAG_SIZE];
+        tag[0..4].copy_from_slice(&t0.to_le_bytes());
+        tag[4..8].copy_from_slice(&t1.to_le_bytes());
+        tag[8..12].copy_from_slice(&t2.to_le_bytes());
+        tag[12..16].copy_from_slice(&t3.to_le_bytes());
+        tag
+    }
+}
+
+impl Drop for Poly1305 {
+    fn drop(&mut self) {
+        zeroize_u32(&mut self.r);
+        zeroize_u32(&mut self.h);
+        zeroize_u32(&mut self.pad);
+        zeroize(&mut self.buf);
+    }
+}
@@ -0,0 +1,278 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//!
+//! The RSA primitives RSAEP/RSAVP1 and RSADP/RSASP1 (RFC 8017, section 5).
+//!
+//! Key components are taken in the little endian byte order used by
+//! sgx_create_rsa_key_pair; message representatives are big endian octet
+//! strings of the modulus length.
+//!
+
+use super::bignum::{self, Limbs, Modulus, MAX_LIMBS};
+use super::ct_eq;
+
+const HALF_LIMBS: usize = MAX_LIMBS / 2;
+
+pub struct RsaPublicKey {
+    n: Limbs,
+    e: Limbs,
+    n_len: usize,
+    e_len: usize,
+    size: usize,
+}
+
+impl RsaPublicKey {
+    pub fn new(n: &[u8], e: &[u8]) -> Option<RsaPublicKey> {
+        let mut key = RsaPublicKey {
+            n: [0_u64; MAX_LIMBS],
+            e: [0_u64; MAX_LIMBS],
+            n_len: 0,
+            e_len: 0,
+            size: 0,
+        };
+        if !bignum::from_le_bytes(n, &mut key.n) || !bignum::from_le_bytes(e, &mut key.e) {
+            return None;
+        }
+        if !key.init() {
+            return None;
+        }
+        Some(key)
+    }
+
+    fn init(&mut self) -> bool {
+        let bits = bignum::bit_len(&self.n);
+        if bits < 512 || self.n[0] & 1 == 0 || bignum::bit_len(&self.e) < 2 || self.e[0] & 1 == 0 {
+            return false;
+        }
+        self.n_len = bignum::limbs_for((bits + 7) / 8);
+        self.e_len = bignum::limbs_for((bignum::bit_len(&self.e) + 7) / 8);
+        self.size = (bits + 7) / 8;
+        true
+    }
+
+    /// Length of the modulus in bytes.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Length of the modulus in bits.
+    pub fn bits(&self) -> usize {
+        bignum::bit_len(&self.n[..self.n_len])
+    }
+
+    /// Parses a representative, rejecting values not less than n.
+    fn load(&self, input: &[u8], out: &mut Limbs) -> bool {
+        input.len() == self.size
+            && bignum::from_be_bytes(input, out)
+            && bignum::lt(&out[..self.n_len], &self.n[..self.n_len])
+    }
+
+    /// out = input ^ e mod n.
+    pub fn public_op(&self, input: &[u8], out: &mut [u8]) -> bool {
+        if out.len() != self.size {
+            return false;
+        }
+        let mut c = [0_u64; MAX_LIMBS];
+        if !self.load(input, &mut c) {
+            return false;
+        }
+        let modulus = match Modulus::new(&self.n[..self.n_len]) {
+            Some(modulus) => modulus,
+            None => return false,
+        };
+        let mut m = [0_u64; MAX_LIMBS];
+        modulus.mod_exp(&c, &self.e[..self.e_len], &mut m);
+        bignum::to_be_bytes(&m[..self.n_len], out)
+    }
+}
+
+pub struct RsaPrivateKey {
+    public: RsaPublicKey,
+    d: Limbs,
+    p: Limbs,
+    q: Limbs,
+    dp: Limbs,
+    dq: Limbs,
+    qinv: Limbs,
+    d_len: usize,
+    half_len: usize,
+    crt: bool,
+}
+
+impl RsaPrivateKey {
+    fn empty() -> RsaPrivateKey {
+        RsaPrivateKey {
+            public: RsaPublicKey {
+                n: [0_u64; MAX_LIMBS],
+                e: [0_u64; MAX_LIMBS],
+                n_len: 0,
+                e_len: 0,
+                size: 0,
+            },
+            d: [0_u64; MAX_LIMBS],
+            p: [0_u64; MAX_LIMBS],
+            q: [0_u64; MAX_LIMBS],
+            dp: [0_u64; MAX_LIMBS],
+            dq: [0_u64; MAX_LIMBS],
+            qinv: [0_u64; MAX_LIMBS],
+            d_len: 0,
+            half_len: 0,
+            crt: false,
+        }
+    }
+
+    /// A key in CRT form; the modulus is computed as p * q.
+    pub fn from_crt(
+        e: &[u8],
+        p: &[u8],
+        q: &[u8],
+        dmp1: &[u8],
+        dmq1: &[u8],
+        iqmp: &[u8],
+    ) -> Option<RsaPrivateKey> {
+        let mut key = RsaPrivateKey::empty();
+        if !bignum::from_le_bytes(p, &mut key.p[..HALF_LIMBS])
+            || !bignum::from_le_bytes(q, &mut key.q[..HALF_LIMBS])
+            || !bignum::from_le_bytes(dmp1, &mut key.dp[..HALF_LIMBS])
+            || !bignum::from_le_bytes(dmq1, &mut key.dq[..HALF_LIMBS])
+            || !bignum::from_le_bytes(iqmp, &mut key.qinv[..HALF_LIMBS])
+            || !bignum::from_le_bytes(e, &mut key.public.e)
+        {
+            return None;
+        }
+        key.half_len = bignum::limbs_for((bignum::bit_len(&key.p).max(bignum::bit_len(&key.q)) + 7) / 8);
+        bignum::mul(&key.p[..HALF_LIMBS], &key.q[..HALF_LIMBS], &mut key.public.n);
+        key.crt = true;
+        if !key.public.init() {
+            return None;
+        }
+        Some(key)
+    }
+
+    /// A key given by its modulus and private exponent.
+    pub fn from_exponent(n: &[u8], e: &[u8], d: &[u8]) -> Option<RsaPrivateKey> {
+        let mut key = RsaPrivateKey::empty();
+        if !bignum::from_le_bytes(n, &mut key.public.n)
+            || !bignum::from_le_bytes(e, &mut key.public.e)
+            || !bignum::from_le_bytes(d, &mut key.d)
+        {
+            return None;
+        }
+        if !key.public.init() {
+            return None;
+        }
+        key.d_len = key.public.n_len;
+        if bignum::is_zero(&key.d)
+            || bignum::bit_len(&key.d) > key.public.bits()
+            || !bignum::lt(&key.d[..key.d_len], &key.public.n[..key.d_len])
+        {
+            return None;
+        }
+        Some(key)
+    }
+
+    pub fn public_key(&self) -> &RsaPublicKey {
+        &self.public
+    }
+
+    /// out = input ^ d mod n. The result is checked with the public
+    /// exponent before it is released, so a faulty computation never leaks
+    /// a value that would reveal a factor of n.
+    pub fn private_op(&self, input: &[u8], out: &mut [u8]) -> bool {
+        let public = &self.public;
+        if out.len() != public.size {
+            return false;
+        }
+        let mut c = [0_u64; MAX_LIMBS];
+        if !public.load(input, &mut c) {
+            return false;
+        }
+
+        let mut m = [0_u64; MAX_LIMBS];
+        let done = if self.crt {
+            self.crt_exp(&c[..public.n_len], &mut m)
+        } else {
+            match Modulus::new(&public.n[..public.n_len]) {
+                Some(modulus) => {
+                    modulus.mod_exp(&c, &self.d[..self.d_len], &mut m);
+                    true
+                }
+                None => false,
+            }
+        };
+        let ok = done && bignum::to_be_bytes(&m[..public.n_len], out);
+        bignum::zeroize_limbs(&mut m);
+        if !ok {
+            return false;
+        }
+
+        let mut check = [0_u8; MAX_LIMBS * 8];
+        if !public.public_op(out, &mut check[..public.size]) || !ct_eq(&check[..public.size], input) {
+            super::zeroize(out);
+            return false;
+        }
+        true
+    }
+
+    fn crt_exp(&self, c: &[u64], out: &mut Limbs) -> bool {
+        let len = self.half_len;
+        let (mp, mq) = match (Modulus::new(&self.p[..len]), Modulus::new(&self.q[..len])) {
+            (Some(mp), Some(mq)) => (mp, mq),
+            _ => return false,
+        };
+        let (plen, qlen) = (mp.limbs().len(), mq.limbs().len());
+
+        let mut t = [0_u64; MAX_LIMBS];
+        let mut m1 = [0_u64; MAX_LIMBS];
+        let mut m2 = [0_u64; MAX_LIMBS];
+        let mut h = [0_u64; MAX_LIMBS];
+
+        // m1 = c^dP mod p, m2 = c^dQ mod q
+        mp.reduce(c, &mut t);
+        mp.mod_exp(&t, &self.dp[..len], &mut m1);
+        mq.reduce(c, &mut t);
+        mq.mod_exp(&t, &self.dq[..len], &mut m2);
+
+        // h = qInv * (m1 - m2) mod p
+        mp.reduce(&m2[..qlen], &mut t);
+        mp.mod_sub(&m1, &t, &mut h);
+        mp.reduce(&self.qinv[..len], &mut t);
+        mp.mod_mul(&h, &t, &mut m1);
+
+        // m = m2 + q * h
+        bignum::mul(&m1[..plen], mq.limbs(), &mut out[..plen + qlen]);
+        bignum::add_assign(&mut out[..plen + qlen], &m2[..qlen]);
+
+        bignum::zeroize_limbs(&mut t);
+        bignum::zeroize_limbs(&mut m1);
+        bignum::zeroize_limbs(&mut m2);
+        bignum::zeroize_limbs(&mut h);
+        true
+    }
+}
+
+impl Drop for RsaPrivateKey {
+    fn drop(&mut self) {
+        bignum::zeroize_limbs(&mut self.d);
+        bignum::zeroize_limbs(&mut self.p);
+        bignum::zeroize_limbs(&mut self.q);
+        bignum::zeroize_limbs(&mut self.dp);
+        bignum::zeroize_limbs(&mut self.dq);
+        bignum::zeroize_limbs(&mut self.qinv);
+    }
+}
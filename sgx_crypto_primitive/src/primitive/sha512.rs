@@ -0,0 +1,262 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//!
+//! SHA-512 and SHA-384 (FIPS 180-4), and HMAC over them (RFC 2104).
+//!
+
+use super::zeroize;
+
+pub const SHA512_BLOCK_SIZE: usize = 128;
+pub const SHA512_DIGEST_SIZE: usize = 64;
+pub const SHA384_DIGEST_SIZE: usize = 48;
+
+const K: [u64; 80] = [
+    0x428a_2f98_d728_ae22, 0x7137_4491_23ef_65cd, 0xb5c0_fbcf_ec4d_3b2f, 0xe9b5_dba5_8189_dbbc,
+    0x3956_c25b_f348_b538, 0x59f1_11f1_b605_d019, 0x923f_82a4_af19_4f9b, 0xab1c_5ed5_da6d_8118,
+    0xd807_aa98_a303_0242, 0x1283_5b01_4570_6fbe, 0x2431_85be_4ee4_b28c, 0x550c_7dc3_d5ff_b4e2,
+    0x72be_5d74_f27b_896f, 0x80de_b1fe_3b16_96b1, 0x9bdc_06a7_25c7_1235, 0xc19b_f174_cf69_2694,
+    0xe49b_69c1_9ef1_4ad2, 0xefbe_4786_384f_25e3, 0x0fc1_9dc6_8b8c_d5b5, 0x240c_a1cc_77ac_9c65,
+    0x2de9_2c6f_592b_0275, 0x4a74_84aa_6ea6_e483, 0x5cb0_a9dc_bd41_fbd4, 0x76f9_88da_8311_53b5,
+    0x983e_5152_ee66_dfab, 0xa831_c66d_2db4_3210, 0xb003_27c8_98fb_213f, 0xbf59_7fc7_beef_0ee4,
+    0xc6e0_0bf3_3da8_8fc2, 0xd5a7_9147_930a_a725, 0x06ca_6351_e003_826f, 0x1429_2967_0a0e_6e70,
+    0x27b7_0a85_46d2_2ffc, 0x2e1b_2138_5c26_c926, 0x4d2c_6dfc_5ac4_2aed, 0x5338_0d13_9d95_b3df,
+    0x650a_7354_8baf_63de, 0x766a_0abb_3c77_b2a8, 0x81c2_c92e_47ed_aee6, 0x9272_2c85_1482_353b,
+    0xa2bf_e8a1_4cf1_0364, 0xa81a_664b_bc42_3001, 0xc24b_8b70_d0f8_9791, 0xc76c_51a3_0654_be30,
+    0xd192_e819_d6ef_5218, 0xd699_0624_5565_a910, 0xf40e_3585_5771_202a, 0x106a_a070_32bb_d1b8,
+    0x19a4_c116_b8d2_d0c8, 0x1e37_6c08_5141_ab53, 0x2748_774c_df8e_eb99, 0x34b0_bcb5_e19b_48a8,
+    0x391c_0cb3_c5c9_5a63, 0x4ed8_aa4a_e341_8acb, 0x5b9c_ca4f_7763_e373, 0x682e_6ff3_d6b2_b8a3,
+    0x748f_82ee_5def_b2fc, 0x78a5_636f_4317_2f60, 0x84c8_7814_a1f0_ab72, 0x8cc7_0208_1a64_39ec,
+    0x90be_fffa_2363_1e28, 0xa450_6ceb_de82_bde9, 0xbef9_a3f7_b2c6_7915, 0xc671_78f2_e372_532b,
+    0xca27_3ece_ea26_619c, 0xd186_b8c7_21c0_c207, 0xeada_7dd6_cde0_eb1e, 0xf57d_4f7f_ee6e_d178,
+    0x06f0_67aa_7217_6fba, 0x0a63_7dc5_a2c8_98a6, 0x113f_9804_bef9_0dae, 0x1b71_0b35_131c_471b,
+    0x28db_77f5_2304_7d84, 0x32ca_ab7b_40c7_2493, 0x3c9e_be0a_15c9_bebc, 0x431d_67c4_9c10_0d4c,
+    0x4cc5_d4be_cb3e_42b6, 0x597f_299c_fc65_7e2a, 0x5fcb_6fab_3ad6_faec, 0x6c44_198c_4a47_5817,
+];
+
+const SHA512_IV: [u64; 8] = [
+    0x6a09_e667_f3bc_c908, 0xbb67_ae85_84ca_a73b, 0x3c6e_f372_fe94_f82b, 0xa54f_f53a_5f1d_36f1,
+    0x510e_527f_ade6_82d1, 0x9b05_688c_2b3e_6c1f, 0x1f83_d9ab_fb41_bd6b, 0x5be0_cd19_137e_2179,
+];
+
+const SHA384_IV: [u64; 8] = [
+    0xcbbb_9d5d_c105_9ed8, 0x629a_292a_367c_d507, 0x9159_015a_3070_dd17, 0x152f_ecd8_f70e_5939,
+    0x6733_2667_ffc0_0b31, 0x8eb4_4a87_6858_1511, 0xdb0c_2e0d_64f9_8fa7, 0x47b5_481d_befa_4fa4,
+];
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Sha512Variant {
+    Sha384,
+    Sha512,
+}
+
+impl Sha512Variant {
+    pub fn digest_size(self) -> usize {
+        match self {
+            Sha512Variant::Sha384 => SHA384_DIGEST_SIZE,
+            Sha512Variant::Sha512 => SHA512_DIGEST_SIZE,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Sha512 {
+    variant: Sha512Variant,
+    h: [u64; 8],
+    buf: [u8; SHA512_BLOCK_SIZE],
+    buf_len: usize,
+    total_len: u128,
+}
+
+impl Sha512 {
+    pub fn new(variant: Sha512Variant) -> Sha512 {
+        Sha512 {
+            variant,
+            h: match variant {
+                Sha512Variant::Sha384 => SHA384_IV,
+                Sha512Variant::Sha512 => SHA512_IV,
+            },
+            buf: [0_u8; SHA512_BLOCK_SIZE],
+            buf_len: 0,
+            total_len: 0,
+        }
+    }
+
+    pub fn variant(&self) -> Sha512Variant {
+        self.variant
+    }
+
+    #[allow(clippy::many_single_char_names)]
+    fn compress(&mut self, block: &[u8]) {
+        let mut w = [0_u64; 80];
+        for (i, chunk) in block.chunks(8).enumerate().take(16) {
+            let mut v = [0_u8; 8];
+            v.copy_from_slice(chunk);
+            w[i] = u64::from_be_bytes(v);
+        }
+        for i in 16..80 {
+            let s0 = w[i - 15].rotate_right(1) ^ w[i - 15].rotate_right(8) ^ (w[i - 15] >> 7);
+            let s1 = w[i - 2].rotate_right(19) ^ w[i - 2].rotate_right(61) ^ (w[i - 2] >> 6);
+            w[i] = w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = self.h;
+        for i in 0..80 {
+            let s1 = e.rotate_right(14) ^ e.rotate_right(18) ^ e.rotate_right(41);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = h
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(K[i])
+                .wrapping_add(w[i]);
+            let s0 = a.rotate_right(28) ^ a.rotate_right(34) ^ a.rotate_right(39);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+
+        for (s, v) in self.h.iter_mut().zip([a, b, c, d, e, f, g, h].iter()) {
+            *s = s.wrapping_add(*v);
+        }
+        for v in w.iter_mut() {
+            unsafe { core::ptr::write_volatile(v, 0) };
+        }
+    }
+
+    pub fn update(&mut self, mut data: &[u8]) {
+        self.total_len += data.len() as u128;
+        if self.buf_len > 0 {
+            let n = core::cmp::min(SHA512_BLOCK_SIZE - self.buf_len, data.len());
+            self.buf[self.buf_len..self.buf_len + n].copy_from_slice(&data[..n]);
+            self.buf_len += n;
+            data = &data[n..];
+            if self.buf_len < SHA512_BLOCK_SIZE {
+                return;
+            }
+            let block = self.buf;
+            self.compress(&block);
+            self.buf_len = 0;
+        }
+        while data.len() >= SHA512_BLOCK_SIZE {
+            self.compress(&data[..SHA512_BLOCK_SIZE]);
+            data = &data[SHA512_BLOCK_SIZE..];
+        }
+        if !data.is_empty() {
+            self.buf[..data.len()].copy_from_slice(data);
+            self.buf_len = data.len();
+        }
+    }
+
+    ///
+    /// Writes the digest into the first digest_size() bytes of out.
+    ///
+    pub fn finalize_into(mut self, out: &mut [u8]) {
+        let bit_len = self.total_len << 3;
+        let mut pad = [0_u8; SHA512_BLOCK_SIZE + 16];
+        pad[0] = 0x80;
+        let pad_len = if self.buf_len < 112 {
+            112 - self.buf_len
+        } else {
+            240 - self.buf_len
+        };
+        self.update(&pad[..pad_len]);
+        pad[..16].copy_from_slice(&bit_len.to_be_bytes());
+        self.update(&pad[..16]);
+
+        let mut digest = [0_u8; SHA512_DIGEST_SIZE];
+        for (chunk, v) in digest.chunks_mut(8).zip(self.h.iter()) {
+            chunk.copy_from_slice(&v.to_be_bytes());
+        }
+        let size = self.variant.digest_size();
+        out[..size].copy_from_slice(&digest[..size]);
+        zeroize(&mut digest);
+    }
+
+    pub fn digest(variant: Sha512Variant, data: &[u8], out: &mut [u8]) {
+        let mut sha = Sha512::new(variant);
+        sha.update(data);
+        sha.finalize_into(out);
+    }
+}
+
+impl Drop for Sha512 {
+    fn drop(&mut self) {
+        for v in self.h.iter_mut() {
+            unsafe { core::ptr::write_volatile(v, 0) };
+        }
+        zeroize(&mut self.buf);
+    }
+}
+
+#[derive(Clone)]
+pub struct HmacSha512 {
+    inner: Sha512,
+    outer: Sha512,
+}
+
+impl HmacSha512 {
+    pub fn new(variant: Sha512Variant, key: &[u8]) -> HmacSha512 {
+        let mut block = [0_u8; SHA512_BLOCK_SIZE];
+        if key.len() > SHA512_BLOCK_SIZE {
+            Sha512::digest(variant, key, &mut block);
+        } else {
+            block[..key.len()].copy_from_slice(key);
+        }
+
+        for b in block.iter_mut() {
+            *b ^= 0x36;
+        }
+        let mut inner = Sha512::new(variant);
+        inner.update(&block);
+
+        for b in block.iter_mut() {
+            *b ^= 0x36 ^ 0x5c;
+        }
+        let mut outer = Sha512::new(variant);
+        outer.update(&block);
+        zeroize(&mut block);
+
+        HmacSha512 { inner, outer }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        self.inner.update(data);
+    }
+
+    ///
+    /// Writes the MAC into the first digest_size() bytes of out.
+    ///
+    pub fn finalize_into(self, out: &mut [u8]) {
+        let HmacSha512 { inner, mut outer } = self;
+        let size = inner.variant().digest_size();
+        let mut inner_hash = [0_u8; SHA512_DIGEST_SIZE];
+        inner.finalize_into(&mut inner_hash);
+        outer.update(&inner_hash[..size]);
+        zeroize(&mut inner_hash);
+        outer.finalize_into(out);
+    }
+}
@@ -0,0 +1,466 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//!
+//! RSASSA-PSS, RSASSA-PKCS1-v1_5 and RSAES-OAEP (RFC 8017) over the
+//! primitive RSA keys
+//!
+//! The schemes support 2048, 3072 and 4096-bit moduli, with SHA-256,
+//! SHA-384 or SHA-512 as the message digest and MGF1 hash. SHA-256 is
+//! computed by libsgx_tcrypto.a, SHA-384 and SHA-512 by primitive::sha512.
+//!
+
+use crate::primitive::rsa::{RsaPrivateKey, RsaPublicKey};
+use crate::primitive::sha512::{Sha512, Sha512Variant};
+use crate::primitive::{ct_eq, zeroize};
+use core::ptr;
+use sgx_types::*;
+
+///
+/// The largest digest size, for the buffers passed to digest.
+///
+pub const MAX_HASH_SIZE: usize = SGX_SHA512_HASH_SIZE;
+const MAX_KEY_SIZE: usize = SGX_RSA4096_KEY_SIZE;
+
+const SHA256_DIGEST_INFO: [u8; 19] = [
+    0x30, 0x31, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01, 0x05,
+    0x00, 0x04, 0x20,
+];
+const SHA384_DIGEST_INFO: [u8; 19] = [
+    0x30, 0x41, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x02, 0x05,
+    0x00, 0x04, 0x30,
+];
+const SHA512_DIGEST_INFO: [u8; 19] = [
+    0x30, 0x51, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x03, 0x05,
+    0x00, 0x04, 0x40,
+];
+
+fn read_rand(buf: &mut [u8]) -> SgxError {
+    let ret = unsafe { sgx_read_rand(buf.as_mut_ptr(), buf.len()) };
+    match ret {
+        sgx_status_t::SGX_SUCCESS => Ok(()),
+        _ => Err(ret),
+    }
+}
+
+///
+/// hash_size returns the digest size of a hash algorithm.
+///
+pub fn hash_size(hash: sgx_hash_algorithm_t) -> usize {
+    match hash {
+        sgx_hash_algorithm_t::SGX_HASH_SHA256 => SGX_SHA256_HASH_SIZE,
+        sgx_hash_algorithm_t::SGX_HASH_SHA384 => SGX_SHA384_HASH_SIZE,
+        sgx_hash_algorithm_t::SGX_HASH_SHA512 => SGX_SHA512_HASH_SIZE,
+    }
+}
+
+fn digest_info(hash: sgx_hash_algorithm_t) -> &'static [u8] {
+    match hash {
+        sgx_hash_algorithm_t::SGX_HASH_SHA256 => &SHA256_DIGEST_INFO,
+        sgx_hash_algorithm_t::SGX_HASH_SHA384 => &SHA384_DIGEST_INFO,
+        sgx_hash_algorithm_t::SGX_HASH_SHA512 => &SHA512_DIGEST_INFO,
+    }
+}
+
+struct Sha256 {
+    handle: sgx_sha_state_handle_t,
+}
+
+impl Sha256 {
+    fn new() -> SgxResult<Sha256> {
+        let mut handle: sgx_sha_state_handle_t = ptr::null_mut();
+        let ret = unsafe { sgx_sha256_init(&mut handle) };
+        match ret {
+            sgx_status_t::SGX_SUCCESS => Ok(Sha256 { handle }),
+            _ => Err(ret),
+        }
+    }
+
+    fn update(&mut self, src: &[u8]) -> SgxError {
+        if src.is_empty() {
+            return Ok(());
+        }
+        if src.len() > u32::MAX as usize {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+
+        let ret = unsafe { sgx_sha256_update(src.as_ptr(), src.len() as u32, self.handle) };
+        match ret {
+            sgx_status_t::SGX_SUCCESS => Ok(()),
+            _ => Err(ret),
+        }
+    }
+
+    fn finalize_into(self, out: &mut [u8]) -> SgxError {
+        let mut hash = sgx_sha256_hash_t::default();
+        let ret = unsafe { sgx_sha256_get_hash(self.handle, &mut hash as *mut sgx_sha256_hash_t) };
+        match ret {
+            sgx_status_t::SGX_SUCCESS => {
+                out.copy_from_slice(&hash);
+                Ok(())
+            }
+            _ => Err(ret),
+        }
+    }
+}
+
+impl Drop for Sha256 {
+    fn drop(&mut self) {
+        if !self.handle.is_null() {
+            unsafe { sgx_sha256_close(self.handle) };
+        }
+    }
+}
+
+///
+/// digest hashes the concatenation of parts into the first hash_size(hash)
+/// bytes of out.
+///
+pub fn digest(hash: sgx_hash_algorithm_t, parts: &[&[u8]], out: &mut [u8]) -> SgxError {
+    let out = &mut out[..hash_size(hash)];
+    let variant = match hash {
+        sgx_hash_algorithm_t::SGX_HASH_SHA256 => {
+            let mut state = Sha256::new()?;
+            for part in parts {
+                state.update(part)?;
+            }
+            return state.finalize_into(out);
+        }
+        sgx_hash_algorithm_t::SGX_HASH_SHA384 => Sha512Variant::Sha384,
+        sgx_hash_algorithm_t::SGX_HASH_SHA512 => Sha512Variant::Sha512,
+    };
+    let mut state = Sha512::new(variant);
+    for part in parts {
+        state.update(part);
+    }
+    state.finalize_into(out);
+    Ok(())
+}
+
+/// XORs MGF1(seed) into out.
+fn mgf1_xor(hash: sgx_hash_algorithm_t, seed: &[u8], out: &mut [u8]) -> SgxError {
+    let h_len = hash_size(hash);
+    let mut mask = [0_u8; MAX_HASH_SIZE];
+    for (counter, chunk) in out.chunks_mut(h_len).enumerate() {
+        digest(hash, &[seed, &(counter as u32).to_be_bytes()], &mut mask)?;
+        for (b, m) in chunk.iter_mut().zip(mask.iter()) {
+            *b ^= m;
+        }
+    }
+    zeroize(&mut mask);
+    Ok(())
+}
+
+fn check_key_size(size: usize) -> SgxError {
+    match size {
+        SGX_RSA2048_KEY_SIZE | SGX_RSA3072_KEY_SIZE | SGX_RSA4096_KEY_SIZE => Ok(()),
+        _ => Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER),
+    }
+}
+
+fn check_digest(hash: sgx_hash_algorithm_t, digest: &[u8]) -> SgxError {
+    if digest.len() != hash_size(hash) {
+        return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+    }
+    Ok(())
+}
+
+/// EMSA-PSS-ENCODE with a salt as long as the digest, written right
+/// aligned into em, which is as long as the modulus.
+fn pss_encode(hash: sgx_hash_algorithm_t, m_hash: &[u8], em_bits: usize, em: &mut [u8]) -> SgxError {
+    let h_len = hash_size(hash);
+    let s_len = h_len;
+    let em_len = (em_bits + 7) / 8;
+    if em_len < h_len + s_len + 2 {
+        return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+    }
+
+    let mut salt = [0_u8; MAX_HASH_SIZE];
+    read_rand(&mut salt[..s_len])?;
+    let mut h = [0_u8; MAX_HASH_SIZE];
+    digest(hash, &[&[0_u8; 8], m_hash, &salt[..s_len]], &mut h)?;
+
+    let offset = em.len() - em_len;
+    for b in em.iter_mut() {
+        *b = 0;
+    }
+    let em = &mut em[offset..];
+    let (db, rest) = em.split_at_mut(em_len - h_len - 1);
+    let ps_len = em_len - s_len - h_len - 2;
+    db[ps_len] = 0x01;
+    db[ps_len + 1..].copy_from_slice(&salt[..s_len]);
+    mgf1_xor(hash, &h[..h_len], db)?;
+    db[0] &= 0xff >> (8 * em_len - em_bits);
+    rest[..h_len].copy_from_slice(&h[..h_len]);
+    rest[h_len] = 0xbc;
+    Ok(())
+}
+
+/// EMSA-PSS-VERIFY, accepting any salt length.
+fn pss_verify(hash: sgx_hash_algorithm_t, m_hash: &[u8], em_bits: usize, em: &[u8]) -> SgxResult<bool> {
+    let h_len = hash_size(hash);
+    let em_len = (em_bits + 7) / 8;
+    let offset = em.len() - em_len;
+    if em[..offset].iter().any(|b| *b != 0) {
+        return Ok(false);
+    }
+    let em = &em[offset..];
+    if em_len < h_len + 2 || em[em_len - 1] != 0xbc {
+        return Ok(false);
+    }
+
+    let top_mask = 0xff_u8 >> (8 * em_len - em_bits);
+    let mut db = [0_u8; MAX_KEY_SIZE];
+    let db = &mut db[..em_len - h_len - 1];
+    db.copy_from_slice(&em[..em_len - h_len - 1]);
+    let h = &em[em_len - h_len - 1..em_len - 1];
+    if db[0] & !top_mask != 0 {
+        return Ok(false);
+    }
+    mgf1_xor(hash, h, db)?;
+    db[0] &= top_mask;
+
+    let salt = match db.iter().position(|b| *b != 0) {
+        Some(i) if db[i] == 0x01 => &db[i + 1..],
+        _ => return Ok(false),
+    };
+    let mut h2 = [0_u8; MAX_HASH_SIZE];
+    digest(hash, &[&[0_u8; 8], m_hash, salt], &mut h2)?;
+    Ok(ct_eq(&h2[..h_len], h))
+}
+
+/// EMSA-PKCS1-v1_5-ENCODE into em, which is as long as the modulus.
+fn pkcs1_v15_encode(hash: sgx_hash_algorithm_t, m_hash: &[u8], em: &mut [u8]) -> SgxError {
+    let prefix = digest_info(hash);
+    let t_len = prefix.len() + m_hash.len();
+    let em_len = em.len();
+    if em_len < t_len + 11 {
+        return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+    }
+    em[0] = 0x00;
+    em[1] = 0x01;
+    for b in em[2..em_len - t_len - 1].iter_mut() {
+        *b = 0xff;
+    }
+    em[em_len - t_len - 1] = 0x00;
+    em[em_len - t_len..em_len - m_hash.len()].copy_from_slice(prefix);
+    em[em_len - m_hash.len()..].copy_from_slice(m_hash);
+    Ok(())
+}
+
+impl RsaPrivateKey {
+    ///
+    /// sign_pss computes an RSASSA-PSS signature over a message digest, with a
+    /// salt as long as the digest read from sgx_read_rand. The signature must
+    /// be exactly as long as the modulus.
+    ///
+    pub fn sign_pss(&self, hash: sgx_hash_algorithm_t, digest: &[u8], signature: &mut [u8]) -> SgxError {
+        let size = self.public_key().size();
+        check_key_size(size)?;
+        check_digest(hash, digest)?;
+        if signature.len() != size {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+
+        let mut em = [0_u8; MAX_KEY_SIZE];
+        pss_encode(hash, digest, self.public_key().bits() - 1, &mut em[..size])?;
+        let ok = self.private_op(&em[..size], signature);
+        zeroize(&mut em);
+        if ok {
+            Ok(())
+        } else {
+            Err(sgx_status_t::SGX_ERROR_UNEXPECTED)
+        }
+    }
+
+    ///
+    /// sign_pkcs1_v15 computes an RSASSA-PKCS1-v1_5 signature over a message
+    /// digest. The signature must be exactly as long as the modulus.
+    ///
+    pub fn sign_pkcs1_v15(&self, hash: sgx_hash_algorithm_t, digest: &[u8], signature: &mut [u8]) -> SgxError {
+        let size = self.public_key().size();
+        check_key_size(size)?;
+        check_digest(hash, digest)?;
+        if signature.len() != size {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+
+        let mut em = [0_u8; MAX_KEY_SIZE];
+        pkcs1_v15_encode(hash, digest, &mut em[..size])?;
+        if self.private_op(&em[..size], signature) {
+            Ok(())
+        } else {
+            Err(sgx_status_t::SGX_ERROR_UNEXPECTED)
+        }
+    }
+
+    ///
+    /// decrypt_oaep decrypts an RSAES-OAEP ciphertext. If out_len is zero it
+    /// receives the largest plaintext size and nothing is decrypted.
+    ///
+    pub fn decrypt_oaep(
+        &self,
+        hash: sgx_hash_algorithm_t,
+        label: &[u8],
+        out_data: &mut [u8],
+        out_len: &mut usize,
+        in_data: &[u8],
+    ) -> SgxError {
+        let size = self.public_key().size();
+        check_key_size(size)?;
+        let h_len = hash_size(hash);
+        if *out_len == 0 {
+            *out_len = size - 2 * h_len - 2;
+            return Ok(());
+        }
+        if out_data.len() != *out_len || in_data.len() != size {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+
+        let mut l_hash = [0_u8; MAX_HASH_SIZE];
+        digest(hash, &[label], &mut l_hash)?;
+
+        let mut em = [0_u8; MAX_KEY_SIZE];
+        if !self.private_op(in_data, &mut em[..size]) {
+            return Err(sgx_status_t::SGX_ERROR_UNEXPECTED);
+        }
+        let result = oaep_decode(hash, &l_hash[..h_len], &mut em[..size], out_data, out_len);
+        zeroize(&mut em);
+        result
+    }
+}
+
+/// EME-OAEP decoding. The checks on the padding run in constant time and
+/// all failures are reported with the same error.
+fn oaep_decode(
+    hash: sgx_hash_algorithm_t,
+    l_hash: &[u8],
+    em: &mut [u8],
+    out_data: &mut [u8],
+    out_len: &mut usize,
+) -> SgxError {
+    let h_len = l_hash.len();
+    let y = em[0];
+    let (seed, db) = em[1..].split_at_mut(h_len);
+    mgf1_xor(hash, db, seed)?;
+    mgf1_xor(hash, seed, db)?;
+
+    let mut good = ct_eq(&db[..h_len], l_hash) as usize & (((usize::from(y) + 0xff) >> 8) ^ 1);
+    let mut looking = 1_usize;
+    let mut index = 0_usize;
+    let mut invalid = 0_usize;
+    for (i, b) in db.iter().enumerate().skip(h_len) {
+        let b = usize::from(*b);
+        let is_zero = ((b + 0xff) >> 8) ^ 1;
+        let is_one = (((b ^ 1) + 0xff) >> 8) ^ 1;
+        let found = looking & is_one;
+        index ^= found.wrapping_neg() & (index ^ i);
+        invalid |= looking & !is_zero & !is_one & 1;
+        looking &= !is_one & 1;
+    }
+    good &= !looking & !invalid & 1;
+    if good == 0 {
+        return Err(sgx_status_t::SGX_ERROR_UNEXPECTED);
+    }
+
+    let m = &db[index + 1..];
+    if m.len() > out_data.len() {
+        return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+    }
+    out_data[..m.len()].copy_from_slice(m);
+    *out_len = m.len();
+    Ok(())
+}
+
+impl RsaPublicKey {
+    ///
+    /// verify_pss verifies an RSASSA-PSS signature with any salt length over a
+    /// message digest.
+    ///
+    pub fn verify_pss(&self, hash: sgx_hash_algorithm_t, digest: &[u8], signature: &[u8]) -> SgxResult<bool> {
+        let size = self.size();
+        check_key_size(size)?;
+        check_digest(hash, digest)?;
+
+        let mut em = [0_u8; MAX_KEY_SIZE];
+        if !self.public_op(signature, &mut em[..size]) {
+            return Ok(false);
+        }
+        pss_verify(hash, digest, self.bits() - 1, &em[..size])
+    }
+
+    ///
+    /// verify_pkcs1_v15 verifies an RSASSA-PKCS1-v1_5 signature over a message
+    /// digest.
+    ///
+    pub fn verify_pkcs1_v15(&self, hash: sgx_hash_algorithm_t, digest: &[u8], signature: &[u8]) -> SgxResult<bool> {
+        let size = self.size();
+        check_key_size(size)?;
+        check_digest(hash, digest)?;
+
+        let mut em = [0_u8; MAX_KEY_SIZE];
+        if !self.public_op(signature, &mut em[..size]) {
+            return Ok(false);
+        }
+        let mut expected = [0_u8; MAX_KEY_SIZE];
+        pkcs1_v15_encode(hash, digest, &mut expected[..size])?;
+        Ok(ct_eq(&em[..size], &expected[..size]))
+    }
+
+    ///
+    /// encrypt_oaep encrypts a message with RSAES-OAEP. If out_len is zero it
+    /// receives the ciphertext size and nothing is encrypted.
+    ///
+    pub fn encrypt_oaep(
+        &self,
+        hash: sgx_hash_algorithm_t,
+        label: &[u8],
+        out_data: &mut [u8],
+        out_len: &mut usize,
+        in_data: &[u8],
+    ) -> SgxError {
+        let size = self.size();
+        check_key_size(size)?;
+        let h_len = hash_size(hash);
+        if *out_len == 0 {
+            *out_len = size;
+            return Ok(());
+        }
+        if out_data.len() != *out_len || *out_len < size || in_data.len() > size - 2 * h_len - 2 {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+
+        let mut em = [0_u8; MAX_KEY_SIZE];
+        {
+            let (seed, db) = em[1..size].split_at_mut(h_len);
+            read_rand(seed)?;
+            digest(hash, &[label], &mut db[..h_len])?;
+            let db_len = db.len();
+            db[db_len - in_data.len() - 1] = 0x01;
+            db[db_len - in_data.len()..].copy_from_slice(in_data);
+            mgf1_xor(hash, seed, db)?;
+            mgf1_xor(hash, db, seed)?;
+        }
+        let ok = self.public_op(&em[..size], &mut out_data[..size]);
+        zeroize(&mut em);
+        if !ok {
+            return Err(sgx_status_t::SGX_ERROR_UNEXPECTED);
+        }
+        *out_len = size;
+        Ok(())
+    }
+}
@@ -0,0 +1,510 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//!
+//! SHA-384, SHA-512, HMAC-SHA384 and HMAC-SHA512
+//!
+//! libsgx_tcrypto.a only provides the SHA-1 and SHA-256 families. The
+//! functions below are implemented in Rust and follow the conventions of
+//! rsgx_sha256_msg/rsgx_sha256_slice, SgxShaHandle and SgxHmacHandle.
+//!
+
+use crate::primitive::sha512::{HmacSha512, Sha512, Sha512Variant};
+use core::cell::RefCell;
+use core::mem;
+use core::ops::Drop;
+use core::slice;
+use sgx_types::marker::ContiguousMemory;
+use sgx_types::*;
+
+fn msg_bytes<T>(src: &T) -> SgxResult<&[u8]>
+where
+    T: Copy + ContiguousMemory,
+{
+    let size = mem::size_of::<T>();
+    if size == 0 {
+        return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+    }
+    Ok(unsafe { slice::from_raw_parts(src as *const _ as *const u8, size) })
+}
+
+fn slice_bytes<T>(src: &[T]) -> SgxResult<&[u8]>
+where
+    T: Copy + ContiguousMemory,
+{
+    let size = mem::size_of_val(src);
+    if size == 0 {
+        return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+    }
+    Ok(unsafe { slice::from_raw_parts(src.as_ptr() as *const u8, size) })
+}
+
+fn sha384(src: &[u8]) -> sgx_sha384_hash_t {
+    let mut hash = [0_u8; SGX_SHA384_HASH_SIZE];
+    Sha512::digest(Sha512Variant::Sha384, src, &mut hash);
+    hash
+}
+
+fn sha512(src: &[u8]) -> sgx_sha512_hash_t {
+    let mut hash = [0_u8; SGX_SHA512_HASH_SIZE];
+    Sha512::digest(Sha512Variant::Sha512, src, &mut hash);
+    hash
+}
+
+///
+/// The rsgx_sha384_msg function performs a standard SHA384 hash over the input data buffer.
+///
+/// # Description
+///
+/// The function should be used if the complete input data stream is available.
+/// Otherwise, SgxSha384Handle should be used to compute a SHA384 hash over
+/// multiple input data sets.
+///
+/// # Parameters
+///
+/// **src**
+///
+/// A pointer to the input data stream to be hashed.
+///
+/// # Return value
+///
+/// The 384-bit hash that has been SHA384 calculated
+///
+/// # Errors
+///
+/// **SGX_ERROR_INVALID_PARAMETER**
+///
+/// The input data is empty.
+///
+pub fn rsgx_sha384_msg<T>(src: &T) -> SgxResult<sgx_sha384_hash_t>
+where
+    T: Copy + ContiguousMemory,
+{
+    msg_bytes(src).map(sha384)
+}
+
+///
+/// The rsgx_sha384_slice function performs a standard SHA384 hash over the input data buffer.
+///
+pub fn rsgx_sha384_slice<T>(src: &[T]) -> SgxResult<sgx_sha384_hash_t>
+where
+    T: Copy + ContiguousMemory,
+{
+    slice_bytes(src).map(sha384)
+}
+
+///
+/// The rsgx_sha512_msg function performs a standard SHA512 hash over the input data buffer.
+///
+/// # Description
+///
+/// The function should be used if the complete input data stream is available.
+/// Otherwise, SgxSha512Handle should be used to compute a SHA512 hash over
+/// multiple input data sets.
+///
+/// # Parameters
+///
+/// **src**
+///
+/// A pointer to the input data stream to be hashed.
+///
+/// # Return value
+///
+/// The 512-bit hash that has been SHA512 calculated
+///
+/// # Errors
+///
+/// **SGX_ERROR_INVALID_PARAMETER**
+///
+/// The input data is empty.
+///
+pub fn rsgx_sha512_msg<T>(src: &T) -> SgxResult<sgx_sha512_hash_t>
+where
+    T: Copy + ContiguousMemory,
+{
+    msg_bytes(src).map(sha512)
+}
+
+///
+/// The rsgx_sha512_slice function performs a standard SHA512 hash over the input data buffer.
+///
+pub fn rsgx_sha512_slice<T>(src: &[T]) -> SgxResult<sgx_sha512_hash_t>
+where
+    T: Copy + ContiguousMemory,
+{
+    slice_bytes(src).map(sha512)
+}
+
+fn hmac_sha384(key: &[u8], src: &[u8]) -> sgx_hmac_384bit_tag_t {
+    let mut hmac = HmacSha512::new(Sha512Variant::Sha384, key);
+    hmac.update(src);
+    let mut mac = [0_u8; SGX_HMAC384_MAC_SIZE];
+    hmac.finalize_into(&mut mac);
+    mac
+}
+
+fn hmac_sha512(key: &[u8], src: &[u8]) -> sgx_hmac_512bit_tag_t {
+    let mut hmac = HmacSha512::new(Sha512Variant::Sha512, key);
+    hmac.update(src);
+    let mut mac = [0_u8; SGX_HMAC512_MAC_SIZE];
+    hmac.finalize_into(&mut mac);
+    mac
+}
+
+///
+/// The rsgx_hmac_sha384_msg function computes the HMAC-SHA384 of the input data buffer.
+///
+/// # Parameters
+///
+/// **key**
+///
+/// The HMAC key. Keys longer than the 128-byte block size are hashed first, as RFC 2104 specifies.
+///
+/// **src**
+///
+/// A pointer to the input data stream to be authenticated.
+///
+/// # Errors
+///
+/// **SGX_ERROR_INVALID_PARAMETER**
+///
+/// The input data is empty.
+///
+pub fn rsgx_hmac_sha384_msg<T>(key: &[u8], src: &T) -> SgxResult<sgx_hmac_384bit_tag_t>
+where
+    T: Copy + ContiguousMemory,
+{
+    msg_bytes(src).map(|src| hmac_sha384(key, src))
+}
+
+pub fn rsgx_hmac_sha384_slice<T>(key: &[u8], src: &[T]) -> SgxResult<sgx_hmac_384bit_tag_t>
+where
+    T: Copy + ContiguousMemory,
+{
+    slice_bytes(src).map(|src| hmac_sha384(key, src))
+}
+
+///
+/// The rsgx_hmac_sha512_msg function computes the HMAC-SHA512 of the input data buffer.
+///
+/// See rsgx_hmac_sha384_msg for a description of the parameters.
+///
+pub fn rsgx_hmac_sha512_msg<T>(key: &[u8], src: &T) -> SgxResult<sgx_hmac_512bit_tag_t>
+where
+    T: Copy + ContiguousMemory,
+{
+    msg_bytes(src).map(|src| hmac_sha512(key, src))
+}
+
+pub fn rsgx_hmac_sha512_slice<T>(key: &[u8], src: &[T]) -> SgxResult<sgx_hmac_512bit_tag_t>
+where
+    T: Copy + ContiguousMemory,
+{
+    slice_bytes(src).map(|src| hmac_sha512(key, src))
+}
+
+///
+/// SHA384 algorithm context state.
+///
+/// This is a handle to the context state used to perform an iterative SHA384 hash,
+/// with the same Init, Update … Update, Final interface as SgxShaHandle.
+///
+pub struct SgxSha384Handle {
+    state: RefCell<Option<Sha512>>,
+}
+
+impl SgxSha384Handle {
+    pub fn new() -> SgxSha384Handle {
+        SgxSha384Handle {
+            state: RefCell::new(None),
+        }
+    }
+
+    pub fn init(&self) -> SgxError {
+        let mut state = self.state.borrow_mut();
+        if state.is_none() {
+            *state = Some(Sha512::new(Sha512Variant::Sha384));
+        }
+        Ok(())
+    }
+
+    pub fn update_msg<T>(&self, src: &T) -> SgxError
+    where
+        T: Copy + ContiguousMemory,
+    {
+        let src = msg_bytes(src)?;
+        let mut state = self.state.borrow_mut();
+        let state = state.as_mut().ok_or(sgx_status_t::SGX_ERROR_INVALID_STATE)?;
+        state.update(src);
+        Ok(())
+    }
+
+    pub fn update_slice<T>(&self, src: &[T]) -> SgxError
+    where
+        T: Copy + ContiguousMemory,
+    {
+        let src = slice_bytes(src)?;
+        let mut state = self.state.borrow_mut();
+        let state = state.as_mut().ok_or(sgx_status_t::SGX_ERROR_INVALID_STATE)?;
+        state.update(src);
+        Ok(())
+    }
+
+    ///
+    /// get_hash obtains the SHA384 hash of the datasets processed so far.
+    ///
+    pub fn get_hash(&self) -> SgxResult<sgx_sha384_hash_t> {
+        let state = self.state.borrow();
+        let state = state.as_ref().ok_or(sgx_status_t::SGX_ERROR_INVALID_STATE)?;
+        let mut hash = [0_u8; SGX_SHA384_HASH_SIZE];
+        state.clone().finalize_into(&mut hash);
+        Ok(hash)
+    }
+
+    pub fn close(&self) -> SgxError {
+        *self.state.borrow_mut() = None;
+        Ok(())
+    }
+}
+
+impl Default for SgxSha384Handle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for SgxSha384Handle {
+    fn drop(&mut self) {
+        let _ = self.close();
+    }
+}
+
+///
+/// SHA512 algorithm context state.
+///
+/// This is a handle to the context state used to perform an iterative SHA512 hash,
+/// with the same Init, Update … Update, Final interface as SgxShaHandle.
+///
+pub struct SgxSha512Handle {
+    state: RefCell<Option<Sha512>>,
+}
+
+impl SgxSha512Handle {
+    pub fn new() -> SgxSha512Handle {
+        SgxSha512Handle {
+            state: RefCell::new(None),
+        }
+    }
+
+    pub fn init(&self) -> SgxError {
+        let mut state = self.state.borrow_mut();
+        if state.is_none() {
+            *state = Some(Sha512::new(Sha512Variant::Sha512));
+        }
+        Ok(())
+    }
+
+    pub fn update_msg<T>(&self, src: &T) -> SgxError
+    where
+        T: Copy + ContiguousMemory,
+    {
+        let src = msg_bytes(src)?;
+        let mut state = self.state.borrow_mut();
+        let state = state.as_mut().ok_or(sgx_status_t::SGX_ERROR_INVALID_STATE)?;
+        state.update(src);
+        Ok(())
+    }
+
+    pub fn update_slice<T>(&self, src: &[T]) -> SgxError
+    where
+        T: Copy + ContiguousMemory,
+    {
+        let src = slice_bytes(src)?;
+        let mut state = self.state.borrow_mut();
+        let state = state.as_mut().ok_or(sgx_status_t::SGX_ERROR_INVALID_STATE)?;
+        state.update(src);
+        Ok(())
+    }
+
+    ///
+    /// get_hash obtains the SHA512 hash of the datasets processed so far.
+    ///
+    pub fn get_hash(&self) -> SgxResult<sgx_sha512_hash_t> {
+        let state = self.state.borrow();
+        let state = state.as_ref().ok_or(sgx_status_t::SGX_ERROR_INVALID_STATE)?;
+        let mut hash = [0_u8; SGX_SHA512_HASH_SIZE];
+        state.clone().finalize_into(&mut hash);
+        Ok(hash)
+    }
+
+    pub fn close(&self) -> SgxError {
+        *self.state.borrow_mut() = None;
+        Ok(())
+    }
+}
+
+impl Default for SgxSha512Handle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for SgxSha512Handle {
+    fn drop(&mut self) {
+        let _ = self.close();
+    }
+}
+
+///
+/// HMAC-SHA384 algorithm context state, the SHA384 counterpart of SgxHmacHandle.
+///
+pub struct SgxHmac384Handle {
+    state: RefCell<Option<HmacSha512>>,
+}
+
+impl SgxHmac384Handle {
+    pub fn new() -> SgxHmac384Handle {
+        SgxHmac384Handle {
+            state: RefCell::new(None),
+        }
+    }
+
+    pub fn init(&self, key: &[u8]) -> SgxError {
+        let mut state = self.state.borrow_mut();
+        if state.is_none() {
+            *state = Some(HmacSha512::new(Sha512Variant::Sha384, key));
+        }
+        Ok(())
+    }
+
+    pub fn update_msg<T>(&self, src: &T) -> SgxError
+    where
+        T: Copy + ContiguousMemory,
+    {
+        let src = msg_bytes(src)?;
+        let mut state = self.state.borrow_mut();
+        let state = state.as_mut().ok_or(sgx_status_t::SGX_ERROR_INVALID_STATE)?;
+        state.update(src);
+        Ok(())
+    }
+
+    pub fn update_slice<T>(&self, src: &[T]) -> SgxError
+    where
+        T: Copy + ContiguousMemory,
+    {
+        let src = slice_bytes(src)?;
+        let mut state = self.state.borrow_mut();
+        let state = state.as_mut().ok_or(sgx_status_t::SGX_ERROR_INVALID_STATE)?;
+        state.update(src);
+        Ok(())
+    }
+
+    pub fn get_hash(&self) -> SgxResult<sgx_hmac_384bit_tag_t> {
+        let state = self.state.borrow();
+        let state = state.as_ref().ok_or(sgx_status_t::SGX_ERROR_INVALID_STATE)?;
+        let mut mac = [0_u8; SGX_HMAC384_MAC_SIZE];
+        state.clone().finalize_into(&mut mac);
+        Ok(mac)
+    }
+
+    pub fn close(&self) -> SgxError {
+        *self.state.borrow_mut() = None;
+        Ok(())
+    }
+}
+
+impl Default for SgxHmac384Handle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for SgxHmac384Handle {
+    fn drop(&mut self) {
+        let _ = self.close();
+    }
+}
+
+///
+/// HMAC-SHA512 algorithm context state, the SHA512 counterpart of SgxHmacHandle.
+///
+pub struct SgxHmac512Handle {
+    state: RefCell<Option<HmacSha512>>,
+}
+
+impl SgxHmac512Handle {
+    pub fn new() -> SgxHmac512Handle {
+        SgxHmac512Handle {
+            state: RefCell::new(None),
+        }
+    }
+
+    pub fn init(&self, key: &[u8]) -> SgxError {
+        let mut state = self.state.borrow_mut();
+        if state.is_none() {
+            *state = Some(HmacSha512::new(Sha512Variant::Sha512, key));
+        }
+        Ok(())
+    }
+
+    pub fn update_msg<T>(&self, src: &T) -> SgxError
+    where
+        T: Copy + ContiguousMemory,
+    {
+        let src = msg_bytes(src)?;
+        let mut state = self.state.borrow_mut();
+        let state = state.as_mut().ok_or(sgx_status_t::SGX_ERROR_INVALID_STATE)?;
+        state.update(src);
+        Ok(())
+    }
+
+    pub fn update_slice<T>(&self, src: &[T]) -> SgxError
+    where
+        T: Copy + ContiguousMemory,
+    {
+        let src = slice_bytes(src)?;
+        let mut state = self.state.borrow_mut();
+        let state = state.as_mut().ok_or(sgx_status_t::SGX_ERROR_INVALID_STATE)?;
+        state.update(src);
+        Ok(())
+    }
+
+    pub fn get_hash(&self) -> SgxResult<sgx_hmac_512bit_tag_t> {
+        let state = self.state.borrow();
+        let state = state.as_ref().ok_or(sgx_status_t::SGX_ERROR_INVALID_STATE)?;
+        let mut mac = [0_u8; SGX_HMAC512_MAC_SIZE];
+        state.clone().finalize_into(&mut mac);
+        Ok(mac)
+    }
+
+    pub fn close(&self) -> SgxError {
+        *self.state.borrow_mut() = None;
+        Ok(())
+    }
+}
+
+impl Default for SgxHmac512Handle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for SgxHmac512Handle {
+    fn drop(&mut self) {
+        let _ = self.close();
+    }
+}
@@ -0,0 +1,68 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use sgx_types::*;
+use std::prelude::v1::*;
+use std::slice;
+
+/// The collateral needed to verify a quote offline, as returned by
+/// `sgx_ql_get_quote_verification_collateral` or fetched from the Intel PCS.
+///
+/// Certificate chains and CRLs may be PEM or DER encoded, and CRLs may also
+/// be hex strings, as the PCS returns them. Trailing NUL bytes are ignored.
+#[derive(Clone, Default)]
+pub struct Collateral {
+    pub pck_crl_issuer_chain: Vec<u8>,
+    pub root_ca_crl: Vec<u8>,
+    pub pck_crl: Vec<u8>,
+    pub tcb_info_issuer_chain: Vec<u8>,
+    pub tcb_info: Vec<u8>,
+    pub qe_identity_issuer_chain: Vec<u8>,
+    pub qe_identity: Vec<u8>,
+}
+
+impl Collateral {
+    /// Copies the buffers of a collateral returned by the Quote Provider
+    /// Library.
+    ///
+    /// # Safety
+    ///
+    /// Every non-null pointer of `collateral` must be valid for reads of its
+    /// size.
+    pub unsafe fn from_raw(collateral: &sgx_ql_qve_collateral_t) -> Collateral {
+        Collateral {
+            pck_crl_issuer_chain: copy(collateral.pck_crl_issuer_chain, collateral.pck_crl_issuer_chain_size),
+            root_ca_crl: copy(collateral.root_ca_crl, collateral.root_ca_crl_size),
+            pck_crl: copy(collateral.pck_crl, collateral.pck_crl_size),
+            tcb_info_issuer_chain: copy(collateral.tcb_info_issuer_chain, collateral.tcb_info_issuer_chain_size),
+            tcb_info: copy(collateral.tcb_info, collateral.tcb_info_size),
+            qe_identity_issuer_chain: copy(
+                collateral.qe_identity_issuer_chain,
+                collateral.qe_identity_issuer_chain_size,
+            ),
+            qe_identity: copy(collateral.qe_identity, collateral.qe_identity_size),
+        }
+    }
+}
+
+unsafe fn copy(data: *const c_char, size: uint32_t) -> Vec<u8> {
+    if data.is_null() || size == 0 {
+        Vec::new()
+    } else {
+        slice::from_raw_parts(data as *const u8, size as usize).to_vec()
+    }
+}
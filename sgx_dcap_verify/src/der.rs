@@ -0,0 +1,237 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+// A reader for the subset of DER used by X.509 certificates and CRLs:
+// single byte tags and definite lengths of up to four bytes.
+
+pub const BOOLEAN: u8 = 0x01;
+pub const INTEGER: u8 = 0x02;
+pub const BIT_STRING: u8 = 0x03;
+pub const OCTET_STRING: u8 = 0x04;
+pub const OID: u8 = 0x06;
+pub const ENUMERATED: u8 = 0x0a;
+pub const UTF8_STRING: u8 = 0x0c;
+pub const PRINTABLE_STRING: u8 = 0x13;
+pub const UTC_TIME: u8 = 0x17;
+pub const GENERALIZED_TIME: u8 = 0x18;
+pub const SEQUENCE: u8 = 0x30;
+pub const SET: u8 = 0x31;
+pub const CONTEXT_0: u8 = 0xa0;
+pub const CONTEXT_3: u8 = 0xa3;
+
+#[derive(Clone, Copy)]
+pub struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(data: &'a [u8]) -> Reader<'a> {
+        Reader { data }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn peek_tag(&self) -> Option<u8> {
+        self.data.first().copied()
+    }
+
+    // Returns the tag, the content and the whole encoding of the next value.
+    pub fn read_any(&mut self) -> Option<(u8, &'a [u8], &'a [u8])> {
+        let data = self.data;
+        let tag = *data.get(0)?;
+        if tag & 0x1f == 0x1f {
+            return None;
+        }
+        let first = *data.get(1)? as usize;
+        let (len, header) = if first < 0x80 {
+            (first, 2)
+        } else {
+            let count = first & 0x7f;
+            if count == 0 || count > 4 || data.len() < 2 + count {
+                return None;
+            }
+            let mut len = 0_usize;
+            for &byte in &data[2..2 + count] {
+                len = (len << 8) | byte as usize;
+            }
+            if len < 0x80 || (count > 1 && data[2] == 0) {
+                return None;
+            }
+            (len, 2 + count)
+        };
+        let end = header.checked_add(len)?;
+        if end > data.len() {
+            return None;
+        }
+        self.data = &data[end..];
+        Some((tag, &data[header..end], &data[..end]))
+    }
+
+    pub fn read(&mut self, tag: u8) -> Option<&'a [u8]> {
+        match self.read_any()? {
+            (t, content, _) if t == tag => Some(content),
+            _ => None,
+        }
+    }
+
+    pub fn read_optional(&mut self, tag: u8) -> Option<Option<&'a [u8]>> {
+        if self.peek_tag() == Some(tag) {
+            self.read(tag).map(Some)
+        } else {
+            Some(None)
+        }
+    }
+
+    // Returns the whole encoding of the next value, which must have `tag`.
+    pub fn read_raw(&mut self, tag: u8) -> Option<&'a [u8]> {
+        match self.read_any()? {
+            (t, _, raw) if t == tag => Some(raw),
+            _ => None,
+        }
+    }
+
+    pub fn read_sequence(&mut self) -> Option<Reader<'a>> {
+        self.read(SEQUENCE).map(Reader::new)
+    }
+
+    pub fn read_bit_string(&mut self) -> Option<&'a [u8]> {
+        match self.read(BIT_STRING)? {
+            [0, bits @ ..] => Some(bits),
+            _ => None,
+        }
+    }
+
+    pub fn read_unsigned(&mut self) -> Option<u64> {
+        let content = self.read(INTEGER)?;
+        unsigned(content)
+    }
+
+    pub fn read_time(&mut self) -> Option<i64> {
+        match self.read_any()? {
+            (UTC_TIME, content, _) => parse_utc_time(content),
+            (GENERALIZED_TIME, content, _) => parse_generalized_time(content),
+            _ => None,
+        }
+    }
+
+    pub fn read_time_optional(&mut self) -> Option<Option<i64>> {
+        match self.peek_tag() {
+            Some(UTC_TIME) | Some(GENERALIZED_TIME) => self.read_time().map(Some),
+            _ => Some(None),
+        }
+    }
+}
+
+pub fn unsigned(content: &[u8]) -> Option<u64> {
+    match content {
+        [] => None,
+        [first, ..] if first & 0x80 != 0 => None,
+        _ => {
+            let content = match content {
+                [0, rest @ ..] if !rest.is_empty() => rest,
+                _ => content,
+            };
+            if content.len() > 8 {
+                return None;
+            }
+            Some(content.iter().fold(0, |value, &byte| (value << 8) | byte as u64))
+        }
+    }
+}
+
+// YYMMDDHHMMSSZ, with years 50 to 99 in the 20th century (RFC 5280).
+fn parse_utc_time(content: &[u8]) -> Option<i64> {
+    if content.len() != 13 || content[12] != b'Z' {
+        return None;
+    }
+    let year = digits(&content[..2])?;
+    let year = if year >= 50 { 1900 + year } else { 2000 + year };
+    unix_time(year, &content[2..12])
+}
+
+// YYYYMMDDHHMMSSZ.
+fn parse_generalized_time(content: &[u8]) -> Option<i64> {
+    if content.len() != 15 || content[14] != b'Z' {
+        return None;
+    }
+    unix_time(digits(&content[..4])?, &content[4..14])
+}
+
+// MMDDHHMMSS of `year`.
+fn unix_time(year: i64, rest: &[u8]) -> Option<i64> {
+    from_civil(
+        year,
+        digits(&rest[..2])?,
+        digits(&rest[2..4])?,
+        digits(&rest[4..6])?,
+        digits(&rest[6..8])?,
+        digits(&rest[8..10])?,
+    )
+}
+
+fn digits(text: &[u8]) -> Option<i64> {
+    if text.is_empty() {
+        return None;
+    }
+    text.iter().try_fold(0, |value, &c| {
+        if c.is_ascii_digit() {
+            Some(value * 10 + (c - b'0') as i64)
+        } else {
+            None
+        }
+    })
+}
+
+// Seconds since the epoch of a UTC date and time, see
+// http://howardhinnant.github.io/date_algorithms.html#days_from_civil.
+pub fn from_civil(year: i64, month: i64, day: i64, hour: i64, minute: i64, second: i64) -> Option<i64> {
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = (if y >= 0 { y } else { y - 399 }) / 400;
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+    Some(days * 86400 + hour * 3600 + minute * 60 + second)
+}
+
+// An RFC 3339 date and time in UTC, as used by the TCB info and the QE
+// identity: YYYY-MM-DDTHH:MM:SSZ, with optional fractional seconds.
+pub fn parse_rfc3339(text: &str) -> Option<i64> {
+    let text = text.as_bytes();
+    if text.len() < 20 || text[4] != b'-' || text[7] != b'-' || text[10] != b'T' || text[13] != b':' || text[16] != b':' {
+        return None;
+    }
+    match &text[19..] {
+        b"Z" => (),
+        [b'.', fraction @ .., b'Z'] if !fraction.is_empty() && fraction.iter().all(u8::is_ascii_digit) => (),
+        _ => return None,
+    }
+    from_civil(
+        digits(&text[..4])?,
+        digits(&text[5..7])?,
+        digits(&text[8..10])?,
+        digits(&text[11..13])?,
+        digits(&text[14..16])?,
+        digits(&text[17..19])?,
+    )
+}
@@ -0,0 +1,65 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+// ECDSA P-256 with SHA-256, the only signature scheme of DCAP, on top of
+// sgx_tcrypto in an enclave and sgx_ucrypto outside.
+
+use crate::crypto::{
+    rsgx_ec256_public_from_sec1, rsgx_ec256_signature_from_der, rsgx_ec256_signature_from_raw, rsgx_sha256_slice,
+    SgxEccHandle,
+};
+use sgx_types::{sgx_ec256_signature_t, sgx_sha256_hash_t};
+
+// An uncompressed P-256 point, x || y in big endian.
+pub type PublicKey = [u8; 64];
+
+pub fn sha256(data: &[u8]) -> Option<sgx_sha256_hash_t> {
+    rsgx_sha256_slice(data).ok()
+}
+
+// Verifies a signature encoded as r || s, as found in quotes and in the TCB
+// info and QE identity.
+pub fn verify_raw(public: &PublicKey, data: &[u8], signature: &[u8]) -> bool {
+    match rsgx_ec256_signature_from_raw(signature) {
+        Ok(signature) => verify(public, data, &signature),
+        Err(_) => false,
+    }
+}
+
+// Verifies a DER signature, as found in certificates and CRLs.
+pub fn verify_der(public: &PublicKey, data: &[u8], signature: &[u8]) -> bool {
+    match rsgx_ec256_signature_from_der(signature) {
+        Ok(signature) => verify(public, data, &signature),
+        Err(_) => false,
+    }
+}
+
+fn verify(public: &PublicKey, data: &[u8], signature: &sgx_ec256_signature_t) -> bool {
+    let mut sec1 = [0_u8; 65];
+    sec1[0] = 0x04;
+    sec1[1..].copy_from_slice(public);
+    let public = match rsgx_ec256_public_from_sec1(&sec1) {
+        Ok(public) => public,
+        Err(_) => return false,
+    };
+
+    let handle = SgxEccHandle::new();
+    if handle.open().is_err() {
+        return false;
+    }
+    handle.ecdsa_verify_slice(data, &public, signature).unwrap_or(false)
+}
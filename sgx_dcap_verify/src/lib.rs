@@ -0,0 +1,63 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//! # DCAP Quote Verification Library
+//!
+//! This crate parses version 3 ECDSA quotes generated by the Intel(R) SGX
+//! DCAP Quoting Enclave, and verifies them against a collateral bundle
+//! (`sgx_ql_qve_collateral_t`) without the Quote Verification Enclave or a
+//! network connection. It checks the PCK certificate chain and the CRLs up to
+//! a trusted root, the QE report and the attestation key, the quote
+//! signature, and evaluates the TCB info and the QE identity into a TCB
+//! status.
+//!
+//! It utilizes sgx_tcrypto and sgx_ucrypto, so that the same verifier runs in
+//! an enclave and in an untrusted app.
+//!
+
+#![cfg_attr(all(feature = "mesalock_sgx", not(target_env = "sgx")), no_std)]
+#![cfg_attr(target_env = "sgx", feature(rustc_private))]
+
+#[cfg(all(feature = "mesalock_sgx", not(target_env = "sgx")))]
+#[macro_use]
+extern crate sgx_tstd as std;
+
+extern crate sgx_types;
+#[cfg(any(feature = "mesalock_sgx", target_env = "sgx"))]
+extern crate sgx_tcrypto as crypto;
+#[cfg(not(any(feature = "mesalock_sgx", target_env = "sgx")))]
+extern crate sgx_ucrypto as crypto;
+
+use sgx_types::sgx_quote3_error_t;
+
+mod der;
+mod ecdsa;
+mod json;
+mod pem;
+mod tcb;
+mod x509;
+
+mod collateral;
+pub use self::collateral::*;
+mod quote;
+pub use self::quote::*;
+mod verify;
+pub use self::verify::*;
+
+/// The result of the parsing and verification of quotes, with the error
+/// codes of the Quote Verification Library.
+pub type SgxQlResult<T> = Result<T, sgx_quote3_error_t>;
@@ -0,0 +1,116 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+// Decoding of the text encodings found in quotes and collateral: PEM blocks,
+// hex strings and C strings with a trailing NUL.
+
+use std::prelude::v1::*;
+use std::str;
+
+// Returns the contents of the PEM blocks labelled `label` in `text`, in
+// order.
+pub fn decode_all(text: &[u8], label: &str) -> Option<Vec<Vec<u8>>> {
+    let text = str::from_utf8(trim_nul(text)).ok()?;
+    let begin = format!("-----BEGIN {}-----", label);
+    let end = format!("-----END {}-----", label);
+
+    let mut blocks = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find(&begin) {
+        let body = &rest[start + begin.len()..];
+        let stop = body.find(&end)?;
+        blocks.push(base64_decode(&body[..stop])?);
+        rest = &body[stop + end.len()..];
+    }
+    Some(blocks)
+}
+
+// Accepts a DER structure as PEM, as a hex string or as it is.
+pub fn decode_der(data: &[u8], label: &str) -> Option<Vec<u8>> {
+    if data.first() == Some(&0x30) {
+        return Some(data.to_vec());
+    }
+    let data = trim_nul(data);
+    let mut blocks = decode_all(data, label)?;
+    match blocks.len() {
+        0 => hex_decode(str::from_utf8(data).ok()?.trim()),
+        1 => blocks.pop(),
+        _ => None,
+    }
+}
+
+// The text before the first NUL byte, for C strings.
+pub fn trim_nul(data: &[u8]) -> &[u8] {
+    match data.iter().position(|&b| b == 0) {
+        Some(end) => &data[..end],
+        None => data,
+    }
+}
+
+pub fn hex_decode(text: &str) -> Option<Vec<u8>> {
+    let text = text.as_bytes();
+    if text.len() % 2 != 0 {
+        return None;
+    }
+    text.chunks(2)
+        .map(|pair| Some((hex_digit(pair[0])? << 4) | hex_digit(pair[1])?))
+        .collect()
+}
+
+fn hex_digit(c: u8) -> Option<u8> {
+    match c {
+        b'0'..=b'9' => Some(c - b'0'),
+        b'a'..=b'f' => Some(c - b'a' + 10),
+        b'A'..=b'F' => Some(c - b'A' + 10),
+        _ => None,
+    }
+}
+
+fn base64_decode(text: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(text.len() / 4 * 3);
+    let mut acc = 0_u32;
+    let mut bits = 0;
+    let mut padding = 0;
+    for c in text.bytes() {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            b'=' => {
+                padding += 1;
+                continue;
+            }
+            b' ' | b'\t' | b'\r' | b'\n' => continue,
+            _ => return None,
+        };
+        if padding > 0 {
+            return None;
+        }
+        acc = (acc << 6) | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+        }
+    }
+    if padding > 2 || bits >= 6 {
+        return None;
+    }
+    Some(out)
+}
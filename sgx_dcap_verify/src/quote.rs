@@ -0,0 +1,178 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use crate::SgxQlResult;
+use sgx_types::*;
+use std::mem;
+use std::ptr;
+
+const ECDSA_SIG_DATA_SIZE: usize = 64 + 64 + SGX_REPORT_BODY_SIZE + 64;
+
+const QUOTE_VERSION: u16 = 3;
+
+/// The QE vendor ID of the Intel(R) Quoting Enclave.
+pub const INTEL_QE_VENDOR_ID: [u8; 16] = [
+    0x93, 0x9a, 0x72, 0x33, 0xf7, 0x9c, 0x4c, 0xa9, 0x94, 0x0a, 0x0d, 0xb3, 0x95, 0x7f, 0x06, 0x07,
+];
+
+/// The certification data type of a PEM encoded PCK certificate chain.
+pub const PCK_CERT_CHAIN: u16 = 5;
+
+/// A version 3 ECDSA P-256 quote, borrowed from the bytes it was parsed from.
+///
+/// Parsing only checks the layout of the quote; the signatures are checked
+/// by [`verify_quote`](fn.verify_quote.html).
+pub struct Quote<'a> {
+    quote: SgxQuote3<'a>,
+    header: sgx_quote_header_t,
+    report_body: sgx_report_body_t,
+    signature: &'a [u8],
+    attest_key: &'a [u8],
+    qe_report: &'a [u8],
+    qe_report_signature: &'a [u8],
+    auth_data: &'a [u8],
+    certification_data_type: u16,
+    certification_data: &'a [u8],
+}
+
+impl<'a> Quote<'a> {
+    /// Parses a quote as returned by `sgx_qe_get_quote`. Returns
+    /// `SGX_QL_QUOTE_FORMAT_UNSUPPORTED` if the quote is truncated, is not a
+    /// version 3 ECDSA P-256 quote, or was not generated by the Intel QE.
+    pub fn parse(data: &'a [u8]) -> SgxQlResult<Quote<'a>> {
+        Quote::parse_quote(data).ok_or(sgx_quote3_error_t::SGX_QL_QUOTE_FORMAT_UNSUPPORTED)
+    }
+
+    fn parse_quote(data: &'a [u8]) -> Option<Quote<'a>> {
+        let quote = SgxQuote3::parse(data).ok()?;
+        if quote.version() != QUOTE_VERSION
+            || quote.att_key_type() != sgx_ql_attestation_algorithm_id_t::SGX_QL_ALG_ECDSA_P256 as u16
+            || *quote.qe_vendor_id() != INTEL_QE_VENDOR_ID
+        {
+            return None;
+        }
+        let header = unsafe { read(&data[..SGX_QUOTE3_HEADER_SIZE]) };
+        let report_body = quote.report_body().to_raw();
+
+        let mut rest = quote.signature_data();
+        if rest.len() < ECDSA_SIG_DATA_SIZE {
+            return None;
+        }
+        let signature = take(&mut rest, 64)?;
+        let attest_key = take(&mut rest, 64)?;
+        let qe_report = take(&mut rest, SGX_REPORT_BODY_SIZE)?;
+        let qe_report_signature = take(&mut rest, 64)?;
+        let auth_data_len = read_u16(take(&mut rest, 2)?)? as usize;
+        let auth_data = take(&mut rest, auth_data_len)?;
+        let certification_data_type = read_u16(take(&mut rest, 2)?)?;
+        let certification_data_len = read_u32(take(&mut rest, 4)?)? as usize;
+        let certification_data = take(&mut rest, certification_data_len)?;
+
+        Some(Quote {
+            quote,
+            header,
+            report_body,
+            signature,
+            attest_key,
+            qe_report,
+            qe_report_signature,
+            auth_data,
+            certification_data_type,
+            certification_data,
+        })
+    }
+
+    /// The header of the quote.
+    pub fn header(&self) -> &sgx_quote_header_t {
+        &self.header
+    }
+
+    /// The report body of the attested enclave.
+    pub fn report_body(&self) -> &sgx_report_body_t {
+        &self.report_body
+    }
+
+    /// The report body of the Quoting Enclave.
+    pub fn qe_report_body(&self) -> SgxReportBody<'a> {
+        SgxReportBody::parse(self.qe_report).unwrap()
+    }
+
+    /// The ECDSA signature of the header and report body, r || s.
+    pub fn signature(&self) -> &'a [u8] {
+        self.signature
+    }
+
+    /// The ECDSA attestation public key, x || y.
+    pub fn attest_key(&self) -> &'a [u8] {
+        self.attest_key
+    }
+
+    /// The QE authentication data, bound to the attestation key by the QE
+    /// report data.
+    pub fn auth_data(&self) -> &'a [u8] {
+        self.auth_data
+    }
+
+    /// The type of the QE certification data, `PCK_CERT_CHAIN` for the quotes
+    /// verified by this crate.
+    pub fn certification_data_type(&self) -> u16 {
+        self.certification_data_type
+    }
+
+    /// The QE certification data.
+    pub fn certification_data(&self) -> &'a [u8] {
+        self.certification_data
+    }
+
+    pub(crate) fn signed_data(&self) -> &'a [u8] {
+        self.quote.signed_bytes()
+    }
+
+    pub(crate) fn qe_report(&self) -> &'a [u8] {
+        self.qe_report
+    }
+
+    pub(crate) fn qe_report_signature(&self) -> &'a [u8] {
+        self.qe_report_signature
+    }
+}
+
+unsafe fn read<T: Copy>(data: &[u8]) -> T {
+    debug_assert!(data.len() >= mem::size_of::<T>());
+    ptr::read_unaligned(data.as_ptr() as *const T)
+}
+
+fn take<'a>(data: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+    if data.len() < len {
+        return None;
+    }
+    let (head, tail) = data.split_at(len);
+    *data = tail;
+    Some(head)
+}
+
+fn read_u16(data: &[u8]) -> Option<u16> {
+    let mut bytes = [0_u8; 2];
+    bytes.copy_from_slice(data.get(..2)?);
+    Some(u16::from_le_bytes(bytes))
+}
+
+fn read_u32(data: &[u8]) -> Option<u32> {
+    let mut bytes = [0_u8; 4];
+    bytes.copy_from_slice(data.get(..4)?);
+    Some(u32::from_le_bytes(bytes))
+}
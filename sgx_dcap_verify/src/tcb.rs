@@ -0,0 +1,246 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+// The TCB info and the QE identity published by the Intel PCS. Both are
+// JSON documents of the form {"<body>": {...}, "signature": "<hex>"}, where
+// the signature is a raw ECDSA signature of the text of the body made with
+// the TCB signing key.
+
+use crate::der;
+use crate::ecdsa;
+use crate::json::Json;
+use crate::pem;
+use crate::verify::TcbStatus;
+use crate::x509::Certificate;
+use std::prelude::v1::*;
+use std::str;
+
+// The common name of the certificate the TCB info and the QE identity are
+// signed with.
+const TCB_SIGNING: &str = "Intel SGX TCB Signing";
+
+pub struct TcbLevel {
+    pub components: [u8; 16],
+    pub pce_svn: u16,
+    pub status: TcbStatus,
+    pub advisory_ids: Vec<String>,
+}
+
+pub struct TcbInfo {
+    pub fmspc: Vec<u8>,
+    pub pce_id: Vec<u8>,
+    pub next_update: i64,
+    pub levels: Vec<TcbLevel>,
+}
+
+pub struct QeTcbLevel {
+    pub isv_svn: u16,
+    pub status: TcbStatus,
+    pub advisory_ids: Vec<String>,
+}
+
+pub struct QeIdentity {
+    pub misc_select: u32,
+    pub misc_select_mask: u32,
+    pub attributes: Vec<u8>,
+    pub attributes_mask: Vec<u8>,
+    pub mr_signer: Vec<u8>,
+    pub isv_prod_id: u16,
+    pub next_update: i64,
+    pub levels: Vec<QeTcbLevel>,
+}
+
+// A signed document whose signature is not valid is reported apart from a
+// malformed one.
+pub enum Error {
+    Format,
+    Signature,
+}
+
+impl TcbInfo {
+    pub fn parse(data: &[u8], signer: &Certificate) -> Result<TcbInfo, Error> {
+        let text = str::from_utf8(pem::trim_nul(data)).map_err(|_| Error::Format)?;
+        let root = Json::parse(text).ok_or(Error::Format)?;
+        let body = signed_body(&root, "tcbInfo", signer)?;
+        TcbInfo::parse_body(body).ok_or(Error::Format)
+    }
+
+    fn parse_body(body: &Json) -> Option<TcbInfo> {
+        let version = body.get("version")?.as_u64()?;
+        match version {
+            2 => {
+                if body.get("tcbType")?.as_u64()? != 0 {
+                    return None;
+                }
+            }
+            3 => {
+                if body.get("id")?.as_str()? != "SGX" {
+                    return None;
+                }
+            }
+            _ => return None,
+        }
+
+        let mut levels = Vec::new();
+        for level in body.get("tcbLevels")?.as_array()? {
+            let tcb = level.get("tcb")?;
+            let mut components = [0_u8; 16];
+            if version == 2 {
+                for (i, component) in components.iter_mut().enumerate() {
+                    *component = svn(tcb.get(&format!("sgxtcbcomp{:02}svn", i + 1))?, 0xff)? as u8;
+                }
+            } else {
+                let values = tcb.get("sgxtcbcomponents")?.as_array()?;
+                if values.len() != components.len() {
+                    return None;
+                }
+                for (component, value) in components.iter_mut().zip(values) {
+                    *component = svn(value.get("svn")?, 0xff)? as u8;
+                }
+            }
+            levels.push(TcbLevel {
+                components,
+                pce_svn: svn(tcb.get("pcesvn")?, 0xffff)?,
+                status: TcbStatus::parse(level.get("tcbStatus")?.as_str()?)?,
+                advisory_ids: advisory_ids(level)?,
+            });
+        }
+
+        Some(TcbInfo {
+            fmspc: pem::hex_decode(body.get("fmspc")?.as_str()?)?,
+            pce_id: pem::hex_decode(body.get("pceId")?.as_str()?)?,
+            next_update: der::parse_rfc3339(body.get("nextUpdate")?.as_str()?)?,
+            levels,
+        })
+    }
+
+    // The first level, from the most recent, that the platform TCB is at
+    // or above.
+    pub fn level(&self, components: &[u8; 16], pce_svn: u16) -> Option<&TcbLevel> {
+        self.levels.iter().find(|level| {
+            pce_svn >= level.pce_svn && components.iter().zip(level.components.iter()).all(|(have, need)| have >= need)
+        })
+    }
+}
+
+impl QeIdentity {
+    pub fn parse(data: &[u8], signer: &Certificate) -> Result<QeIdentity, Error> {
+        let text = str::from_utf8(pem::trim_nul(data)).map_err(|_| Error::Format)?;
+        let root = Json::parse(text).ok_or(Error::Format)?;
+        let body = signed_body(&root, "enclaveIdentity", signer)?;
+        QeIdentity::parse_body(body).ok_or(Error::Format)
+    }
+
+    fn parse_body(body: &Json) -> Option<QeIdentity> {
+        if body.get("version")?.as_u64()? != 2 || body.get("id")?.as_str()? != "QE" {
+            return None;
+        }
+
+        let mut levels = Vec::new();
+        for level in body.get("tcbLevels")?.as_array()? {
+            let status = TcbStatus::parse(level.get("tcbStatus")?.as_str()?)?;
+            match status {
+                TcbStatus::UpToDate | TcbStatus::OutOfDate | TcbStatus::Revoked => (),
+                _ => return None,
+            }
+            levels.push(QeTcbLevel {
+                isv_svn: svn(level.get("tcb")?.get("isvsvn")?, 0xffff)?,
+                status,
+                advisory_ids: advisory_ids(level)?,
+            });
+        }
+
+        let identity = QeIdentity {
+            misc_select: hex_u32(body.get("miscselect")?)?,
+            misc_select_mask: hex_u32(body.get("miscselectMask")?)?,
+            attributes: pem::hex_decode(body.get("attributes")?.as_str()?)?,
+            attributes_mask: pem::hex_decode(body.get("attributesMask")?.as_str()?)?,
+            mr_signer: pem::hex_decode(body.get("mrsigner")?.as_str()?)?,
+            isv_prod_id: svn(body.get("isvprodid")?, 0xffff)?,
+            next_update: der::parse_rfc3339(body.get("nextUpdate")?.as_str()?)?,
+            levels,
+        };
+        if identity.attributes.len() != 16 || identity.attributes_mask.len() != 16 || identity.mr_signer.len() != 32 {
+            return None;
+        }
+        Some(identity)
+    }
+
+    // Whether `misc_select`, `attributes` (flags || xfrm, little endian) and
+    // `mr_signer` match the identity under its masks.
+    pub fn matches(&self, misc_select: u32, attributes: &[u8; 16], mr_signer: &[u8; 32], isv_prod_id: u16) -> bool {
+        let attributes_match = attributes
+            .iter()
+            .zip(self.attributes_mask.iter())
+            .zip(self.attributes.iter())
+            .all(|((have, mask), need)| have & mask == *need);
+        misc_select & self.misc_select_mask == self.misc_select
+            && attributes_match
+            && mr_signer[..] == self.mr_signer[..]
+            && isv_prod_id == self.isv_prod_id
+    }
+
+    pub fn level(&self, isv_svn: u16) -> Option<&QeTcbLevel> {
+        self.levels.iter().find(|level| isv_svn >= level.isv_svn)
+    }
+}
+
+// Whether `cert` is the TCB signing certificate. Any certificate of the
+// Intel SGX PKI chains to the root, so the signer is also told apart from
+// the CAs and the PCK certificates by its name.
+pub fn is_signer(cert: &Certificate) -> bool {
+    !cert.is_ca && cert.common_name() == Some(TCB_SIGNING)
+}
+
+fn signed_body<'r, 'a>(root: &'r Json<'a>, name: &str, signer: &Certificate) -> Result<&'r Json<'a>, Error> {
+    if !is_signer(signer) {
+        return Err(Error::Signature);
+    }
+    let body = root.member(name).ok_or(Error::Format)?;
+    let signature = root
+        .get("signature")
+        .and_then(Json::as_str)
+        .and_then(pem::hex_decode)
+        .ok_or(Error::Format)?;
+    if ecdsa::verify_raw(&signer.public_key, body.text.as_bytes(), &signature) {
+        Ok(&body.value)
+    } else {
+        Err(Error::Signature)
+    }
+}
+
+fn svn(value: &Json, max: u64) -> Option<u16> {
+    match value.as_u64()? {
+        svn if svn <= max => Some(svn as u16),
+        _ => None,
+    }
+}
+
+fn hex_u32(value: &Json) -> Option<u32> {
+    let bytes = pem::hex_decode(value.as_str()?)?;
+    if bytes.len() != 4 {
+        return None;
+    }
+    Some(bytes.iter().fold(0, |value, &byte| (value << 8) | byte as u32))
+}
+
+fn advisory_ids(level: &Json) -> Option<Vec<String>> {
+    match level.get("advisoryIDs") {
+        Some(ids) => ids.as_array()?.iter().map(|id| id.as_str().map(String::from)).collect(),
+        None => Some(Vec::new()),
+    }
+}
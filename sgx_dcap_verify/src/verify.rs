@@ -0,0 +1,407 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use crate::collateral::Collateral;
+use crate::ecdsa;
+use crate::quote::{Quote, PCK_CERT_CHAIN};
+use crate::tcb::{self, QeIdentity, TcbInfo};
+use crate::x509::{self, Certificate, Crl, SgxExtension};
+use crate::SgxQlResult;
+use sgx_types::*;
+use std::prelude::v1::*;
+
+/// The TCB status of a platform, or of a Quoting Enclave, as evaluated from
+/// the TCB info and the QE identity.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum TcbStatus {
+    UpToDate,
+    SwHardeningNeeded,
+    ConfigurationNeeded,
+    ConfigurationAndSwHardeningNeeded,
+    OutOfDate,
+    OutOfDateConfigurationNeeded,
+    Revoked,
+}
+
+impl TcbStatus {
+    pub(crate) fn parse(status: &str) -> Option<TcbStatus> {
+        match status {
+            "UpToDate" => Some(TcbStatus::UpToDate),
+            "SWHardeningNeeded" => Some(TcbStatus::SwHardeningNeeded),
+            "ConfigurationNeeded" => Some(TcbStatus::ConfigurationNeeded),
+            "ConfigurationAndSWHardeningNeeded" => Some(TcbStatus::ConfigurationAndSwHardeningNeeded),
+            "OutOfDate" => Some(TcbStatus::OutOfDate),
+            "OutOfDateConfigurationNeeded" => Some(TcbStatus::OutOfDateConfigurationNeeded),
+            "Revoked" => Some(TcbStatus::Revoked),
+            _ => None,
+        }
+    }
+
+    /// The result `sgx_qv_verify_quote` reports for this status.
+    pub fn qv_result(self) -> sgx_ql_qv_result_t {
+        match self {
+            TcbStatus::UpToDate => sgx_ql_qv_result_t::SGX_QL_QV_RESULT_OK,
+            TcbStatus::SwHardeningNeeded => sgx_ql_qv_result_t::SGX_QL_QV_RESULT_SW_HARDENING_NEEDED,
+            TcbStatus::ConfigurationNeeded => sgx_ql_qv_result_t::SGX_QL_QV_RESULT_CONFIG_NEEDED,
+            TcbStatus::ConfigurationAndSwHardeningNeeded => {
+                sgx_ql_qv_result_t::SGX_QL_QV_RESULT_CONFIG_AND_SW_HARDENING_NEEDED
+            }
+            TcbStatus::OutOfDate => sgx_ql_qv_result_t::SGX_QL_QV_RESULT_OUT_OF_DATE,
+            TcbStatus::OutOfDateConfigurationNeeded => sgx_ql_qv_result_t::SGX_QL_QV_RESULT_OUT_OF_DATE_CONFIG_NEEDED,
+            TcbStatus::Revoked => sgx_ql_qv_result_t::SGX_QL_QV_RESULT_REVOKED,
+        }
+    }
+
+    // The platform status, given the status of the QE that generated the
+    // quote.
+    fn converge(self, qe: TcbStatus) -> TcbStatus {
+        match (qe, self) {
+            (TcbStatus::Revoked, _) => TcbStatus::Revoked,
+            (TcbStatus::OutOfDate, TcbStatus::UpToDate) | (TcbStatus::OutOfDate, TcbStatus::SwHardeningNeeded) => {
+                TcbStatus::OutOfDate
+            }
+            (TcbStatus::OutOfDate, TcbStatus::ConfigurationNeeded)
+            | (TcbStatus::OutOfDate, TcbStatus::ConfigurationAndSwHardeningNeeded) => {
+                TcbStatus::OutOfDateConfigurationNeeded
+            }
+            (_, platform) => platform,
+        }
+    }
+}
+
+/// The outcome of a successful quote verification.
+///
+/// The signatures of the quote and of the collateral are valid; whether the
+/// enclave and the platform are acceptable is left to the relying party,
+/// from `report_body`, `tcb_status` and `collateral_expired`.
+pub struct QuoteVerification {
+    /// The report body of the attested enclave.
+    pub report_body: sgx_report_body_t,
+    /// The TCB status of the platform and of the Quoting Enclave.
+    pub tcb_status: TcbStatus,
+    /// The Intel security advisories that apply to the TCB levels matched.
+    pub advisory_ids: Vec<String>,
+    /// The FMSPC of the platform, from the PCK certificate.
+    pub fmspc: Vec<u8>,
+    /// The earliest expiration of the certificates, CRLs, TCB info and QE
+    /// identity, in seconds since the epoch.
+    pub earliest_expiration: i64,
+    /// Whether any of the collateral had expired at the verification time.
+    pub collateral_expired: bool,
+}
+
+/// Verifies a quote against `collateral`, with `trusted_root` as the root CA
+/// certificate (PEM or DER), at `now` seconds since the epoch.
+///
+/// Errors use the codes of the Quote Verification Library: for instance
+/// `SGX_QL_PCK_CERT_CHAIN_ERROR` when the PCK certificate chain does not
+/// lead to `trusted_root` or starts with a CA, `SGX_QL_QE_REPORT_INVALID_SIGNATURE`
+/// when the QE report is not signed by the PCK key, `SGX_QL_INVALID_REPORT`
+/// when the quote is not signed by the attestation key,
+/// `SGX_QL_TCBINFO_CHAIN_ERROR` when the TCB info is not signed by the TCB
+/// signing certificate, and `SGX_QL_TCBINFO_MISMATCH` when the TCB info does
+/// not cover the platform. A revoked certificate is not an error but a
+/// `Revoked` status.
+pub fn verify_quote(
+    quote: &Quote<'_>,
+    collateral: &Collateral,
+    trusted_root: &[u8],
+    now: i64,
+) -> SgxQlResult<QuoteVerification> {
+    let root = parse_root(trusted_root)?;
+    let mut expiration = root.not_after;
+    let mut revoked = false;
+
+    // The PCK certificate chain, from the quote.
+    if quote.certification_data_type() != PCK_CERT_CHAIN {
+        return Err(sgx_quote3_error_t::SGX_QL_QUOTE_CERTIFICATION_DATA_UNSUPPORTED);
+    }
+    let pck_chain = Certificate::parse_chain(quote.certification_data())
+        .ok_or(sgx_quote3_error_t::SGX_QL_PCK_CERT_UNSUPPORTED_FORMAT)?;
+    expiration = expiration.min(
+        x509::verify_chain(&pck_chain, &root, now).ok_or(sgx_quote3_error_t::SGX_QL_PCK_CERT_CHAIN_ERROR)?,
+    );
+    let pck = &pck_chain[0];
+    if pck.is_ca {
+        return Err(sgx_quote3_error_t::SGX_QL_PCK_CERT_CHAIN_ERROR);
+    }
+    let extension = pck
+        .sgx_extension
+        .as_ref()
+        .and_then(|extension| SgxExtension::parse(extension))
+        .ok_or(sgx_quote3_error_t::SGX_QL_PCK_CERT_UNSUPPORTED_FORMAT)?;
+
+    // The CRLs of the root CA and of the PCK CA.
+    let root_crl = Crl::parse(&collateral.root_ca_crl).ok_or(sgx_quote3_error_t::SGX_QL_CRL_UNSUPPORTED_FORMAT)?;
+    if !root_crl.is_signed_by(&root) {
+        return Err(sgx_quote3_error_t::SGX_QL_CRL_UNSUPPORTED_FORMAT);
+    }
+    let pck_crl_chain = Certificate::parse_chain(&collateral.pck_crl_issuer_chain)
+        .ok_or(sgx_quote3_error_t::SGX_QL_CRL_UNSUPPORTED_FORMAT)?;
+    expiration = expiration.min(
+        x509::verify_chain(&pck_crl_chain, &root, now).ok_or(sgx_quote3_error_t::SGX_QL_CRL_UNSUPPORTED_FORMAT)?,
+    );
+    let pck_crl = Crl::parse(&collateral.pck_crl).ok_or(sgx_quote3_error_t::SGX_QL_CRL_UNSUPPORTED_FORMAT)?;
+    if pck_crl.issuer != pck.issuer || !pck_crl.is_signed_by(&pck_crl_chain[0]) {
+        return Err(sgx_quote3_error_t::SGX_QL_CRL_UNSUPPORTED_FORMAT);
+    }
+    expiration = expiration.min(root_crl.next_update).min(pck_crl.next_update);
+    revoked |= pck_crl.is_revoked(pck);
+    revoked |= pck_chain.iter().chain(pck_crl_chain.iter()).any(|cert| root_crl.is_revoked(cert));
+
+    // The QE report, signed by the PCK key, binds the attestation key.
+    if !ecdsa::verify_raw(&pck.public_key, quote.qe_report(), quote.qe_report_signature()) {
+        return Err(sgx_quote3_error_t::SGX_QL_QE_REPORT_INVALID_SIGNATURE);
+    }
+    let qe_report = quote.qe_report_body();
+    let mut bound = quote.attest_key().to_vec();
+    bound.extend_from_slice(quote.auth_data());
+    let hash = ecdsa::sha256(&bound).ok_or(sgx_quote3_error_t::SGX_QL_ERROR_UNEXPECTED)?;
+    let report_data = &qe_report.report_data().d;
+    if report_data[..32] != hash[..] || report_data[32..].iter().any(|&b| b != 0) {
+        return Err(sgx_quote3_error_t::SGX_QL_QE_REPORT_INVALID_SIGNATURE);
+    }
+
+    // The quote, signed by the attestation key.
+    let mut attest_key = [0_u8; 64];
+    attest_key.copy_from_slice(quote.attest_key());
+    if !ecdsa::verify_raw(&attest_key, quote.signed_data(), quote.signature()) {
+        return Err(sgx_quote3_error_t::SGX_QL_INVALID_REPORT);
+    }
+
+    // The TCB info, for the TCB level of the platform.
+    let tcb_chain = Certificate::parse_chain(&collateral.tcb_info_issuer_chain)
+        .ok_or(sgx_quote3_error_t::SGX_QL_TCBINFO_CHAIN_ERROR)?;
+    expiration = expiration.min(
+        x509::verify_chain(&tcb_chain, &root, now).ok_or(sgx_quote3_error_t::SGX_QL_TCBINFO_CHAIN_ERROR)?,
+    );
+    if !tcb::is_signer(&tcb_chain[0]) {
+        return Err(sgx_quote3_error_t::SGX_QL_TCBINFO_CHAIN_ERROR);
+    }
+    revoked |= tcb_chain.iter().any(|cert| root_crl.is_revoked(cert));
+    let tcb_info = TcbInfo::parse(&collateral.tcb_info, &tcb_chain[0]).map_err(|e| match e {
+        tcb::Error::Format => sgx_quote3_error_t::SGX_QL_TCBINFO_UNSUPPORTED_FORMAT,
+        tcb::Error::Signature => sgx_quote3_error_t::SGX_QL_TCBINFO_CHAIN_ERROR,
+    })?;
+    if tcb_info.fmspc != extension.fmspc || tcb_info.pce_id != extension.pce_id {
+        return Err(sgx_quote3_error_t::SGX_QL_TCBINFO_MISMATCH);
+    }
+    let tcb_level = tcb_info
+        .level(&extension.components, extension.pce_svn)
+        .ok_or(sgx_quote3_error_t::SGX_QL_TCBINFO_MISMATCH)?;
+    expiration = expiration.min(tcb_info.next_update);
+
+    // The QE identity, for the TCB level of the QE.
+    let qe_chain = Certificate::parse_chain(&collateral.qe_identity_issuer_chain)
+        .ok_or(sgx_quote3_error_t::SGX_QL_QEIDENTITY_CHAIN_ERROR)?;
+    expiration = expiration.min(
+        x509::verify_chain(&qe_chain, &root, now).ok_or(sgx_quote3_error_t::SGX_QL_QEIDENTITY_CHAIN_ERROR)?,
+    );
+    if !tcb::is_signer(&qe_chain[0]) {
+        return Err(sgx_quote3_error_t::SGX_QL_QEIDENTITY_CHAIN_ERROR);
+    }
+    revoked |= qe_chain.iter().any(|cert| root_crl.is_revoked(cert));
+    let qe_identity = QeIdentity::parse(&collateral.qe_identity, &qe_chain[0]).map_err(|e| match e {
+        tcb::Error::Format => sgx_quote3_error_t::SGX_QL_QEIDENTITY_UNSUPPORTED_FORMAT,
+        tcb::Error::Signature => sgx_quote3_error_t::SGX_QL_QEIDENTITY_CHAIN_ERROR,
+    })?;
+    let mut attributes = [0_u8; 16];
+    attributes[..8].copy_from_slice(&qe_report.attributes().flags.to_le_bytes());
+    attributes[8..].copy_from_slice(&qe_report.attributes().xfrm.to_le_bytes());
+    if !qe_identity.matches(
+        qe_report.misc_select(),
+        &attributes,
+        &qe_report.mr_signer().m,
+        qe_report.isv_prod_id(),
+    ) {
+        return Err(sgx_quote3_error_t::SGX_QL_QEIDENTITY_MISMATCH);
+    }
+    let (qe_status, qe_advisory_ids) = match qe_identity.level(qe_report.isv_svn()) {
+        Some(level) => (level.status, &level.advisory_ids[..]),
+        None => (TcbStatus::OutOfDate, &[][..]),
+    };
+    expiration = expiration.min(qe_identity.next_update);
+
+    let mut advisory_ids = tcb_level.advisory_ids.clone();
+    for id in qe_advisory_ids {
+        if !advisory_ids.contains(id) {
+            advisory_ids.push(id.clone());
+        }
+    }
+    let tcb_status = if revoked {
+        TcbStatus::Revoked
+    } else {
+        tcb_level.status.converge(qe_status)
+    };
+
+    Ok(QuoteVerification {
+        report_body: *quote.report_body(),
+        tcb_status,
+        advisory_ids,
+        fmspc: extension.fmspc,
+        earliest_expiration: expiration,
+        collateral_expired: now > expiration,
+    })
+}
+
+fn parse_root(trusted_root: &[u8]) -> SgxQlResult<Certificate> {
+    let mut certs = Certificate::parse_chain(trusted_root).ok_or(sgx_quote3_error_t::SGX_QL_ERROR_INVALID_PARAMETER)?;
+    match certs.pop() {
+        Some(root) if certs.is_empty() && root.is_ca && root.is_signed_by(&root) => Ok(root),
+        _ => Err(sgx_quote3_error_t::SGX_QL_ERROR_INVALID_PARAMETER),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The fixtures are written by testdata/gen.py and valid at NOW.
+    const NOW: i64 = 1_790_812_800;
+    const DAY: i64 = 86_400;
+    const QUOTE: &[u8] = include_bytes!("../testdata/quote.bin");
+    const ROOT: &[u8] = include_bytes!("../testdata/root.pem");
+
+    fn collateral() -> Collateral {
+        Collateral {
+            pck_crl_issuer_chain: include_bytes!("../testdata/pck_crl_chain.pem").to_vec(),
+            root_ca_crl: include_bytes!("../testdata/root_crl.der").to_vec(),
+            pck_crl: include_bytes!("../testdata/pck_crl.hex").to_vec(),
+            tcb_info_issuer_chain: include_bytes!("../testdata/tcb_chain.pem").to_vec(),
+            tcb_info: include_bytes!("../testdata/tcb_info.json").to_vec(),
+            qe_identity_issuer_chain: include_bytes!("../testdata/tcb_chain.pem").to_vec(),
+            qe_identity: include_bytes!("../testdata/qe_identity.json").to_vec(),
+        }
+    }
+
+    fn verify(quote: &[u8], collateral: &Collateral, root: &[u8], now: i64) -> SgxQlResult<QuoteVerification> {
+        verify_quote(&Quote::parse(quote)?, collateral, root, now)
+    }
+
+    fn error(quote: &[u8], collateral: &Collateral, root: &[u8]) -> Option<sgx_quote3_error_t> {
+        verify(quote, collateral, root, NOW).err()
+    }
+
+    #[test]
+    fn valid_quote() {
+        for root in &[ROOT, &include_bytes!("../testdata/root.der")[..]] {
+            let verification = verify(QUOTE, &collateral(), root, NOW).unwrap();
+            assert_eq!(verification.report_body.mr_enclave.m, [0xee; 32]);
+            assert_eq!(verification.report_body.report_data.d[..], [0xab; 64][..]);
+            // The platform is at SWHardeningNeeded and the QE out of date.
+            assert_eq!(verification.tcb_status, TcbStatus::OutOfDate);
+            assert_eq!(verification.advisory_ids, vec!["INTEL-SA-00334", "INTEL-SA-00477"]);
+            assert_eq!(verification.fmspc, vec![0x00, 0x90, 0x6e, 0xa1, 0x00, 0x00]);
+            assert_eq!(verification.earliest_expiration, NOW + 20 * DAY);
+            assert!(!verification.collateral_expired);
+        }
+    }
+
+    #[test]
+    fn expired_collateral() {
+        let verification = verify(QUOTE, &collateral(), ROOT, NOW + 21 * DAY).unwrap();
+        assert!(verification.collateral_expired);
+        assert_eq!(verification.earliest_expiration, NOW + 20 * DAY);
+    }
+
+    #[test]
+    fn bad_quote_signature() {
+        // The signature follows the header, the report body and its length.
+        let mut quote = QUOTE.to_vec();
+        quote[436] ^= 1;
+        assert_eq!(error(&quote, &collateral(), ROOT), Some(sgx_quote3_error_t::SGX_QL_INVALID_REPORT));
+        // The report data is signed.
+        let mut quote = QUOTE.to_vec();
+        quote[48 + 320] ^= 1;
+        assert_eq!(error(&quote, &collateral(), ROOT), Some(sgx_quote3_error_t::SGX_QL_INVALID_REPORT));
+    }
+
+    #[test]
+    fn bad_qe_report_signature() {
+        // The QE report signature follows the quote signature, the
+        // attestation key and the QE report.
+        let mut quote = QUOTE.to_vec();
+        quote[436 + 64 + 64 + 384] ^= 1;
+        assert_eq!(
+            error(&quote, &collateral(), ROOT),
+            Some(sgx_quote3_error_t::SGX_QL_QE_REPORT_INVALID_SIGNATURE)
+        );
+    }
+
+    #[test]
+    fn wrong_root() {
+        assert_eq!(
+            error(QUOTE, &collateral(), include_bytes!("../testdata/other_root.pem")),
+            Some(sgx_quote3_error_t::SGX_QL_PCK_CERT_CHAIN_ERROR)
+        );
+        assert_eq!(
+            error(QUOTE, &collateral(), include_bytes!("../testdata/tcb_chain.pem")),
+            Some(sgx_quote3_error_t::SGX_QL_ERROR_INVALID_PARAMETER)
+        );
+    }
+
+    #[test]
+    fn revoked_pck_serial() {
+        let mut collateral = collateral();
+        collateral.pck_crl = include_bytes!("../testdata/pck_crl_revoked.pem").to_vec();
+        let verification = verify(QUOTE, &collateral, ROOT, NOW).unwrap();
+        assert_eq!(verification.tcb_status, TcbStatus::Revoked);
+    }
+
+    #[test]
+    fn fmspc_mismatch() {
+        let mut collateral = collateral();
+        collateral.tcb_info = include_bytes!("../testdata/tcb_info_other_fmspc.json").to_vec();
+        assert_eq!(error(QUOTE, &collateral, ROOT), Some(sgx_quote3_error_t::SGX_QL_TCBINFO_MISMATCH));
+    }
+
+    #[test]
+    fn tcb_signer_is_not_a_ca() {
+        let mut tcb_info = collateral();
+        tcb_info.tcb_info_issuer_chain = include_bytes!("../testdata/tcb_chain_ca.pem").to_vec();
+        tcb_info.tcb_info = include_bytes!("../testdata/tcb_info_ca.json").to_vec();
+        assert_eq!(error(QUOTE, &tcb_info, ROOT), Some(sgx_quote3_error_t::SGX_QL_TCBINFO_CHAIN_ERROR));
+
+        let mut qe_identity = collateral();
+        qe_identity.qe_identity_issuer_chain = include_bytes!("../testdata/tcb_chain_ca.pem").to_vec();
+        assert_eq!(error(QUOTE, &qe_identity, ROOT), Some(sgx_quote3_error_t::SGX_QL_QEIDENTITY_CHAIN_ERROR));
+    }
+
+    #[test]
+    fn tcb_signer_is_the_tcb_signing_certificate() {
+        let mut tcb_info = collateral();
+        tcb_info.tcb_info_issuer_chain = include_bytes!("../testdata/tcb_chain_pck.pem").to_vec();
+        tcb_info.tcb_info = include_bytes!("../testdata/tcb_info_pck.json").to_vec();
+        assert_eq!(error(QUOTE, &tcb_info, ROOT), Some(sgx_quote3_error_t::SGX_QL_TCBINFO_CHAIN_ERROR));
+
+        let mut qe_identity = collateral();
+        qe_identity.qe_identity_issuer_chain = include_bytes!("../testdata/tcb_chain_pck.pem").to_vec();
+        assert_eq!(error(QUOTE, &qe_identity, ROOT), Some(sgx_quote3_error_t::SGX_QL_QEIDENTITY_CHAIN_ERROR));
+
+        let signer = Certificate::parse_chain(include_bytes!("../testdata/tcb_chain_pck.pem")).unwrap();
+        let tcb_info = include_bytes!("../testdata/tcb_info_pck.json");
+        assert!(TcbInfo::parse(tcb_info, &signer[0]).is_err());
+    }
+
+    #[test]
+    fn pck_certificate_is_not_a_ca() {
+        assert_eq!(
+            error(include_bytes!("../testdata/quote_ca_leaf.bin"), &collateral(), ROOT),
+            Some(sgx_quote3_error_t::SGX_QL_PCK_CERT_CHAIN_ERROR)
+        );
+    }
+}
@@ -0,0 +1,330 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+// The X.509 certificates and CRLs of the Intel SGX PKI, all of them signed
+// with ECDSA P-256 and SHA-256.
+
+use crate::der::{self, Reader};
+use crate::ecdsa::{self, PublicKey};
+use crate::pem;
+use std::prelude::v1::*;
+use std::str;
+
+// 1.2.840.10045.4.3.2
+const ECDSA_WITH_SHA256: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02];
+// 1.2.840.10045.2.1
+const EC_PUBLIC_KEY: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01];
+// 1.2.840.10045.3.1.7
+const PRIME256V1: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07];
+// 2.5.4.3
+const COMMON_NAME: &[u8] = &[0x55, 0x04, 0x03];
+// 2.5.29.19
+const BASIC_CONSTRAINTS: &[u8] = &[0x55, 0x1d, 0x13];
+// 1.2.840.113741.1.13.1
+const SGX_EXTENSION: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf8, 0x4d, 0x01, 0x0d, 0x01];
+
+pub struct Certificate {
+    pub der: Vec<u8>,
+    tbs: Vec<u8>,
+    signature: Vec<u8>,
+    pub serial: Vec<u8>,
+    pub issuer: Vec<u8>,
+    pub subject: Vec<u8>,
+    pub not_before: i64,
+    pub not_after: i64,
+    pub public_key: PublicKey,
+    pub is_ca: bool,
+    pub sgx_extension: Option<Vec<u8>>,
+}
+
+impl Certificate {
+    pub fn parse(der: &[u8]) -> Option<Certificate> {
+        let mut outer = Reader::new(der);
+        let mut cert = outer.read_sequence()?;
+        if !outer.is_empty() {
+            return None;
+        }
+        let tbs = cert.read_raw(der::SEQUENCE)?;
+        let algorithm = cert.read_sequence()?;
+        let signature = cert.read_bit_string()?;
+        if !cert.is_empty() || !is_ecdsa_with_sha256(algorithm) {
+            return None;
+        }
+
+        let mut fields = Reader::new(tbs).read_sequence()?;
+        if let Some(version) = fields.read_optional(der::CONTEXT_0)? {
+            // v3 only
+            if version != &[der::INTEGER, 1, 2][..] {
+                return None;
+            }
+        }
+        let serial = fields.read(der::INTEGER)?;
+        if !is_ecdsa_with_sha256(fields.read_sequence()?) {
+            return None;
+        }
+        let issuer = fields.read_raw(der::SEQUENCE)?;
+        let mut validity = fields.read_sequence()?;
+        let not_before = validity.read_time()?;
+        let not_after = validity.read_time()?;
+        let subject = fields.read_raw(der::SEQUENCE)?;
+        let public_key = parse_public_key(fields.read_sequence()?)?;
+
+        let mut is_ca = false;
+        let mut sgx_extension = None;
+        while let Some((tag, content, _)) = fields.read_any() {
+            if tag != der::CONTEXT_3 {
+                continue;
+            }
+            let mut extensions = Reader::new(content).read_sequence()?;
+            while !extensions.is_empty() {
+                let mut extension = extensions.read_sequence()?;
+                let id = extension.read(der::OID)?;
+                extension.read_optional(der::BOOLEAN)?;
+                let value = extension.read(der::OCTET_STRING)?;
+                if id == BASIC_CONSTRAINTS {
+                    let mut constraints = Reader::new(value).read_sequence()?;
+                    is_ca = constraints.read_optional(der::BOOLEAN)? == Some(&[0xff][..]);
+                } else if id == SGX_EXTENSION {
+                    sgx_extension = Some(value.to_vec());
+                }
+            }
+        }
+
+        Some(Certificate {
+            der: der.to_vec(),
+            tbs: tbs.to_vec(),
+            signature: signature.to_vec(),
+            serial: serial.to_vec(),
+            issuer: issuer.to_vec(),
+            subject: subject.to_vec(),
+            not_before,
+            not_after,
+            public_key,
+            is_ca,
+            sgx_extension,
+        })
+    }
+
+    // Parses the certificates of a PEM chain, or a single DER certificate.
+    pub fn parse_chain(data: &[u8]) -> Option<Vec<Certificate>> {
+        if data.first() == Some(&0x30) {
+            return Certificate::parse(data).map(|cert| vec![cert]);
+        }
+        let blocks = pem::decode_all(data, "CERTIFICATE")?;
+        if blocks.is_empty() {
+            return None;
+        }
+        blocks.iter().map(|block| Certificate::parse(block)).collect()
+    }
+
+    pub fn is_signed_by(&self, issuer: &Certificate) -> bool {
+        self.issuer == issuer.subject && ecdsa::verify_der(&issuer.public_key, &self.tbs, &self.signature)
+    }
+
+    // The common name of the subject, if it has exactly one.
+    pub fn common_name(&self) -> Option<&str> {
+        let mut name = Reader::new(&self.subject).read_sequence()?;
+        let mut common_name = None;
+        while !name.is_empty() {
+            let mut attributes = Reader::new(name.read(der::SET)?);
+            while !attributes.is_empty() {
+                let mut attribute = attributes.read_sequence()?;
+                if attribute.read(der::OID)? != COMMON_NAME {
+                    continue;
+                }
+                let (tag, value, _) = attribute.read_any()?;
+                if common_name.is_some() || (tag != der::UTF8_STRING && tag != der::PRINTABLE_STRING) {
+                    return None;
+                }
+                common_name = Some(str::from_utf8(value).ok()?);
+            }
+        }
+        common_name
+    }
+}
+
+pub struct Crl {
+    tbs: Vec<u8>,
+    signature: Vec<u8>,
+    pub issuer: Vec<u8>,
+    pub this_update: i64,
+    pub next_update: i64,
+    revoked: Vec<Vec<u8>>,
+}
+
+impl Crl {
+    pub fn parse(data: &[u8]) -> Option<Crl> {
+        let der = pem::decode_der(data, "X509 CRL")?;
+        let mut outer = Reader::new(&der);
+        let mut crl = outer.read_sequence()?;
+        if !outer.is_empty() {
+            return None;
+        }
+        let tbs = crl.read_raw(der::SEQUENCE)?;
+        let algorithm = crl.read_sequence()?;
+        let signature = crl.read_bit_string()?;
+        if !crl.is_empty() || !is_ecdsa_with_sha256(algorithm) {
+            return None;
+        }
+
+        let mut fields = Reader::new(tbs).read_sequence()?;
+        if fields.peek_tag() == Some(der::INTEGER) {
+            fields.read(der::INTEGER)?;
+        }
+        if !is_ecdsa_with_sha256(fields.read_sequence()?) {
+            return None;
+        }
+        let issuer = fields.read_raw(der::SEQUENCE)?;
+        let this_update = fields.read_time()?;
+        let next_update = fields.read_time_optional()?.unwrap_or(i64::MAX);
+
+        let mut revoked = Vec::new();
+        if fields.peek_tag() == Some(der::SEQUENCE) {
+            let mut entries = fields.read_sequence()?;
+            while !entries.is_empty() {
+                let mut entry = entries.read_sequence()?;
+                revoked.push(entry.read(der::INTEGER)?.to_vec());
+            }
+        }
+
+        Some(Crl {
+            tbs: tbs.to_vec(),
+            signature: signature.to_vec(),
+            issuer: issuer.to_vec(),
+            this_update,
+            next_update,
+            revoked,
+        })
+    }
+
+    pub fn is_signed_by(&self, issuer: &Certificate) -> bool {
+        self.issuer == issuer.subject && ecdsa::verify_der(&issuer.public_key, &self.tbs, &self.signature)
+    }
+
+    pub fn is_revoked(&self, cert: &Certificate) -> bool {
+        cert.issuer == self.issuer && self.revoked.iter().any(|serial| *serial == cert.serial)
+    }
+}
+
+// The platform identity and TCB carried by a PCK certificate, in the
+// extension 1.2.840.113741.1.13.1.
+pub struct SgxExtension {
+    pub components: [u8; 16],
+    pub pce_svn: u16,
+    pub cpu_svn: [u8; 16],
+    pub pce_id: Vec<u8>,
+    pub fmspc: Vec<u8>,
+}
+
+impl SgxExtension {
+    pub fn parse(value: &[u8]) -> Option<SgxExtension> {
+        let mut components = [0_u8; 16];
+        let mut pce_svn = None;
+        let mut cpu_svn = None;
+        let mut pce_id = None;
+        let mut fmspc = None;
+
+        let mut entries = Reader::new(value).read_sequence()?;
+        while !entries.is_empty() {
+            let mut entry = entries.read_sequence()?;
+            let id = entry.read(der::OID)?;
+            match sgx_arc(id) {
+                Some([2]) => {
+                    let mut tcb = entry.read_sequence()?;
+                    while !tcb.is_empty() {
+                        let mut component = tcb.read_sequence()?;
+                        let id = component.read(der::OID)?;
+                        match sgx_arc(id) {
+                            Some([2, n @ 1..=16]) => {
+                                components[*n as usize - 1] = component.read_unsigned()?.min(255) as u8
+                            }
+                            Some([2, 17]) => pce_svn = Some(component.read_unsigned()?.min(0xffff) as u16),
+                            Some([2, 18]) => {
+                                let mut svn = [0_u8; 16];
+                                let value = component.read(der::OCTET_STRING)?;
+                                if value.len() != svn.len() {
+                                    return None;
+                                }
+                                svn.copy_from_slice(value);
+                                cpu_svn = Some(svn);
+                            }
+                            _ => (),
+                        }
+                    }
+                }
+                Some([3]) => pce_id = Some(entry.read(der::OCTET_STRING)?.to_vec()),
+                Some([4]) => fmspc = Some(entry.read(der::OCTET_STRING)?.to_vec()),
+                _ => (),
+            }
+        }
+
+        Some(SgxExtension {
+            components,
+            pce_svn: pce_svn?,
+            cpu_svn: cpu_svn?,
+            pce_id: pce_id?,
+            fmspc: fmspc?,
+        })
+    }
+}
+
+// The arcs of `id` under the SGX extension OID, all of them below 128.
+fn sgx_arc(id: &[u8]) -> Option<&[u8]> {
+    if id.starts_with(SGX_EXTENSION) {
+        Some(&id[SGX_EXTENSION.len()..])
+    } else {
+        None
+    }
+}
+
+// Checks that every certificate of `chain` is signed by the next one, and
+// the last one by `root` unless it is `root`, and that they are valid at
+// `now`. Returns the earliest expiration of the chain.
+pub fn verify_chain(chain: &[Certificate], root: &Certificate, now: i64) -> Option<i64> {
+    let last = chain.last()?;
+    if last.der != root.der && !last.is_signed_by(root) {
+        return None;
+    }
+    for pair in chain.windows(2) {
+        if !pair[1].is_ca || !pair[0].is_signed_by(&pair[1]) {
+            return None;
+        }
+    }
+    if chain.iter().chain(Some(root)).any(|cert| cert.not_before > now) {
+        return None;
+    }
+    chain.iter().chain(Some(root)).map(|cert| cert.not_after).min()
+}
+
+fn is_ecdsa_with_sha256(mut algorithm: Reader<'_>) -> bool {
+    algorithm.read(der::OID) == Some(ECDSA_WITH_SHA256) && algorithm.is_empty()
+}
+
+fn parse_public_key(mut info: Reader<'_>) -> Option<PublicKey> {
+    let mut algorithm = info.read_sequence()?;
+    if algorithm.read(der::OID)? != EC_PUBLIC_KEY || algorithm.read(der::OID)? != PRIME256V1 {
+        return None;
+    }
+    match info.read_bit_string()? {
+        [0x04, point @ ..] if point.len() == 64 => {
+            let mut public_key = [0_u8; 64];
+            public_key.copy_from_slice(point);
+            Some(public_key)
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,225 @@
+# Licensed to the Apache Software Foundation (ASF) under one
+# or more contributor license agreements.  See the NOTICE file
+# distributed with this work for additional information
+# regarding copyright ownership.  The ASF licenses this file
+# to you under the Apache License, Version 2.0 (the
+# "License"); you may not use this file except in compliance
+# with the License.  You may obtain a copy of the License at
+#
+#   http://www.apache.org/licenses/LICENSE-2.0
+#
+# Unless required by applicable law or agreed to in writing,
+# software distributed under the License is distributed on an
+# "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+# KIND, either express or implied.  See the License for the
+# specific language governing permissions and limitations
+# under the License.
+
+# Generates the quote and collateral fixtures of the tests in verify.rs: a
+# PKI shaped like the Intel SGX one, valid at 2026-10-01T00:00:00Z, and a
+# version 3 ECDSA quote of a platform at an out of date TCB level. Needs the
+# python `cryptography` package. The keys are random, so every run rewrites
+# all the fixtures.
+
+import datetime
+import hashlib
+import json
+import os
+import struct
+
+from cryptography import x509
+from cryptography.hazmat.primitives import hashes, serialization
+from cryptography.hazmat.primitives.asymmetric import ec, utils
+from cryptography.x509.oid import NameOID, ObjectIdentifier
+
+OUT = os.path.dirname(os.path.abspath(__file__))
+NOW = datetime.datetime(2026, 10, 1, tzinfo=datetime.timezone.utc)
+SGX = [1, 2, 840, 113741, 1, 13, 1]
+FMSPC = bytes.fromhex('00906ea10000')
+PCE_ID = bytes.fromhex('0000')
+QE_MRSIGNER = bytes(range(32))
+
+
+def days(n):
+    return NOW + datetime.timedelta(days=n)
+
+
+def write(name, data):
+    with open(os.path.join(OUT, name), 'wb') as f:
+        f.write(data)
+
+
+def key():
+    return ec.generate_private_key(ec.SECP256R1())
+
+
+def name(cn):
+    return x509.Name([x509.NameAttribute(NameOID.COMMON_NAME, cn)])
+
+
+def cert(subject, key, issuer, issuer_key, ca, serial, extension=None, valid=365):
+    builder = (x509.CertificateBuilder()
+               .subject_name(name(subject)).issuer_name(name(issuer))
+               .public_key(key.public_key()).serial_number(serial)
+               .not_valid_before(days(-1)).not_valid_after(days(valid))
+               .add_extension(x509.BasicConstraints(ca=ca, path_length=None), critical=True))
+    if extension is not None:
+        builder = builder.add_extension(extension, critical=False)
+    return builder.sign(issuer_key, hashes.SHA256())
+
+
+def pem(*certs):
+    return b''.join(c.public_bytes(serialization.Encoding.PEM) for c in certs)
+
+
+def crl(issuer, issuer_key, revoked=()):
+    builder = (x509.CertificateRevocationListBuilder().issuer_name(name(issuer))
+               .last_update(days(-1)).next_update(days(20)))
+    for serial in revoked:
+        builder = builder.add_revoked_certificate(
+            x509.RevokedCertificateBuilder().serial_number(serial).revocation_date(days(-1)).build())
+    return builder.sign(issuer_key, hashes.SHA256())
+
+
+def raw_sign(key, data):
+    r, s = utils.decode_dss_signature(key.sign(data, ec.ECDSA(hashes.SHA256())))
+    return r.to_bytes(32, 'big') + s.to_bytes(32, 'big')
+
+
+# The SGX extension of PCK certificates, encoded by hand.
+def tlv(tag, content):
+    n = len(content)
+    if n < 0x80:
+        length = bytes([n])
+    elif n < 0x100:
+        length = bytes([0x81, n])
+    else:
+        length = bytes([0x82, n >> 8, n & 0xff])
+    return bytes([tag]) + length + content
+
+
+def oid(arcs):
+    content = bytes([40 * arcs[0] + arcs[1]])
+    for arc in arcs[2:]:
+        parts = [arc & 0x7f]
+        arc >>= 7
+        while arc:
+            parts.insert(0, 0x80 | (arc & 0x7f))
+            arc >>= 7
+        content += bytes(parts)
+    return tlv(0x06, content)
+
+
+def integer(value):
+    return tlv(0x02, value.to_bytes((value.bit_length() + 8) // 8, 'big'))
+
+
+def sgx_extension(components, pce_svn):
+    tcb = b''.join(tlv(0x30, oid(SGX + [2, i + 1]) + integer(c)) for i, c in enumerate(components))
+    tcb += tlv(0x30, oid(SGX + [2, 17]) + integer(pce_svn))
+    tcb += tlv(0x30, oid(SGX + [2, 18]) + tlv(0x04, bytes(components)))
+    value = tlv(0x30, tlv(0x30, oid(SGX + [1]) + tlv(0x04, b'\x11' * 16))
+                + tlv(0x30, oid(SGX + [2]) + tlv(0x30, tcb))
+                + tlv(0x30, oid(SGX + [3]) + tlv(0x04, PCE_ID))
+                + tlv(0x30, oid(SGX + [4]) + tlv(0x04, FMSPC))
+                + tlv(0x30, oid(SGX + [5]) + tlv(0x0a, b'\x00')))
+    return x509.UnrecognizedExtension(ObjectIdentifier('.'.join(map(str, SGX))), value)
+
+
+# The signed JSON documents of the PCS.
+def signed(member, body, key):
+    text = json.dumps(body, separators=(',', ':'))
+    return ('{"%s":%s,"signature":"%s"}' % (member, text, raw_sign(key, text.encode()).hex())).encode()
+
+
+def timestamp(n):
+    return days(n).strftime('%Y-%m-%dT%H:%M:%SZ')
+
+
+def tcb_level(svn, pce_svn, status, advisory_ids):
+    tcb = {'sgxtcbcomp%02dsvn' % (i + 1): svn for i in range(16)}
+    tcb['pcesvn'] = pce_svn
+    return {'tcb': tcb, 'tcbDate': timestamp(-100), 'tcbStatus': status, 'advisoryIDs': advisory_ids}
+
+
+def tcb_info(fmspc):
+    return {'version': 2, 'issueDate': timestamp(0), 'nextUpdate': timestamp(30), 'fmspc': fmspc.hex(),
+            'pceId': PCE_ID.hex(), 'tcbType': 0, 'tcbEvaluationDataNumber': 5,
+            'tcbLevels': [tcb_level(7, 11, 'UpToDate', []),
+                          tcb_level(5, 10, 'SWHardeningNeeded', ['INTEL-SA-00334']),
+                          tcb_level(3, 9, 'OutOfDate', ['INTEL-SA-00161', 'INTEL-SA-00334'])]}
+
+
+QE_IDENTITY = {
+    'id': 'QE', 'version': 2, 'issueDate': timestamp(0), 'nextUpdate': timestamp(25), 'tcbEvaluationDataNumber': 5,
+    'miscselect': '00000000', 'miscselectMask': 'FFFFFFFF',
+    'attributes': '11000000000000000000000000000000', 'attributesMask': 'FBFFFFFFFFFFFFFF0000000000000000',
+    'mrsigner': QE_MRSIGNER.hex().upper(), 'isvprodid': 1,
+    'tcbLevels': [{'tcb': {'isvsvn': 8}, 'tcbDate': timestamp(-10), 'tcbStatus': 'UpToDate'},
+                  {'tcb': {'isvsvn': 6}, 'tcbDate': timestamp(-100), 'tcbStatus': 'OutOfDate',
+                   'advisoryIDs': ['INTEL-SA-00477']}],
+}
+
+
+# A quote of an enclave with MRENCLAVE ee..ee and report data ab..ab,
+# certified by `pck` and the chain after it.
+def report_body(misc_select, flags, xfrm, mr_enclave, mr_signer, isv_prod_id, isv_svn, report_data):
+    body = bytes(16) + struct.pack('<I', misc_select) + bytes(28) + struct.pack('<QQ', flags, xfrm)
+    body += mr_enclave + bytes(32) + mr_signer + bytes(96)
+    body += struct.pack('<HH', isv_prod_id, isv_svn) + bytes(60) + report_data
+    assert len(body) == 384
+    return body
+
+
+def quote(chain, pck_key):
+    attest_key = key()
+    attest_public = attest_key.public_key().public_bytes(
+        serialization.Encoding.X962, serialization.PublicFormat.UncompressedPoint)[1:]
+    auth_data = bytes(range(32))
+    header = struct.pack('<HHIHH', 3, 2, 0, 6, 10) + bytes.fromhex('939a7233f79c4ca9940a0db3957f0607') + bytes(20)
+    report = report_body(0, 0x7, 0x3, b'\xee' * 32, b'\x55' * 32, 3, 4, b'\xab' * 64)
+    qe_report = report_body(0, 0x15, 0xe7, b'\x01' * 32, QE_MRSIGNER, 1, 6,
+                            hashlib.sha256(attest_public + auth_data).digest() + bytes(32))
+    certification_data = pem(*chain) + b'\0'
+    signature = raw_sign(attest_key, header + report) + attest_public + qe_report + raw_sign(pck_key, qe_report)
+    signature += struct.pack('<H', len(auth_data)) + auth_data
+    signature += struct.pack('<HI', 5, len(certification_data)) + certification_data
+    return header + report + struct.pack('<I', len(signature)) + signature
+
+
+root_key, pck_ca_key, pck_key, tcb_key = key(), key(), key(), key()
+root = cert('Intel SGX Root CA', root_key, 'Intel SGX Root CA', root_key, True, 1, valid=3650)
+pck_ca = cert('Intel SGX PCK Processor CA', pck_ca_key, 'Intel SGX Root CA', root_key, True, 2)
+extension = sgx_extension([5] * 16, 10)
+pck = cert('Intel SGX PCK Certificate', pck_key, 'Intel SGX PCK Processor CA', pck_ca_key, False, 77, extension)
+tcb_signing = cert('Intel SGX TCB Signing', tcb_key, 'Intel SGX Root CA', root_key, False, 3)
+
+write('root.pem', pem(root))
+write('root.der', root.public_bytes(serialization.Encoding.DER))
+other_root_key = key()
+write('other_root.pem', pem(cert('Intel SGX Root CA', other_root_key, 'Intel SGX Root CA', other_root_key, True, 1)))
+write('root_crl.der', crl('Intel SGX Root CA', root_key).public_bytes(serialization.Encoding.DER))
+write('pck_crl_chain.pem', pem(pck_ca, root) + b'\0')
+write('pck_crl.hex', crl('Intel SGX PCK Processor CA', pck_ca_key).public_bytes(serialization.Encoding.DER).hex().encode())
+write('pck_crl_revoked.pem',
+      crl('Intel SGX PCK Processor CA', pck_ca_key, [5, 77]).public_bytes(serialization.Encoding.PEM))
+write('tcb_chain.pem', pem(tcb_signing, root) + b'\0')
+write('tcb_info.json', signed('tcbInfo', tcb_info(FMSPC), tcb_key))
+write('tcb_info_other_fmspc.json', signed('tcbInfo', tcb_info(bytes.fromhex('00906ed50000')), tcb_key))
+write('qe_identity.json', signed('enclaveIdentity', QE_IDENTITY, tcb_key))
+write('quote.bin', quote([pck, pck_ca, root], pck_key))
+
+# Certificates of the PKI that are not the TCB signing certificate: a CA
+# with its name, and a PCK certificate.
+tcb_ca_key = key()
+tcb_ca = cert('Intel SGX TCB Signing', tcb_ca_key, 'Intel SGX Root CA', root_key, True, 4)
+write('tcb_chain_ca.pem', pem(tcb_ca, root))
+write('tcb_info_ca.json', signed('tcbInfo', tcb_info(FMSPC), tcb_ca_key))
+write('tcb_chain_pck.pem', pem(pck, pck_ca, root))
+write('tcb_info_pck.json', signed('tcbInfo', tcb_info(FMSPC), pck_key))
+
+# A quote certified by a CA in place of a PCK certificate.
+pck_ca_leaf_key = key()
+pck_ca_leaf = cert('Intel SGX PCK Certificate', pck_ca_leaf_key, 'Intel SGX PCK Processor CA', pck_ca_key, True, 78,
+                   extension)
+write('quote_ca_leaf.bin', quote([pck_ca_leaf, pck_ca, root], pck_ca_leaf_key))
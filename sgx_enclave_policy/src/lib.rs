@@ -0,0 +1,442 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//! # Enclave Identity Policy
+//!
+//! After a report or a quote has been verified, the identity of the enclave
+//! it describes still has to be checked. This crate provides
+//! `EnclavePolicy`, which collects the measurements, signers, minimum
+//! security versions, KSS configurations and TCB statuses a relying party
+//! accepts, and reports every way an identity falls short of it.
+//!
+//! The policy applies to report bodies (from `rsgx_verify_report`, or from a
+//! quote verified by IAS or by `sgx_dcap_verify`) and to the identities
+//! returned by the `sgx_tdh` local attestation. It only depends on `alloc`,
+//! so it can be used in an enclave and in an untrusted app.
+//!
+
+#![no_std]
+#![cfg_attr(target_env = "sgx", feature(rustc_private))]
+#![allow(non_camel_case_types)]
+
+extern crate alloc;
+
+extern crate sgx_types;
+
+use alloc::vec::Vec;
+use sgx_types::*;
+
+///
+/// The identity of an enclave as described by a report body, a quote or a
+/// local attestation session.
+///
+pub trait EnclaveIdentity {
+    fn mr_enclave(&self) -> &sgx_measurement_t;
+    fn mr_signer(&self) -> &sgx_measurement_t;
+    fn isv_prod_id(&self) -> sgx_prod_id_t;
+    fn isv_svn(&self) -> sgx_isv_svn_t;
+    fn attributes(&self) -> sgx_attributes_t;
+    ///
+    /// The KSS configuration ID, `None` when the source does not carry it.
+    ///
+    fn config_id(&self) -> Option<&sgx_config_id_t>;
+}
+
+impl EnclaveIdentity for sgx_report_body_t {
+    fn mr_enclave(&self) -> &sgx_measurement_t {
+        &self.mr_enclave
+    }
+
+    fn mr_signer(&self) -> &sgx_measurement_t {
+        &self.mr_signer
+    }
+
+    fn isv_prod_id(&self) -> sgx_prod_id_t {
+        self.isv_prod_id
+    }
+
+    fn isv_svn(&self) -> sgx_isv_svn_t {
+        self.isv_svn
+    }
+
+    fn attributes(&self) -> sgx_attributes_t {
+        self.attributes
+    }
+
+    fn config_id(&self) -> Option<&sgx_config_id_t> {
+        Some(&self.config_id)
+    }
+}
+
+impl<'a> EnclaveIdentity for SgxReportBody<'a> {
+    fn mr_enclave(&self) -> &sgx_measurement_t {
+        SgxReportBody::mr_enclave(self)
+    }
+
+    fn mr_signer(&self) -> &sgx_measurement_t {
+        SgxReportBody::mr_signer(self)
+    }
+
+    fn isv_prod_id(&self) -> sgx_prod_id_t {
+        SgxReportBody::isv_prod_id(self)
+    }
+
+    fn isv_svn(&self) -> sgx_isv_svn_t {
+        SgxReportBody::isv_svn(self)
+    }
+
+    fn attributes(&self) -> sgx_attributes_t {
+        SgxReportBody::attributes(self)
+    }
+
+    fn config_id(&self) -> Option<&sgx_config_id_t> {
+        Some(SgxReportBody::config_id(self))
+    }
+}
+
+impl EnclaveIdentity for sgx_dh_session_enclave_identity_t {
+    fn mr_enclave(&self) -> &sgx_measurement_t {
+        &self.mr_enclave
+    }
+
+    fn mr_signer(&self) -> &sgx_measurement_t {
+        &self.mr_signer
+    }
+
+    fn isv_prod_id(&self) -> sgx_prod_id_t {
+        self.isv_prod_id
+    }
+
+    fn isv_svn(&self) -> sgx_isv_svn_t {
+        self.isv_svn
+    }
+
+    fn attributes(&self) -> sgx_attributes_t {
+        self.attributes
+    }
+
+    fn config_id(&self) -> Option<&sgx_config_id_t> {
+        None
+    }
+}
+
+///
+/// A way in which an enclave identity does not satisfy a policy.
+///
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum PolicyViolation {
+    /// The policy accepts neither a measurement nor a signer, so it would
+    /// accept any enclave.
+    Unconstrained,
+    /// MRENCLAVE is not one of the accepted measurements.
+    MrEnclave([u8; SGX_HASH_SIZE]),
+    /// MRSIGNER is not one of the accepted signers.
+    MrSigner([u8; SGX_HASH_SIZE]),
+    /// ISVPRODID is not one of the accepted products.
+    IsvProdId(sgx_prod_id_t),
+    /// ISVSVN is below the minimum for the product.
+    IsvSvn {
+        isv_prod_id: sgx_prod_id_t,
+        isv_svn: sgx_isv_svn_t,
+        min_isv_svn: sgx_isv_svn_t,
+    },
+    /// CONFIGID is not one of the accepted configurations, or is unknown.
+    ConfigId,
+    /// The enclave is a debug enclave.
+    Debug,
+    /// The TCB status of the platform is not accepted.
+    TcbStatus(sgx_ql_qv_result_t),
+    /// The policy sets the accepted TCB statuses, but the identity was
+    /// checked without the TCB status of its platform.
+    TcbStatusMissing,
+}
+
+///
+/// The enclave identities a relying party accepts.
+///
+/// An empty set of measurements or signers accepts any value, but at least
+/// one of the two must be set. Debug enclaves are rejected, and only the
+/// `SGX_QL_QV_RESULT_OK` TCB status is accepted, unless allowed explicitly.
+///
+/// A policy whose TCB statuses are set with `tcb_status` requires the TCB
+/// status of the platform: `check` rejects every identity, and only
+/// `check_with_tcb_status` can accept one.
+///
+#[derive(Clone)]
+pub struct EnclavePolicy {
+    mr_enclaves: Vec<sgx_measurement_t>,
+    mr_signers: Vec<sgx_measurement_t>,
+    min_isv_svns: Vec<(sgx_prod_id_t, sgx_isv_svn_t)>,
+    config_ids: Vec<sgx_config_id_t>,
+    allow_debug: bool,
+    tcb_statuses: Vec<sgx_ql_qv_result_t>,
+}
+
+impl Default for EnclavePolicy {
+    fn default() -> EnclavePolicy {
+        EnclavePolicy::new()
+    }
+}
+
+impl EnclavePolicy {
+    ///
+    /// Create a policy that accepts no enclave until a measurement or a
+    /// signer is added.
+    ///
+    pub fn new() -> EnclavePolicy {
+        EnclavePolicy {
+            mr_enclaves: Vec::new(),
+            mr_signers: Vec::new(),
+            min_isv_svns: Vec::new(),
+            config_ids: Vec::new(),
+            allow_debug: false,
+            tcb_statuses: Vec::new(),
+        }
+    }
+
+    ///
+    /// Accept enclaves measured as `mr_enclave`.
+    ///
+    pub fn mr_enclave(mut self, mr_enclave: sgx_measurement_t) -> EnclavePolicy {
+        self.mr_enclaves.push(mr_enclave);
+        self
+    }
+
+    ///
+    /// Accept enclaves signed by the key whose hash is `mr_signer`.
+    ///
+    pub fn mr_signer(mut self, mr_signer: sgx_measurement_t) -> EnclavePolicy {
+        self.mr_signers.push(mr_signer);
+        self
+    }
+
+    ///
+    /// Accept the product `isv_prod_id` from version `min_isv_svn`. Once a
+    /// product is added, enclaves of other products are rejected.
+    ///
+    pub fn min_isv_svn(mut self, isv_prod_id: sgx_prod_id_t, min_isv_svn: sgx_isv_svn_t) -> EnclavePolicy {
+        self.min_isv_svns.retain(|&(id, _)| id != isv_prod_id);
+        self.min_isv_svns.push((isv_prod_id, min_isv_svn));
+        self
+    }
+
+    ///
+    /// Accept enclaves launched with the KSS configuration `config_id`. Once
+    /// a configuration is added, enclaves with other configurations, and
+    /// identities that do not carry one, are rejected.
+    ///
+    pub fn config_id(mut self, config_id: sgx_config_id_t) -> EnclavePolicy {
+        self.config_ids.push(config_id);
+        self
+    }
+
+    ///
+    /// Accept debug enclaves, for development only.
+    ///
+    pub fn allow_debug(mut self, allow: bool) -> EnclavePolicy {
+        self.allow_debug = allow;
+        self
+    }
+
+    ///
+    /// Accept platforms whose TCB status is `status`, in addition to
+    /// `SGX_QL_QV_RESULT_OK`, and require the TCB status of the platform.
+    ///
+    /// `tcb_status(SGX_QL_QV_RESULT_OK)` only requires the TCB status.
+    ///
+    pub fn tcb_status(mut self, status: sgx_ql_qv_result_t) -> EnclavePolicy {
+        if !self.tcb_statuses.contains(&status) {
+            self.tcb_statuses.push(status);
+        }
+        self
+    }
+
+    ///
+    /// Check `identity` against the policy, and return every violation.
+    ///
+    /// This is meant for identities that come without a TCB status, such as
+    /// the ones of local attestation. The identity of a quote should be
+    /// checked with `check_with_tcb_status`.
+    ///
+    pub fn check<T: EnclaveIdentity + ?Sized>(&self, identity: &T) -> Result<(), Vec<PolicyViolation>> {
+        let mut violations = self.violations(identity);
+        if !self.tcb_statuses.is_empty() {
+            violations.push(PolicyViolation::TcbStatusMissing);
+        }
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+
+    ///
+    /// Check `identity` and the TCB status of the platform it runs on, as
+    /// returned by the verification of its quote.
+    ///
+    pub fn check_with_tcb_status<T: EnclaveIdentity + ?Sized>(
+        &self,
+        identity: &T,
+        status: sgx_ql_qv_result_t,
+    ) -> Result<(), Vec<PolicyViolation>> {
+        let mut violations = self.violations(identity);
+        if status != sgx_ql_qv_result_t::SGX_QL_QV_RESULT_OK && !self.tcb_statuses.contains(&status) {
+            violations.push(PolicyViolation::TcbStatus(status));
+        }
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+
+    fn violations<T: EnclaveIdentity + ?Sized>(&self, identity: &T) -> Vec<PolicyViolation> {
+        let mut violations = Vec::new();
+        if self.mr_enclaves.is_empty() && self.mr_signers.is_empty() {
+            violations.push(PolicyViolation::Unconstrained);
+        }
+
+        let mr_enclave = identity.mr_enclave();
+        if !self.mr_enclaves.is_empty() && !self.mr_enclaves.iter().any(|m| m.m == mr_enclave.m) {
+            violations.push(PolicyViolation::MrEnclave(mr_enclave.m));
+        }
+        let mr_signer = identity.mr_signer();
+        if !self.mr_signers.is_empty() && !self.mr_signers.iter().any(|m| m.m == mr_signer.m) {
+            violations.push(PolicyViolation::MrSigner(mr_signer.m));
+        }
+
+        if !self.min_isv_svns.is_empty() {
+            let isv_prod_id = identity.isv_prod_id();
+            let isv_svn = identity.isv_svn();
+            match self.min_isv_svns.iter().find(|&&(id, _)| id == isv_prod_id) {
+                Some(&(_, min_isv_svn)) if isv_svn < min_isv_svn => violations.push(PolicyViolation::IsvSvn {
+                    isv_prod_id,
+                    isv_svn,
+                    min_isv_svn,
+                }),
+                Some(_) => (),
+                None => violations.push(PolicyViolation::IsvProdId(isv_prod_id)),
+            }
+        }
+
+        if !self.config_ids.is_empty() {
+            let accepted = match identity.config_id() {
+                Some(config_id) => self.config_ids.iter().any(|id| id[..] == config_id[..]),
+                None => false,
+            };
+            if !accepted {
+                violations.push(PolicyViolation::ConfigId);
+            }
+        }
+
+        if !self.allow_debug && identity.attributes().debug() {
+            violations.push(PolicyViolation::Debug);
+        }
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    const MR_ENCLAVE: sgx_measurement_t = sgx_measurement_t { m: [0xee; SGX_HASH_SIZE] };
+
+    fn body() -> sgx_report_body_t {
+        let mut body = sgx_report_body_t::default();
+        body.mr_enclave = MR_ENCLAVE;
+        body.attributes.flags = SGX_FLAGS_INITTED | SGX_FLAGS_MODE64BIT;
+        body
+    }
+
+    #[test]
+    fn tcb_status_defaults_to_ok() {
+        let policy = EnclavePolicy::new().mr_enclave(MR_ENCLAVE);
+        let status = sgx_ql_qv_result_t::SGX_QL_QV_RESULT_OUT_OF_DATE;
+        assert!(policy.check(&body()).is_ok());
+        assert!(policy
+            .check_with_tcb_status(&body(), sgx_ql_qv_result_t::SGX_QL_QV_RESULT_OK)
+            .is_ok());
+        assert_eq!(
+            policy.check_with_tcb_status(&body(), status),
+            Err(vec![PolicyViolation::TcbStatus(status)])
+        );
+    }
+
+    #[test]
+    fn tcb_status_is_required_once_set() {
+        let status = sgx_ql_qv_result_t::SGX_QL_QV_RESULT_SW_HARDENING_NEEDED;
+        let policy = EnclavePolicy::new().mr_enclave(MR_ENCLAVE).tcb_status(status);
+        assert_eq!(policy.check(&body()), Err(vec![PolicyViolation::TcbStatusMissing]));
+        assert!(policy.check_with_tcb_status(&body(), status).is_ok());
+        assert!(policy
+            .check_with_tcb_status(&body(), sgx_ql_qv_result_t::SGX_QL_QV_RESULT_OK)
+            .is_ok());
+
+        let policy = EnclavePolicy::new()
+            .mr_enclave(MR_ENCLAVE)
+            .tcb_status(sgx_ql_qv_result_t::SGX_QL_QV_RESULT_OK);
+        assert_eq!(policy.check(&body()), Err(vec![PolicyViolation::TcbStatusMissing]));
+        assert_eq!(
+            policy.check_with_tcb_status(&body(), status),
+            Err(vec![PolicyViolation::TcbStatus(status)])
+        );
+    }
+
+    #[test]
+    fn every_violation_is_reported() {
+        let mut body = body();
+        body.mr_signer.m = [0x66; SGX_HASH_SIZE];
+        body.isv_prod_id = 1;
+        body.isv_svn = 2;
+        body.attributes.flags |= SGX_FLAGS_DEBUG;
+        let policy = EnclavePolicy::new()
+            .mr_signer(sgx_measurement_t { m: [0x55; SGX_HASH_SIZE] })
+            .min_isv_svn(1, 3)
+            .config_id([7; SGX_CONFIGID_SIZE]);
+        assert_eq!(
+            policy.check(&body),
+            Err(vec![
+                PolicyViolation::MrSigner([0x66; SGX_HASH_SIZE]),
+                PolicyViolation::IsvSvn {
+                    isv_prod_id: 1,
+                    isv_svn: 2,
+                    min_isv_svn: 3,
+                },
+                PolicyViolation::ConfigId,
+                PolicyViolation::Debug,
+            ])
+        );
+        assert_eq!(
+            EnclavePolicy::new().allow_debug(true).check(&body),
+            Err(vec![PolicyViolation::Unconstrained])
+        );
+    }
+
+    #[test]
+    fn local_identities_carry_no_config_id() {
+        let mut identity = sgx_dh_session_enclave_identity_t::default();
+        identity.mr_enclave = MR_ENCLAVE;
+        let policy = EnclavePolicy::new().mr_enclave(MR_ENCLAVE);
+        assert!(policy.check(&identity).is_ok());
+        assert_eq!(
+            policy.config_id([7; SGX_CONFIGID_SIZE]).check(&identity),
+            Err(vec![PolicyViolation::ConfigId])
+        );
+    }
+}
@@ -0,0 +1,400 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use crate::crypto::{rsgx_ec256_public_to_sec1, rsgx_sha256_slice, SgxEccHandle};
+use crate::der::{self, Reader};
+use crate::verify::ias_report_body;
+use sgx_dcap_verify::Quote;
+use sgx_types::*;
+use std::prelude::v1::*;
+
+// 1.2.840.10045.4.3.2
+const ECDSA_WITH_SHA256: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02];
+// 1.2.840.10045.2.1
+const EC_PUBLIC_KEY: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01];
+// 1.2.840.10045.3.1.7
+const PRIME256V1: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07];
+// 2.5.4.3
+const COMMON_NAME: &[u8] = &[0x55, 0x04, 0x03];
+
+// The extensions of RA-TLS certificates, in the 1.2.840.113741.1337 arc.
+const IAS_REPORT_OID: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf8, 0x4d, 0x8a, 0x39, 0x02];
+const IAS_SIGNING_CERT_OID: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf8, 0x4d, 0x8a, 0x39, 0x04];
+const IAS_SIGNATURE_OID: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf8, 0x4d, 0x8a, 0x39, 0x05];
+const QUOTE_OID: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf8, 0x4d, 0x8a, 0x39, 0x06];
+
+const SUBJECT: &str = "Teaclave RA-TLS";
+// The validity of the certificate is not checked; the quote is what matters.
+const NOT_BEFORE: &str = "200101000000Z";
+const NOT_AFTER: &str = "491231235959Z";
+
+///
+/// The attestation evidence carried by an RA-TLS certificate.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Evidence {
+    /// An EPID quote verified by the Intel Attestation Service: the body of
+    /// the attestation verification report, its signature decoded from the
+    /// `X-IASReport-Signature` header, and the DER encoded certificate from
+    /// the `X-IASReport-Signing-Certificate` header that signed it.
+    Epid {
+        report: Vec<u8>,
+        signature: Vec<u8>,
+        signing_cert: Vec<u8>,
+    },
+    /// An ECDSA quote generated by the Intel(R) SGX DCAP Quoting Enclave.
+    Dcap { quote: Vec<u8> },
+}
+
+///
+/// The report data an enclave must put in the report it has quoted, to bind
+/// the key `public` into the evidence of its RA-TLS certificate: the
+/// SHA-256 hash of the DER encoded SubjectPublicKeyInfo of the key, followed
+/// by zeros.
+///
+pub fn report_data(public: &sgx_ec256_public_t) -> SgxResult<sgx_report_data_t> {
+    key_binding(&public_key_info(public))
+}
+
+pub(crate) fn key_binding(public_key_info: &[u8]) -> SgxResult<sgx_report_data_t> {
+    let hash = rsgx_sha256_slice(public_key_info)?;
+    let mut report_data = sgx_report_data_t::default();
+    report_data.d[..SGX_SHA256_HASH_SIZE].copy_from_slice(&hash);
+    Ok(report_data)
+}
+
+///
+/// A self-signed certificate carrying the attestation evidence of an
+/// enclave, and its P-256 private key in PKCS #8, both DER encoded.
+///
+/// The private key is overwritten with zeros when the certificate is
+/// dropped.
+///
+pub struct RaTlsCert {
+    cert: Vec<u8>,
+    key: Vec<u8>,
+}
+
+impl RaTlsCert {
+    ///
+    /// Creates the certificate of the key pair `private`, `public` with
+    /// `evidence`, whose report data must be `report_data(public)`.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_PARAMETER**
+    ///
+    /// The evidence is empty, or its quote does not carry
+    /// `report_data(public)`.
+    ///
+    /// **SGX_ERROR_OUT_OF_MEMORY**
+    ///
+    /// Not enough memory is available to sign the certificate.
+    ///
+    /// **SGX_ERROR_UNEXPECTED**
+    ///
+    /// The signature failed due to an internal cryptography library failure.
+    ///
+    pub fn new(
+        private: &sgx_ec256_private_t,
+        public: &sgx_ec256_public_t,
+        evidence: &Evidence,
+    ) -> SgxResult<RaTlsCert> {
+        let binding = report_data(public)?;
+        match evidence_report_data(evidence) {
+            Some(report_data) if report_data.d[..] == binding.d[..] => RaTlsCert::sign(private, public, evidence),
+            _ => Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER),
+        }
+    }
+
+    // Creates the certificate without checking that the evidence binds the
+    // key, which the verifier must then reject.
+    pub(crate) fn sign(
+        private: &sgx_ec256_private_t,
+        public: &sgx_ec256_public_t,
+        evidence: &Evidence,
+    ) -> SgxResult<RaTlsCert> {
+        let extensions = match evidence {
+            Evidence::Epid {
+                report,
+                signature,
+                signing_cert,
+            } => {
+                if report.is_empty() || signature.is_empty() || signing_cert.is_empty() {
+                    return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+                }
+                vec![
+                    extension(IAS_REPORT_OID, report),
+                    extension(IAS_SIGNING_CERT_OID, signing_cert),
+                    extension(IAS_SIGNATURE_OID, signature),
+                ]
+            }
+            Evidence::Dcap { quote } => {
+                if quote.is_empty() {
+                    return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+                }
+                vec![extension(QUOTE_OID, quote)]
+            }
+        };
+
+        let algorithm = der::sequence(&[&der::encode(der::OID, ECDSA_WITH_SHA256)]);
+        let name = der::sequence(&[&der::encode(
+            der::SET,
+            &der::sequence(&[
+                &der::encode(der::OID, COMMON_NAME),
+                &der::encode(der::UTF8_STRING, SUBJECT.as_bytes()),
+            ]),
+        )]);
+        let validity = der::sequence(&[
+            &der::encode(der::UTC_TIME, NOT_BEFORE.as_bytes()),
+            &der::encode(der::UTC_TIME, NOT_AFTER.as_bytes()),
+        ]);
+        let extensions = der::encode(der::CONTEXT_3, &der::encode(der::SEQUENCE, &extensions.concat()));
+        let tbs = der::sequence(&[
+            // Version 3
+            &der::encode(der::CONTEXT_0, &der::unsigned(&[2])),
+            &der::unsigned(&[1]),
+            &algorithm,
+            &name,
+            &validity,
+            &name,
+            &public_key_info(public),
+            &extensions,
+        ]);
+
+        let handle = SgxEccHandle::new();
+        handle.open()?;
+        let signature = handle.ecdsa_sign_slice_der(&tbs, private)?;
+        let cert = der::sequence(&[&tbs, &algorithm, &der::bit_string(signature.as_bytes())]);
+
+        Ok(RaTlsCert {
+            cert,
+            key: private_key_info(private, public),
+        })
+    }
+
+    pub fn cert_der(&self) -> &[u8] {
+        &self.cert
+    }
+
+    pub fn key_der(&self) -> &[u8] {
+        &self.key
+    }
+
+    pub fn certificate(&self) -> rustls::Certificate {
+        rustls::Certificate(self.cert.clone())
+    }
+
+    pub fn private_key(&self) -> rustls::PrivateKey {
+        rustls::PrivateKey(self.key.clone())
+    }
+}
+
+impl Drop for RaTlsCert {
+    fn drop(&mut self) {
+        zeroize_bytes(&mut self.key);
+    }
+}
+
+// The report data of the quote in `evidence`, which is not verified.
+fn evidence_report_data(evidence: &Evidence) -> Option<sgx_report_data_t> {
+    match evidence {
+        Evidence::Epid { report, .. } => ias_report_body(report).ok().map(|(_, body)| body.report_data),
+        Evidence::Dcap { quote } => Quote::parse(quote).ok().map(|quote| quote.report_body().report_data),
+    }
+}
+
+fn extension(oid: &[u8], value: &[u8]) -> Vec<u8> {
+    der::sequence(&[&der::encode(der::OID, oid), &der::encode(der::OCTET_STRING, value)])
+}
+
+// The AlgorithmIdentifier of P-256 keys.
+fn ec_p256() -> Vec<u8> {
+    der::sequence(&[
+        &der::encode(der::OID, EC_PUBLIC_KEY),
+        &der::encode(der::OID, PRIME256V1),
+    ])
+}
+
+fn public_key_info(public: &sgx_ec256_public_t) -> Vec<u8> {
+    der::sequence(&[&ec_p256(), &der::bit_string(&rsgx_ec256_public_to_sec1(public))])
+}
+
+fn private_key_info(private: &sgx_ec256_private_t, public: &sgx_ec256_public_t) -> Vec<u8> {
+    // The private key is little endian.
+    let mut scalar = private.r;
+    scalar.reverse();
+    let mut ec_private_key = der::encode(der::OCTET_STRING, &scalar);
+    let mut inner = der::sequence(&[
+        &der::unsigned(&[1]),
+        &ec_private_key,
+        &der::encode(der::CONTEXT_1, &der::bit_string(&rsgx_ec256_public_to_sec1(public))),
+    ]);
+    let mut octets = der::encode(der::OCTET_STRING, &inner);
+    let key = der::sequence(&[&der::unsigned(&[0]), &ec_p256(), &octets]);
+    zeroize_bytes(&mut scalar);
+    zeroize_bytes(&mut ec_private_key);
+    zeroize_bytes(&mut inner);
+    zeroize_bytes(&mut octets);
+    key
+}
+
+///
+/// The public key and the evidence of a peer certificate.
+///
+pub(crate) struct PeerCert<'a> {
+    pub public_key_info: &'a [u8],
+    pub evidence: Evidence,
+}
+
+impl<'a> PeerCert<'a> {
+    // The signature of the certificate is not checked: the key is bound by
+    // the evidence, and the TLS handshake proves its possession.
+    pub fn parse(der: &'a [u8]) -> Option<PeerCert<'a>> {
+        let mut cert = Reader::new(der).read_sequence()?;
+        let mut tbs = cert.read_sequence()?;
+        tbs.read_optional(der::CONTEXT_0)?;
+        tbs.read(der::INTEGER)?;
+        tbs.read(der::SEQUENCE)?;
+        tbs.read(der::SEQUENCE)?;
+        tbs.read(der::SEQUENCE)?;
+        tbs.read(der::SEQUENCE)?;
+        let public_key_info = tbs.read_raw(der::SEQUENCE)?;
+        tbs.read_optional(der::CONTEXT_1)?;
+        tbs.read_optional(der::CONTEXT_2)?;
+        let mut extensions = Reader::new(tbs.read(der::CONTEXT_3)?).read_sequence()?;
+
+        let mut values: Vec<(&[u8], &[u8])> = Vec::new();
+        while !extensions.is_empty() {
+            let mut extension = extensions.read_sequence()?;
+            let oid = extension.read(der::OID)?;
+            extension.read_optional(der::BOOLEAN)?;
+            values.push((oid, extension.read(der::OCTET_STRING)?));
+        }
+        let value = |oid: &[u8]| {
+            values
+                .iter()
+                .find(|(id, _)| *id == oid)
+                .map(|(_, value)| value.to_vec())
+        };
+
+        let evidence = match value(QUOTE_OID) {
+            Some(quote) => Evidence::Dcap { quote },
+            None => Evidence::Epid {
+                report: value(IAS_REPORT_OID)?,
+                signature: value(IAS_SIGNATURE_OID)?,
+                signing_cert: value(IAS_SIGNING_CERT_OID)?,
+            },
+        };
+        Some(PeerCert {
+            public_key_info,
+            evidence,
+        })
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    // The fixtures are written by testdata/gen.py: the key pair of key.bin is
+    // bound into the report data of both evidences.
+    const KEY: &[u8] = include_bytes!("../testdata/key.bin");
+
+    pub fn key_pair() -> (sgx_ec256_private_t, sgx_ec256_public_t) {
+        let mut private = sgx_ec256_private_t::default();
+        let mut public = sgx_ec256_public_t::default();
+        private.r.copy_from_slice(&KEY[..32]);
+        public.gx.copy_from_slice(&KEY[32..64]);
+        public.gy.copy_from_slice(&KEY[64..]);
+        (private, public)
+    }
+
+    pub fn other_key_pair() -> (SgxEc256PrivateSecret, sgx_ec256_public_t) {
+        let handle = SgxEccHandle::new();
+        handle.open().unwrap();
+        handle.create_key_pair().unwrap()
+    }
+
+    pub fn dcap() -> Evidence {
+        Evidence::Dcap {
+            quote: include_bytes!("../testdata/quote.bin").to_vec(),
+        }
+    }
+
+    pub fn epid() -> Evidence {
+        Evidence::Epid {
+            report: include_bytes!("../testdata/ias_report.json").to_vec(),
+            signature: include_bytes!("../testdata/ias_signature.bin").to_vec(),
+            signing_cert: include_bytes!("../testdata/ias_signing.der").to_vec(),
+        }
+    }
+
+    #[test]
+    fn cert_carries_key_and_evidence() {
+        let (private, public) = key_pair();
+        for evidence in &[dcap(), epid()] {
+            let cert = RaTlsCert::new(&private, &public, evidence).unwrap();
+            let peer = PeerCert::parse(cert.cert_der()).unwrap();
+            assert_eq!(peer.public_key_info, &public_key_info(&public)[..]);
+            assert_eq!(&peer.evidence, evidence);
+        }
+    }
+
+    #[test]
+    fn cert_is_self_signed() {
+        let (private, public) = key_pair();
+        let cert = RaTlsCert::new(&private, &public, &dcap()).unwrap();
+        assert!(webpki::EndEntityCert::from(cert.cert_der()).is_ok());
+        assert!(rustls::sign::any_ecdsa_type(&cert.private_key()).is_ok());
+
+        let mut reader = Reader::new(cert.cert_der()).read_sequence().unwrap();
+        let tbs = reader.read_raw(der::SEQUENCE).unwrap();
+        reader.read(der::SEQUENCE).unwrap();
+        let signature = reader.read(der::BIT_STRING).unwrap();
+        assert!(reader.is_empty());
+        let handle = SgxEccHandle::new();
+        handle.open().unwrap();
+        assert!(handle.ecdsa_verify_slice_der(tbs, &public, &signature[1..]).unwrap());
+    }
+
+    #[test]
+    fn new_rejects_evidence_of_another_key() {
+        let (private, public) = other_key_pair();
+        for evidence in &[dcap(), epid()] {
+            assert_eq!(
+                RaTlsCert::new(&private, &public, evidence).err(),
+                Some(sgx_status_t::SGX_ERROR_INVALID_PARAMETER)
+            );
+        }
+    }
+
+    #[test]
+    fn new_rejects_missing_evidence() {
+        let (private, public) = key_pair();
+        let mut epid = epid();
+        if let Evidence::Epid { ref mut signature, .. } = epid {
+            signature.clear();
+        }
+        for evidence in &[Evidence::Dcap { quote: Vec::new() }, epid] {
+            assert_eq!(
+                RaTlsCert::new(&private, &public, evidence).err(),
+                Some(sgx_status_t::SGX_ERROR_INVALID_PARAMETER)
+            );
+        }
+    }
+}
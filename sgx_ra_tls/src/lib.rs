@@ -0,0 +1,78 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//! # Remote Attestation TLS
+//!
+//! RA-TLS binds the remote attestation of an enclave into a TLS handshake.
+//! The enclave creates a key pair, gets a quote of a report whose report
+//! data is the hash of the public key (`report_data`), and presents a
+//! self-signed certificate of the key that carries the quote (`RaTlsCert`).
+//! The peer accepts the certificate if the quote verifies, binds the key,
+//! and describes an enclave accepted by its `EnclavePolicy`
+//! (`RaTlsVerifier`); the handshake then proves that the enclave holds the
+//! private key.
+//!
+//! EPID evidence is the attestation verification report of IAS, in the
+//! extensions 1.2.840.113741.1337.2 (report), .4 (signing certificate) and
+//! .5 (signature). DCAP evidence is the ECDSA quote, in the extension
+//! 1.2.840.113741.1337.6, and is verified with `sgx_dcap_verify`.
+//!
+//! The certificates and the verifier work with rustls, in an enclave with
+//! the `mesalock_sgx` feature and in an untrusted app.
+//!
+
+#![cfg_attr(all(feature = "mesalock_sgx", not(target_env = "sgx")), no_std)]
+#![cfg_attr(target_env = "sgx", feature(rustc_private))]
+
+#[cfg(all(feature = "mesalock_sgx", not(target_env = "sgx")))]
+#[macro_use]
+extern crate sgx_tstd as std;
+
+extern crate sgx_dcap_verify;
+extern crate sgx_enclave_policy;
+extern crate sgx_types;
+#[cfg(any(feature = "mesalock_sgx", target_env = "sgx"))]
+extern crate sgx_tcrypto as crypto;
+#[cfg(not(any(feature = "mesalock_sgx", target_env = "sgx")))]
+extern crate sgx_ucrypto as crypto;
+
+#[cfg(feature = "mesalock_sgx")]
+extern crate base64_sgx as base64;
+#[cfg(feature = "mesalock_sgx")]
+extern crate rustls_sgx as rustls;
+#[cfg(feature = "mesalock_sgx")]
+extern crate serde_json_sgx as serde_json;
+#[cfg(feature = "mesalock_sgx")]
+extern crate webpki_sgx as webpki;
+
+#[cfg(not(feature = "mesalock_sgx"))]
+extern crate base64;
+#[cfg(not(feature = "mesalock_sgx"))]
+extern crate rustls;
+#[cfg(not(feature = "mesalock_sgx"))]
+extern crate serde_json;
+#[cfg(not(feature = "mesalock_sgx"))]
+extern crate webpki;
+
+pub use sgx_enclave_policy::{EnclavePolicy, PolicyViolation};
+
+mod der;
+
+mod cert;
+pub use self::cert::*;
+mod verify;
+pub use self::verify::*;
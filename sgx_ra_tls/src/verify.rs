@@ -0,0 +1,399 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use crate::cert::{key_binding, Evidence, PeerCert};
+use sgx_dcap_verify::{verify_quote, Collateral, Quote, SgxQlResult};
+use sgx_enclave_policy::{EnclavePolicy, PolicyViolation};
+use sgx_types::*;
+use std::io::BufReader;
+use std::prelude::v1::*;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+#[cfg(any(feature = "mesalock_sgx", target_env = "sgx"))]
+use std::untrusted::time::SystemTimeEx;
+
+// The algorithms of the IAS report signing certificates.
+static IAS_SIG_ALGS: &[&webpki::SignatureAlgorithm] = &[
+    &webpki::RSA_PKCS1_2048_8192_SHA256,
+    &webpki::RSA_PKCS1_2048_8192_SHA384,
+    &webpki::RSA_PKCS1_2048_8192_SHA512,
+    &webpki::RSA_PKCS1_3072_8192_SHA384,
+];
+
+///
+/// The reason an RA-TLS certificate was rejected.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The root CA certificate given to the verifier is malformed.
+    RootCa,
+    /// The certificate is malformed, or does not carry evidence.
+    Certificate,
+    /// The verifier does not accept the kind of evidence of the certificate.
+    Unsupported,
+    /// The IAS attestation verification report is malformed, or is not signed
+    /// under the IAS root CA.
+    IasReport,
+    /// The DCAP quote or its collateral failed verification.
+    Quote(sgx_quote3_error_t),
+    /// The collateral of the DCAP quote has expired.
+    CollateralExpired,
+    /// The public key of the certificate is not bound into the report data
+    /// of the quote.
+    KeyBinding,
+    /// The enclave or its platform is not accepted by the policy.
+    Policy(Vec<PolicyViolation>),
+}
+
+///
+/// The enclave that presented a certificate accepted by a verifier.
+///
+#[derive(Copy, Clone)]
+pub struct VerifiedEnclave {
+    pub report_body: sgx_report_body_t,
+    /// The TCB status of the platform, as reported by IAS or by the
+    /// verification of the DCAP quote.
+    pub tcb_status: sgx_ql_qv_result_t,
+}
+
+type CollateralSource = dyn Fn(&Quote<'_>) -> SgxQlResult<Collateral> + Send + Sync;
+
+///
+/// Verifies RA-TLS certificates: the evidence, the binding of the key of the
+/// certificate into the quote, and the identity of the enclave against an
+/// `EnclavePolicy`.
+///
+/// A verifier accepts no evidence until EPID (`ias`) or DCAP (`dcap`)
+/// evidence is enabled. It can be installed as the rustls
+/// `ServerCertVerifier` of a client, with
+/// `ClientConfig::dangerous().set_certificate_verifier`, and as the
+/// `ClientCertVerifier` of a server.
+///
+#[derive(Clone)]
+pub struct RaTlsVerifier {
+    policy: EnclavePolicy,
+    ias_root: Option<Vec<u8>>,
+    dcap_root: Option<Vec<u8>>,
+    collateral: Option<Arc<CollateralSource>>,
+}
+
+impl RaTlsVerifier {
+    ///
+    /// Create a verifier that checks enclaves against `policy`, and accepts
+    /// no evidence until IAS or DCAP is configured.
+    ///
+    pub fn new(policy: EnclavePolicy) -> RaTlsVerifier {
+        RaTlsVerifier {
+            policy,
+            ias_root: None,
+            dcap_root: None,
+            collateral: None,
+        }
+    }
+
+    ///
+    /// Accept EPID evidence whose attestation verification report is signed
+    /// under `root_ca`, the PEM or DER encoded Attestation Report Signing CA
+    /// certificate of IAS.
+    ///
+    /// # Errors
+    ///
+    /// **Error::RootCa**
+    ///
+    /// `root_ca` is not a certificate that can be a trust anchor.
+    ///
+    pub fn ias(mut self, root_ca: &[u8]) -> Result<RaTlsVerifier, Error> {
+        self.ias_root = Some(trust_anchor_der(root_ca)?);
+        Ok(self)
+    }
+
+    ///
+    /// Accept DCAP evidence, verified by `sgx_dcap_verify` with `root_ca` as
+    /// the PEM or DER encoded Intel SGX Root CA certificate, against the
+    /// collateral returned by `collateral` for the quote.
+    ///
+    /// # Errors
+    ///
+    /// **Error::RootCa**
+    ///
+    /// `root_ca` is not a certificate that can be a trust anchor.
+    ///
+    pub fn dcap<F>(mut self, root_ca: &[u8], collateral: F) -> Result<RaTlsVerifier, Error>
+    where
+        F: Fn(&Quote<'_>) -> SgxQlResult<Collateral> + Send + Sync + 'static,
+    {
+        self.dcap_root = Some(trust_anchor_der(root_ca)?);
+        self.collateral = Some(Arc::new(collateral));
+        Ok(self)
+    }
+
+    ///
+    /// Verifies the DER encoded certificate `cert`, at `now` seconds since
+    /// the epoch.
+    ///
+    pub fn verify(&self, cert: &[u8], now: u64) -> Result<VerifiedEnclave, Error> {
+        let cert = PeerCert::parse(cert).ok_or(Error::Certificate)?;
+        let verified = match cert.evidence {
+            Evidence::Epid {
+                ref report,
+                ref signature,
+                ref signing_cert,
+            } => self.verify_ias(report, signature, signing_cert, now)?,
+            Evidence::Dcap { ref quote } => self.verify_dcap(quote, now)?,
+        };
+
+        let binding = key_binding(cert.public_key_info).map_err(|_| Error::KeyBinding)?;
+        if verified.report_body.report_data.d[..] != binding.d[..] {
+            return Err(Error::KeyBinding);
+        }
+        self.policy
+            .check_with_tcb_status(&verified.report_body, verified.tcb_status)
+            .map_err(Error::Policy)?;
+        Ok(verified)
+    }
+
+    fn verify_ias(
+        &self,
+        report: &[u8],
+        signature: &[u8],
+        signing_cert: &[u8],
+        now: u64,
+    ) -> Result<VerifiedEnclave, Error> {
+        let root = self.ias_root.as_ref().ok_or(Error::Unsupported)?;
+        let anchor = webpki::trust_anchor_util::cert_der_as_trust_anchor(root).map_err(|_| Error::IasReport)?;
+        let signer = webpki::EndEntityCert::from(signing_cert).map_err(|_| Error::IasReport)?;
+        signer
+            .verify_is_valid_tls_server_cert(
+                IAS_SIG_ALGS,
+                &webpki::TLSServerTrustAnchors(&[anchor]),
+                &[],
+                webpki::Time::from_seconds_since_unix_epoch(now),
+            )
+            .map_err(|_| Error::IasReport)?;
+        signer
+            .verify_signature(&webpki::RSA_PKCS1_2048_8192_SHA256, report, signature)
+            .map_err(|_| Error::IasReport)?;
+
+        let (tcb_status, report_body) = ias_report_body(report)?;
+        Ok(VerifiedEnclave {
+            report_body,
+            tcb_status,
+        })
+    }
+
+    fn verify_dcap(&self, quote: &[u8], now: u64) -> Result<VerifiedEnclave, Error> {
+        let (root, collateral) = match (&self.dcap_root, &self.collateral) {
+            (Some(root), Some(collateral)) => (root, collateral),
+            _ => return Err(Error::Unsupported),
+        };
+        let quote = Quote::parse(quote).map_err(Error::Quote)?;
+        let collateral = collateral(&quote).map_err(Error::Quote)?;
+        let verification = verify_quote(&quote, &collateral, root, now as i64).map_err(Error::Quote)?;
+        if verification.collateral_expired {
+            return Err(Error::CollateralExpired);
+        }
+        Ok(VerifiedEnclave {
+            report_body: verification.report_body,
+            tcb_status: verification.tcb_status.qv_result(),
+        })
+    }
+
+    fn verify_now(&self, certs: &[rustls::Certificate]) -> Result<VerifiedEnclave, rustls::TLSError> {
+        let cert = certs.first().ok_or(rustls::TLSError::NoCertificatesPresented)?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|_| rustls::TLSError::FailedToGetCurrentTime)?;
+        self.verify(&cert.0, now.as_secs())
+            .map_err(|err| rustls::TLSError::General(format!("RA-TLS certificate rejected: {:?}", err)))
+    }
+}
+
+// The DER encoding of the PEM or DER encoded root CA certificate `root_ca`,
+// checked to be usable as a trust anchor.
+fn trust_anchor_der(root_ca: &[u8]) -> Result<Vec<u8>, Error> {
+    let root = if root_ca.starts_with(b"-----BEGIN") {
+        rustls::internal::pemfile::certs(&mut BufReader::new(root_ca))
+            .ok()
+            .and_then(|certs| certs.into_iter().next())
+            .map(|cert| cert.0)
+            .ok_or(Error::RootCa)?
+    } else {
+        root_ca.to_vec()
+    };
+    webpki::trust_anchor_util::cert_der_as_trust_anchor(&root).map_err(|_| Error::RootCa)?;
+    Ok(root)
+}
+
+// The TCB status and the report body of the quote of an IAS attestation
+// verification report, whose signature is not checked here.
+pub(crate) fn ias_report_body(report: &[u8]) -> Result<(sgx_ql_qv_result_t, sgx_report_body_t), Error> {
+    let report: serde_json::Value = serde_json::from_slice(report).map_err(|_| Error::IasReport)?;
+    let status = report["isvEnclaveQuoteStatus"].as_str().ok_or(Error::IasReport)?;
+    let body = report["isvEnclaveQuoteBody"]
+        .as_str()
+        .and_then(|body| base64::decode(body).ok())
+        .ok_or(Error::IasReport)?;
+    let quote = SgxQuote::parse(&body).map_err(|_| Error::IasReport)?;
+    Ok((ias_tcb_status(status), quote.report_body().to_raw()))
+}
+
+// Maps the isvEnclaveQuoteStatus of an IAS report to the TCB status of the
+// Quote Verification Library.
+fn ias_tcb_status(status: &str) -> sgx_ql_qv_result_t {
+    match status {
+        "OK" => sgx_ql_qv_result_t::SGX_QL_QV_RESULT_OK,
+        "GROUP_OUT_OF_DATE" => sgx_ql_qv_result_t::SGX_QL_QV_RESULT_OUT_OF_DATE,
+        "CONFIGURATION_NEEDED" => sgx_ql_qv_result_t::SGX_QL_QV_RESULT_CONFIG_NEEDED,
+        "SW_HARDENING_NEEDED" => sgx_ql_qv_result_t::SGX_QL_QV_RESULT_SW_HARDENING_NEEDED,
+        "CONFIGURATION_AND_SW_HARDENING_NEEDED" => sgx_ql_qv_result_t::SGX_QL_QV_RESULT_CONFIG_AND_SW_HARDENING_NEEDED,
+        "GROUP_REVOKED" | "KEY_REVOKED" => sgx_ql_qv_result_t::SGX_QL_QV_RESULT_REVOKED,
+        "SIGNATURE_INVALID" => sgx_ql_qv_result_t::SGX_QL_QV_RESULT_INVALID_SIGNATURE,
+        _ => sgx_ql_qv_result_t::SGX_QL_QV_RESULT_UNSPECIFIED,
+    }
+}
+
+impl rustls::ServerCertVerifier for RaTlsVerifier {
+    fn verify_server_cert(
+        &self,
+        _roots: &rustls::RootCertStore,
+        presented_certs: &[rustls::Certificate],
+        _dns_name: webpki::DNSNameRef<'_>,
+        _ocsp_response: &[u8],
+    ) -> Result<rustls::ServerCertVerified, rustls::TLSError> {
+        self.verify_now(presented_certs)?;
+        Ok(rustls::ServerCertVerified::assertion())
+    }
+}
+
+impl rustls::ClientCertVerifier for RaTlsVerifier {
+    fn client_auth_root_subjects(&self, _sni: Option<&webpki::DNSName>) -> Option<rustls::DistinguishedNames> {
+        Some(rustls::DistinguishedNames::new())
+    }
+
+    fn verify_client_cert(
+        &self,
+        presented_certs: &[rustls::Certificate],
+        _sni: Option<&webpki::DNSName>,
+    ) -> Result<rustls::ClientCertVerified, rustls::TLSError> {
+        self.verify_now(presented_certs)?;
+        Ok(rustls::ClientCertVerified::assertion())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cert::tests::{dcap, epid, key_pair, other_key_pair};
+    use crate::cert::RaTlsCert;
+
+    // The fixtures are written by testdata/gen.py and valid at NOW.
+    const NOW: u64 = 1_790_812_800;
+    const ROOT: &[u8] = include_bytes!("../testdata/root.pem");
+    const IAS_ROOT: &[u8] = include_bytes!("../testdata/ias_root.pem");
+    const IAS_SIGNING: &[u8] = include_bytes!("../testdata/ias_signing.der");
+    const MR_ENCLAVE: [u8; SGX_HASH_SIZE] = [0xee; SGX_HASH_SIZE];
+
+    fn collateral() -> Collateral {
+        Collateral {
+            pck_crl_issuer_chain: include_bytes!("../testdata/pck_crl_chain.pem").to_vec(),
+            root_ca_crl: include_bytes!("../testdata/root_crl.der").to_vec(),
+            pck_crl: include_bytes!("../testdata/pck_crl.hex").to_vec(),
+            tcb_info_issuer_chain: include_bytes!("../testdata/tcb_chain.pem").to_vec(),
+            tcb_info: include_bytes!("../testdata/tcb_info.json").to_vec(),
+            qe_identity_issuer_chain: include_bytes!("../testdata/tcb_chain.pem").to_vec(),
+            qe_identity: include_bytes!("../testdata/qe_identity.json").to_vec(),
+        }
+    }
+
+    fn policy(mr_enclave: [u8; SGX_HASH_SIZE]) -> EnclavePolicy {
+        EnclavePolicy::new().mr_enclave(sgx_measurement_t { m: mr_enclave })
+    }
+
+    fn verifier(policy: EnclavePolicy) -> RaTlsVerifier {
+        RaTlsVerifier::new(policy)
+            .ias(IAS_ROOT)
+            .unwrap()
+            .dcap(ROOT, |_| Ok(collateral()))
+            .unwrap()
+    }
+
+    fn cert(evidence: &Evidence) -> RaTlsCert {
+        let (private, public) = key_pair();
+        RaTlsCert::new(&private, &public, evidence).unwrap()
+    }
+
+    #[test]
+    fn accepts_bound_evidence() {
+        for evidence in &[dcap(), epid()] {
+            let verified = verifier(policy(MR_ENCLAVE)).verify(cert(evidence).cert_der(), NOW).unwrap();
+            assert_eq!(verified.report_body.mr_enclave.m, MR_ENCLAVE);
+            assert_eq!(verified.tcb_status, sgx_ql_qv_result_t::SGX_QL_QV_RESULT_OK);
+        }
+    }
+
+    #[test]
+    fn rejects_key_binding_mismatch() {
+        let (private, public) = other_key_pair();
+        for evidence in &[dcap(), epid()] {
+            let cert = RaTlsCert::sign(&private, &public, evidence).unwrap();
+            assert_eq!(
+                verifier(policy(MR_ENCLAVE)).verify(cert.cert_der(), NOW).err(),
+                Some(Error::KeyBinding)
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_wrong_policy() {
+        for evidence in &[dcap(), epid()] {
+            assert_eq!(
+                verifier(policy([0xdd; SGX_HASH_SIZE])).verify(cert(evidence).cert_der(), NOW).err(),
+                Some(Error::Policy(vec![PolicyViolation::MrEnclave(MR_ENCLAVE)]))
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_missing_evidence() {
+        assert_eq!(
+            verifier(policy(MR_ENCLAVE)).verify(IAS_SIGNING, NOW).err(),
+            Some(Error::Certificate)
+        );
+        for evidence in &[dcap(), epid()] {
+            assert_eq!(
+                RaTlsVerifier::new(policy(MR_ENCLAVE)).verify(cert(evidence).cert_der(), NOW).err(),
+                Some(Error::Unsupported)
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_root() {
+        for root in &[&b"-----BEGIN CERTIFICATE-----\n"[..], b"", b"\x30\x00"] {
+            assert_eq!(
+                RaTlsVerifier::new(policy(MR_ENCLAVE)).ias(root).err(),
+                Some(Error::RootCa)
+            );
+            assert_eq!(
+                RaTlsVerifier::new(policy(MR_ENCLAVE))
+                    .dcap(root, |_| Ok(collateral()))
+                    .err(),
+                Some(Error::RootCa)
+            );
+        }
+    }
+}
@@ -0,0 +1,241 @@
+# Licensed to the Apache Software Foundation (ASF) under one
+# or more contributor license agreements.  See the NOTICE file
+# distributed with this work for additional information
+# regarding copyright ownership.  The ASF licenses this file
+# to you under the Apache License, Version 2.0 (the
+# "License"); you may not use this file except in compliance
+# with the License.  You may obtain a copy of the License at
+#
+#   http://www.apache.org/licenses/LICENSE-2.0
+#
+# Unless required by applicable law or agreed to in writing,
+# software distributed under the License is distributed on an
+# "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+# KIND, either express or implied.  See the License for the
+# specific language governing permissions and limitations
+# under the License.
+
+# Generates the fixtures of the tests in cert.rs and verify.rs: the P-256 key
+# of an RA-TLS certificate, and the evidence of an enclave with MRENCLAVE
+# ee..ee that binds it, as a DCAP quote with its collateral and as an IAS
+# attestation verification report. Everything is valid at
+# 2026-10-01T00:00:00Z and describes an up to date platform. Needs the python
+# `cryptography` package. The keys are random, so every run rewrites all the
+# fixtures.
+
+import base64
+import datetime
+import hashlib
+import json
+import os
+import struct
+
+from cryptography import x509
+from cryptography.hazmat.primitives import hashes, serialization
+from cryptography.hazmat.primitives.asymmetric import ec, padding, rsa, utils
+from cryptography.x509.oid import NameOID, ObjectIdentifier
+
+OUT = os.path.dirname(os.path.abspath(__file__))
+NOW = datetime.datetime(2026, 10, 1, tzinfo=datetime.timezone.utc)
+SGX = [1, 2, 840, 113741, 1, 13, 1]
+FMSPC = bytes.fromhex('00906ea10000')
+PCE_ID = bytes.fromhex('0000')
+QE_MRSIGNER = bytes(range(32))
+
+
+def days(n):
+    return NOW + datetime.timedelta(days=n)
+
+
+def write(name, data):
+    with open(os.path.join(OUT, name), 'wb') as f:
+        f.write(data)
+
+
+def key():
+    return ec.generate_private_key(ec.SECP256R1())
+
+
+def name(cn):
+    return x509.Name([x509.NameAttribute(NameOID.COMMON_NAME, cn)])
+
+
+def cert(subject, key, issuer, issuer_key, ca, serial, extension=None, valid=365):
+    builder = (x509.CertificateBuilder()
+               .subject_name(name(subject)).issuer_name(name(issuer))
+               .public_key(key.public_key()).serial_number(serial)
+               .not_valid_before(days(-1)).not_valid_after(days(valid))
+               .add_extension(x509.BasicConstraints(ca=ca, path_length=None), critical=True))
+    if extension is not None:
+        builder = builder.add_extension(extension, critical=False)
+    return builder.sign(issuer_key, hashes.SHA256())
+
+
+def pem(*certs):
+    return b''.join(c.public_bytes(serialization.Encoding.PEM) for c in certs)
+
+
+def der(cert):
+    return cert.public_bytes(serialization.Encoding.DER)
+
+
+def crl(issuer, issuer_key):
+    builder = (x509.CertificateRevocationListBuilder().issuer_name(name(issuer))
+               .last_update(days(-1)).next_update(days(20)))
+    return builder.sign(issuer_key, hashes.SHA256())
+
+
+def raw_sign(key, data):
+    r, s = utils.decode_dss_signature(key.sign(data, ec.ECDSA(hashes.SHA256())))
+    return r.to_bytes(32, 'big') + s.to_bytes(32, 'big')
+
+
+# The SGX extension of PCK certificates, encoded by hand.
+def tlv(tag, content):
+    n = len(content)
+    if n < 0x80:
+        length = bytes([n])
+    elif n < 0x100:
+        length = bytes([0x81, n])
+    else:
+        length = bytes([0x82, n >> 8, n & 0xff])
+    return bytes([tag]) + length + content
+
+
+def oid(arcs):
+    content = bytes([40 * arcs[0] + arcs[1]])
+    for arc in arcs[2:]:
+        parts = [arc & 0x7f]
+        arc >>= 7
+        while arc:
+            parts.insert(0, 0x80 | (arc & 0x7f))
+            arc >>= 7
+        content += bytes(parts)
+    return tlv(0x06, content)
+
+
+def integer(value):
+    return tlv(0x02, value.to_bytes((value.bit_length() + 8) // 8, 'big'))
+
+
+def sgx_extension(components, pce_svn):
+    tcb = b''.join(tlv(0x30, oid(SGX + [2, i + 1]) + integer(c)) for i, c in enumerate(components))
+    tcb += tlv(0x30, oid(SGX + [2, 17]) + integer(pce_svn))
+    tcb += tlv(0x30, oid(SGX + [2, 18]) + tlv(0x04, bytes(components)))
+    value = tlv(0x30, tlv(0x30, oid(SGX + [1]) + tlv(0x04, b'\x11' * 16))
+                + tlv(0x30, oid(SGX + [2]) + tlv(0x30, tcb))
+                + tlv(0x30, oid(SGX + [3]) + tlv(0x04, PCE_ID))
+                + tlv(0x30, oid(SGX + [4]) + tlv(0x04, FMSPC))
+                + tlv(0x30, oid(SGX + [5]) + tlv(0x0a, b'\x00')))
+    return x509.UnrecognizedExtension(ObjectIdentifier('.'.join(map(str, SGX))), value)
+
+
+# The signed JSON documents of the PCS.
+def signed(member, body, key):
+    text = json.dumps(body, separators=(',', ':'))
+    return ('{"%s":%s,"signature":"%s"}' % (member, text, raw_sign(key, text.encode()).hex())).encode()
+
+
+def timestamp(n):
+    return days(n).strftime('%Y-%m-%dT%H:%M:%SZ')
+
+
+def tcb_level(svn, pce_svn, status, advisory_ids):
+    tcb = {'sgxtcbcomp%02dsvn' % (i + 1): svn for i in range(16)}
+    tcb['pcesvn'] = pce_svn
+    return {'tcb': tcb, 'tcbDate': timestamp(-100), 'tcbStatus': status, 'advisoryIDs': advisory_ids}
+
+
+TCB_INFO = {
+    'version': 2, 'issueDate': timestamp(0), 'nextUpdate': timestamp(30), 'fmspc': FMSPC.hex(),
+    'pceId': PCE_ID.hex(), 'tcbType': 0, 'tcbEvaluationDataNumber': 5,
+    'tcbLevels': [tcb_level(7, 11, 'UpToDate', []),
+                  tcb_level(3, 9, 'OutOfDate', ['INTEL-SA-00161'])],
+}
+
+QE_IDENTITY = {
+    'id': 'QE', 'version': 2, 'issueDate': timestamp(0), 'nextUpdate': timestamp(25), 'tcbEvaluationDataNumber': 5,
+    'miscselect': '00000000', 'miscselectMask': 'FFFFFFFF',
+    'attributes': '11000000000000000000000000000000', 'attributesMask': 'FBFFFFFFFFFFFFFF0000000000000000',
+    'mrsigner': QE_MRSIGNER.hex().upper(), 'isvprodid': 1,
+    'tcbLevels': [{'tcb': {'isvsvn': 8}, 'tcbDate': timestamp(-10), 'tcbStatus': 'UpToDate'}],
+}
+
+
+def report_body(misc_select, flags, xfrm, mr_enclave, mr_signer, isv_prod_id, isv_svn, report_data):
+    body = bytes(16) + struct.pack('<I', misc_select) + bytes(28) + struct.pack('<QQ', flags, xfrm)
+    body += mr_enclave + bytes(32) + mr_signer + bytes(96)
+    body += struct.pack('<HH', isv_prod_id, isv_svn) + bytes(60) + report_data
+    assert len(body) == 384
+    return body
+
+
+# The RA-TLS key, as sgx_ec256_private_t followed by sgx_ec256_public_t:
+# little endian scalar and coordinates.
+ra_tls_key = key()
+numbers = ra_tls_key.private_numbers()
+write('key.bin', numbers.private_value.to_bytes(32, 'little')
+      + numbers.public_numbers.x.to_bytes(32, 'little') + numbers.public_numbers.y.to_bytes(32, 'little'))
+public_key_info = ra_tls_key.public_key().public_bytes(
+    serialization.Encoding.DER, serialization.PublicFormat.SubjectPublicKeyInfo)
+REPORT = report_body(0, 0x5, 0x3, b'\xee' * 32, b'\x55' * 32, 3, 4,
+                     hashlib.sha256(public_key_info).digest() + bytes(32))
+
+
+# DCAP: a version 3 ECDSA quote and its collateral.
+def quote(chain, pck_key):
+    attest_key = key()
+    attest_public = attest_key.public_key().public_bytes(
+        serialization.Encoding.X962, serialization.PublicFormat.UncompressedPoint)[1:]
+    auth_data = bytes(range(32))
+    header = struct.pack('<HHIHH', 3, 2, 0, 6, 10) + bytes.fromhex('939a7233f79c4ca9940a0db3957f0607') + bytes(20)
+    qe_report = report_body(0, 0x15, 0xe7, b'\x01' * 32, QE_MRSIGNER, 1, 8,
+                            hashlib.sha256(attest_public + auth_data).digest() + bytes(32))
+    certification_data = pem(*chain) + b'\0'
+    signature = raw_sign(attest_key, header + REPORT) + attest_public + qe_report + raw_sign(pck_key, qe_report)
+    signature += struct.pack('<H', len(auth_data)) + auth_data
+    signature += struct.pack('<HI', 5, len(certification_data)) + certification_data
+    return header + REPORT + struct.pack('<I', len(signature)) + signature
+
+
+root_key, pck_ca_key, pck_key, tcb_key = key(), key(), key(), key()
+root = cert('Intel SGX Root CA', root_key, 'Intel SGX Root CA', root_key, True, 1, valid=3650)
+pck_ca = cert('Intel SGX PCK Processor CA', pck_ca_key, 'Intel SGX Root CA', root_key, True, 2)
+pck = cert('Intel SGX PCK Certificate', pck_key, 'Intel SGX PCK Processor CA', pck_ca_key, False, 77,
+           sgx_extension([7] * 16, 11))
+tcb_signing = cert('Intel SGX TCB Signing', tcb_key, 'Intel SGX Root CA', root_key, False, 3)
+
+write('root.pem', pem(root))
+write('root_crl.der', der(crl('Intel SGX Root CA', root_key)))
+write('pck_crl_chain.pem', pem(pck_ca, root) + b'\0')
+write('pck_crl.hex', der(crl('Intel SGX PCK Processor CA', pck_ca_key)).hex().encode())
+write('tcb_chain.pem', pem(tcb_signing, root) + b'\0')
+write('tcb_info.json', signed('tcbInfo', TCB_INFO, tcb_key))
+write('qe_identity.json', signed('enclaveIdentity', QE_IDENTITY, tcb_key))
+write('quote.bin', quote([pck, pck_ca, root], pck_key))
+
+
+# EPID: a report of IAS on the EPID quote body of the same enclave.
+def rsa_cert(subject, key, issuer, issuer_key, ca, serial):
+    builder = (x509.CertificateBuilder()
+               .subject_name(name(subject)).issuer_name(name(issuer))
+               .public_key(key.public_key()).serial_number(serial)
+               .not_valid_before(days(-1)).not_valid_after(days(365))
+               .add_extension(x509.BasicConstraints(ca=ca, path_length=None), critical=True))
+    return builder.sign(issuer_key, hashes.SHA256())
+
+
+ias_root_key = rsa.generate_private_key(65537, 3072)
+ias_signing_key = rsa.generate_private_key(65537, 2048)
+ias_root = rsa_cert('Intel SGX Attestation Report Signing CA', ias_root_key,
+                    'Intel SGX Attestation Report Signing CA', ias_root_key, True, 9)
+ias_signing = rsa_cert('Intel SGX Attestation Report Signing', ias_signing_key,
+                       'Intel SGX Attestation Report Signing CA', ias_root_key, False, 10)
+quote_body = struct.pack('<HHIHHI', 2, 1, 0, 11, 10, 0) + bytes(32) + REPORT
+ias_report = json.dumps({'id': '1', 'timestamp': '2026-10-01T00:00:00.000000', 'version': 4,
+                         'isvEnclaveQuoteStatus': 'OK',
+                         'isvEnclaveQuoteBody': base64.b64encode(quote_body).decode()}).encode()
+write('ias_root.pem', pem(ias_root))
+write('ias_signing.der', der(ias_signing))
+write('ias_report.json', ias_report)
+write('ias_signature.bin', ias_signing_key.sign(ias_report, padding.PKCS1v15(), hashes.SHA256()))
@@ -1206,7 +1206,20 @@ impl<'a, T: 'a + ?Sized + DeSerializable> DeSerializeHelper<'a, T> {
     /// ```
     ///
     pub fn decode(&self) -> Option<T> {
-        let mut decoder = DataDecoder::new(&self.data[..], 0);
+        Self::decode_from(&self.data[..])
+    }
+
+    /// Deserialize the type T from a borrowed buffer returned by SerializeHelper::encode,
+    /// without taking a copy of it. Use it to decode secrets the caller zeroizes afterwards.
+    ///
+    /// ```
+    /// let helper = SerializeHelper::new();
+    /// let data = helper.encode(a).unwrap();
+    /// let c = DeSerializeHelper::<TestEnum>::decode_from(&data).unwrap();
+    /// ```
+    ///
+    pub fn decode_from(data: &[u8]) -> Option<T> {
+        let mut decoder = DataDecoder::new(data, 0);
         match DeSerializable::decode(&mut decoder) {
             Result::Err(_) => Option::None,
             Result::Ok(d) => Option::Some(d),
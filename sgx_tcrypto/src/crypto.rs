@@ -18,10 +18,11 @@
 //!
 //! Cryptographic Functions
 //!
-use core::cell::{Cell, RefCell};
+use core::cell::{Cell, Ref, RefCell};
 use core::mem;
 use core::ops::{DerefMut, Drop};
 use core::ptr;
+use sgx_crypto_primitive::primitive::rsa::{RsaPrivateKey, RsaPublicKey};
 use sgx_types::marker::ContiguousMemory;
 use sgx_types::*;
 
@@ -2240,10 +2241,10 @@ impl SgxEccHandle {
     ///
     /// # Return value
     ///
-    /// **sgx_ec256_private_t**
+    /// **SgxEc256PrivateSecret**
     ///
     /// The private key which is a number that lies in the range of [1, n-1] where n is the order
-    /// of the elliptic curve base point.
+    /// of the elliptic curve base point. It is overwritten with zeros when it is dropped.
     ///
     /// **sgx_ec256_public_t**
     ///
@@ -2269,14 +2270,14 @@ impl SgxEccHandle {
     ///
     /// The key creation process failed due to an internal cryptography library failure.
     ///
-    pub fn create_key_pair(&self) -> SgxResult<(sgx_ec256_private_t, sgx_ec256_public_t)> {
+    pub fn create_key_pair(&self) -> SgxResult<(SgxEc256PrivateSecret, sgx_ec256_public_t)> {
         if !self.initflag.get() {
             return Err(sgx_status_t::SGX_ERROR_INVALID_STATE);
         }
 
-        let mut private = sgx_ec256_private_t::default();
+        let mut private = SgxEc256PrivateSecret::zeroed();
         let mut public = sgx_ec256_public_t::default();
-        let ret = rsgx_ecc256_create_key_pair(&mut private, &mut public, *self.handle.borrow());
+        let ret = rsgx_ecc256_create_key_pair(private.expose_mut(), &mut public, *self.handle.borrow());
 
         match ret {
             sgx_status_t::SGX_SUCCESS => Ok((private, public)),
@@ -2284,14 +2285,19 @@ impl SgxEccHandle {
         }
     }
 
+    ///
+    /// create_align_key_pair is create_key_pair with the private key returned
+    /// in a 64-byte aligned buffer. It is overwritten with zeros when it is
+    /// dropped.
+    ///
     pub fn create_align_key_pair(
         &self,
-    ) -> SgxResult<(sgx_align_ec256_private_t, sgx_ec256_public_t)> {
+    ) -> SgxResult<(SgxAlignEc256PrivateSecret, sgx_ec256_public_t)> {
         if !self.initflag.get() {
             return Err(sgx_status_t::SGX_ERROR_INVALID_STATE);
         }
 
-        let mut private = sgx_align_ec256_private_t::default();
+        let mut private = SgxAlignEc256PrivateSecret::zeroed();
         let mut public = sgx_ec256_public_t::default();
         let ret = rsgx_ecc256_create_key_pair(&mut private.key, &mut public, *self.handle.borrow());
 
@@ -2438,6 +2444,7 @@ impl SgxEccHandle {
     /// # Return value
     ///
     /// The secret key generated by this function which is a common point on the elliptic curve.
+    /// It is overwritten with zeros when it is dropped.
     ///
     /// # Errors
     ///
@@ -2461,16 +2468,16 @@ impl SgxEccHandle {
         &self,
         private_b: &sgx_ec256_private_t,
         public_ga: &sgx_ec256_public_t,
-    ) -> SgxResult<sgx_ec256_dh_shared_t> {
+    ) -> SgxResult<SgxEc256SharedSecret> {
         if !self.initflag.get() {
             return Err(sgx_status_t::SGX_ERROR_INVALID_STATE);
         }
 
-        let mut shared_key = sgx_ec256_dh_shared_t::default();
+        let mut shared_key = SgxEc256SharedSecret::zeroed();
         let ret = rsgx_ecc256_compute_shared_dhkey(
             private_b,
             public_ga,
-            &mut shared_key,
+            shared_key.expose_mut(),
             *self.handle.borrow(),
         );
         match ret {
@@ -2479,16 +2486,21 @@ impl SgxEccHandle {
         }
     }
 
+    ///
+    /// compute_align_shared_dhkey is compute_shared_dhkey with the shared key
+    /// returned in a 64-byte aligned buffer. It is overwritten with zeros when
+    /// it is dropped.
+    ///
     pub fn compute_align_shared_dhkey(
         &self,
         private_b: &sgx_ec256_private_t,
         public_ga: &sgx_ec256_public_t,
-    ) -> SgxResult<sgx_align_ec256_dh_shared_t> {
+    ) -> SgxResult<SgxAlignEc256SharedSecret> {
         if !self.initflag.get() {
             return Err(sgx_status_t::SGX_ERROR_INVALID_STATE);
         }
 
-        let mut shared_key = sgx_align_ec256_dh_shared_t::default();
+        let mut shared_key = SgxAlignEc256SharedSecret::zeroed();
         let ret = rsgx_ecc256_compute_shared_dhkey(
             private_b,
             public_ga,
@@ -3293,6 +3305,7 @@ pub struct SgxRsaPrivKey {
     mod_size: Cell<i32>,
     exp_size: Cell<i32>,
     createflag: Cell<bool>,
+    material: RefCell<Option<RsaPrivateKey>>,
 }
 
 impl SgxRsaPrivKey {
@@ -3302,7 +3315,18 @@ impl SgxRsaPrivKey {
             mod_size: Cell::new(0),
             exp_size: Cell::new(0),
             createflag: Cell::new(false),
+            material: RefCell::new(None),
+        }
+    }
+
+    ///
+    /// The key components kept for the operations implemented in Rust.
+    ///
+    pub(crate) fn material(&self) -> SgxResult<Ref<'_, Option<RsaPrivateKey>>> {
+        if !self.createflag.get() {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_STATE);
         }
+        Ok(self.material.borrow())
     }
 
     #[inline]
@@ -3348,6 +3372,7 @@ impl SgxRsaPrivKey {
         );
         match ret {
             sgx_status_t::SGX_SUCCESS => {
+                *self.material.borrow_mut() = RsaPrivateKey::from_crt(e, p, q, dmp1, dmq1, iqmp);
                 self.mod_size.set(mod_size);
                 self.exp_size.set(exp_size);
                 self.createflag.set(true);
@@ -3381,6 +3406,7 @@ impl SgxRsaPrivKey {
         );
         match ret {
             sgx_status_t::SGX_SUCCESS => {
+                *self.material.borrow_mut() = RsaPrivateKey::from_exponent(n, e, d);
                 self.mod_size.set(mod_size);
                 self.exp_size.set(exp_size);
                 self.createflag.set(true);
@@ -3430,6 +3456,7 @@ impl SgxRsaPrivKey {
             sgx_status_t::SGX_SUCCESS => {
                 self.createflag.set(false);
                 *self.key.borrow_mut() = ptr::null_mut();
+                *self.material.borrow_mut() = None;
                 Ok(())
             }
             _ => Err(ret),
@@ -3454,6 +3481,7 @@ pub struct SgxRsaPubKey {
     mod_size: Cell<i32>,
     exp_size: Cell<i32>,
     createflag: Cell<bool>,
+    material: RefCell<Option<RsaPublicKey>>,
 }
 
 impl SgxRsaPubKey {
@@ -3463,7 +3491,18 @@ impl SgxRsaPubKey {
             mod_size: Cell::new(0),
             exp_size: Cell::new(0),
             createflag: Cell::new(false),
+            material: RefCell::new(None),
+        }
+    }
+
+    ///
+    /// The key components kept for the operations implemented in Rust.
+    ///
+    pub(crate) fn material(&self) -> SgxResult<Ref<'_, Option<RsaPublicKey>>> {
+        if !self.createflag.get() {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_STATE);
         }
+        Ok(self.material.borrow())
     }
 
     pub fn create(&self, mod_size: i32, exp_size: i32, n: &[u8], e: &[u8]) -> SgxError {
@@ -3475,6 +3514,7 @@ impl SgxRsaPubKey {
             rsgx_create_rsa_pub1_key(mod_size, exp_size, n, e, self.key.borrow_mut().deref_mut());
         match ret {
             sgx_status_t::SGX_SUCCESS => {
+                *self.material.borrow_mut() = RsaPublicKey::new(n, e);
                 self.mod_size.set(mod_size);
                 self.exp_size.set(exp_size);
                 self.createflag.set(true);
@@ -3524,6 +3564,7 @@ impl SgxRsaPubKey {
             sgx_status_t::SGX_SUCCESS => {
                 self.createflag.set(false);
                 *self.key.borrow_mut() = ptr::null_mut();
+                *self.material.borrow_mut() = None;
                 Ok(())
             }
             _ => Err(ret),
@@ -0,0 +1,145 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//!
+//! ECDSA sign/verify entry points on DER encoded and r || s signatures
+//!
+//! See sgx_crypto_primitive for the conversions between these encodings and
+//! the little endian sgx_ec256_signature_t used by libsgx_tcrypto.a.
+//!
+
+use crate::crypto::SgxEccHandle;
+use sgx_crypto_primitive::ecc::{
+    rsgx_ec256_signature_from_der, rsgx_ec256_signature_from_raw, rsgx_ec256_signature_to_der,
+    rsgx_ec256_signature_to_raw, SgxEc256DerSignature,
+};
+use sgx_types::marker::ContiguousMemory;
+use sgx_types::*;
+
+impl SgxEccHandle {
+    ///
+    /// ecdsa_sign_msg_der computes an ECDSA signature over an input dataset and
+    /// returns it DER encoded. See ecdsa_sign_msg.
+    ///
+    pub fn ecdsa_sign_msg_der<T>(&self, data: &T, private: &sgx_ec256_private_t) -> SgxResult<SgxEc256DerSignature>
+    where
+        T: Copy + ContiguousMemory,
+    {
+        self.ecdsa_sign_msg(data, private)
+            .map(|signature| rsgx_ec256_signature_to_der(&signature))
+    }
+
+    ///
+    /// ecdsa_sign_slice_der computes an ECDSA signature over an input dataset
+    /// and returns it DER encoded. See ecdsa_sign_slice.
+    ///
+    pub fn ecdsa_sign_slice_der<T>(&self, data: &[T], private: &sgx_ec256_private_t) -> SgxResult<SgxEc256DerSignature>
+    where
+        T: Copy + ContiguousMemory,
+    {
+        self.ecdsa_sign_slice(data, private)
+            .map(|signature| rsgx_ec256_signature_to_der(&signature))
+    }
+
+    ///
+    /// ecdsa_sign_msg_raw computes an ECDSA signature over an input dataset and
+    /// returns it as r || s in big endian order. See ecdsa_sign_msg.
+    ///
+    pub fn ecdsa_sign_msg_raw<T>(&self, data: &T, private: &sgx_ec256_private_t) -> SgxResult<sgx_ec256_raw_signature_t>
+    where
+        T: Copy + ContiguousMemory,
+    {
+        self.ecdsa_sign_msg(data, private)
+            .map(|signature| rsgx_ec256_signature_to_raw(&signature))
+    }
+
+    ///
+    /// ecdsa_sign_slice_raw computes an ECDSA signature over an input dataset
+    /// and returns it as r || s in big endian order. See ecdsa_sign_slice.
+    ///
+    pub fn ecdsa_sign_slice_raw<T>(&self, data: &[T], private: &sgx_ec256_private_t) -> SgxResult<sgx_ec256_raw_signature_t>
+    where
+        T: Copy + ContiguousMemory,
+    {
+        self.ecdsa_sign_slice(data, private)
+            .map(|signature| rsgx_ec256_signature_to_raw(&signature))
+    }
+
+    ///
+    /// ecdsa_verify_msg_der verifies a DER encoded ECDSA signature over an input
+    /// dataset. A signature that is not valid DER is reported as not valid.
+    /// See ecdsa_verify_msg.
+    ///
+    pub fn ecdsa_verify_msg_der<T>(&self, data: &T, public: &sgx_ec256_public_t, der: &[u8]) -> SgxResult<bool>
+    where
+        T: Copy + ContiguousMemory,
+    {
+        match rsgx_ec256_signature_from_der(der) {
+            Ok(signature) => self.ecdsa_verify_msg(data, public, &signature),
+            Err(_) => Ok(false),
+        }
+    }
+
+    ///
+    /// ecdsa_verify_slice_der verifies a DER encoded ECDSA signature over an
+    /// input dataset. A signature that is not valid DER is reported as not
+    /// valid. See ecdsa_verify_slice.
+    ///
+    pub fn ecdsa_verify_slice_der<T>(&self, data: &[T], public: &sgx_ec256_public_t, der: &[u8]) -> SgxResult<bool>
+    where
+        T: Copy + ContiguousMemory,
+    {
+        match rsgx_ec256_signature_from_der(der) {
+            Ok(signature) => self.ecdsa_verify_slice(data, public, &signature),
+            Err(_) => Ok(false),
+        }
+    }
+
+    ///
+    /// ecdsa_verify_msg_raw verifies an ECDSA signature encoded as r || s over an
+    /// input dataset. See ecdsa_verify_msg.
+    ///
+    pub fn ecdsa_verify_msg_raw<T>(
+        &self,
+        data: &T,
+        public: &sgx_ec256_public_t,
+        raw: &sgx_ec256_raw_signature_t,
+    ) -> SgxResult<bool>
+    where
+        T: Copy + ContiguousMemory,
+    {
+        let signature = rsgx_ec256_signature_from_raw(raw)?;
+        self.ecdsa_verify_msg(data, public, &signature)
+    }
+
+    ///
+    /// ecdsa_verify_slice_raw verifies an ECDSA signature encoded as r || s over
+    /// an input dataset. See ecdsa_verify_slice.
+    ///
+    pub fn ecdsa_verify_slice_raw<T>(
+        &self,
+        data: &[T],
+        public: &sgx_ec256_public_t,
+        raw: &sgx_ec256_raw_signature_t,
+    ) -> SgxResult<bool>
+    where
+        T: Copy + ContiguousMemory,
+    {
+        let signature = rsgx_ec256_signature_from_raw(raw)?;
+        self.ecdsa_verify_slice(data, public, &signature)
+    }
+}
@@ -27,7 +27,15 @@
 #![allow(non_snake_case)]
 #![allow(clippy::too_many_arguments)]
 
+extern crate sgx_crypto_primitive;
 extern crate sgx_types;
 
 mod crypto;
 pub use self::crypto::*;
+pub use sgx_crypto_primitive::aead::*;
+pub use sgx_crypto_primitive::curve25519::*;
+pub use sgx_crypto_primitive::ecc::*;
+pub use sgx_crypto_primitive::kdf::*;
+pub use sgx_crypto_primitive::sha512::*;
+mod ecc;
+mod rsa;
@@ -26,7 +26,7 @@ use alloc::vec::Vec;
 use core::mem;
 use core::ptr;
 use sgx_tcrypto::*;
-use sgx_trts::memeq::ConsttimeMemEq;
+use sgx_trts::ct::ConstantTimeEq;
 use sgx_trts::trts::*;
 use sgx_tse::*;
 use sgx_types::marker::ContiguousMemory;
@@ -285,13 +285,13 @@ impl SgxDhResponder {
         }
 
         if self.state != SgxDhSessionState::SGX_DH_SESSION_STATE_RESET {
-            *self = Self::default();
+            self.reset();
             return Err(sgx_status_t::SGX_ERROR_INVALID_STATE);
         }
 
         let error = self.dh_generate_message1(msg1);
         if let Err(mut ret) = error {
-            *self = Self::default();
+            self.reset();
             if ret != sgx_status_t::SGX_ERROR_OUT_OF_MEMORY {
                 ret = sgx_status_t::SGX_ERROR_UNEXPECTED;
             }
@@ -321,7 +321,8 @@ impl SgxDhResponder {
     ///
     /// **aek**
     ///
-    /// A pointer that points to instance of sgx_key_128bit_t. The aek is derived as follows:
+    /// A pointer that points to instance of SgxKey128Secret, which clears the key when it is dropped.
+    /// The aek is derived as follows:
     ///
     /// ```
     /// KDK := CMAC(key0, LittleEndian(gab x-coordinate))
@@ -372,21 +373,21 @@ impl SgxDhResponder {
         &mut self,
         msg2: &SgxDhMsg2,
         msg3: &mut SgxDhMsg3,
-        aek: &mut sgx_key_128bit_t,
+        aek: &mut SgxKey128Secret,
         initiator_identity: &mut sgx_dh_session_enclave_identity_t,
     ) -> SgxError {
         if !rsgx_data_is_within_enclave(self) {
             return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
         }
         if !rsgx_data_is_within_enclave(msg2)
-            || !rsgx_data_is_within_enclave(aek)
+            || !rsgx_data_is_within_enclave(aek.expose())
             || !rsgx_data_is_within_enclave(initiator_identity)
             || !rsgx_raw_is_within_enclave(
                 msg3 as *const _ as *const u8,
                 mem::size_of::<SgxDhMsg3>(),
             )
         {
-            *self = Self::default();
+            self.reset();
             self.state = SgxDhSessionState::SGX_DH_SESSION_STATE_ERROR;
             return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
         }
@@ -395,25 +396,27 @@ impl SgxDhResponder {
                 || (msg3.msg3_body.additional_prop.len()
                     > (u32::MAX as usize) - mem::size_of::<sgx_dh_msg3_t>()))
         {
-            *self = Self::default();
+            self.reset();
             self.state = SgxDhSessionState::SGX_DH_SESSION_STATE_ERROR;
             return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
         }
 
         if self.state != SgxDhSessionState::SGX_DH_SESSION_RESPONDER_WAIT_M2 {
-            *self = Self::default();
+            self.reset();
             self.state = SgxDhSessionState::SGX_DH_SESSION_STATE_ERROR;
             return Err(sgx_status_t::SGX_ERROR_INVALID_STATE);
         }
 
         let ecc_state = SgxEccHandle::new();
         ecc_state.open().map_err(|ret| self.set_error(ret))?;
-        self.shared_key = ecc_state
+        let shared_key = ecc_state
             .compute_align_shared_dhkey(&self.prv_key.key, &msg2.g_b)
             .map_err(|ret| self.set_error(ret))?;
+        self.shared_key.key = shared_key.key;
 
-        self.smk_aek =
+        let smk_aek =
             derive_key(&self.shared_key.key, &EC_SMK_LABEL).map_err(|ret| self.set_error(ret))?;
+        self.smk_aek.key = smk_aek.key;
 
         #[cfg(feature = "use_lav2")]
         self.lav2_verify_message2(msg2)
@@ -437,8 +440,8 @@ impl SgxDhResponder {
 
         let align_aek =
             derive_key(&self.shared_key.key, &EC_AEK_LABEL).map_err(|ret| self.set_error(ret))?;
-        *aek = align_aek.key;
-        *self = Self::default();
+        *aek.expose_mut() = align_aek.key;
+        self.reset();
         self.state = SgxDhSessionState::SGX_DH_SESSION_ACTIVE;
 
         Ok(())
@@ -458,7 +461,7 @@ impl SgxDhResponder {
         ecc_state.open()?;
         let (prv_key, pub_key) = ecc_state.create_align_key_pair()?;
 
-        self.prv_key = prv_key;
+        self.prv_key.key = prv_key.key;
         self.pub_key = pub_key;
         msg1.g_a = pub_key;
         msg1.target = target;
@@ -477,7 +480,7 @@ impl SgxDhResponder {
 
         let report = msg2.report;
         let data_mac = rsgx_rijndael128_cmac_msg(&self.smk_aek.key, &report)?;
-        if !data_mac.consttime_memeq(&msg2.cmac) {
+        if data_mac.ct_ne(&msg2.cmac) {
             return Err(sgx_status_t::SGX_ERROR_MAC_MISMATCH);
         }
 
@@ -509,7 +512,7 @@ impl SgxDhResponder {
 
         rsgx_verify_report(&report)?;
         let data_mac = rsgx_rijndael128_cmac_msg(&self.smk_aek.key, &msg2.g_b)?;
-        if !data_mac.consttime_memeq(&msg2.cmac) {
+        if data_mac.ct_ne(&msg2.cmac) {
             return Err(sgx_status_t::SGX_ERROR_MAC_MISMATCH);
         }
 
@@ -586,8 +589,15 @@ impl SgxDhResponder {
         Ok(())
     }
 
-    fn set_error(&mut self, sgx_ret: sgx_status_t) -> sgx_status_t {
+    fn reset(&mut self) {
+        zeroize_bytes(&mut self.prv_key.key.r);
+        zeroize_bytes(&mut self.smk_aek.key);
+        zeroize_bytes(&mut self.shared_key.key.s);
         *self = Self::default();
+    }
+
+    fn set_error(&mut self, sgx_ret: sgx_status_t) -> sgx_status_t {
+        self.reset();
         self.state = SgxDhSessionState::SGX_DH_SESSION_STATE_ERROR;
         match sgx_ret {
             sgx_status_t::SGX_ERROR_OUT_OF_MEMORY => sgx_status_t::SGX_ERROR_OUT_OF_MEMORY,
@@ -678,30 +688,31 @@ impl SgxDhInitiator {
             return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
         }
         if !rsgx_data_is_within_enclave(msg1) || !rsgx_data_is_within_enclave(msg2) {
-            *self = Self::default();
+            self.reset();
             self.state = SgxDhSessionState::SGX_DH_SESSION_STATE_ERROR;
             return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
         }
 
         if self.state != SgxDhSessionState::SGX_DH_SESSION_INITIATOR_WAIT_M1 {
-            *self = Self::default();
+            self.reset();
             self.state = SgxDhSessionState::SGX_DH_SESSION_STATE_ERROR;
             return Err(sgx_status_t::SGX_ERROR_INVALID_STATE);
         }
 
         let ecc_state = SgxEccHandle::new();
         ecc_state.open().map_err(|ret| self.set_error(ret))?;
-        let (mut prv_key, pub_key) = ecc_state
+        let (prv_key, pub_key) = ecc_state
             .create_align_key_pair()
             .map_err(|ret| self.set_error(ret))?;
-        self.shared_key = ecc_state
+        let shared_key = ecc_state
             .compute_align_shared_dhkey(&prv_key.key, &msg1.g_a)
             .map_err(|ret| self.set_error(ret))?;
+        self.shared_key.key = shared_key.key;
 
-        prv_key = sgx_align_ec256_private_t::default();
         self.pub_key = pub_key;
-        self.smk_aek =
+        let smk_aek =
             derive_key(&self.shared_key.key, &EC_SMK_LABEL).map_err(|ret| self.set_error(ret))?;
+        self.smk_aek.key = smk_aek.key;
 
         #[cfg(feature = "use_lav2")]
         self.lav2_generate_message2(msg1, msg2)
@@ -732,7 +743,8 @@ impl SgxDhInitiator {
     ///
     /// **aek**
     ///
-    /// A pointer that points to instance of sgx_key_128bit_t. The aek is derived as follows:
+    /// A pointer that points to instance of SgxKey128Secret, which clears the key when it is dropped.
+    /// The aek is derived as follows:
     ///
     /// ```
     /// KDK:= CMAC(key0, LittleEndian(gab x-coordinate))
@@ -778,17 +790,17 @@ impl SgxDhInitiator {
     pub fn proc_msg3(
         &mut self,
         msg3: &SgxDhMsg3,
-        aek: &mut sgx_key_128bit_t,
+        aek: &mut SgxKey128Secret,
         responder_identity: &mut sgx_dh_session_enclave_identity_t,
     ) -> SgxError {
         if !rsgx_data_is_within_enclave(self) {
             return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
         }
         if !rsgx_raw_is_within_enclave(msg3 as *const _ as *const u8, mem::size_of::<SgxDhMsg3>())
-            || !rsgx_data_is_within_enclave(aek)
+            || !rsgx_data_is_within_enclave(aek.expose())
             || !rsgx_data_is_within_enclave(responder_identity)
         {
-            *self = Self::default();
+            self.reset();
             self.state = SgxDhSessionState::SGX_DH_SESSION_STATE_ERROR;
             return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
         }
@@ -797,13 +809,13 @@ impl SgxDhInitiator {
                 || (msg3.msg3_body.additional_prop.len()
                     > (u32::MAX as usize) - mem::size_of::<sgx_dh_msg3_t>()))
         {
-            *self = Self::default();
+            self.reset();
             self.state = SgxDhSessionState::SGX_DH_SESSION_STATE_ERROR;
             return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
         }
 
         if self.state != SgxDhSessionState::SGX_DH_SESSION_INITIATOR_WAIT_M3 {
-            *self = Self::default();
+            self.reset();
             self.state = SgxDhSessionState::SGX_DH_SESSION_STATE_ERROR;
             return Err(sgx_status_t::SGX_ERROR_INVALID_STATE);
         }
@@ -815,9 +827,9 @@ impl SgxDhInitiator {
 
         let align_aek =
             derive_key(&self.shared_key.key, &EC_AEK_LABEL).map_err(|ret| self.set_error(ret))?;
-        *aek = align_aek.key;
+        *aek.expose_mut() = align_aek.key;
 
-        *self = Self::default();
+        self.reset();
         self.state = SgxDhSessionState::SGX_DH_SESSION_ACTIVE;
 
         responder_identity.cpu_svn = msg3.msg3_body.report.body.cpu_svn;
@@ -892,7 +904,7 @@ impl SgxDhInitiator {
         }
         let data_mac = cmac_handle.get_hash()?;
 
-        if !data_mac.consttime_memeq(&msg3.cmac) {
+        if data_mac.ct_ne(&msg3.cmac) {
             return Err(sgx_status_t::SGX_ERROR_MAC_MISMATCH);
         }
 
@@ -937,15 +949,21 @@ impl SgxDhInitiator {
         cmac_handle.update_msg(&self.peer_pub_key)?;
         let data_mac = cmac_handle.get_hash()?;
 
-        if !data_mac.consttime_memeq(&msg3.cmac) {
+        if data_mac.ct_ne(&msg3.cmac) {
             return Err(sgx_status_t::SGX_ERROR_MAC_MISMATCH);
         }
 
         Ok(())
     }
 
-    fn set_error(&mut self, sgx_ret: sgx_status_t) -> sgx_status_t {
+    fn reset(&mut self) {
+        zeroize_bytes(&mut self.smk_aek.key);
+        zeroize_bytes(&mut self.shared_key.key.s);
         *self = Self::default();
+    }
+
+    fn set_error(&mut self, sgx_ret: sgx_status_t) -> sgx_status_t {
+        self.reset();
         self.state = SgxDhSessionState::SGX_DH_SESSION_STATE_ERROR;
         match sgx_ret {
             sgx_status_t::SGX_ERROR_OUT_OF_MEMORY => sgx_status_t::SGX_ERROR_OUT_OF_MEMORY,
@@ -27,7 +27,7 @@ pub const EC_DERIVATION_BUFFER_SIZE: usize = 7;
 pub fn derive_key(
     shared_key: &sgx_ec256_dh_shared_t,
     label: &[u8; EC_LABEL_LENGTH],
-) -> SgxResult<sgx_align_key_128bit_t> {
+) -> SgxResult<SgxAlignKey128Secret> {
     let cmac_key = sgx_cmac_128bit_key_t::default();
     let mut key_derive_key = rsgx_rijndael128_cmac_msg(&cmac_key, shared_key).map_err(set_error)?;
 
@@ -42,13 +42,14 @@ pub fn derive_key(
     derivation_buffer[6] = 0x00;
 
     let result = rsgx_rijndael128_align_cmac_slice(&key_derive_key, &derivation_buffer)
-        .map(|align_mac| {
-            let mut align_key = sgx_align_key_128bit_t::default();
+        .map(|mut align_mac| {
+            let mut align_key = SgxAlignKey128Secret::zeroed();
             align_key.key = align_mac.mac;
+            zeroize_bytes(&mut align_mac.mac);
             align_key
         })
         .map_err(set_error);
-    key_derive_key = Default::default();
+    zeroize_bytes(&mut key_derive_key);
     result
 }
 
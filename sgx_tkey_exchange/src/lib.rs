@@ -20,6 +20,8 @@
 //! The library allow an ISV to exchange secrets between its server and its enclaves. They are used in
 //! concert with untrusted Key Exchange functions.
 //!
+//! `RaSession` owns a key exchange context and closes it when dropped.
+//!
 
 #![no_std]
 #![cfg_attr(target_env = "sgx", feature(rustc_private))]
@@ -27,6 +29,9 @@
 extern crate sgx_types;
 use sgx_types::*;
 
+mod session;
+pub use self::session::*;
+
 ///
 /// The rsgx_ra_init function creates a context for the remote attestation and key exchange process.
 ///
@@ -182,7 +187,7 @@ pub fn rsgx_ra_init_ex(
 ///
 /// **keytype**
 ///
-/// The type of the keys, which can be SGX_RA_KEY_MK, SGX_RA_KEY_SK, or SGX_RA_VK.
+/// The type of the keys, which can be SGX_RA_KEY_MK or SGX_RA_KEY_SK.
 ///
 /// # Requirements
 ///
@@ -192,7 +197,7 @@ pub fn rsgx_ra_init_ex(
 ///
 /// # Return value
 ///
-/// The key returned.
+/// The key returned, overwritten with zeros when it is dropped.
 ///
 /// # Errors
 ///
@@ -208,9 +213,9 @@ pub fn rsgx_ra_init_ex(
 pub fn rsgx_ra_get_keys(
     context: sgx_ra_context_t,
     keytype: sgx_ra_key_type_t,
-) -> SgxResult<sgx_ra_key_128_t> {
-    let mut key = sgx_ra_key_128_t::default();
-    let ret = unsafe { sgx_ra_get_keys(context, keytype, &mut key as *mut sgx_ra_key_128_t) };
+) -> SgxResult<SgxKey128Secret> {
+    let mut key = SgxKey128Secret::zeroed();
+    let ret = unsafe { sgx_ra_get_keys(context, keytype, key.expose_mut() as *mut sgx_ra_key_128_t) };
     match ret {
         sgx_status_t::SGX_SUCCESS => Ok(key),
         _ => Err(ret),
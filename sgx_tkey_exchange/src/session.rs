@@ -0,0 +1,116 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use crate::{rsgx_ra_close, rsgx_ra_get_keys, rsgx_ra_init, rsgx_ra_init_ex};
+use core::mem;
+use sgx_types::*;
+
+///
+/// The keys negotiated by a remote attestation and key exchange session.
+///
+/// The VK key is not part of them: the key exchange only uses it to bind
+/// msg3 to the quote, and `rsgx_ra_get_keys` does not return it.
+///
+/// Each key is overwritten with zeros when it is dropped.
+///
+pub struct RaKeys {
+    /// The shared key, to protect the secrets provisioned by the service provider.
+    pub sk: SgxKey128Secret,
+    /// The MAC key, to authenticate the messages of the service provider.
+    pub mk: SgxKey128Secret,
+}
+
+///
+/// A remote attestation and key exchange context, closed when dropped.
+///
+/// The session is created in the enclave, and its context is passed to the
+/// untrusted `sgx_ra_get_msg1` and `sgx_ra_proc_msg2` functions (see
+/// `sgx_urts::ra`), which call back into the enclave to process msg2 and
+/// generate msg3. Once the service provider has accepted msg3, the keys can
+/// be retrieved from the session.
+///
+pub struct RaSession {
+    context: sgx_ra_context_t,
+}
+
+impl RaSession {
+    ///
+    /// Creates a session with the service provider whose EC public key is
+    /// `sp_pub_key`. See `rsgx_ra_init`.
+    ///
+    pub fn new(sp_pub_key: &sgx_ec256_public_t) -> SgxResult<RaSession> {
+        rsgx_ra_init(sp_pub_key, 0).map(|context| RaSession { context })
+    }
+
+    ///
+    /// Creates a session whose keys are derived by `derive_key_cb` from the
+    /// shared secret. See `rsgx_ra_init_ex`.
+    ///
+    pub fn new_ex(
+        sp_pub_key: &sgx_ec256_public_t,
+        derive_key_cb: sgx_ra_derive_secret_keys_t,
+    ) -> SgxResult<RaSession> {
+        rsgx_ra_init_ex(sp_pub_key, 0, derive_key_cb).map(|context| RaSession { context })
+    }
+
+    ///
+    /// The context to hand to the untrusted key exchange functions. It stays
+    /// valid as long as the session is alive.
+    ///
+    pub fn context(&self) -> sgx_ra_context_t {
+        self.context
+    }
+
+    ///
+    /// Returns one of the negotiated keys. See `rsgx_ra_get_keys`.
+    ///
+    pub fn key(&self, keytype: sgx_ra_key_type_t) -> SgxResult<SgxKey128Secret> {
+        rsgx_ra_get_keys(self.context, keytype)
+    }
+
+    ///
+    /// Returns the SK and MK keys of the session.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_STATE**
+    ///
+    /// msg2 has not been processed successfully yet.
+    ///
+    pub fn keys(&self) -> SgxResult<RaKeys> {
+        Ok(RaKeys {
+            sk: self.key(sgx_ra_key_type_t::SGX_RA_KEY_SK)?,
+            mk: self.key(sgx_ra_key_type_t::SGX_RA_KEY_MK)?,
+        })
+    }
+
+    ///
+    /// Closes the session and reports the error of `rsgx_ra_close`, which
+    /// dropping the session ignores.
+    ///
+    pub fn close(self) -> SgxError {
+        let context = self.context;
+        mem::forget(self);
+        rsgx_ra_close(context)
+    }
+}
+
+impl Drop for RaSession {
+    fn drop(&mut self) {
+        let _ = rsgx_ra_close(self.context);
+    }
+}
@@ -389,6 +389,34 @@ impl SgxFileStream {
         unsafe { rsgx_fseek(self.stream, offset, whence) }
     }
 
+    ///
+    /// The len function returns the size of the plaintext content of the file.
+    ///
+    /// # Description
+    ///
+    /// len moves the position indicator to the end of the file to find the size of its
+    /// content, then restores the position indicator. The size does not include the meta
+    /// data and MACs stored in the file on the disk.
+    ///
+    /// # Requirements
+    ///
+    /// Header: sgx_tprotected_fs.edl
+    ///
+    /// Library: libsgx_tprotected_fs.a
+    ///
+    /// # Return value
+    ///
+    /// If the function succeeds, it returns the size of the file content in bytes,
+    /// otherwise, error code is returned.
+    ///
+    pub fn len(&self) -> SysResult<u64> {
+        let pos = self.tell()?;
+        self.seek(0, SeekFrom::End)?;
+        let len = self.tell();
+        self.seek(pos, SeekFrom::Start)?;
+        len.map(|len| len as u64)
+    }
+
     ///
     /// The flush function forces a cache flush, and if it returns successfully, it is guaranteed
     /// that your changes are committed to a file on the disk.
@@ -0,0 +1,136 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//! Constant time comparison, selection and copy.
+//!
+//! The running time of these functions depends only on the length of their
+//! inputs, never on their contents or on the value of a `choice` flag. Use
+//! them wherever a secret value, such as a MAC tag, a hash or a key, is
+//! compared or chosen, so that the outcome does not leak through timing.
+//!
+//! Lengths are treated as public: comparing slices of different lengths
+//! returns `false` immediately.
+
+use core::ptr;
+
+/// Keeps the optimizer from turning a mask back into a branch.
+#[inline(never)]
+fn black_box(value: u8) -> u8 {
+    unsafe { ptr::read_volatile(&value) }
+}
+
+/// Turns `choice` into an all-ones (true) or all-zeros (false) byte.
+#[inline]
+fn mask(choice: bool) -> u8 {
+    0_u8.wrapping_sub(black_box(choice as u8))
+}
+
+///
+/// Compares two byte slices in constant time.
+///
+/// Returns `true` when both slices have the same length and contents.
+///
+pub fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    sgx_types::ct_eq_bytes(a, b)
+}
+
+///
+/// Selects one of two values in constant time.
+///
+/// Returns `a` when `choice` is `true`, otherwise `b`.
+///
+pub fn ct_select<T: ConstantTimeSelect>(choice: bool, a: T, b: T) -> T {
+    T::ct_select(choice, a, b)
+}
+
+///
+/// Copies `src` into `dst` when `choice` is `true`, leaving `dst` unchanged
+/// otherwise. Both cases touch every byte of `dst`.
+///
+/// # Panics
+///
+/// This function panics if the two slices have different lengths.
+///
+pub fn ct_copy_if(choice: bool, dst: &mut [u8], src: &[u8]) {
+    assert_eq!(dst.len(), src.len(), "ct_copy_if: slices have different lengths");
+    let m = mask(choice);
+    for (d, s) in dst.iter_mut().zip(src.iter()) {
+        *d ^= m & (*d ^ *s);
+    }
+}
+
+/// Integer types that can be chosen between in constant time.
+pub trait ConstantTimeSelect: Copy {
+    fn ct_select(choice: bool, a: Self, b: Self) -> Self;
+}
+
+macro_rules! impl_ct_select {
+    ($($t:ty)*) => {$(
+        impl ConstantTimeSelect for $t {
+            #[inline]
+            fn ct_select(choice: bool, a: $t, b: $t) -> $t {
+                let m = (0 as $t).wrapping_sub(black_box(choice as u8) as $t);
+                b ^ (m & (a ^ b))
+            }
+        }
+    )*}
+}
+
+impl_ct_select! { u8 i8 u16 i16 u32 i32 u64 i64 usize isize }
+
+///
+/// Equality that takes the same time whatever the contents of the operands.
+///
+/// It is implemented for byte slices and for the fixed size byte arrays
+/// behind the MAC tag and hash types in `sgx_types`, such as
+/// `sgx_cmac_128bit_tag_t`, `sgx_aes_gcm_128bit_tag_t`, `sgx_mac_t`,
+/// `sgx_sha1_hash_t`, `sgx_sha256_hash_t`, `sgx_hmac_256bit_tag_t`,
+/// `sgx_sha384_hash_t` and `sgx_sha512_hash_t`.
+///
+pub trait ConstantTimeEq<Rhs: ?Sized = Self> {
+    fn ct_eq(&self, other: &Rhs) -> bool;
+    fn ct_ne(&self, other: &Rhs) -> bool {
+        !self.ct_eq(other)
+    }
+}
+
+impl ConstantTimeEq for [u8] {
+    #[inline]
+    fn ct_eq(&self, other: &[u8]) -> bool {
+        ct_eq(self, other)
+    }
+}
+
+macro_rules! impl_ct_eq_array {
+    ($($n:expr)*) => {$(
+        impl ConstantTimeEq for [u8; $n] {
+            #[inline]
+            fn ct_eq(&self, other: &[u8; $n]) -> bool {
+                ct_eq(&self[..], &other[..])
+            }
+        }
+
+        impl ConstantTimeEq<[u8]> for [u8; $n] {
+            #[inline]
+            fn ct_eq(&self, other: &[u8]) -> bool {
+                ct_eq(&self[..], other)
+            }
+        }
+    )*}
+}
+
+impl_ct_eq_array! { 16 20 32 48 64 }
@@ -90,6 +90,7 @@ pub mod ascii;
 pub mod c_str;
 pub mod cpu_feature;
 pub mod cpuid;
+pub mod ct;
 pub mod enclave;
 pub mod memchr;
 pub mod memeq;
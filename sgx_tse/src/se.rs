@@ -162,7 +162,7 @@ pub fn rsgx_verify_report(report: &sgx_report_t) -> SgxError {
 ///
 /// # Return value
 ///
-/// Cryptographic key
+/// Cryptographic key, overwritten with zeros when it is dropped.
 ///
 /// # Errors
 ///
@@ -194,12 +194,12 @@ pub fn rsgx_verify_report(report: &sgx_report_t) -> SgxError {
 ///
 /// Indicates an unexpected error occurs during the key generation process.
 ///
-pub fn rsgx_get_key(key_request: &sgx_key_request_t) -> SgxResult<sgx_key_128bit_t> {
-    let mut key = sgx_key_128bit_t::default();
+pub fn rsgx_get_key(key_request: &sgx_key_request_t) -> SgxResult<SgxKey128Secret> {
+    let mut key = SgxKey128Secret::zeroed();
     let ret = unsafe {
         sgx_get_key(
             key_request as *const sgx_key_request_t,
-            &mut key as *mut sgx_key_128bit_t,
+            key.expose_mut() as *mut sgx_key_128bit_t,
         )
     };
     match ret {
@@ -208,12 +208,15 @@ pub fn rsgx_get_key(key_request: &sgx_key_request_t) -> SgxResult<sgx_key_128bit
     }
 }
 
-pub fn rsgx_get_align_key(key_request: &sgx_key_request_t) -> SgxResult<sgx_align_key_128bit_t> {
-    let mut align_key = sgx_align_key_128bit_t::default();
+///
+/// rsgx_get_align_key is rsgx_get_key, with the key returned in a 32-byte aligned buffer.
+///
+pub fn rsgx_get_align_key(key_request: &sgx_key_request_t) -> SgxResult<SgxAlignKey128Secret> {
+    let mut align_key = SgxAlignKey128Secret::zeroed();
     let ret = unsafe {
         sgx_get_key(
             key_request as *const sgx_key_request_t,
-            &mut align_key.key as *mut sgx_key_128bit_t,
+            &mut align_key.expose_mut().key as *mut sgx_key_128bit_t,
         )
     };
     match ret {
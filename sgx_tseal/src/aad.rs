@@ -21,6 +21,7 @@
 use crate::internal::*;
 use alloc::boxed::Box;
 use alloc::slice;
+use alloc::vec::Vec;
 use core::marker::PhantomData;
 use core::mem;
 use sgx_types::marker::ContiguousMemory;
@@ -479,4 +480,34 @@ impl<'a, T: 'a + ?Sized> SgxMacAadata<'a, T> {
     pub fn get_add_mac_txt_len(&self) -> u32 {
         self.inner.get_add_mac_txt_len()
     }
+
+    ///
+    /// Serialize SgxMacAadata into a byte vector which can be written to a file or
+    /// sent over the network.
+    ///
+    /// The layout is the same as the one of `SgxSealedData::to_bytes`.
+    ///
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.inner.to_bytes()
+    }
+
+    ///
+    /// Parse bytes produced by `to_bytes` into SgxMacAadata.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_PARAMETER**
+    ///
+    /// The header is malformed, or the lengths in it do not match the buffer.
+    ///
+    /// **SGX_ERROR_INVALID_VERSION**
+    ///
+    /// The bytes were written by an unsupported format version.
+    ///
+    pub fn from_bytes(bytes: &[u8]) -> SgxResult<Self> {
+        SgxInternalSealedData::from_bytes(bytes).map(|x| SgxMacAadata {
+            inner: x,
+            marker: PhantomData,
+        })
+    }
 }
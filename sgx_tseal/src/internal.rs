@@ -19,11 +19,17 @@ use alloc::boxed::Box;
 use alloc::vec::Vec;
 use core::mem;
 use core::ptr;
+use core::slice;
 use sgx_tcrypto::*;
+use sgx_trts::ct::ConstantTimeEq;
 use sgx_trts::trts::*;
 use sgx_tse::*;
 use sgx_types::*;
 
+const SEALED_BLOB_MAGIC: [u8; 4] = *b"SGXS";
+const SEALED_BLOB_VERSION: u32 = 1;
+const SEALED_BLOB_HEADER_SIZE: usize = 16;
+
 /* intel sgx sdk 2.4 */
 const KEY_POLICY_KSS: uint16_t =
     SGX_KEYPOLICY_CONFIGID | SGX_KEYPOLICY_ISVFAMILYID | SGX_KEYPOLICY_ISVEXTPRODID;
@@ -188,6 +194,72 @@ impl SgxInternalSealedData {
         Some(p)
     }
 
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let encrypt_len = self.payload_data.encrypt.len();
+        let additional_len = self.payload_data.additional.len();
+        let body_len = mem::size_of::<sgx_sealed_data_t>() + encrypt_len + additional_len;
+
+        let mut raw_sealed_data = sgx_sealed_data_t::default();
+        raw_sealed_data.key_request = self.key_request;
+        raw_sealed_data.plain_text_offset = encrypt_len as u32;
+        raw_sealed_data.aes_data.payload_size = self.payload_data.payload_size;
+        raw_sealed_data.aes_data.payload_tag = self.payload_data.payload_tag;
+        let raw_bytes = unsafe {
+            slice::from_raw_parts(
+                &raw_sealed_data as *const sgx_sealed_data_t as *const u8,
+                mem::size_of::<sgx_sealed_data_t>(),
+            )
+        };
+
+        let mut bytes = Vec::with_capacity(SEALED_BLOB_HEADER_SIZE + body_len);
+        bytes.extend_from_slice(&SEALED_BLOB_MAGIC);
+        bytes.extend_from_slice(&SEALED_BLOB_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&(body_len as u32).to_le_bytes());
+        bytes.extend_from_slice(&[0_u8; 4]);
+        bytes.extend_from_slice(raw_bytes);
+        bytes.extend_from_slice(&self.payload_data.encrypt);
+        bytes.extend_from_slice(&self.payload_data.additional);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> SgxResult<Self> {
+        if bytes.len() < SEALED_BLOB_HEADER_SIZE || bytes[0..4] != SEALED_BLOB_MAGIC {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+        let version = u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+        if version != SEALED_BLOB_VERSION {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_VERSION);
+        }
+        let body_len = u32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]) as usize;
+        if bytes[12..16] != [0_u8; 4] {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+
+        let body = &bytes[SEALED_BLOB_HEADER_SIZE..];
+        let header_len = mem::size_of::<sgx_sealed_data_t>();
+        if body.len() != body_len || body_len < header_len {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+
+        let raw_sealed_data =
+            unsafe { ptr::read_unaligned(body.as_ptr() as *const sgx_sealed_data_t) };
+        let payload_size = raw_sealed_data.aes_data.payload_size as usize;
+        let encrypt_len = raw_sealed_data.plain_text_offset as usize;
+        if payload_size != body_len - header_len || encrypt_len > payload_size {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+
+        let payload = &body[header_len..];
+        let mut sealed_data = Self::default();
+        sealed_data.key_request = raw_sealed_data.key_request;
+        sealed_data.payload_data.payload_size = raw_sealed_data.aes_data.payload_size;
+        sealed_data.payload_data.payload_tag = raw_sealed_data.aes_data.payload_tag;
+        sealed_data.payload_data.encrypt = payload[..encrypt_len].to_vec().into_boxed_slice();
+        sealed_data.payload_data.additional = payload[encrypt_len..].to_vec().into_boxed_slice();
+
+        Ok(sealed_data)
+    }
+
     #[allow(clippy::cast_ptr_alignment)]
     pub unsafe fn from_raw_sealed_data_t(p: *mut sgx_sealed_data_t, len: u32) -> Option<Self> {
         if p.is_null() {
@@ -265,7 +337,7 @@ impl SgxInternalSealedData {
         Some(sealed_data)
     }
 
-    pub fn seal_data(additional_text: &[u8], encrypt_text: &[u8]) -> SgxResult<Self> {
+    pub fn default_key_policy() -> (u16, sgx_attributes_t, sgx_misc_select_t) {
         //let attribute_mask = sgx_attributes_t{flags: SGX_FLAGS_RESERVED | SGX_FLAGS_INITTED | SGX_FLAGS_DEBUG, xfrm: 0};
         /* intel sgx sdk 1.8 */
         let attribute_mask = sgx_attributes_t {
@@ -279,39 +351,25 @@ impl SgxInternalSealedData {
             key_policy = SGX_KEYPOLICY_MRSIGNER | KEY_POLICY_KSS;
         }
 
+        (key_policy, attribute_mask, TSEAL_DEFAULT_MISCMASK)
+    }
+
+    pub fn seal_data(additional_text: &[u8], encrypt_text: &[u8]) -> SgxResult<Self> {
+        let (key_policy, attribute_mask, misc_mask) = Self::default_key_policy();
         Self::seal_data_ex(
             key_policy,
             attribute_mask,
-            TSEAL_DEFAULT_MISCMASK,
+            misc_mask,
             additional_text,
             encrypt_text,
         )
     }
 
-    pub fn seal_data_ex(
+    pub fn new_key_request(
         key_policy: u16,
         attribute_mask: sgx_attributes_t,
         misc_mask: sgx_misc_select_t,
-        additional_text: &[u8],
-        encrypt_text: &[u8],
-    ) -> SgxResult<Self> {
-        let additional_len = additional_text.len();
-        let encrypt_len = encrypt_text.len();
-
-        if (additional_len >= u32::MAX as usize)
-            || (encrypt_len >= u32::MAX as usize)
-        {
-            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
-        }
-        if Self::calc_raw_sealed_data_size(additional_len as u32, encrypt_len as u32)
-            == u32::MAX
-        {
-            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
-        }
-        if encrypt_len == 0 {
-            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
-        }
-
+    ) -> SgxResult<sgx_key_request_t> {
         if (key_policy
             & (!(SGX_KEYPOLICY_MRENCLAVE
                 | SGX_KEYPOLICY_MRSIGNER
@@ -328,17 +386,6 @@ impl SgxInternalSealedData {
             return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
         }
 
-        if !rsgx_slice_is_within_enclave(encrypt_text) {
-            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
-        }
-
-        if additional_len > 0
-            && !rsgx_slice_is_within_enclave(additional_text)
-            && !rsgx_slice_is_outside_enclave(additional_text)
-        {
-            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
-        }
-
         //let target_info = sgx_target_info_t::default();
         //let report_data = sgx_report_data_t::default();
         let mut key_id = sgx_key_id_t::default();
@@ -366,17 +413,64 @@ impl SgxInternalSealedData {
             reserved2: [0_u8; SGX_KEY_REQUEST_RESERVED2_BYTES],
         };
 
+        report = sgx_report_t::default();
+        key_id = sgx_key_id_t::default();
+
+        Ok(key_request)
+    }
+
+    pub fn seal_data_ex(
+        key_policy: u16,
+        attribute_mask: sgx_attributes_t,
+        misc_mask: sgx_misc_select_t,
+        additional_text: &[u8],
+        encrypt_text: &[u8],
+    ) -> SgxResult<Self> {
+        let key_request = Self::new_key_request(key_policy, attribute_mask, misc_mask)?;
+        Self::seal_data_with_key_request(&key_request, additional_text, encrypt_text)
+    }
+
+    pub fn seal_data_with_key_request(
+        key_request: &sgx_key_request_t,
+        additional_text: &[u8],
+        encrypt_text: &[u8],
+    ) -> SgxResult<Self> {
+        let additional_len = additional_text.len();
+        let encrypt_len = encrypt_text.len();
+
+        if (additional_len >= u32::MAX as usize)
+            || (encrypt_len >= u32::MAX as usize)
+        {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+        if Self::calc_raw_sealed_data_size(additional_len as u32, encrypt_len as u32)
+            == u32::MAX
+        {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+        if encrypt_len == 0 {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+
+        if !rsgx_slice_is_within_enclave(encrypt_text) {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+
+        if additional_len > 0
+            && !rsgx_slice_is_within_enclave(additional_text)
+            && !rsgx_slice_is_outside_enclave(additional_text)
+        {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+
         let payload_iv = [0_u8; SGX_SEAL_IV_SIZE];
         let mut result =
-            Self::seal_data_iv(additional_text, encrypt_text, &payload_iv, &key_request);
+            Self::seal_data_iv(additional_text, encrypt_text, &payload_iv, key_request);
 
         if let Ok(ref mut sealed_data) = result {
-            sealed_data.key_request = key_request
+            sealed_data.key_request = *key_request
         };
 
-        report = sgx_report_t::default();
-        key_id = sgx_key_id_t::default();
-
         result
     }
 
@@ -409,6 +503,17 @@ impl SgxInternalSealedData {
         self.unseal_data_helper()
     }
 
+    pub fn reseal(&self, key_request: &sgx_key_request_t) -> SgxResult<Self> {
+        let mut unsealed_data = self.unseal_data()?;
+        let result = Self::seal_data_with_key_request(
+            key_request,
+            unsealed_data.get_additional_txt(),
+            unsealed_data.get_decrypt_txt(),
+        );
+        zeroize_bytes(&mut unsealed_data.decrypt);
+        result
+    }
+
     pub fn mac_aadata(additional_text: &[u8]) -> SgxResult<Self> {
         let attribute_mask = sgx_attributes_t {
             flags: TSEAL_DEFAULT_FLAGSMASK,
@@ -534,7 +639,20 @@ impl SgxInternalSealedData {
             return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
         }
 
-        self.unseal_data_helper()
+        self.unmac_data_helper()
+    }
+
+    pub fn get_unseal_key(key_request: &sgx_key_request_t) -> SgxResult<SgxAlignKey128Secret> {
+        rsgx_get_align_key(key_request).map_err(|ret| {
+            if (ret == sgx_status_t::SGX_ERROR_INVALID_CPUSVN)
+                || (ret == sgx_status_t::SGX_ERROR_INVALID_ISVSVN)
+                || (ret == sgx_status_t::SGX_ERROR_OUT_OF_MEMORY)
+            {
+                ret
+            } else {
+                sgx_status_t::SGX_ERROR_MAC_MISMATCH
+            }
+        })
     }
 
     fn seal_data_iv(
@@ -543,7 +661,7 @@ impl SgxInternalSealedData {
         payload_iv: &[u8],
         key_request: &sgx_key_request_t,
     ) -> SgxResult<Self> {
-        let mut seal_key = rsgx_get_align_key(key_request).map_err(|ret| {
+        let seal_key = rsgx_get_align_key(key_request).map_err(|ret| {
             if ret != sgx_status_t::SGX_ERROR_OUT_OF_MEMORY {
                 sgx_status_t::SGX_ERROR_UNEXPECTED
             } else {
@@ -563,7 +681,6 @@ impl SgxInternalSealedData {
             &mut sealed_data.payload_data.payload_tag,
         );
         if error.is_err() {
-            seal_key.key = sgx_key_128bit_t::default();
             return Err(error.unwrap_err());
         }
 
@@ -572,22 +689,11 @@ impl SgxInternalSealedData {
             sealed_data.payload_data.additional = additional_text.to_vec().into_boxed_slice();
         }
 
-        seal_key.key = sgx_key_128bit_t::default();
-
         Ok(sealed_data)
     }
 
     fn unseal_data_helper(&self) -> SgxResult<SgxInternalUnsealedData> {
-        let mut seal_key = rsgx_get_align_key(self.get_key_request()).map_err(|ret| {
-            if (ret == sgx_status_t::SGX_ERROR_INVALID_CPUSVN)
-                || (ret == sgx_status_t::SGX_ERROR_INVALID_ISVSVN)
-                || (ret == sgx_status_t::SGX_ERROR_OUT_OF_MEMORY)
-            {
-                ret
-            } else {
-                sgx_status_t::SGX_ERROR_MAC_MISMATCH
-            }
-        })?;
+        let seal_key = Self::get_unseal_key(self.get_key_request())?;
 
         //
         // code that calls sgx_unseal_data commonly does some sanity checks
@@ -610,7 +716,6 @@ impl SgxInternalSealedData {
             &mut unsealed_data.decrypt,
         );
         if error.is_err() {
-            seal_key.key = sgx_key_128bit_t::default();
             return Err(error.unwrap_err());
         }
 
@@ -619,8 +724,41 @@ impl SgxInternalSealedData {
         }
         unsealed_data.payload_size = self.get_payload_size();
 
-        seal_key.key = sgx_key_128bit_t::default();
+        Ok(unsealed_data)
+    }
+
+    fn unmac_data_helper(&self) -> SgxResult<SgxInternalUnsealedData> {
+        let seal_key = Self::get_unseal_key(self.get_key_request())?;
+
+        rsgx_lfence();
+
+        //
+        // There is no encrypted text, so the tag is a GMAC over the additional
+        // text. Recompute it and compare in constant time.
+        //
+        let payload_iv = [0_u8; SGX_SEAL_IV_SIZE];
+        let mut payload_tag = sgx_aes_gcm_128bit_tag_t::default();
+        let error = rsgx_rijndael128GCM_encrypt(
+            &seal_key.key,
+            &[],
+            &payload_iv,
+            self.get_additional_txt(),
+            &mut [],
+            &mut payload_tag,
+        );
+        drop(seal_key);
+        if error.is_err() {
+            return Err(sgx_status_t::SGX_ERROR_MAC_MISMATCH);
+        }
+        if payload_tag.ct_ne(self.get_payload_tag()) {
+            return Err(sgx_status_t::SGX_ERROR_MAC_MISMATCH);
+        }
 
+        let unsealed_data = SgxInternalUnsealedData {
+            payload_size: self.get_payload_size(),
+            decrypt: Box::default(),
+            additional: self.get_additional_txt().to_vec().into_boxed_slice(),
+        };
         Ok(unsealed_data)
     }
 }
@@ -76,6 +76,11 @@
 #![allow(non_camel_case_types)]
 #![allow(unused_assignments)]
 
+#[cfg(all(not(target_env = "sgx"), feature = "std"))]
+extern crate sgx_tstd as std;
+#[cfg(all(target_env = "sgx", feature = "std"))]
+extern crate std;
+
 #[macro_use]
 extern crate alloc;
 
@@ -83,6 +88,8 @@ extern crate sgx_tcrypto;
 extern crate sgx_trts;
 extern crate sgx_tse;
 extern crate sgx_types;
+#[cfg(feature = "serialize")]
+extern crate sgx_serialize;
 
 mod seal;
 pub use self::seal::{SgxSealedData, SgxUnsealedData};
@@ -90,4 +97,26 @@ pub use self::seal::{SgxSealedData, SgxUnsealedData};
 mod aad;
 pub use self::aad::SgxMacAadata;
 
+mod policy;
+pub use self::policy::{SealIdentity, SealPolicy};
+
+mod rollback;
+pub use self::rollback::{SgxMemoryCounter, SgxRollbackGuard};
+pub use sgx_types::{MonotonicCounter, MonotonicCounterId};
+#[cfg(feature = "std")]
+pub use self::rollback::SgxFileCounter;
+
+#[cfg(feature = "serialize")]
+mod object;
+#[cfg(feature = "serialize")]
+pub use self::object::SgxSealedObject;
+
+#[cfg(feature = "std")]
+mod stream;
+#[cfg(feature = "std")]
+pub use self::stream::{
+    SgxSealWriter, SgxUnsealReader, SGX_SEAL_STREAM_DEFAULT_CHUNK_SIZE,
+    SGX_SEAL_STREAM_MAX_CHUNK_SIZE,
+};
+
 mod internal;
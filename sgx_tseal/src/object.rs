@@ -0,0 +1,184 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use crate::internal::*;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use sgx_serialize::{DeSerializable, DeSerializeHelper, Serializable, SerializeHelper};
+use sgx_types::*;
+
+/// Sealed data holding a value encoded with sgx_serialize.
+///
+/// Unlike `SgxSealedData`, the sealed type does not need to be `Copy` or
+/// `ContiguousMemory`: any type implementing `Serializable` can be sealed, and
+/// unsealing decodes it back with `DeSerializable`. Collections such as
+/// `HashMap` or `Vec<String>` and nested structures are supported.
+pub struct SgxSealedObject<T> {
+    inner: SgxInternalSealedData,
+    marker: PhantomData<T>,
+}
+
+impl<T> Default for SgxSealedObject<T> {
+    fn default() -> SgxSealedObject<T> {
+        SgxSealedObject {
+            inner: SgxInternalSealedData::new(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Clone for SgxSealedObject<T> {
+    fn clone(&self) -> SgxSealedObject<T> {
+        SgxSealedObject {
+            inner: self.inner.clone(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T: Serializable> SgxSealedObject<T> {
+    ///
+    /// Encode `value` and seal it with the default key policy of `SgxSealedData::seal_data`.
+    ///
+    /// # Parameters
+    ///
+    /// **additional_text**
+    ///
+    /// Data that is not encrypted but is covered by the MAC, for example a
+    /// description or version of the sealed value. It may be empty.
+    ///
+    /// **value**
+    ///
+    /// The value to seal.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_PARAMETER**
+    ///
+    /// The value could not be encoded, or it encodes to no bytes at all.
+    ///
+    /// Other errors are the ones of `SgxSealedData::seal_data`.
+    ///
+    pub fn seal(additional_text: &[u8], value: &T) -> SgxResult<Self> {
+        let mut encoded = encode(value)?;
+        let result = SgxInternalSealedData::seal_data(additional_text, &encoded);
+        zeroize_bytes(&mut encoded);
+        result.map(|x| SgxSealedObject {
+            inner: x,
+            marker: PhantomData,
+        })
+    }
+
+    ///
+    /// Encode `value` and seal it with an explicit key policy, attribute mask
+    /// and misc mask, as `SgxSealedData::seal_data_ex` does.
+    ///
+    pub fn seal_ex(
+        key_policy: u16,
+        attribute_mask: sgx_attributes_t,
+        misc_mask: sgx_misc_select_t,
+        additional_text: &[u8],
+        value: &T,
+    ) -> SgxResult<Self> {
+        let mut encoded = encode(value)?;
+        let result = SgxInternalSealedData::seal_data_ex(
+            key_policy,
+            attribute_mask,
+            misc_mask,
+            additional_text,
+            &encoded,
+        );
+        zeroize_bytes(&mut encoded);
+        result.map(|x| SgxSealedObject {
+            inner: x,
+            marker: PhantomData,
+        })
+    }
+}
+
+impl<T: DeSerializable> SgxSealedObject<T> {
+    ///
+    /// Unseal and decode the value.
+    ///
+    /// The additional text returned by `get_additional_txt` is authenticated
+    /// once this function succeeds.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_PARAMETER**
+    ///
+    /// The unsealed bytes are not a valid encoding of T.
+    ///
+    /// Other errors are the ones of `SgxSealedData::unseal_data`.
+    ///
+    pub fn unseal(&self) -> SgxResult<T> {
+        let mut unsealed_data = self.inner.unseal_data()?;
+        let value = DeSerializeHelper::<T>::decode_from(&unsealed_data.decrypt);
+        zeroize_bytes(&mut unsealed_data.decrypt);
+        value.ok_or(sgx_status_t::SGX_ERROR_INVALID_PARAMETER)
+    }
+}
+
+impl<T> SgxSealedObject<T> {
+    ///
+    /// Get the size of payload in SgxSealedObject.
+    ///
+    pub fn get_payload_size(&self) -> u32 {
+        self.inner.get_payload_size()
+    }
+
+    ///
+    /// Get the pointer of sgx_key_request_t in SgxSealedObject.
+    ///
+    pub fn get_key_request(&self) -> &sgx_key_request_t {
+        self.inner.get_key_request()
+    }
+
+    ///
+    /// Get a slice of additional text in SgxSealedObject.
+    ///
+    pub fn get_additional_txt(&self) -> &[u8] {
+        self.inner.get_additional_txt()
+    }
+
+    ///
+    /// Serialize SgxSealedObject into a byte vector, in the layout of
+    /// `SgxSealedData::to_bytes`.
+    ///
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.inner.to_bytes()
+    }
+
+    ///
+    /// Parse bytes produced by `to_bytes` into SgxSealedObject.
+    ///
+    /// The errors are the ones of `SgxSealedData::from_bytes`.
+    ///
+    pub fn from_bytes(bytes: &[u8]) -> SgxResult<Self> {
+        SgxInternalSealedData::from_bytes(bytes).map(|x| SgxSealedObject {
+            inner: x,
+            marker: PhantomData,
+        })
+    }
+}
+
+fn encode<T: Serializable>(value: &T) -> SgxResult<Vec<u8>> {
+    match SerializeHelper::new().encode(value) {
+        Some(encoded) if !encoded.is_empty() => Ok(encoded),
+        _ => Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER),
+    }
+}
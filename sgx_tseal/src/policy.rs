@@ -0,0 +1,223 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use crate::internal::*;
+use sgx_tse::rsgx_self_report;
+use sgx_types::*;
+
+/// The enclave identity a seal key is bound to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SealIdentity {
+    /// Only the same enclave build (MRENCLAVE) can unseal.
+    MrEnclave,
+    /// Any enclave from the same signer (MRSIGNER) and product can unseal.
+    MrSigner,
+}
+
+///
+/// Describes how a seal key is derived, in place of the raw `key_policy`,
+/// `attribute_mask` and `misc_mask` arguments of `SgxSealedData::seal_data_ex`.
+///
+/// `SealPolicy::default()` is the policy used by `SgxSealedData::seal_data`.
+///
+/// # Examples
+///
+/// ```
+/// let policy = SealPolicy::new(SealIdentity::MrSigner)
+///     .isv_family_id(true)
+///     .isv_svn(2);
+/// let sealed_data = SgxSealedData::<[u8]>::seal_data_with_policy(&policy, &aad, &data)?;
+/// ```
+///
+#[derive(Clone, Copy)]
+pub struct SealPolicy {
+    key_policy: u16,
+    attribute_mask: sgx_attributes_t,
+    misc_mask: sgx_misc_select_t,
+    isv_svn: Option<sgx_isv_svn_t>,
+    cpu_svn: Option<sgx_cpu_svn_t>,
+}
+
+impl Default for SealPolicy {
+    fn default() -> SealPolicy {
+        let (key_policy, attribute_mask, misc_mask) = SgxInternalSealedData::default_key_policy();
+        SealPolicy {
+            key_policy,
+            attribute_mask,
+            misc_mask,
+            isv_svn: None,
+            cpu_svn: None,
+        }
+    }
+}
+
+impl SealPolicy {
+    ///
+    /// Create a policy bound to `identity`, with the default attribute and misc
+    /// masks and without the KSS fields.
+    ///
+    pub fn new(identity: SealIdentity) -> SealPolicy {
+        SealPolicy {
+            key_policy: 0,
+            attribute_mask: sgx_attributes_t {
+                flags: TSEAL_DEFAULT_FLAGSMASK,
+                xfrm: 0,
+            },
+            misc_mask: TSEAL_DEFAULT_MISCMASK,
+            isv_svn: None,
+            cpu_svn: None,
+        }
+        .identity(identity)
+    }
+
+    ///
+    /// Bind the key to MRENCLAVE or MRSIGNER.
+    ///
+    pub fn identity(mut self, identity: SealIdentity) -> SealPolicy {
+        self.key_policy &= !(SGX_KEYPOLICY_MRENCLAVE | SGX_KEYPOLICY_MRSIGNER);
+        self.key_policy |= match identity {
+            SealIdentity::MrEnclave => SGX_KEYPOLICY_MRENCLAVE,
+            SealIdentity::MrSigner => SGX_KEYPOLICY_MRSIGNER,
+        };
+        self
+    }
+
+    ///
+    /// Derive the key without the enclave's ISVPRODID, so that enclaves of other
+    /// products from the same signer can unseal.
+    ///
+    pub fn no_isv_prod_id(self, enable: bool) -> SealPolicy {
+        self.flag(SGX_KEYPOLICY_NOISVPRODID, enable)
+    }
+
+    ///
+    /// Derive the key with the enclave's CONFIGID (KSS).
+    ///
+    pub fn config_id(self, enable: bool) -> SealPolicy {
+        self.flag(SGX_KEYPOLICY_CONFIGID, enable)
+    }
+
+    ///
+    /// Derive the key with the enclave's ISVFAMILYID (KSS).
+    ///
+    pub fn isv_family_id(self, enable: bool) -> SealPolicy {
+        self.flag(SGX_KEYPOLICY_ISVFAMILYID, enable)
+    }
+
+    ///
+    /// Derive the key with the enclave's ISVEXTPRODID (KSS).
+    ///
+    pub fn isv_ext_prod_id(self, enable: bool) -> SealPolicy {
+        self.flag(SGX_KEYPOLICY_ISVEXTPRODID, enable)
+    }
+
+    ///
+    /// Set the attributes that are bound into the key. SGX_FLAGS_INITTED and
+    /// SGX_FLAGS_DEBUG are required.
+    ///
+    pub fn attribute_mask(mut self, attribute_mask: sgx_attributes_t) -> SealPolicy {
+        self.attribute_mask = attribute_mask;
+        self
+    }
+
+    ///
+    /// Set the MISCSELECT bits that are bound into the key.
+    ///
+    pub fn misc_mask(mut self, misc_mask: sgx_misc_select_t) -> SealPolicy {
+        self.misc_mask = misc_mask;
+        self
+    }
+
+    ///
+    /// Seal under an ISVSVN lower than the current one, so that older versions of
+    /// the enclave, back to `isv_svn`, can unseal. By default the current ISVSVN
+    /// is used.
+    ///
+    pub fn isv_svn(mut self, isv_svn: sgx_isv_svn_t) -> SealPolicy {
+        self.isv_svn = Some(isv_svn);
+        self
+    }
+
+    ///
+    /// Seal under a CPUSVN other than the current one, so that the blob can be
+    /// unsealed on a platform whose TCB has not been updated to the current
+    /// level. By default the current CPUSVN is used.
+    ///
+    pub fn cpu_svn(mut self, cpu_svn: sgx_cpu_svn_t) -> SealPolicy {
+        self.cpu_svn = Some(cpu_svn);
+        self
+    }
+
+    ///
+    /// Get the raw key policy bits.
+    ///
+    pub fn get_key_policy(&self) -> u16 {
+        self.key_policy
+    }
+
+    ///
+    /// Get the attribute mask.
+    ///
+    pub fn get_attribute_mask(&self) -> sgx_attributes_t {
+        self.attribute_mask
+    }
+
+    ///
+    /// Get the misc mask.
+    ///
+    pub fn get_misc_mask(&self) -> sgx_misc_select_t {
+        self.misc_mask
+    }
+
+    fn flag(mut self, bit: u16, enable: bool) -> SealPolicy {
+        if enable {
+            self.key_policy |= bit;
+        } else {
+            self.key_policy &= !bit;
+        }
+        self
+    }
+
+    pub(crate) fn key_request(&self) -> SgxResult<sgx_key_request_t> {
+        let mut key_request = SgxInternalSealedData::new_key_request(
+            self.key_policy,
+            self.attribute_mask,
+            self.misc_mask,
+        )?;
+        if let Some(isv_svn) = self.isv_svn {
+            if isv_svn > key_request.isv_svn {
+                return Err(sgx_status_t::SGX_ERROR_INVALID_ISVSVN);
+            }
+            key_request.isv_svn = isv_svn;
+        }
+        if let Some(cpu_svn) = self.cpu_svn {
+            key_request.cpu_svn = cpu_svn;
+        }
+        Ok(key_request)
+    }
+}
+
+///
+/// Check whether a blob sealed with `key_request` was sealed under an older
+/// ISVSVN, CPUSVN or CONFIGSVN than the current enclave and platform have.
+///
+pub(crate) fn is_outdated(key_request: &sgx_key_request_t) -> bool {
+    let report = rsgx_self_report();
+    key_request.isv_svn < report.body.isv_svn
+        || key_request.cpu_svn.svn != report.body.cpu_svn.svn
+        || key_request.config_svn < report.body.config_svn
+}
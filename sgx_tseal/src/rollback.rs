@@ -0,0 +1,388 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//!
+//! Rollback protection for sealed data.
+//!
+//! Sealing alone does not stop the untrusted host from handing an enclave an
+//! older, validly sealed snapshot of its state. `SgxRollbackGuard` binds the
+//! identifier and the value of a monotonic counter into the additional text of
+//! every blob it seals, and on unseal rejects any blob that does not carry the
+//! identifier and the current value of its counter.
+//!
+//! The additional text of a guarded blob is laid out as:
+//!
+//! ```text
+//! magic "SGXR" | counter id [u8; 16] | counter value u64 (little endian)
+//!     | caller's additional text
+//! ```
+//!
+//! The counter itself is provided through the `MonotonicCounter` trait of
+//! sgx_types, since the SGX platform services counters are deprecated. The
+//! protection is only as strong as the counter backend: it must not be
+//! possible for the host to reset it or roll it back.
+//!
+
+use crate::policy::SealPolicy;
+use crate::seal::{SgxSealedData, SgxUnsealedData};
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicU64, Ordering};
+use sgx_types::marker::ContiguousMemory;
+use sgx_types::*;
+
+#[cfg(feature = "std")]
+use std::io::{self, Read, Write};
+#[cfg(feature = "std")]
+use std::path::{Path, PathBuf};
+#[cfg(feature = "std")]
+use std::sgxfs::SgxFile;
+#[cfg(feature = "std")]
+use sgx_trts::trts::rsgx_read_rand;
+
+const ROLLBACK_MAGIC: [u8; 4] = *b"SGXR";
+const ROLLBACK_HEADER_SIZE: usize = 28;
+
+static NEXT_MEMORY_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+///
+/// A counter kept in enclave memory.
+///
+/// It is lost when the enclave is destroyed, so it gives no protection across
+/// enclave restarts. It is meant for tests and for state that does not outlive
+/// the enclave.
+///
+/// Every counter created in an enclave gets its own identifier.
+///
+#[derive(Clone, Debug)]
+pub struct SgxMemoryCounter {
+    id: MonotonicCounterId,
+    value: u64,
+}
+
+impl SgxMemoryCounter {
+    ///
+    /// Create a counter starting at zero.
+    ///
+    pub fn new() -> SgxMemoryCounter {
+        SgxMemoryCounter::with_value(0)
+    }
+
+    ///
+    /// Create a counter starting at `value`.
+    ///
+    pub fn with_value(value: u64) -> SgxMemoryCounter {
+        let mut id = MonotonicCounterId::default();
+        id[..8].copy_from_slice(&NEXT_MEMORY_COUNTER.fetch_add(1, Ordering::Relaxed).to_le_bytes());
+        SgxMemoryCounter { id, value }
+    }
+}
+
+impl Default for SgxMemoryCounter {
+    fn default() -> SgxMemoryCounter {
+        SgxMemoryCounter::new()
+    }
+}
+
+impl MonotonicCounter for SgxMemoryCounter {
+    fn id(&self) -> MonotonicCounterId {
+        self.id
+    }
+
+    fn read(&mut self) -> SgxResult<u64> {
+        Ok(self.value)
+    }
+
+    fn increment(&mut self) -> SgxResult<u64> {
+        self.value = self
+            .value
+            .checked_add(1)
+            .ok_or(sgx_status_t::SGX_ERROR_MC_USED_UP)?;
+        Ok(self.value)
+    }
+}
+
+///
+/// A counter persisted in a protected file, for reference and development.
+///
+/// The file is an `SgxFile`, so its content is confidential and integrity
+/// protected, but the host can still replace it with an older copy. This
+/// backend therefore only protects against rollback when the file lives on
+/// storage the host cannot roll back; production deployments should implement
+/// `MonotonicCounter` on top of a trusted counter service instead.
+///
+/// The file holds a random identifier chosen when the counter is created.
+///
+#[cfg(feature = "std")]
+pub struct SgxFileCounter {
+    path: PathBuf,
+    id: MonotonicCounterId,
+    value: u64,
+}
+
+#[cfg(feature = "std")]
+impl SgxFileCounter {
+    const MAGIC: [u8; 4] = *b"SGXM";
+    const FILE_SIZE: usize = 28;
+
+    ///
+    /// Create a counter file at `path` starting at zero, with a new identifier,
+    /// replacing any existing file.
+    ///
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<SgxFileCounter> {
+        let mut id = MonotonicCounterId::default();
+        rsgx_read_rand(&mut id).map_err(io::Error::from_sgx_error)?;
+        let counter = SgxFileCounter {
+            path: path.as_ref().to_path_buf(),
+            id,
+            value: 0,
+        };
+        counter.store(0)?;
+        Ok(counter)
+    }
+
+    ///
+    /// Open an existing counter file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind `InvalidData` if the file is not a counter file,
+    /// and the errors of `SgxFile::open` otherwise.
+    ///
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<SgxFileCounter> {
+        let mut file = SgxFile::open(path.as_ref())?;
+        let mut buf = Vec::with_capacity(Self::FILE_SIZE);
+        file.read_to_end(&mut buf)?;
+        if buf.len() != Self::FILE_SIZE || buf[..4] != Self::MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a monotonic counter file",
+            ));
+        }
+        let mut id = MonotonicCounterId::default();
+        id.copy_from_slice(&buf[4..20]);
+        let mut value = [0_u8; 8];
+        value.copy_from_slice(&buf[20..]);
+        Ok(SgxFileCounter {
+            path: path.as_ref().to_path_buf(),
+            id,
+            value: u64::from_le_bytes(value),
+        })
+    }
+
+    ///
+    /// Get the path of the counter file.
+    ///
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn store(&self, value: u64) -> io::Result<()> {
+        let mut buf = [0_u8; Self::FILE_SIZE];
+        buf[..4].copy_from_slice(&Self::MAGIC);
+        buf[4..20].copy_from_slice(&self.id);
+        buf[20..].copy_from_slice(&value.to_le_bytes());
+        let mut file = SgxFile::create(&self.path)?;
+        file.write_all(&buf)?;
+        file.flush()
+    }
+}
+
+#[cfg(feature = "std")]
+impl MonotonicCounter for SgxFileCounter {
+    fn id(&self) -> MonotonicCounterId {
+        self.id
+    }
+
+    fn read(&mut self) -> SgxResult<u64> {
+        Ok(self.value)
+    }
+
+    fn increment(&mut self) -> SgxResult<u64> {
+        let value = self
+            .value
+            .checked_add(1)
+            .ok_or(sgx_status_t::SGX_ERROR_MC_USED_UP)?;
+        self.store(value)
+            .map_err(|e| e.raw_sgx_error().unwrap_or(sgx_status_t::SGX_ERROR_UNEXPECTED))?;
+        self.value = value;
+        Ok(value)
+    }
+}
+
+///
+/// Seals and unseals data bound to the value of a monotonic counter.
+///
+/// Every call to `seal_data` increments the counter, which makes all blobs
+/// sealed before it stale. Only the blob returned by the latest `seal_data`
+/// can be unsealed, so the caller must persist it before anything else: if it
+/// is lost, the previous state can no longer be recovered.
+///
+/// # Examples
+///
+/// ```
+/// let mut guard = SgxRollbackGuard::new(SgxFileCounter::open("state.ctr")?);
+/// let sealed = guard.seal_data(b"state", &state)?;
+/// // ...
+/// let unsealed = guard.unseal_data(&sealed)?;
+/// ```
+///
+pub struct SgxRollbackGuard<C: MonotonicCounter> {
+    counter: C,
+}
+
+impl<C: MonotonicCounter> SgxRollbackGuard<C> {
+    ///
+    /// Create a guard on top of `counter`.
+    ///
+    pub fn new(counter: C) -> SgxRollbackGuard<C> {
+        SgxRollbackGuard { counter }
+    }
+
+    ///
+    /// Get a reference to the counter.
+    ///
+    pub fn get_ref(&self) -> &C {
+        &self.counter
+    }
+
+    ///
+    /// Get a mutable reference to the counter.
+    ///
+    pub fn get_mut(&mut self) -> &mut C {
+        &mut self.counter
+    }
+
+    ///
+    /// Unwrap the guard, returning the counter.
+    ///
+    pub fn into_inner(self) -> C {
+        self.counter
+    }
+
+    ///
+    /// Increment the counter and seal `encrypt_text` bound to its new value,
+    /// with the default key policy of `SgxSealedData::seal_data`.
+    ///
+    /// The errors are the ones of `seal_data_with_policy`.
+    ///
+    pub fn seal_data<'a, T: 'a + Copy + ContiguousMemory>(
+        &mut self,
+        additional_text: &[u8],
+        encrypt_text: &'a [T],
+    ) -> SgxResult<SgxSealedData<'a, [T]>> {
+        self.seal_data_with_policy(&SealPolicy::default(), additional_text, encrypt_text)
+    }
+
+    ///
+    /// Increment the counter and seal `encrypt_text` bound to its identifier
+    /// and new value, with the key derivation described by `policy`.
+    ///
+    /// The data is sealed before the counter is incremented, so a failure to
+    /// seal leaves the counter, and the blob sealed before, untouched.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_STATE**
+    ///
+    /// The counter was incremented by someone else while sealing.
+    ///
+    /// Other errors are the ones of the counter backend and of
+    /// `SgxSealedData::seal_data_with_policy`.
+    ///
+    pub fn seal_data_with_policy<'a, T: 'a + Copy + ContiguousMemory>(
+        &mut self,
+        policy: &SealPolicy,
+        additional_text: &[u8],
+        encrypt_text: &'a [T],
+    ) -> SgxResult<SgxSealedData<'a, [T]>> {
+        let value = self
+            .counter
+            .read()?
+            .checked_add(1)
+            .ok_or(sgx_status_t::SGX_ERROR_MC_USED_UP)?;
+
+        let mut aad = Vec::with_capacity(ROLLBACK_HEADER_SIZE + additional_text.len());
+        aad.extend_from_slice(&ROLLBACK_MAGIC);
+        aad.extend_from_slice(&self.counter.id());
+        aad.extend_from_slice(&value.to_le_bytes());
+        aad.extend_from_slice(additional_text);
+        let sealed = SgxSealedData::<[T]>::seal_data_with_policy(policy, &aad, encrypt_text)?;
+
+        if self.counter.increment()? != value {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_STATE);
+        }
+        Ok(sealed)
+    }
+
+    ///
+    /// Unseal a blob produced by `seal_data` and check that it is bound to the
+    /// identifier and the current value of the counter.
+    ///
+    /// The additional text of the returned data is the one passed to
+    /// `seal_data`, without the counter identifier and value.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_PARAMETER**
+    ///
+    /// The blob was not sealed by `SgxRollbackGuard`.
+    ///
+    /// **SGX_ERROR_INVALID_STATE**
+    ///
+    /// The blob is bound to an older value of the counter: it is a replayed snapshot.
+    ///
+    /// **SGX_ERROR_MC_NOT_FOUND**
+    ///
+    /// The blob is bound to another counter, or to a value the counter has not
+    /// reached: the counter was reset or replaced.
+    ///
+    /// Other errors are the ones of the counter backend and of
+    /// `SgxSealedData::unseal_data`.
+    ///
+    pub fn unseal_data<'a, T: 'a + Copy + ContiguousMemory>(
+        &mut self,
+        sealed_data: &SgxSealedData<'a, [T]>,
+    ) -> SgxResult<SgxUnsealedData<'a, [T]>> {
+        let (id, value) = sealed_counter(sealed_data.get_additional_txt())
+            .ok_or(sgx_status_t::SGX_ERROR_INVALID_PARAMETER)?;
+
+        let mut unsealed_data = sealed_data.unseal_data()?;
+        if id != self.counter.id() {
+            return Err(sgx_status_t::SGX_ERROR_MC_NOT_FOUND);
+        }
+        let current = self.counter.read()?;
+        if value < current {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_STATE);
+        } else if value > current {
+            return Err(sgx_status_t::SGX_ERROR_MC_NOT_FOUND);
+        }
+
+        unsealed_data.additional = unsealed_data.additional[ROLLBACK_HEADER_SIZE..].into();
+        Ok(unsealed_data)
+    }
+}
+
+fn sealed_counter(additional_text: &[u8]) -> Option<(MonotonicCounterId, u64)> {
+    if additional_text.len() < ROLLBACK_HEADER_SIZE || additional_text[..4] != ROLLBACK_MAGIC {
+        return None;
+    }
+    let mut id = MonotonicCounterId::default();
+    id.copy_from_slice(&additional_text[4..20]);
+    let mut value = [0_u8; 8];
+    value.copy_from_slice(&additional_text[20..ROLLBACK_HEADER_SIZE]);
+    Some((id, u64::from_le_bytes(value)))
+}
@@ -28,8 +28,10 @@
 //! The library also provides APIs to help calculate the sealed data size, encrypt text length, and Message Authentication Code (MAC) text length.
 //!
 use crate::internal::*;
+use crate::policy::{is_outdated, SealPolicy};
 use alloc::boxed::Box;
 use alloc::slice;
+use alloc::vec::Vec;
 use core::marker::PhantomData;
 use core::mem;
 use sgx_types::marker::ContiguousMemory;
@@ -302,6 +304,47 @@ impl<'a, T: 'a + Copy + ContiguousMemory> SgxSealedData<'a, T> {
         })
     }
 
+    ///
+    /// Seal the data with a key derived as described by `policy`.
+    ///
+    /// This is `seal_data_ex` with the key policy, attribute mask and misc mask
+    /// taken from a `SealPolicy`, which may also pin the ISVSVN and CPUSVN.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_ISVSVN**
+    ///
+    /// The policy pins an ISVSVN higher than the one of the enclave.
+    ///
+    /// Other errors are the ones of `seal_data_ex`.
+    ///
+    pub fn seal_data_with_policy(
+        policy: &SealPolicy,
+        additional_text: &[u8],
+        encrypt_text: &'a T,
+    ) -> SgxResult<Self> {
+        let size = mem::size_of::<T>();
+        if size == 0 {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+        let encrypt_slice: &[u8] = unsafe {
+            slice::from_raw_parts(
+                encrypt_text as *const _ as *const u8,
+                mem::size_of_val(encrypt_text),
+            )
+        };
+        let key_request = policy.key_request()?;
+        let result = SgxInternalSealedData::seal_data_with_key_request(
+            &key_request,
+            additional_text,
+            encrypt_slice,
+        );
+        result.map(|x| SgxSealedData {
+            inner: x,
+            marker: PhantomData,
+        })
+    }
+
     ///
     /// This function is used to AES-GCM decrypt the input sealed data structure.
     /// Two output data sets result: one is the decrypted data; the second is the
@@ -618,6 +661,45 @@ impl<'a, T: 'a + Copy + ContiguousMemory> SgxSealedData<'a, [T]> {
         })
     }
 
+    ///
+    /// Seal the data with a key derived as described by `policy`.
+    ///
+    /// This is `seal_data_ex` with the key policy, attribute mask and misc mask
+    /// taken from a `SealPolicy`, which may also pin the ISVSVN and CPUSVN.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_ISVSVN**
+    ///
+    /// The policy pins an ISVSVN higher than the one of the enclave.
+    ///
+    /// Other errors are the ones of `seal_data_ex`.
+    ///
+    pub fn seal_data_with_policy(
+        policy: &SealPolicy,
+        additional_text: &[u8],
+        encrypt_text: &'a [T],
+    ) -> SgxResult<Self> {
+        let size = mem::size_of::<T>();
+        let len = mem::size_of_val(encrypt_text);
+        if size == 0 || len == 0 {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+        let encrypt_slice: &[u8] =
+            unsafe { slice::from_raw_parts(encrypt_text.as_ptr() as *const u8, len) };
+
+        let key_request = policy.key_request()?;
+        let result = SgxInternalSealedData::seal_data_with_key_request(
+            &key_request,
+            additional_text,
+            encrypt_slice,
+        );
+        result.map(|x| SgxSealedData {
+            inner: x,
+            marker: PhantomData,
+        })
+    }
+
     ///
     /// This function is used to AES-GCM decrypt the input sealed data structure.
     /// Two output data sets result: one is the decrypted data; the second is the
@@ -825,4 +907,73 @@ impl<'a, T: 'a + ?Sized> SgxSealedData<'a, T> {
     pub fn get_encrypt_txt_len(&self) -> u32 {
         self.inner.get_encrypt_txt_len()
     }
+
+    ///
+    /// Check whether the data was sealed under an older ISVSVN, CPUSVN or CONFIGSVN
+    /// than the current enclave and platform have, in which case it should be
+    /// migrated with `reseal`.
+    ///
+    pub fn needs_reseal(&self) -> bool {
+        is_outdated(self.inner.get_key_request())
+    }
+
+    ///
+    /// Unseal the data and seal it again under `policy` and the current identity.
+    ///
+    /// This migrates data sealed by an older version of the enclave, or on a
+    /// platform before a TCB update, to the current ISVSVN and CPUSVN. The
+    /// additional text is carried over unchanged.
+    ///
+    /// # Errors
+    ///
+    /// The errors are the ones of `unseal_data` and `seal_data_with_policy`.
+    ///
+    pub fn reseal(&self, policy: &SealPolicy) -> SgxResult<Self> {
+        let key_request = policy.key_request()?;
+        self.inner.reseal(&key_request).map(|x| SgxSealedData {
+            inner: x,
+            marker: PhantomData,
+        })
+    }
+
+    ///
+    /// Serialize SgxSealedData into a byte vector which can be written to a file or sent
+    /// over the network.
+    ///
+    /// The bytes start with a 16-byte header: the magic `SGXS`, a little endian
+    /// u32 format version (currently 1), the little endian u32 length of the rest
+    /// of the buffer and four reserved zero bytes. The header is followed by the
+    /// sgx_sealed_data_t structure, the encrypt text and the additional text, in
+    /// the layout produced by sgx_seal_data.
+    ///
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.inner.to_bytes()
+    }
+
+    ///
+    /// Parse bytes produced by `to_bytes` into SgxSealedData.
+    ///
+    /// # Parameters
+    ///
+    /// **bytes**
+    ///
+    /// The serialized sealed data. The buffer must contain exactly one sealed
+    /// blob, without trailing bytes.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_PARAMETER**
+    ///
+    /// The header is malformed, or the lengths in it do not match the buffer.
+    ///
+    /// **SGX_ERROR_INVALID_VERSION**
+    ///
+    /// The bytes were written by an unsupported format version.
+    ///
+    pub fn from_bytes(bytes: &[u8]) -> SgxResult<Self> {
+        SgxInternalSealedData::from_bytes(bytes).map(|x| SgxSealedData {
+            inner: x,
+            marker: PhantomData,
+        })
+    }
 }
@@ -0,0 +1,423 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//!
+//! Chunked sealing of payloads too large to be sealed in one piece.
+//!
+//! A sealed stream starts with a header, followed by a sequence of chunks:
+//!
+//! ```text
+//! header: magic "SGXC" | version u32 | chunk size u32 | nonce prefix [u8; 4] | sgx_key_request_t
+//! chunk:  flags u8 | reserved [u8; 3] | length u32 | ciphertext [u8; length] | tag [u8; 16]
+//! ```
+//!
+//! All integers are little endian. Each chunk is encrypted with AES-GCM under a
+//! seal key unique to the stream. Its IV is the nonce prefix followed by the big
+//! endian chunk index, and its additional data covers the stream header, the
+//! chunk header and the chunk index. Only the last chunk carries the final flag,
+//! so a reader detects chunks that were dropped, reordered, duplicated or moved
+//! to another stream, as well as truncation of the stream.
+//!
+
+use crate::internal::*;
+use core::mem;
+use core::ptr;
+use core::slice;
+use sgx_tcrypto::*;
+use sgx_trts::trts::rsgx_read_rand;
+use sgx_tse::rsgx_get_align_key;
+use sgx_types::*;
+use std::io::{self, Read, Write};
+use std::vec::Vec;
+
+const STREAM_MAGIC: [u8; 4] = *b"SGXC";
+const STREAM_VERSION: u32 = 1;
+const STREAM_HEADER_SIZE: usize = 16 + SGX_KEY_REQUEST_SIZE;
+const CHUNK_HEADER_SIZE: usize = 8;
+const CHUNK_FLAG_FINAL: u8 = 1;
+const SGX_KEY_REQUEST_SIZE: usize = mem::size_of::<sgx_key_request_t>();
+
+/// The chunk size used by `SgxSealWriter::new`.
+pub const SGX_SEAL_STREAM_DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+/// The largest chunk size a sealed stream may use.
+pub const SGX_SEAL_STREAM_MAX_CHUNK_SIZE: usize = 16 * 1024 * 1024;
+
+///
+/// Seals everything written to it into a chunked stream on the inner writer.
+///
+/// Call `finish` once all data is written: it emits the final chunk. A stream
+/// that is dropped without `finish` is rejected by `SgxUnsealReader` as
+/// truncated.
+///
+pub struct SgxSealWriter<W: Write> {
+    inner: Option<W>,
+    key: SgxKey128Secret,
+    header: [u8; STREAM_HEADER_SIZE],
+    header_written: bool,
+    chunk_size: usize,
+    index: u64,
+    buf: Vec<u8>,
+    out: Vec<u8>,
+}
+
+impl<W: Write> SgxSealWriter<W> {
+    ///
+    /// Create a writer which seals with the default key policy of `SgxSealedData::seal_data`
+    /// and the default chunk size.
+    ///
+    pub fn new(inner: W) -> SgxResult<Self> {
+        let (key_policy, attribute_mask, misc_mask) = SgxInternalSealedData::default_key_policy();
+        Self::with_policy(
+            inner,
+            key_policy,
+            attribute_mask,
+            misc_mask,
+            SGX_SEAL_STREAM_DEFAULT_CHUNK_SIZE,
+        )
+    }
+
+    ///
+    /// Create a writer with an explicit key policy, attribute mask and misc mask,
+    /// as `SgxSealedData::seal_data_ex` takes them.
+    ///
+    /// # Parameters
+    ///
+    /// **chunk_size**
+    ///
+    /// The plaintext size of every chunk but the last, between 1 and
+    /// `SGX_SEAL_STREAM_MAX_CHUNK_SIZE`. This much data is buffered in the enclave.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_PARAMETER**
+    ///
+    /// The chunk size or the key policy is invalid.
+    ///
+    /// **SGX_ERROR_UNEXPECTED**
+    ///
+    /// Indicates a crypto library failure or the RDRAND instruction fails to generate a
+    /// random number.
+    ///
+    pub fn with_policy(
+        inner: W,
+        key_policy: u16,
+        attribute_mask: sgx_attributes_t,
+        misc_mask: sgx_misc_select_t,
+        chunk_size: usize,
+    ) -> SgxResult<Self> {
+        if chunk_size == 0 || chunk_size > SGX_SEAL_STREAM_MAX_CHUNK_SIZE {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+
+        let key_request = SgxInternalSealedData::new_key_request(key_policy, attribute_mask, misc_mask)?;
+        let mut nonce_prefix = [0_u8; 4];
+        rsgx_read_rand(&mut nonce_prefix)?;
+        let seal_key = rsgx_get_align_key(&key_request).map_err(|ret| {
+            if ret != sgx_status_t::SGX_ERROR_OUT_OF_MEMORY {
+                sgx_status_t::SGX_ERROR_UNEXPECTED
+            } else {
+                ret
+            }
+        })?;
+        let key = SgxKey128Secret::new(seal_key.key);
+
+        let mut header = [0_u8; STREAM_HEADER_SIZE];
+        header[0..4].copy_from_slice(&STREAM_MAGIC);
+        header[4..8].copy_from_slice(&STREAM_VERSION.to_le_bytes());
+        header[8..12].copy_from_slice(&(chunk_size as u32).to_le_bytes());
+        header[12..16].copy_from_slice(&nonce_prefix);
+        header[16..].copy_from_slice(key_request_as_bytes(&key_request));
+
+        Ok(SgxSealWriter {
+            inner: Some(inner),
+            key,
+            header,
+            header_written: false,
+            chunk_size,
+            index: 0,
+            buf: Vec::with_capacity(chunk_size),
+            out: Vec::with_capacity(CHUNK_HEADER_SIZE + chunk_size + SGX_SEAL_TAG_SIZE),
+        })
+    }
+
+    ///
+    /// Seal the buffered data as the final chunk and return the inner writer.
+    ///
+    pub fn finish(mut self) -> io::Result<W> {
+        self.emit_chunk(true)?;
+        let mut inner = self.inner.take().unwrap();
+        inner.flush()?;
+        Ok(inner)
+    }
+
+    fn emit_chunk(&mut self, last: bool) -> io::Result<()> {
+        let inner = match self.inner.as_mut() {
+            Some(inner) => inner,
+            None => return Err(io::Error::new(io::ErrorKind::Other, "sealed stream is finished")),
+        };
+        if !self.header_written {
+            inner.write_all(&self.header)?;
+            self.header_written = true;
+        }
+
+        let mut chunk_header = [0_u8; CHUNK_HEADER_SIZE];
+        chunk_header[0] = if last { CHUNK_FLAG_FINAL } else { 0 };
+        chunk_header[4..8].copy_from_slice(&(self.buf.len() as u32).to_le_bytes());
+        let iv = chunk_iv(&self.header, self.index);
+        let aad = chunk_aad(&self.header, &chunk_header, self.index);
+
+        let len = self.buf.len();
+        self.out.clear();
+        self.out.extend_from_slice(&chunk_header);
+        self.out.resize(CHUNK_HEADER_SIZE + len, 0);
+        let mut tag = sgx_aes_gcm_128bit_tag_t::default();
+        rsgx_rijndael128GCM_encrypt(
+            &self.key,
+            &self.buf,
+            &iv,
+            &aad,
+            &mut self.out[CHUNK_HEADER_SIZE..],
+            &mut tag,
+        )
+        .map_err(io::Error::from_sgx_error)?;
+        self.out.extend_from_slice(&tag);
+
+        inner.write_all(&self.out)?;
+        zeroize_bytes(&mut self.buf);
+        self.buf.clear();
+        self.index += 1;
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for SgxSealWriter<W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        // A full chunk is only sealed once more data arrives, so that the
+        // last chunk can always be flagged as final by `finish`.
+        if self.buf.len() == self.chunk_size {
+            self.emit_chunk(false)?;
+        }
+        let n = data.len().min(self.chunk_size - self.buf.len());
+        self.buf.extend_from_slice(&data[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.inner.as_mut() {
+            Some(inner) => inner.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<W: Write> Drop for SgxSealWriter<W> {
+    fn drop(&mut self) {
+        zeroize_bytes(&mut self.buf);
+    }
+}
+
+///
+/// Unseals a stream written by `SgxSealWriter`.
+///
+/// Data is only returned once the chunk holding it has been authenticated.
+/// Authentication failures, truncation and trailing data are reported as
+/// `io::ErrorKind::InvalidData`.
+///
+pub struct SgxUnsealReader<R: Read> {
+    inner: R,
+    key: SgxKey128Secret,
+    key_request: sgx_key_request_t,
+    header: [u8; STREAM_HEADER_SIZE],
+    chunk_size: usize,
+    index: u64,
+    buf: Vec<u8>,
+    pos: usize,
+    done: bool,
+}
+
+impl<R: Read> SgxUnsealReader<R> {
+    ///
+    /// Read the stream header and derive the seal key of the stream.
+    ///
+    /// # Errors
+    ///
+    /// **InvalidData**
+    ///
+    /// The header is malformed or was written by an unsupported format version.
+    ///
+    /// Failures to derive the seal key carry the `sgx_status_t` of `SgxSealedData::unseal_data`,
+    /// such as SGX_ERROR_INVALID_CPUSVN or SGX_ERROR_INVALID_ISVSVN.
+    ///
+    pub fn new(mut inner: R) -> io::Result<Self> {
+        let mut header = [0_u8; STREAM_HEADER_SIZE];
+        inner.read_exact(&mut header).map_err(truncated)?;
+        if header[0..4] != STREAM_MAGIC {
+            return Err(invalid_data("not a sealed stream"));
+        }
+        if u32::from_le_bytes([header[4], header[5], header[6], header[7]]) != STREAM_VERSION {
+            return Err(invalid_data("unsupported sealed stream version"));
+        }
+        let chunk_size = u32::from_le_bytes([header[8], header[9], header[10], header[11]]) as usize;
+        if chunk_size == 0 || chunk_size > SGX_SEAL_STREAM_MAX_CHUNK_SIZE {
+            return Err(invalid_data("invalid sealed stream chunk size"));
+        }
+
+        let key_request = key_request_from_bytes(&header[16..]);
+        let seal_key = SgxInternalSealedData::get_unseal_key(&key_request).map_err(io::Error::from_sgx_error)?;
+        let key = SgxKey128Secret::new(seal_key.key);
+
+        Ok(SgxUnsealReader {
+            inner,
+            key,
+            key_request,
+            header,
+            chunk_size,
+            index: 0,
+            buf: Vec::with_capacity(chunk_size),
+            pos: 0,
+            done: false,
+        })
+    }
+
+    ///
+    /// Get the sgx_key_request_t the stream was sealed with.
+    ///
+    pub fn get_key_request(&self) -> &sgx_key_request_t {
+        &self.key_request
+    }
+
+    fn next_chunk(&mut self) -> io::Result<()> {
+        let mut chunk_header = [0_u8; CHUNK_HEADER_SIZE];
+        self.inner.read_exact(&mut chunk_header).map_err(truncated)?;
+        let flags = chunk_header[0];
+        let len = u32::from_le_bytes([
+            chunk_header[4],
+            chunk_header[5],
+            chunk_header[6],
+            chunk_header[7],
+        ]) as usize;
+        let last = flags == CHUNK_FLAG_FINAL;
+        if (flags & !CHUNK_FLAG_FINAL) != 0
+            || chunk_header[1..4] != [0_u8; 3]
+            || len > self.chunk_size
+            || (!last && len != self.chunk_size)
+        {
+            return Err(invalid_data("malformed sealed stream chunk"));
+        }
+
+        let mut ciphertext = vec![0_u8; len];
+        let mut tag = sgx_aes_gcm_128bit_tag_t::default();
+        self.inner.read_exact(&mut ciphertext).map_err(truncated)?;
+        self.inner.read_exact(&mut tag).map_err(truncated)?;
+
+        let iv = chunk_iv(&self.header, self.index);
+        let aad = chunk_aad(&self.header, &chunk_header, self.index);
+        zeroize_bytes(&mut self.buf);
+        self.buf.clear();
+        self.buf.resize(len, 0);
+        self.pos = 0;
+        if rsgx_rijndael128GCM_decrypt(&self.key, &ciphertext, &iv, &aad, &tag, &mut self.buf).is_err() {
+            zeroize_bytes(&mut self.buf);
+            self.buf.clear();
+            return Err(invalid_data("sealed stream chunk failed authentication"));
+        }
+        self.index += 1;
+
+        if last {
+            let mut trailing = [0_u8; 1];
+            let n = loop {
+                match self.inner.read(&mut trailing) {
+                    Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                    other => break other?,
+                }
+            };
+            if n != 0 {
+                zeroize_bytes(&mut self.buf);
+                self.buf.clear();
+                return Err(invalid_data("trailing data after the final sealed chunk"));
+            }
+            self.done = true;
+        }
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for SgxUnsealReader<R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.buf.len() {
+            if self.done || out.is_empty() {
+                return Ok(0);
+            }
+            self.next_chunk()?;
+        }
+        let n = out.len().min(self.buf.len() - self.pos);
+        out[..n].copy_from_slice(&self.buf[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+impl<R: Read> Drop for SgxUnsealReader<R> {
+    fn drop(&mut self) {
+        zeroize_bytes(&mut self.buf);
+    }
+}
+
+fn chunk_iv(header: &[u8; STREAM_HEADER_SIZE], index: u64) -> [u8; SGX_AESGCM_IV_SIZE] {
+    let mut iv = [0_u8; SGX_AESGCM_IV_SIZE];
+    iv[0..4].copy_from_slice(&header[12..16]);
+    iv[4..12].copy_from_slice(&index.to_be_bytes());
+    iv
+}
+
+fn chunk_aad(
+    header: &[u8; STREAM_HEADER_SIZE],
+    chunk_header: &[u8; CHUNK_HEADER_SIZE],
+    index: u64,
+) -> Vec<u8> {
+    let mut aad = Vec::with_capacity(STREAM_HEADER_SIZE + CHUNK_HEADER_SIZE + 8);
+    aad.extend_from_slice(header);
+    aad.extend_from_slice(chunk_header);
+    aad.extend_from_slice(&index.to_le_bytes());
+    aad
+}
+
+fn key_request_as_bytes(key_request: &sgx_key_request_t) -> &[u8] {
+    unsafe {
+        slice::from_raw_parts(
+            key_request as *const sgx_key_request_t as *const u8,
+            SGX_KEY_REQUEST_SIZE,
+        )
+    }
+}
+
+fn key_request_from_bytes(bytes: &[u8]) -> sgx_key_request_t {
+    assert_eq!(bytes.len(), SGX_KEY_REQUEST_SIZE);
+    unsafe { ptr::read_unaligned(bytes.as_ptr() as *const sgx_key_request_t) }
+}
+
+fn invalid_data(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn truncated(e: io::Error) -> io::Error {
+    if e.kind() == io::ErrorKind::UnexpectedEof {
+        invalid_data("sealed stream is truncated")
+    } else {
+        e
+    }
+}
@@ -0,0 +1,111 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+use crate::io::{self, Error};
+use crate::path::Path;
+use sgx_types::{sgx_cmac_128bit_tag_t, sgx_key_128bit_t, sgx_rijndael128_cmac_msg, sgx_status_t, SgxKey128Secret};
+
+/// A source of keys for protected files.
+///
+/// [`OpenOptions::open_with`] asks the provider for the key of a file every
+/// time the file is opened, so a provider can fetch keys from a key management
+/// service after attestation, derive a key per tenant or per file, or pick the
+/// current key during a key rotation. [`rekey`] moves a file from the key of
+/// one provider to the key of another.
+///
+/// A raw `sgx_key_128bit_t` and an `SgxKey128Secret` are providers that return
+/// the same key for every file.
+///
+/// [`OpenOptions::open_with`]: struct.OpenOptions.html#method.open_with
+/// [`rekey`]: fn.rekey.html
+///
+pub trait KeyProvider {
+    /// Returns the key of the protected file at `path`.
+    ///
+    fn file_key(&self, path: &Path) -> io::Result<SgxKey128Secret>;
+}
+
+impl KeyProvider for sgx_key_128bit_t {
+    fn file_key(&self, _path: &Path) -> io::Result<SgxKey128Secret> {
+        Ok(SgxKey128Secret::new(*self))
+    }
+}
+
+impl KeyProvider for SgxKey128Secret {
+    fn file_key(&self, _path: &Path) -> io::Result<SgxKey128Secret> {
+        Ok(self.clone())
+    }
+}
+
+/// A provider that derives the key of the files of one context, such as a
+/// tenant, from a master key.
+///
+/// The key is AES-CMAC over a fixed label and the context, keyed with the
+/// master key. It does not depend on the path of the file, so files keep
+/// their key when they are renamed.
+///
+/// # Examples
+///
+/// ```
+/// let master = SgxKey128Secret::new(key_from_kms);
+/// let tenant = DerivedKeyProvider::new(master, b"tenant-42");
+/// let mut file = OpenOptions::new().write(true).open_with("data", &tenant)?;
+/// ```
+///
+pub struct DerivedKeyProvider {
+    key: SgxKey128Secret,
+}
+
+impl DerivedKeyProvider {
+    const LABEL: &'static [u8] = b"SGXFS-FILE-KEY";
+
+    /// Derives the key of `context` from `master`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the cryptographic library fails.
+    ///
+    pub fn new(master: SgxKey128Secret, context: &[u8]) -> io::Result<DerivedKeyProvider> {
+        let mut input = Vec::with_capacity(Self::LABEL.len() + 1 + context.len());
+        input.extend_from_slice(Self::LABEL);
+        input.push(0);
+        input.extend_from_slice(context);
+        if input.len() > u32::MAX as usize {
+            return Err(Error::from_sgx_error(sgx_status_t::SGX_ERROR_INVALID_PARAMETER));
+        }
+
+        let mut key = SgxKey128Secret::new(sgx_cmac_128bit_tag_t::default());
+        let status = unsafe {
+            sgx_rijndael128_cmac_msg(
+                master.expose() as *const sgx_key_128bit_t,
+                input.as_ptr(),
+                input.len() as u32,
+                key.expose_mut() as *mut sgx_cmac_128bit_tag_t,
+            )
+        };
+        match status {
+            sgx_status_t::SGX_SUCCESS => Ok(DerivedKeyProvider { key }),
+            status => Err(Error::from_sgx_error(status)),
+        }
+    }
+}
+
+impl KeyProvider for DerivedKeyProvider {
+    fn file_key(&self, _path: &Path) -> io::Result<SgxKey128Secret> {
+        Ok(self.key.clone())
+    }
+}
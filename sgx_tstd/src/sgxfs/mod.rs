@@ -17,12 +17,17 @@
 
 //! Filesystem manipulation operations.
 
-use sgx_types::{sgx_key_128bit_t, sgx_align_key_128bit_t};
+use sgx_types::{sgx_key_128bit_t, sgx_align_key_128bit_t, sgx_ec256_public_t};
 use crate::io::{self, SeekFrom, Seek, Read, Initializer, Write};
-use crate::path::Path;
+use crate::path::{Path, PathBuf};
 use crate::sys::sgxfs as fs_imp;
 use crate::sys_common::{AsInner, AsInnerMut, FromInner, IntoInner};
 
+pub mod vfs;
+
+mod key;
+pub use self::key::{DerivedKeyProvider, KeyProvider};
+
 /// A reference to an open file on the filesystem.
 ///
 /// An instance of a `File` can be read and/or written depending on what options
@@ -44,6 +49,17 @@ pub struct SgxFile {
 #[derive(Clone, Debug)]
 pub struct OpenOptions(fs_imp::OpenOptions);
 
+/// Metadata information about a protected file.
+///
+/// This structure is returned from the [`metadata`] function or method and
+/// describes the content of the file as seen from inside the enclave, not the
+/// encrypted file on the disk.
+///
+#[derive(Clone, Debug)]
+pub struct Metadata {
+    len: u64,
+}
+
 /// Read the entire contents of a file into a bytes vector.
 ///
 /// This is a convenience function for using SgxFile::open and read_to_end
@@ -126,6 +142,19 @@ impl SgxFile {
         OpenOptions::new().write(true).open_ex(path.as_ref(), key)
     }
 
+    /// Attempts to open a file in read-only mode, with the key `provider`
+    /// returns for it.
+    ///
+    pub fn open_with<P: AsRef<Path>, K: KeyProvider + ?Sized>(path: P, provider: &K) -> io::Result<SgxFile> {
+        OpenOptions::new().read(true).open_with(path.as_ref(), provider)
+    }
+
+    /// Opens a file in write-only mode, with the key `provider` returns for it.
+    ///
+    pub fn create_with<P: AsRef<Path>, K: KeyProvider + ?Sized>(path: P, provider: &K) -> io::Result<SgxFile> {
+        OpenOptions::new().write(true).open_with(path.as_ref(), provider)
+    }
+
     pub fn is_eof(&self) -> bool {
         self.inner.is_eof()
     }
@@ -137,6 +166,26 @@ impl SgxFile {
     pub fn clear_cache(&self) -> io::Result<()> {
         self.inner.clear_cache()
     }
+
+    /// Queries metadata about the underlying file.
+    ///
+    pub fn metadata(&self) -> io::Result<Metadata> {
+        self.inner.len().map(|len| Metadata { len })
+    }
+}
+
+impl Metadata {
+    /// Returns the size of the plaintext content of the file, in bytes.
+    ///
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns `true` if the file holds no content.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
 }
 
 impl AsInner<fs_imp::SgxFile> for SgxFile {
@@ -264,6 +313,33 @@ impl OpenOptions {
         self.0.binary(binary); self
     }
 
+    /// Sets the option to protect only the integrity of the file, not its
+    /// confidentiality.
+    ///
+    /// The content is stored in plain text at `path`, where untrusted code
+    /// can read it, and a Merkle tree of SHA-256 hashes over its 4 KiB blocks
+    /// is stored in `<path>.mht`. The root of the tree is authenticated with
+    /// an AES-CMAC tag under the key and signed with an ECDSA key derived
+    /// from it. Every read is checked against the tree, at the cost of one
+    /// block, and fails with `SGX_ERROR_MAC_MISMATCH` if the content or the
+    /// tags were modified outside the enclave. Untrusted code never needs the
+    /// key: it checks the file with `SgxIntegrityFile` from `sgx_ucrypto` and
+    /// the public key returned by [`integrity_public_key`].
+    ///
+    /// Integrity-only files need an explicit key, so they are opened with
+    /// [`open_ex`] or [`open_with`]. The other functions of this module,
+    /// such as [`rename`] and [`copy`], handle encrypted files only.
+    ///
+    /// [`open_ex`]: #method.open_ex
+    /// [`open_with`]: #method.open_with
+    /// [`integrity_public_key`]: fn.integrity_public_key.html
+    /// [`rename`]: fn.rename.html
+    /// [`copy`]: fn.copy.html
+    ///
+    pub fn integrity_only(&mut self, integrity_only: bool) -> &mut OpenOptions {
+        self.0.integrity(integrity_only); self
+    }
+
     /// Opens a file at `path` with the options specified by `self`.
     pub fn open<P: AsRef<Path>>(&self, path: P) -> io::Result<SgxFile> {
         self._open(path.as_ref())
@@ -273,6 +349,19 @@ impl OpenOptions {
         self._open_ex(path.as_ref(), key)
     }
 
+    /// Opens a file at `path` with the options specified by `self` and the
+    /// key `provider` returns for it.
+    ///
+    /// # Errors
+    ///
+    /// This function will return the error of the provider if it cannot
+    /// supply the key, and the errors of [`OpenOptions::open`] otherwise.
+    ///
+    pub fn open_with<P: AsRef<Path>, K: KeyProvider + ?Sized>(&self, path: P, provider: &K) -> io::Result<SgxFile> {
+        let key = provider.file_key(path.as_ref())?;
+        self._open_ex(path.as_ref(), key.expose())
+    }
+
     fn _open(&self, path: &Path) -> io::Result<SgxFile> {
         let inner = fs_imp::SgxFile::open(path, &self.0)?;
         Ok(SgxFile { inner: inner })
@@ -304,6 +393,17 @@ pub fn import_auto_key<P: AsRef<Path>>(path: P, key: &sgx_key_128bit_t) -> io::R
     fs_imp::import_auto_key(path.as_ref(), key)
 }
 
+/// Returns the public key that verifies the integrity-only files written
+/// with `key`.
+///
+/// The key is derived from `key`, so the same key always gives the same
+/// public key. It can be handed to untrusted code, which checks the files
+/// with `SgxIntegrityFile` from `sgx_ucrypto` without learning `key`.
+///
+pub fn integrity_public_key(key: &sgx_key_128bit_t) -> io::Result<sgx_ec256_public_t> {
+    fs_imp::integrity_public_key(key)
+}
+
 /// Copies the contents of one file to another.
 /// This function will **overwrite** the contents of `to`.
 ///
@@ -315,3 +415,108 @@ pub fn import_auto_key<P: AsRef<Path>>(path: P, key: &sgx_key_128bit_t) -> io::R
 pub fn copy<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> io::Result<u64> {
     fs_imp::copy(from.as_ref(), to.as_ref())
 }
+
+/// Given a path, query the file system to get information about a protected
+/// file opened with the automatic key.
+///
+/// # Errors
+///
+/// This function will return an error if `path` does not exist or is not a
+/// protected file. Other errors may also be returned according to
+/// [`OpenOptions::open`].
+///
+pub fn metadata<P: AsRef<Path>>(path: P) -> io::Result<Metadata> {
+    SgxFile::open(path)?.metadata()
+}
+
+/// Same as [`metadata`], for a protected file opened with `key`.
+///
+pub fn metadata_ex<P: AsRef<Path>>(path: P, key: &sgx_key_128bit_t) -> io::Result<Metadata> {
+    SgxFile::open_ex(path, key)?.metadata()
+}
+
+/// Rename a protected file opened with the automatic key, replacing `to` if
+/// it already exists.
+///
+/// The name of a protected file is part of its integrity protected meta
+/// data, so a file renamed on the untrusted file system no longer opens. When
+/// the file name changes, this function writes a new protected file under the
+/// new name next to `to`, moves it over `to` in one step, and then removes
+/// `from`. `to` is therefore either left untouched or holds the complete
+/// content of `from`; if the enclave stops before `from` is removed, both
+/// files remain.
+///
+/// When only the directory changes, the file is moved in place.
+///
+/// # Errors
+///
+/// This function will return an error if `from` does not exist or is not a
+/// protected file, if `to` has no file name, or if the directory of `to` is
+/// not writable.
+///
+pub fn rename<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> io::Result<()> {
+    fs_imp::rename(from.as_ref(), to.as_ref(), None)
+}
+
+/// Same as [`rename`], for a protected file opened with `key`.
+///
+pub fn rename_ex<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q, key: &sgx_key_128bit_t) -> io::Result<()> {
+    fs_imp::rename(from.as_ref(), to.as_ref(), Some(key))
+}
+
+/// Truncate or extend a protected file opened with the automatic key to
+/// `size` bytes.
+///
+/// An extended file is filled with zeros. A protected file cannot shrink in
+/// place, so a truncated file is rewritten and replaced in one step, as
+/// [`rename`] does.
+///
+pub fn truncate<P: AsRef<Path>>(path: P, size: u64) -> io::Result<()> {
+    fs_imp::truncate(path.as_ref(), size, None)
+}
+
+/// Same as [`truncate`], for a protected file opened with `key`.
+///
+pub fn truncate_ex<P: AsRef<Path>>(path: P, size: u64, key: &sgx_key_128bit_t) -> io::Result<()> {
+    fs_imp::truncate(path.as_ref(), size, Some(key))
+}
+
+/// Re-encrypts a protected file from the key `old` returns for it to the key
+/// `new` returns for it.
+///
+/// The content is decrypted and encrypted again inside the enclave, into a
+/// new file that replaces `path` in one step, as [`rename`] does. Use it to
+/// rotate keys, or to move a file from one key provider to another.
+///
+/// # Errors
+///
+/// This function will return an error if either provider cannot supply the
+/// key, or if `path` cannot be opened with the old key.
+///
+pub fn rekey<P, K, N>(path: P, old: &K, new: &N) -> io::Result<()>
+where
+    P: AsRef<Path>,
+    K: KeyProvider + ?Sized,
+    N: KeyProvider + ?Sized,
+{
+    let path = path.as_ref();
+    let old_key = old.file_key(path)?;
+    let new_key = new.file_key(path)?;
+    fs_imp::rekey(path, old_key.expose(), new_key.expose())
+}
+
+/// Returns the paths of the protected files in a directory, sorted.
+///
+/// A file is listed when it starts with the meta data header of a protected
+/// file. The listing comes from the untrusted file system: the host can hide,
+/// add or swap files, and a listed file is only authenticated once it is
+/// opened.
+///
+/// # Errors
+///
+/// This function will return an error if `path` does not exist or is not a
+/// directory.
+///
+pub fn read_dir<P: AsRef<Path>>(path: P) -> io::Result<Vec<PathBuf>> {
+    fs_imp::read_dir(path.as_ref())
+}
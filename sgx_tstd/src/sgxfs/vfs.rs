@@ -0,0 +1,924 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//! A protected file system with an authenticated directory tree.
+//!
+//! Each protected file is protected on its own: the host can still swap two
+//! files, delete one, put back an older copy of one, and it sees every file
+//! name. `ProtectedFs` stores a whole directory tree in one directory of the
+//! untrusted file system and protects it as a unit:
+//!
+//! * every file is kept in a protected file (an object) with a random name
+//!   and its own random key, so names and the shape of the tree stay hidden;
+//! * an index object holds the tree: names, sizes, keys and versions;
+//! * a root file, sealed with the automatic key or protected with the key
+//!   given to `create_ex` and `open_ex`, holds the key and SHA-256 hash of
+//!   the current index, and the version of the tree.
+//!
+//! Every change writes new objects and then replaces the root in one step.
+//! An object that is swapped, deleted or replaced by an older copy no longer
+//! matches the key and size recorded in the index, and fails to open. The
+//! tree could still be rolled back as a whole, by putting back an older root
+//! together with its objects, so every tree is bound to a `MonotonicCounter`:
+//! the counter is brought up to the version of the tree once the new root is
+//! in place, and a root older than the counter is rejected when the tree is
+//! opened.
+//!
+//! Paths are relative to the root of the tree and use `/` as separator. They
+//! must be valid UTF-8 and must not contain `..`.
+
+use crate::collections::BTreeMap;
+use crate::io::{self, Error, ErrorKind, Read, Seek, SeekFrom, Write};
+use crate::path::{Component, Path, PathBuf};
+use crate::sgxfs::SgxFile;
+use crate::untrusted::fs;
+use sgx_trts::ct::ct_eq;
+use sgx_trts::trts::rsgx_read_rand;
+use sgx_types::{
+    sgx_key_128bit_t, sgx_sha256_hash_t, sgx_sha256_msg, sgx_status_t, zeroize_bytes, MonotonicCounter,
+    SgxKey128Secret,
+};
+use core::ops::{Deref, DerefMut};
+
+const ROOT_NAME: &str = "root";
+const STAGING_NAME: &str = ".staging";
+const ROOT_MAGIC: [u8; 4] = *b"SGXV";
+const INDEX_MAGIC: [u8; 4] = *b"SGXI";
+const FORMAT_VERSION: u32 = 1;
+const ROOT_SIZE: usize = 88;
+
+const ENTRY_DIR: u8 = 0;
+const ENTRY_FILE: u8 = 1;
+
+type ObjectId = [u8; 16];
+
+#[derive(Clone)]
+struct FileEntry {
+    id: ObjectId,
+    key: sgx_key_128bit_t,
+    size: u64,
+    version: u64,
+}
+
+impl Drop for FileEntry {
+    fn drop(&mut self) {
+        zeroize_bytes(&mut self.key);
+    }
+}
+
+#[derive(Clone)]
+enum Entry {
+    Dir,
+    File(FileEntry),
+}
+
+// The root and the index hold keys in plain text. They are built and read
+// in a buffer of their final size, so that no copy is left behind by a
+// reallocation, and wiped when dropped, on every path.
+struct SecretBuf(Vec<u8>);
+
+impl SecretBuf {
+    fn zeroed(len: usize) -> SecretBuf {
+        SecretBuf(vec![0_u8; len])
+    }
+
+    fn with_capacity(capacity: usize) -> SecretBuf {
+        SecretBuf(Vec::with_capacity(capacity))
+    }
+}
+
+impl Deref for SecretBuf {
+    type Target = Vec<u8>;
+
+    fn deref(&self) -> &Vec<u8> {
+        &self.0
+    }
+}
+
+impl DerefMut for SecretBuf {
+    fn deref_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.0
+    }
+}
+
+impl Drop for SecretBuf {
+    fn drop(&mut self) {
+        let capacity = self.0.capacity();
+        self.0.resize(capacity, 0);
+        zeroize_bytes(&mut self.0);
+    }
+}
+
+/// A directory tree stored as protected files, see the [module documentation](index.html).
+///
+/// Operations that change the tree take `&mut self` and are committed to the
+/// disk before they return.
+///
+pub struct ProtectedFs<C: MonotonicCounter> {
+    root: PathBuf,
+    key: Option<sgx_key_128bit_t>,
+    counter: C,
+    version: u64,
+    index_id: ObjectId,
+    entries: BTreeMap<String, Entry>,
+}
+
+/// Metadata information about a file or directory of a `ProtectedFs`.
+///
+#[derive(Clone, Debug)]
+pub struct Metadata {
+    is_dir: bool,
+    len: u64,
+    version: u64,
+}
+
+/// An entry returned by `ProtectedFs::read_dir`.
+///
+#[derive(Clone, Debug)]
+pub struct DirEntry {
+    path: PathBuf,
+    metadata: Metadata,
+}
+
+/// A file of a `ProtectedFs` opened for reading.
+///
+pub struct File {
+    inner: SgxFile,
+}
+
+/// A file of a `ProtectedFs` being written.
+///
+/// The content is written to a new object and only replaces the previous
+/// content of the file once `finish` returns. A writer that is dropped
+/// without `finish` leaves the file unchanged.
+///
+pub struct FileWriter<'a, C: MonotonicCounter> {
+    fs: &'a mut ProtectedFs<C>,
+    path: String,
+    id: ObjectId,
+    key: sgx_key_128bit_t,
+    inner: Option<SgxFile>,
+    len: u64,
+    committed: bool,
+}
+
+impl<C: MonotonicCounter> ProtectedFs<C> {
+    /// Creates an empty tree in the directory `dir`, with a root sealed with
+    /// the automatic key and bound to `counter`. The directory is created if
+    /// it does not exist, and the version of the tree starts from the current
+    /// value of the counter.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error of kind `AlreadyExists` if `dir`
+    /// already holds a tree.
+    ///
+    pub fn create<P: AsRef<Path>>(dir: P, counter: C) -> io::Result<ProtectedFs<C>> {
+        ProtectedFs::init(dir.as_ref(), None, counter)
+    }
+
+    /// Same as `create`, with a root protected with `key`.
+    ///
+    pub fn create_ex<P: AsRef<Path>>(dir: P, key: &sgx_key_128bit_t, counter: C) -> io::Result<ProtectedFs<C>> {
+        ProtectedFs::init(dir.as_ref(), Some(*key), counter)
+    }
+
+    /// Opens the tree stored in the directory `dir` by `create`, with the
+    /// counter it was created with.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error of kind `InvalidData` if the index
+    /// does not match the root, or if the version of the root is older than
+    /// the counter, which means the tree was rolled back. It returns the
+    /// errors of `SgxFile::open` if the root or the index cannot be opened.
+    ///
+    pub fn open<P: AsRef<Path>>(dir: P, counter: C) -> io::Result<ProtectedFs<C>> {
+        ProtectedFs::load(dir.as_ref(), None, counter)
+    }
+
+    /// Same as `open`, for a tree created by `create_ex` with `key`.
+    ///
+    pub fn open_ex<P: AsRef<Path>>(dir: P, key: &sgx_key_128bit_t, counter: C) -> io::Result<ProtectedFs<C>> {
+        ProtectedFs::load(dir.as_ref(), Some(*key), counter)
+    }
+
+    /// Returns the version of the tree, which is incremented by every change.
+    ///
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Returns the directory the tree is stored in.
+    ///
+    pub fn path(&self) -> &Path {
+        &self.root
+    }
+
+    /// Creates a new, empty directory.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error of kind `AlreadyExists` if `path`
+    /// already exists, and of kind `NotFound` if its parent does not exist.
+    ///
+    pub fn create_dir<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
+        let path = normalize(path.as_ref())?;
+        check_parent(&self.entries, &path)?;
+        if self.entries.contains_key(&path) {
+            return Err(Error::new(ErrorKind::AlreadyExists, "entry already exists"));
+        }
+        let mut entries = self.entries.clone();
+        entries.insert(path, Entry::Dir);
+        self.commit(entries, Vec::new())
+    }
+
+    /// Creates a directory and all of its missing parents.
+    ///
+    pub fn create_dir_all<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
+        let path = normalize(path.as_ref())?;
+        if path.is_empty() {
+            return Ok(());
+        }
+        let mut entries = self.entries.clone();
+        let mut created = false;
+        for (i, _) in path.match_indices('/').chain(Some((path.len(), ""))) {
+            match entries.get(&path[..i]) {
+                Some(Entry::Dir) => continue,
+                Some(Entry::File(_)) => {
+                    return Err(Error::new(ErrorKind::AlreadyExists, "entry is not a directory"))
+                }
+                None => {
+                    entries.insert(path[..i].to_string(), Entry::Dir);
+                    created = true;
+                }
+            }
+        }
+        if created {
+            self.commit(entries, Vec::new())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Removes an empty directory.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `path` is not a directory or is
+    /// not empty.
+    ///
+    pub fn remove_dir<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
+        let path = normalize(path.as_ref())?;
+        match self.entries.get(&path) {
+            Some(Entry::Dir) => (),
+            Some(Entry::File(_)) => return Err(Error::new(ErrorKind::InvalidInput, "entry is not a directory")),
+            None => return Err(Error::new(ErrorKind::NotFound, "entry not found")),
+        }
+        if children(&self.entries, &path).next().is_some() {
+            return Err(Error::new(ErrorKind::Other, "directory not empty"));
+        }
+        let mut entries = self.entries.clone();
+        entries.remove(&path);
+        self.commit(entries, Vec::new())
+    }
+
+    /// Removes a file.
+    ///
+    pub fn remove_file<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
+        let path = normalize(path.as_ref())?;
+        let id = match self.entries.get(&path) {
+            Some(Entry::File(file)) => file.id,
+            Some(Entry::Dir) => return Err(Error::new(ErrorKind::InvalidInput, "entry is a directory")),
+            None => return Err(Error::new(ErrorKind::NotFound, "entry not found")),
+        };
+        let mut entries = self.entries.clone();
+        entries.remove(&path);
+        self.commit(entries, vec![id])
+    }
+
+    /// Renames a file or directory, replacing `to` if it is a file and `from`
+    /// is a file too.
+    ///
+    /// Only the index changes: the content of the files is not rewritten.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `from` does not exist, if the
+    /// parent of `to` does not exist, if `to` is a directory, or if `from` is a
+    /// directory and `to` exists or is inside `from`.
+    ///
+    pub fn rename<P: AsRef<Path>, Q: AsRef<Path>>(&mut self, from: P, to: Q) -> io::Result<()> {
+        let from = normalize(from.as_ref())?;
+        let to = normalize(to.as_ref())?;
+        check_parent(&self.entries, &to)?;
+        let entry = self
+            .entries
+            .get(&from)
+            .ok_or_else(|| Error::new(ErrorKind::NotFound, "entry not found"))?;
+        if from == to {
+            return Ok(());
+        }
+
+        let mut garbage = Vec::new();
+        match (entry, self.entries.get(&to)) {
+            (_, None) => (),
+            (Entry::File(_), Some(Entry::File(old))) => garbage.push(old.id),
+            _ => return Err(Error::new(ErrorKind::AlreadyExists, "entry already exists")),
+        }
+        if let Entry::Dir = entry {
+            if to.starts_with(&from) && to.as_bytes()[from.len()] == b'/' {
+                return Err(Error::new(ErrorKind::InvalidInput, "cannot move a directory into itself"));
+            }
+        }
+
+        let mut entries = self.entries.clone();
+        let moved: Vec<String> = children(&self.entries, &from).map(|(path, _)| path.clone()).collect();
+        for path in moved {
+            if let Some(entry) = entries.remove(&path) {
+                entries.insert(format!("{}{}", to, &path[from.len()..]), entry);
+            }
+        }
+        if let Some(entry) = entries.remove(&from) {
+            entries.insert(to, entry);
+        }
+        self.commit(entries, garbage)
+    }
+
+    /// Returns the entries of a directory, sorted by path.
+    ///
+    pub fn read_dir<P: AsRef<Path>>(&self, path: P) -> io::Result<Vec<DirEntry>> {
+        let path = normalize(path.as_ref())?;
+        if !is_dir(&self.entries, &path) {
+            return Err(Error::new(ErrorKind::NotFound, "directory not found"));
+        }
+        let skip = if path.is_empty() { 0 } else { path.len() + 1 };
+        Ok(children(&self.entries, &path)
+            .filter(|(child, _)| !child[skip..].contains('/'))
+            .map(|(child, entry)| DirEntry {
+                path: PathBuf::from(child),
+                metadata: metadata(entry),
+            })
+            .collect())
+    }
+
+    /// Queries metadata about a file or directory.
+    ///
+    pub fn metadata<P: AsRef<Path>>(&self, path: P) -> io::Result<Metadata> {
+        let path = normalize(path.as_ref())?;
+        if path.is_empty() {
+            return Ok(metadata(&Entry::Dir));
+        }
+        self.entries
+            .get(&path)
+            .map(metadata)
+            .ok_or_else(|| Error::new(ErrorKind::NotFound, "entry not found"))
+    }
+
+    /// Opens a file for reading.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error of kind `InvalidData` if the object
+    /// of the file does not match the index, for example because it was
+    /// swapped, truncated or rolled back.
+    ///
+    pub fn open_file<P: AsRef<Path>>(&self, path: P) -> io::Result<File> {
+        let path = normalize(path.as_ref())?;
+        let file = match self.entries.get(&path) {
+            Some(Entry::File(file)) => file,
+            Some(Entry::Dir) => return Err(Error::new(ErrorKind::InvalidInput, "entry is a directory")),
+            None => return Err(Error::new(ErrorKind::NotFound, "entry not found")),
+        };
+        let inner = self.open_object(&file.id, &file.key, file.size)?;
+        Ok(File { inner })
+    }
+
+    /// Creates a file, or replaces the content of an existing one.
+    ///
+    /// The new content is committed by `FileWriter::finish`.
+    ///
+    pub fn create_file<P: AsRef<Path>>(&mut self, path: P) -> io::Result<FileWriter<'_, C>> {
+        let path = normalize(path.as_ref())?;
+        check_parent(&self.entries, &path)?;
+        if let Some(Entry::Dir) = self.entries.get(&path) {
+            return Err(Error::new(ErrorKind::InvalidInput, "entry is a directory"));
+        }
+        let id = random_id()?;
+        let mut key = SgxKey128Secret::zeroed();
+        rsgx_read_rand(key.as_bytes_mut()).map_err(Error::from_sgx_error)?;
+        let inner = SgxFile::create_ex(self.object_path(&id), &key)?;
+        Ok(FileWriter {
+            fs: self,
+            path,
+            id,
+            key: *key.expose(),
+            inner: Some(inner),
+            len: 0,
+            committed: false,
+        })
+    }
+
+    /// Reads the entire contents of a file.
+    ///
+    pub fn read<P: AsRef<Path>>(&self, path: P) -> io::Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        self.open_file(path)?.read_to_end(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Writes a slice as the entire contents of a file.
+    ///
+    pub fn write<P: AsRef<Path>, D: AsRef<[u8]>>(&mut self, path: P, contents: D) -> io::Result<()> {
+        let mut writer = self.create_file(path)?;
+        writer.write_all(contents.as_ref())?;
+        writer.finish()
+    }
+
+    fn init(dir: &Path, key: Option<sgx_key_128bit_t>, mut counter: C) -> io::Result<ProtectedFs<C>> {
+        fs::create_dir_all(dir)?;
+        if fs::metadata(dir.join(ROOT_NAME)).is_ok() {
+            return Err(Error::new(ErrorKind::AlreadyExists, "directory already holds a protected file system"));
+        }
+        let version = counter.read().map_err(Error::from_sgx_error)?;
+        let mut pfs = ProtectedFs {
+            root: dir.to_path_buf(),
+            key,
+            counter,
+            version,
+            index_id: ObjectId::default(),
+            entries: BTreeMap::new(),
+        };
+        pfs.commit(BTreeMap::new(), Vec::new())?;
+        Ok(pfs)
+    }
+
+    fn load(dir: &Path, key: Option<sgx_key_128bit_t>, counter: C) -> io::Result<ProtectedFs<C>> {
+        let mut file = match key {
+            Some(ref key) => SgxFile::open_ex(dir.join(ROOT_NAME), key)?,
+            None => SgxFile::open(dir.join(ROOT_NAME))?,
+        };
+        if file.metadata()?.len() != ROOT_SIZE as u64 {
+            return Err(invalid_data("invalid root"));
+        }
+        let mut root = SecretBuf::zeroed(ROOT_SIZE);
+        file.read_exact(&mut root)?;
+        drop(file);
+        if root[..4] != ROOT_MAGIC || read_u32(&root[4..]) != FORMAT_VERSION {
+            return Err(invalid_data("invalid root"));
+        }
+
+        let version = read_u64(&root[8..]);
+        let mut index_id = ObjectId::default();
+        index_id.copy_from_slice(&root[16..32]);
+        let mut index_key = SgxKey128Secret::zeroed();
+        index_key.copy_from_slice(&root[32..48]);
+        let index_len = read_u64(&root[48..]);
+        let mut hash = sgx_sha256_hash_t::default();
+        hash.copy_from_slice(&root[56..88]);
+        drop(root);
+        let mut pfs = ProtectedFs {
+            root: dir.to_path_buf(),
+            key,
+            counter,
+            version,
+            index_id,
+            entries: BTreeMap::new(),
+        };
+
+        if index_len > isize::MAX as u64 {
+            return Err(invalid_data("invalid root"));
+        }
+        let mut file = pfs.open_object(&index_id, &index_key, index_len)?;
+        drop(index_key);
+        let mut index = SecretBuf::zeroed(index_len as usize);
+        file.read_exact(&mut index)?;
+        drop(file);
+        if !ct_eq(&sha256(&index)?, &hash) {
+            return Err(invalid_data("index does not match the root"));
+        }
+        pfs.entries = decode_index(&index)?;
+        pfs.sync_counter()?;
+        Ok(pfs)
+    }
+
+    // Writes `entries` as a new index and points the root to it. The tree is
+    // only updated once the new root is in place; the objects in `garbage`
+    // are removed afterwards.
+    fn commit(&mut self, entries: BTreeMap<String, Entry>, garbage: Vec<ObjectId>) -> io::Result<()> {
+        let version = self
+            .version
+            .checked_add(1)
+            .ok_or_else(|| Error::new(ErrorKind::Other, "version overflow"))?;
+        let index = encode_index(&entries);
+        let hash = sha256(&index)?;
+        let index_id = random_id()?;
+        let mut index_key = SgxKey128Secret::zeroed();
+        rsgx_read_rand(index_key.as_bytes_mut()).map_err(Error::from_sgx_error)?;
+
+        let mut root = SecretBuf::zeroed(ROOT_SIZE);
+        root[..4].copy_from_slice(&ROOT_MAGIC);
+        root[4..8].copy_from_slice(&FORMAT_VERSION.to_le_bytes());
+        root[8..16].copy_from_slice(&version.to_le_bytes());
+        root[16..32].copy_from_slice(&index_id);
+        root[32..48].copy_from_slice(index_key.as_bytes());
+        root[48..56].copy_from_slice(&(index.len() as u64).to_le_bytes());
+        root[56..88].copy_from_slice(&hash);
+
+        let result = self
+            .write_object(&index_id, &index_key, &index)
+            .and_then(|_| self.replace_root(&root));
+        drop(index_key);
+        drop(root);
+        drop(index);
+        if let Err(err) = result {
+            let _ = fs::remove_file(self.object_path(&index_id));
+            return Err(err);
+        }
+
+        let old_index = self.index_id;
+        self.index_id = index_id;
+        self.version = version;
+        self.entries = entries;
+        if old_index != ObjectId::default() {
+            let _ = fs::remove_file(self.object_path(&old_index));
+        }
+        for id in garbage {
+            let _ = fs::remove_file(self.object_path(&id));
+        }
+        self.sync_counter()
+    }
+
+    // Rejects a root older than the counter, and increments the counter up to
+    // the version of the root. The counter is only incremented once the new
+    // root is in place, so it lags behind the root when the enclave stops in
+    // between; `load` then catches up.
+    fn sync_counter(&mut self) -> io::Result<()> {
+        let mut value = self.counter.read().map_err(Error::from_sgx_error)?;
+        if value > self.version {
+            return Err(invalid_data("root is older than the monotonic counter"));
+        }
+        while value < self.version {
+            value = self.counter.increment().map_err(Error::from_sgx_error)?;
+        }
+        Ok(())
+    }
+
+    // The name of a protected file is bound into it, so the new root is
+    // written under the same name in a staging directory and then moved over
+    // the current one.
+    fn replace_root(&self, root: &[u8]) -> io::Result<()> {
+        let staging = self.root.join(STAGING_NAME);
+        if let Err(err) = fs::create_dir(&staging) {
+            if err.kind() != ErrorKind::AlreadyExists {
+                return Err(err);
+            }
+        }
+        let staged = staging.join(ROOT_NAME);
+        let mut file = match self.key {
+            Some(ref key) => SgxFile::create_ex(&staged, key)?,
+            None => SgxFile::create(&staged)?,
+        };
+        file.write_all(root)?;
+        file.flush()?;
+        drop(file);
+        fs::rename(&staged, self.root.join(ROOT_NAME))
+    }
+
+    fn write_object(&self, id: &ObjectId, key: &sgx_key_128bit_t, data: &[u8]) -> io::Result<()> {
+        let mut file = SgxFile::create_ex(self.object_path(id), key)?;
+        file.write_all(data)?;
+        file.flush()
+    }
+
+    fn open_object(&self, id: &ObjectId, key: &sgx_key_128bit_t, len: u64) -> io::Result<SgxFile> {
+        let file = SgxFile::open_ex(self.object_path(id), key)?;
+        if file.metadata()?.len() != len {
+            return Err(invalid_data("object does not match the index"));
+        }
+        Ok(file)
+    }
+
+    fn object_path(&self, id: &ObjectId) -> PathBuf {
+        let mut name = String::with_capacity(id.len() * 2);
+        for b in id.iter() {
+            name.push_str(&format!("{:02x}", b));
+        }
+        self.root.join(name)
+    }
+}
+
+impl Metadata {
+    /// Returns `true` if this metadata is for a directory.
+    ///
+    pub fn is_dir(&self) -> bool {
+        self.is_dir
+    }
+
+    /// Returns `true` if this metadata is for a file.
+    ///
+    pub fn is_file(&self) -> bool {
+        !self.is_dir
+    }
+
+    /// Returns the size of the file, in bytes, or zero for a directory.
+    ///
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns `true` if the file is empty.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of times the content of the file was written, or
+    /// zero for a directory.
+    ///
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+}
+
+impl DirEntry {
+    /// Returns the path of the entry, relative to the root of the tree.
+    ///
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the metadata of the entry.
+    ///
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+}
+
+impl File {
+    /// Returns the size of the file, in bytes.
+    ///
+    pub fn len(&self) -> io::Result<u64> {
+        self.inner.metadata().map(|metadata| metadata.len())
+    }
+}
+
+impl Read for File {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.read(buf)
+    }
+}
+
+impl Seek for File {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+impl<'a, C: MonotonicCounter> FileWriter<'a, C> {
+    /// Commits the content written so far as the content of the file.
+    ///
+    pub fn finish(mut self) -> io::Result<()> {
+        if let Some(mut inner) = self.inner.take() {
+            inner.flush()?;
+        }
+        let (version, garbage) = match self.fs.entries.get(&self.path) {
+            Some(Entry::File(old)) => (old.version + 1, vec![old.id]),
+            Some(Entry::Dir) => return Err(Error::new(ErrorKind::InvalidInput, "entry is a directory")),
+            None => (1, Vec::new()),
+        };
+        let mut entries = self.fs.entries.clone();
+        entries.insert(
+            self.path.clone(),
+            Entry::File(FileEntry {
+                id: self.id,
+                key: self.key,
+                size: self.len,
+                version,
+            }),
+        );
+        self.fs.commit(entries, garbage)?;
+        self.committed = true;
+        Ok(())
+    }
+}
+
+impl<'a, C: MonotonicCounter> Write for FileWriter<'a, C> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let inner = self
+            .inner
+            .as_mut()
+            .ok_or_else(|| Error::new(ErrorKind::Other, "writer is finished"))?;
+        let n = inner.write(buf)?;
+        self.len += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.inner {
+            Some(ref mut inner) => inner.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<'a, C: MonotonicCounter> Drop for FileWriter<'a, C> {
+    fn drop(&mut self) {
+        drop(self.inner.take());
+        if !self.committed {
+            let _ = fs::remove_file(self.fs.object_path(&self.id));
+        }
+        zeroize_bytes(&mut self.key);
+    }
+}
+
+impl<C: MonotonicCounter> Drop for ProtectedFs<C> {
+    fn drop(&mut self) {
+        if let Some(ref mut key) = self.key {
+            zeroize_bytes(key);
+        }
+    }
+}
+
+fn normalize(path: &Path) -> io::Result<String> {
+    let mut parts = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::RootDir | Component::CurDir => (),
+            Component::Normal(name) => parts.push(
+                name.to_str()
+                    .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "path is not valid UTF-8"))?,
+            ),
+            _ => return Err(Error::new(ErrorKind::InvalidInput, "path must not contain `..`")),
+        }
+    }
+    Ok(parts.join("/"))
+}
+
+fn is_dir(entries: &BTreeMap<String, Entry>, path: &str) -> bool {
+    if let Some(Entry::Dir) = entries.get(path) {
+        true
+    } else {
+        path.is_empty()
+    }
+}
+
+fn check_parent(entries: &BTreeMap<String, Entry>, path: &str) -> io::Result<()> {
+    if path.is_empty() {
+        return Err(Error::new(ErrorKind::InvalidInput, "path is the root of the tree"));
+    }
+    let parent = path.rfind('/').map(|i| &path[..i]).unwrap_or("");
+    if is_dir(entries, parent) {
+        Ok(())
+    } else {
+        Err(Error::new(ErrorKind::NotFound, "parent directory not found"))
+    }
+}
+
+// All the entries below `path`, at any depth.
+fn children<'a>(
+    entries: &'a BTreeMap<String, Entry>,
+    path: &str,
+) -> impl Iterator<Item = (&'a String, &'a Entry)> {
+    let prefix = if path.is_empty() { String::new() } else { format!("{}/", path) };
+    entries
+        .range(prefix.clone()..)
+        .take_while(move |(child, _)| child.starts_with(&prefix))
+}
+
+fn metadata(entry: &Entry) -> Metadata {
+    match entry {
+        Entry::Dir => Metadata { is_dir: true, len: 0, version: 0 },
+        Entry::File(file) => Metadata {
+            is_dir: false,
+            len: file.size,
+            version: file.version,
+        },
+    }
+}
+
+// index: magic "SGXI" | format u32 | count u32 | entries
+// entry: path length u32 | path | kind u8 | for a file: id [u8; 16] | key [u8; 16] | size u64 | version u64
+fn encode_index(entries: &BTreeMap<String, Entry>) -> SecretBuf {
+    let len = entries.iter().fold(12, |len, (path, entry)| match entry {
+        Entry::Dir => len + 5 + path.len(),
+        Entry::File(_) => len + 53 + path.len(),
+    });
+    let mut index = SecretBuf::with_capacity(len);
+    index.extend_from_slice(&INDEX_MAGIC);
+    index.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+    index.extend_from_slice(&(entries.len() as u32).to_le_bytes());
+    for (path, entry) in entries {
+        index.extend_from_slice(&(path.len() as u32).to_le_bytes());
+        index.extend_from_slice(path.as_bytes());
+        match entry {
+            Entry::Dir => index.push(ENTRY_DIR),
+            Entry::File(file) => {
+                index.push(ENTRY_FILE);
+                index.extend_from_slice(&file.id);
+                index.extend_from_slice(&file.key);
+                index.extend_from_slice(&file.size.to_le_bytes());
+                index.extend_from_slice(&file.version.to_le_bytes());
+            }
+        }
+    }
+    index
+}
+
+fn decode_index(index: &[u8]) -> io::Result<BTreeMap<String, Entry>> {
+    let mut reader = IndexReader { buf: index };
+    if reader.take(4)? != INDEX_MAGIC || read_u32(reader.take(4)?) != FORMAT_VERSION {
+        return Err(invalid_data("invalid index"));
+    }
+    let count = read_u32(reader.take(4)?);
+    let mut entries = BTreeMap::new();
+    for _ in 0..count {
+        let len = read_u32(reader.take(4)?) as usize;
+        let path = String::from_utf8(reader.take(len)?.to_vec()).map_err(|_| invalid_data("invalid index"))?;
+        let entry = match reader.take(1)?[0] {
+            ENTRY_DIR => Entry::Dir,
+            ENTRY_FILE => {
+                let mut file = FileEntry {
+                    id: ObjectId::default(),
+                    key: sgx_key_128bit_t::default(),
+                    size: 0,
+                    version: 0,
+                };
+                file.id.copy_from_slice(reader.take(16)?);
+                file.key.copy_from_slice(reader.take(16)?);
+                file.size = read_u64(reader.take(8)?);
+                file.version = read_u64(reader.take(8)?);
+                Entry::File(file)
+            }
+            _ => return Err(invalid_data("invalid index")),
+        };
+        entries.insert(path, entry);
+    }
+    if !reader.buf.is_empty() {
+        return Err(invalid_data("invalid index"));
+    }
+    Ok(entries)
+}
+
+struct IndexReader<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> IndexReader<'a> {
+    fn take(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        if self.buf.len() < len {
+            return Err(invalid_data("invalid index"));
+        }
+        let (head, tail) = self.buf.split_at(len);
+        self.buf = tail;
+        Ok(head)
+    }
+}
+
+fn sha256(data: &[u8]) -> io::Result<sgx_sha256_hash_t> {
+    if data.len() > u32::MAX as usize {
+        return Err(Error::new(ErrorKind::InvalidInput, "index too large"));
+    }
+    let mut hash = sgx_sha256_hash_t::default();
+    let status = unsafe { sgx_sha256_msg(data.as_ptr(), data.len() as u32, &mut hash) };
+    match status {
+        sgx_status_t::SGX_SUCCESS => Ok(hash),
+        status => Err(Error::from_sgx_error(status)),
+    }
+}
+
+fn random_id() -> io::Result<ObjectId> {
+    let mut id = ObjectId::default();
+    rsgx_read_rand(&mut id).map_err(Error::from_sgx_error)?;
+    Ok(id)
+}
+
+fn read_u32(buf: &[u8]) -> u32 {
+    let mut bytes = [0_u8; 4];
+    bytes.copy_from_slice(&buf[..4]);
+    u32::from_le_bytes(bytes)
+}
+
+fn read_u64(buf: &[u8]) -> u64 {
+    let mut bytes = [0_u8; 8];
+    bytes.copy_from_slice(&buf[..8]);
+    u64::from_le_bytes(bytes)
+}
+
+fn invalid_data(msg: &'static str) -> Error {
+    Error::new(ErrorKind::InvalidData, msg)
+}
@@ -15,16 +15,27 @@
 // specific language governing permissions and limitations
 // under the License..
 
-use sgx_types::{sgx_status_t, sgx_key_128bit_t, sgx_align_key_128bit_t};
+use sgx_types::{sgx_status_t, sgx_key_128bit_t, sgx_align_key_128bit_t, sgx_ec256_public_t};
 use sgx_trts::libc;
 use sgx_tprotected_fs::{self, SgxFileStream};
 use crate::os::unix::prelude::*;
 use crate::ffi::{CString, CStr};
 use crate::io::{self, Error, ErrorKind, SeekFrom};
-use crate::path::Path;
+use crate::path::{Path, PathBuf};
 use crate::sys_common::FromInner;
 
-pub struct SgxFile(SgxFileStream);
+mod integrity;
+
+use self::integrity::IntegrityFile;
+
+pub struct SgxFile {
+    stream: Stream,
+}
+
+enum Stream {
+    Protected(SgxFileStream),
+    Integrity(IntegrityFile),
+}
 
 #[derive(Clone, Debug)]
 pub struct OpenOptions {
@@ -33,6 +44,7 @@ pub struct OpenOptions {
     append: bool,
     update: bool,
     binary: bool,
+    integrity: bool,
 }
 
 impl OpenOptions {
@@ -43,6 +55,7 @@ impl OpenOptions {
             append: false,
             update: false,
             binary: false,
+            integrity: false,
         }
     }
 
@@ -51,6 +64,7 @@ impl OpenOptions {
     pub fn append(&mut self, append: bool) { self.append = append; }
     pub fn update(&mut self, update: bool) { self.update = update; }
     pub fn binary(&mut self, binary: bool) { self.binary = binary; }
+    pub fn integrity(&mut self, integrity: bool) { self.integrity = integrity; }
 
     fn get_access_mode(&self) -> io::Result<String> {
 
@@ -72,6 +86,9 @@ impl OpenOptions {
 
 impl SgxFile {
     pub fn open(path: &Path, opts: &OpenOptions) -> io::Result<SgxFile> {
+        if opts.integrity {
+            return Err(Error::new(ErrorKind::InvalidInput, "integrity-only files need an explicit key"));
+        }
         let path = cstr(path)?;
         let mode = opts.get_access_mode()?;
         let opts = CString::new(mode.as_bytes())?;
@@ -79,6 +96,10 @@ impl SgxFile {
     }
 
     pub fn open_ex(path: &Path, opts: &OpenOptions, key: &sgx_key_128bit_t) -> io::Result<SgxFile> {
+        if opts.integrity {
+            let file = IntegrityFile::open(path, opts, key)?;
+            return Ok(SgxFile { stream: Stream::Integrity(file) });
+        }
         let path = cstr(path)?;
         let mode = opts.get_access_mode()?;
         let opts = CString::new(mode.as_bytes())?;
@@ -92,7 +113,7 @@ impl SgxFile {
             SgxFileStream::open(path, opts, key)
         };
 
-        file.map(|stream| SgxFile(stream))
+        file.map(|stream| SgxFile::from_inner(stream))
             .map_err(|err| {
                 match err {
                     1 => Error::from_sgx_error(sgx_status_t::SGX_ERROR_UNEXPECTED),
@@ -109,28 +130,50 @@ impl SgxFile {
     }
 
     pub fn read(&self, buf: &mut [u8]) -> io::Result<usize> {
-        self.0.read(buf).map_err(|err| {
-            match err {
-                1 => Error::from_sgx_error(sgx_status_t::SGX_ERROR_UNEXPECTED),
-                2 => Error::from_sgx_error(sgx_status_t::SGX_ERROR_INVALID_PARAMETER),
-                3 => Error::from_sgx_error(sgx_status_t::SGX_ERROR_OUT_OF_MEMORY),
-                4 | 5 => Error::from_raw_os_error(err),
-                r if r > 4096 => {
-                    let status = sgx_status_t::from_repr(r as u32).unwrap_or(sgx_status_t::SGX_ERROR_UNEXPECTED);
-                    Error::from_sgx_error(status)
-                },
-                _ => Error::from_raw_os_error(err),
-            }
-        })
+        match self.stream {
+            Stream::Protected(ref stream) => stream.read(buf).map_err(|err| {
+                match err {
+                    1 => Error::from_sgx_error(sgx_status_t::SGX_ERROR_UNEXPECTED),
+                    2 => Error::from_sgx_error(sgx_status_t::SGX_ERROR_INVALID_PARAMETER),
+                    3 => Error::from_sgx_error(sgx_status_t::SGX_ERROR_OUT_OF_MEMORY),
+                    4 | 5 => Error::from_raw_os_error(err),
+                    r if r > 4096 => {
+                        let status = sgx_status_t::from_repr(r as u32).unwrap_or(sgx_status_t::SGX_ERROR_UNEXPECTED);
+                        Error::from_sgx_error(status)
+                    },
+                    _ => Error::from_raw_os_error(err),
+                }
+            }),
+            Stream::Integrity(ref file) => file.read(buf),
+        }
     }
 
     pub fn write(&self, buf: &[u8]) -> io::Result<usize> {
-        self.0.write(buf).map_err(|err| {
+        match self.stream {
+            Stream::Protected(ref stream) => stream.write(buf).map_err(|err| {
+                match err {
+                    1 => Error::from_sgx_error(sgx_status_t::SGX_ERROR_UNEXPECTED),
+                    2 => Error::from_sgx_error(sgx_status_t::SGX_ERROR_INVALID_PARAMETER),
+                    3 => Error::from_sgx_error(sgx_status_t::SGX_ERROR_OUT_OF_MEMORY),
+                    4 | 5 => Error::from_raw_os_error(err),
+                    r if r > 4096 => {
+                        let status = sgx_status_t::from_repr(r as u32).unwrap_or(sgx_status_t::SGX_ERROR_UNEXPECTED);
+                        Error::from_sgx_error(status)
+                    },
+                    _ => Error::from_raw_os_error(err),
+                }
+            }),
+            Stream::Integrity(ref file) => file.write(buf),
+        }
+    }
+
+    pub fn tell(&self) -> io::Result<u64> {
+        let stream = match self.stream {
+            Stream::Protected(ref stream) => stream,
+            Stream::Integrity(ref file) => return file.tell(),
+        };
+        stream.tell().map_err(|err| {
             match err {
-                1 => Error::from_sgx_error(sgx_status_t::SGX_ERROR_UNEXPECTED),
-                2 => Error::from_sgx_error(sgx_status_t::SGX_ERROR_INVALID_PARAMETER),
-                3 => Error::from_sgx_error(sgx_status_t::SGX_ERROR_OUT_OF_MEMORY),
-                4 | 5 => Error::from_raw_os_error(err),
                 r if r > 4096 => {
                     let status = sgx_status_t::from_repr(r as u32).unwrap_or(sgx_status_t::SGX_ERROR_UNEXPECTED);
                     Error::from_sgx_error(status)
@@ -138,10 +181,15 @@ impl SgxFile {
                 _ => Error::from_raw_os_error(err),
             }
         })
+        .map(|offset| offset as u64)
     }
 
-    pub fn tell(&self) -> io::Result<u64> {
-        self.0.tell().map_err(|err| {
+    pub fn len(&self) -> io::Result<u64> {
+        let stream = match self.stream {
+            Stream::Protected(ref stream) => stream,
+            Stream::Integrity(ref file) => return file.len(),
+        };
+        stream.len().map_err(|err| {
             match err {
                 r if r > 4096 => {
                     let status = sgx_status_t::from_repr(r as u32).unwrap_or(sgx_status_t::SGX_ERROR_UNEXPECTED);
@@ -150,17 +198,20 @@ impl SgxFile {
                 _ => Error::from_raw_os_error(err),
             }
         })
-        .map(|offset| offset as u64)
     }
 
     pub fn seek(&self, pos: SeekFrom) -> io::Result<u64> {
+        let stream = match self.stream {
+            Stream::Protected(ref stream) => stream,
+            Stream::Integrity(ref file) => return file.seek(pos),
+        };
         let (whence, offset) = match pos {
             SeekFrom::Start(off) => (sgx_tprotected_fs::SeekFrom::Start, off as i64),
             SeekFrom::End(off) => (sgx_tprotected_fs::SeekFrom::End, off),
             SeekFrom::Current(off) => (sgx_tprotected_fs::SeekFrom::Current, off),
         };
 
-        self.0.seek(offset, whence).map_err(|err| {
+        stream.seek(offset, whence).map_err(|err| {
             match err {
                 r if r > 4096 => {
                     let status = sgx_status_t::from_repr(r as u32).unwrap_or(sgx_status_t::SGX_ERROR_UNEXPECTED);
@@ -175,7 +226,11 @@ impl SgxFile {
     }
 
     pub fn flush(&self) -> io::Result<()> {
-        self.0.flush().map_err(|err| {
+        let stream = match self.stream {
+            Stream::Protected(ref stream) => stream,
+            Stream::Integrity(ref file) => return file.flush(),
+        };
+        stream.flush().map_err(|err| {
             match err {
                 1 => Error::from_sgx_error(sgx_status_t::SGX_ERROR_UNEXPECTED),
                 2 => Error::from_sgx_error(sgx_status_t::SGX_ERROR_INVALID_PARAMETER),
@@ -191,15 +246,25 @@ impl SgxFile {
     }
 
     pub fn is_eof(&self) -> bool {
-        self.0.is_eof()
+        match self.stream {
+            Stream::Protected(ref stream) => stream.is_eof(),
+            Stream::Integrity(ref file) => file.is_eof(),
+        }
     }
 
     pub fn clearerr(&self) {
-        self.0.clearerr()
+        match self.stream {
+            Stream::Protected(ref stream) => stream.clearerr(),
+            Stream::Integrity(ref file) => file.clearerr(),
+        }
     }
 
     pub fn clear_cache(&self) -> io::Result<()> {
-        self.0.clear_cache().map_err(|err| {
+        let stream = match self.stream {
+            Stream::Protected(ref stream) => stream,
+            Stream::Integrity(ref file) => return file.clear_cache(),
+        };
+        stream.clear_cache().map_err(|err| {
             match err {
                 1 => Error::from_sgx_error(sgx_status_t::SGX_ERROR_UNEXPECTED),
                 2 => Error::from_sgx_error(sgx_status_t::SGX_ERROR_INVALID_PARAMETER),
@@ -283,13 +348,17 @@ pub fn import_auto_key(path: &Path, key: &sgx_key_128bit_t) -> io::Result<()> {
     })
 }
 
+pub fn integrity_public_key(key: &sgx_key_128bit_t) -> io::Result<sgx_ec256_public_t> {
+    integrity::public_key(key)
+}
+
 fn cstr(path: &Path) -> io::Result<CString> {
     Ok(CString::new(path.as_os_str().as_bytes())?)
 }
 
 impl FromInner<SgxFileStream> for SgxFile {
     fn from_inner(stream: SgxFileStream) -> SgxFile {
-        SgxFile(stream)
+        SgxFile { stream: Stream::Protected(stream) }
     }
 }
 
@@ -321,3 +390,143 @@ pub fn copy(from: &Path, to: &Path) -> io::Result<u64> {
     fs::set_permissions(to, perm)?;
     Ok(ret)
 }
+
+// The meta data node at the start of every protected file begins with this id
+// and major version, see sgx_tprotected_fs/protected_fs_file.h.
+const SGX_FILE_ID: u64 = 0x5347_585F_4649_4C45;
+const SGX_FILE_MAJOR_VERSION: u8 = 0x01;
+const SGX_NODE_SIZE: u64 = 4096;
+
+pub fn rename(from: &Path, to: &Path, key: Option<&sgx_key_128bit_t>) -> io::Result<()> {
+    use crate::untrusted::fs;
+
+    // The name of a protected file is bound into its meta data, so only a
+    // move that keeps the file name can be done in place.
+    if from.file_name() == to.file_name() {
+        return fs::rename(from, to);
+    }
+    rewrite(from, to, None, key, key)?;
+    remove(from)
+}
+
+pub fn truncate(path: &Path, size: u64, key: Option<&sgx_key_128bit_t>) -> io::Result<()> {
+    use crate::io::Write;
+    use crate::sgxfs::OpenOptions;
+
+    let len = open_protected(path, OpenOptions::new().read(true), key)?.metadata()?.len();
+    if size < len {
+        rewrite(path, path, Some(size), key, key).map(|_| ())
+    } else if size > len {
+        let mut file = open_protected(path, OpenOptions::new().append(true), key)?;
+        let zeros = [0_u8; SGX_NODE_SIZE as usize];
+        let mut remaining = size - len;
+        while remaining > 0 {
+            let n = remaining.min(SGX_NODE_SIZE) as usize;
+            file.write_all(&zeros[..n])?;
+            remaining -= n as u64;
+        }
+        file.flush()
+    } else {
+        Ok(())
+    }
+}
+
+pub fn rekey(path: &Path, old_key: &sgx_key_128bit_t, new_key: &sgx_key_128bit_t) -> io::Result<()> {
+    rewrite(path, path, None, Some(old_key), Some(new_key)).map(|_| ())
+}
+
+pub fn read_dir(path: &Path) -> io::Result<Vec<PathBuf>> {
+    use crate::io::Read;
+    use crate::untrusted::fs;
+
+    let mut files = Vec::new();
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        if !metadata.is_file() || metadata.len() == 0 || metadata.len() % SGX_NODE_SIZE != 0 {
+            continue;
+        }
+        let mut header = [0_u8; 9];
+        match fs::File::open(entry.path()) {
+            Ok(mut file) if file.read_exact(&mut header).is_ok() => (),
+            _ => continue,
+        }
+        let mut file_id = [0_u8; 8];
+        file_id.copy_from_slice(&header[..8]);
+        if u64::from_le_bytes(file_id) == SGX_FILE_ID && header[8] == SGX_FILE_MAJOR_VERSION {
+            files.push(entry.path());
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn open_protected(
+    path: &Path,
+    opts: &crate::sgxfs::OpenOptions,
+    key: Option<&sgx_key_128bit_t>,
+) -> io::Result<crate::sgxfs::SgxFile> {
+    match key {
+        Some(key) => opts.open_ex(path, key),
+        None => opts.open(path),
+    }
+}
+
+// Copies `from`, or its first `len` bytes, to a new protected file named like
+// `to` in a staging directory next to `to`, then moves it over `to`, so that
+// `to` is replaced at once and never holds a partial copy. The content only
+// passes through an enclave buffer, which is cleared afterwards.
+fn rewrite(
+    from: &Path,
+    to: &Path,
+    len: Option<u64>,
+    from_key: Option<&sgx_key_128bit_t>,
+    to_key: Option<&sgx_key_128bit_t>,
+) -> io::Result<u64> {
+    use crate::io::{Read, Write};
+    use crate::sgxfs::OpenOptions;
+    use crate::untrusted::fs;
+
+    let name = to.file_name().ok_or_else(|| {
+        Error::new(ErrorKind::InvalidInput, "the destination path has no file name")
+    })?;
+    let parent = match to.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+
+    let mut rand = [0_u8; 8];
+    sgx_trts::trts::rsgx_read_rand(&mut rand).map_err(Error::from_sgx_error)?;
+    let staging = parent.join(format!(".sgxfs-{:016x}", u64::from_ne_bytes(rand)));
+    fs::create_dir(&staging)?;
+    let staged = staging.join(name);
+
+    let mut buf = [0_u8; 4096];
+    let result = (|| -> io::Result<u64> {
+        let reader = open_protected(from, OpenOptions::new().read(true), from_key)?;
+        let mut writer = open_protected(&staged, OpenOptions::new().write(true), to_key)?;
+        let mut reader = reader.take(len.unwrap_or(u64::MAX));
+        let mut copied = 0;
+        loop {
+            let n = match reader.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(ref e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            writer.write_all(&buf[..n])?;
+            copied += n as u64;
+        }
+        writer.flush()?;
+        drop(writer);
+        drop(reader);
+        fs::rename(&staged, to)?;
+        Ok(copied)
+    })();
+    sgx_types::zeroize_bytes(&mut buf);
+    if result.is_err() {
+        let _ = fs::remove_file(&staged);
+    }
+    let _ = fs::remove_dir(&staging);
+    result
+}
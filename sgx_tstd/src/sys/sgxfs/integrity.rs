@@ -0,0 +1,416 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+// Integrity-only files keep their content in plain text, so that untrusted
+// code can read it, and a Merkle tree of SHA-256 hashes over its blocks in a
+// tag file named `<path>.mht`, in the format of sgx_types::integrity. The
+// root of the tree, which also covers the file name, is authenticated twice:
+// with an AES-CMAC tag that only the enclave can check, and with an ECDSA
+// signature that untrusted code checks with the public key returned by
+// `public_key`. Only the last component of the path is bound, so a file can
+// be moved to another directory under the same name.
+//
+// The leaves are checked against the root tag when the file is opened and
+// kept in the enclave, and every block is checked against its leaf when it
+// is read, so reads at any offset cost one block and one hash. The tag file
+// is rewritten when the file is flushed, after the content. A crash in
+// between leaves a file that fails verification rather than one that passes
+// with the wrong content.
+
+use super::OpenOptions;
+use sgx_trts::ct::ct_eq;
+use sgx_trts::libc;
+use sgx_types::integrity::{self, TagHeader, BLOCK_SIZE, TAG_SUFFIX};
+use sgx_types::{
+    sgx_cmac_128bit_tag_t, sgx_ec256_private_t, sgx_ec256_public_t, sgx_ec256_signature_t,
+    sgx_ecc256_calculate_pub_from_priv, sgx_ecc256_close_context, sgx_ecc256_open_context, sgx_ecc_state_handle_t,
+    sgx_ecdsa_sign, sgx_key_128bit_t, sgx_rijndael128_cmac_msg, sgx_sha256_hash_t, sgx_status_t, zeroize_bytes,
+    SgxEc256PrivateSecret, SgxKey128Secret,
+};
+use crate::ffi::OsString;
+use crate::io::{self, Error, ErrorKind, SeekFrom};
+use crate::os::unix::prelude::*;
+use crate::path::{Path, PathBuf};
+use crate::untrusted::fs;
+use core::cell::RefCell;
+use core::{cmp, ptr};
+
+const SIGNING_KEY: u8 = 0x03;
+
+// The order n of the P-256 group, little endian.
+const P256_ORDER: [u8; 32] = [
+    0x51, 0x25, 0x63, 0xfc, 0xc2, 0xca, 0xb9, 0xf3, 0x84, 0x9e, 0x17, 0xa7, 0xad, 0xfa, 0xe6, 0xbc, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff,
+];
+
+pub struct IntegrityFile {
+    file: fs::File,
+    tags: PathBuf,
+    name: Vec<u8>,
+    key: SgxKey128Secret,
+    signing_key: SgxEc256PrivateSecret,
+    readable: bool,
+    writable: bool,
+    append: bool,
+    state: RefCell<State>,
+}
+
+struct State {
+    pos: u64,
+    len: u64,
+    leaves: Vec<sgx_sha256_hash_t>,
+    // The last block read or written, already checked against its leaf.
+    block: Option<(u64, Vec<u8>)>,
+    dirty: bool,
+    eof: bool,
+}
+
+impl IntegrityFile {
+    pub fn open(path: &Path, opts: &OpenOptions, key: &sgx_key_128bit_t) -> io::Result<IntegrityFile> {
+        opts.get_access_mode()?;
+        let name = match path.file_name() {
+            Some(name) => name.as_bytes().to_vec(),
+            None => return Err(Error::from_raw_os_error(libc::EINVAL)),
+        };
+        let key = SgxKey128Secret::new(*key);
+        let signing_key = signing_key(key.expose())?;
+        let tags = tag_path(path);
+        let readable = opts.read || opts.update;
+        let writable = opts.write || opts.append || opts.update;
+
+        let (file, len, leaves, dirty) = if opts.write {
+            let file = fs::OpenOptions::new().read(true).write(true).create(true).truncate(true).open(path)?;
+            (file, 0, Vec::new(), true)
+        } else {
+            let file = fs::OpenOptions::new().read(true).write(writable).create(opts.append).open(path)?;
+            let data_len = file.metadata()?.len();
+            match load_tags(&tags, &name, key.expose()) {
+                Ok((len, leaves)) => {
+                    if len != data_len {
+                        return Err(Error::from_sgx_error(sgx_status_t::SGX_ERROR_MAC_MISMATCH));
+                    }
+                    (file, len, leaves, false)
+                }
+                Err(ref e) if opts.append && data_len == 0 && e.kind() == ErrorKind::NotFound => {
+                    (file, 0, Vec::new(), true)
+                }
+                Err(e) => return Err(e),
+            }
+        };
+
+        Ok(IntegrityFile {
+            file,
+            tags,
+            name,
+            key,
+            signing_key,
+            readable,
+            writable,
+            append: opts.append,
+            state: RefCell::new(State {
+                pos: 0,
+                len,
+                leaves,
+                block: None,
+                dirty,
+                eof: false,
+            }),
+        })
+    }
+
+    pub fn read(&self, buf: &mut [u8]) -> io::Result<usize> {
+        if !self.readable {
+            return Err(Error::from_raw_os_error(libc::EBADF));
+        }
+        let mut state = self.state.borrow_mut();
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        if state.pos >= state.len {
+            state.eof = true;
+            return Ok(0);
+        }
+
+        let pos = state.pos;
+        let offset = (pos % BLOCK_SIZE) as usize;
+        self.load_block(&mut state, pos / BLOCK_SIZE)?;
+        let n = match state.block {
+            Some((_, ref block)) => {
+                let n = cmp::min(buf.len(), block.len() - offset);
+                buf[..n].copy_from_slice(&block[offset..offset + n]);
+                n
+            }
+            None => 0,
+        };
+        state.pos += n as u64;
+        Ok(n)
+    }
+
+    // Writes at most up to the end of the block at the current position,
+    // filling a gap left by a seek past the end with zeros first.
+    pub fn write(&self, buf: &[u8]) -> io::Result<usize> {
+        if !self.writable {
+            return Err(Error::from_raw_os_error(libc::EBADF));
+        }
+        let mut state = self.state.borrow_mut();
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        if self.append {
+            state.pos = state.len;
+        }
+
+        let zeros = [0_u8; BLOCK_SIZE as usize];
+        while state.pos > state.len {
+            let len = state.len;
+            let n = cmp::min(BLOCK_SIZE - len % BLOCK_SIZE, state.pos - len);
+            self.store(&mut state, len, &zeros[..n as usize])?;
+        }
+
+        let pos = state.pos;
+        let n = cmp::min(buf.len() as u64, BLOCK_SIZE - pos % BLOCK_SIZE) as usize;
+        self.store(&mut state, pos, &buf[..n])?;
+        state.pos += n as u64;
+        Ok(n)
+    }
+
+    pub fn tell(&self) -> io::Result<u64> {
+        Ok(self.state.borrow().pos)
+    }
+
+    pub fn len(&self) -> io::Result<u64> {
+        Ok(self.state.borrow().len)
+    }
+
+    pub fn seek(&self, pos: SeekFrom) -> io::Result<u64> {
+        let mut state = self.state.borrow_mut();
+        let (base, offset) = match pos {
+            SeekFrom::Start(offset) => (0, offset as i128),
+            SeekFrom::End(offset) => (state.len, offset as i128),
+            SeekFrom::Current(offset) => (state.pos, offset as i128),
+        };
+        let pos = base as i128 + offset;
+        if pos < 0 || pos > i64::MAX as i128 {
+            return Err(Error::from_raw_os_error(libc::EINVAL));
+        }
+        state.pos = pos as u64;
+        state.eof = false;
+        Ok(state.pos)
+    }
+
+    pub fn flush(&self) -> io::Result<()> {
+        let mut state = self.state.borrow_mut();
+        if !state.dirty {
+            return Ok(());
+        }
+        self.file.sync_data()?;
+
+        let mut header = TagHeader::new(state.len);
+        let root = header.root(&self.name, &state.leaves).map_err(Error::from_sgx_error)?;
+        header.root_tag = mac(self.key.expose(), &root)?;
+        header.signature = sign(self.signing_key.expose(), &root)?;
+
+        let mut contents = header.encode().to_vec();
+        for leaf in state.leaves.iter() {
+            contents.extend_from_slice(leaf);
+        }
+        let mut staged = self.tags.clone().into_os_string();
+        staged.push(".tmp");
+        fs::write(&staged, &contents)?;
+        fs::rename(&staged, &self.tags)?;
+        state.dirty = false;
+        Ok(())
+    }
+
+    pub fn is_eof(&self) -> bool {
+        self.state.borrow().eof
+    }
+
+    pub fn clearerr(&self) {
+        self.state.borrow_mut().eof = false;
+    }
+
+    pub fn clear_cache(&self) -> io::Result<()> {
+        self.flush()?;
+        self.state.borrow_mut().block = None;
+        Ok(())
+    }
+
+    // Makes `index` the current block, reading it and checking it against
+    // its leaf unless it already is. The block at the end of the content is
+    // empty.
+    fn load_block(&self, state: &mut State, index: u64) -> io::Result<()> {
+        if let Some((current, _)) = state.block {
+            if current == index {
+                return Ok(());
+            }
+        }
+        state.block = None;
+
+        let start = index * BLOCK_SIZE;
+        let size = cmp::min(BLOCK_SIZE, state.len - start) as usize;
+        let mut block = vec![0_u8; size];
+        if size > 0 {
+            self.file.read_exact_at(&mut block, start)?;
+            let leaf = integrity::leaf(index, &block).map_err(Error::from_sgx_error)?;
+            if !ct_eq(&leaf, &state.leaves[index as usize]) {
+                return Err(Error::from_sgx_error(sgx_status_t::SGX_ERROR_MAC_MISMATCH));
+            }
+        }
+        state.block = Some((index, block));
+        Ok(())
+    }
+
+    // Writes `data`, which lies in one block and starts at or before the end
+    // of the content, and updates the leaf of its block.
+    fn store(&self, state: &mut State, at: u64, data: &[u8]) -> io::Result<()> {
+        let index = at / BLOCK_SIZE;
+        let offset = (at % BLOCK_SIZE) as usize;
+        self.load_block(state, index)?;
+
+        if let Err(e) = self.file.write_all_at(data, at) {
+            state.block = None;
+            return Err(e);
+        }
+        state.dirty = true;
+        state.len = cmp::max(state.len, at + data.len() as u64);
+
+        let leaf = match state.block {
+            Some((_, ref mut block)) => {
+                let end = offset + data.len();
+                if block.len() < end {
+                    block.resize(end, 0);
+                }
+                block[offset..end].copy_from_slice(data);
+                integrity::leaf(index, block).map_err(Error::from_sgx_error)?
+            }
+            None => return Err(Error::from_sgx_error(sgx_status_t::SGX_ERROR_UNEXPECTED)),
+        };
+        if index as usize == state.leaves.len() {
+            state.leaves.push(leaf);
+        } else {
+            state.leaves[index as usize] = leaf;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for IntegrityFile {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+pub fn tag_path(path: &Path) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(TAG_SUFFIX);
+    PathBuf::from(name)
+}
+
+// Returns the public key untrusted code checks the files written with `key`
+// against.
+pub fn public_key(key: &sgx_key_128bit_t) -> io::Result<sgx_ec256_public_t> {
+    let private = signing_key(key)?;
+    let mut public = sgx_ec256_public_t::default();
+    let status = unsafe { sgx_ecc256_calculate_pub_from_priv(private.expose(), &mut public) };
+    match status {
+        sgx_status_t::SGX_SUCCESS => Ok(public),
+        status => Err(Error::from_sgx_error(status)),
+    }
+}
+
+fn load_tags(tags: &Path, name: &[u8], key: &sgx_key_128bit_t) -> io::Result<(u64, Vec<sgx_sha256_hash_t>)> {
+    let contents = fs::read(tags)?;
+    let (header, leaves) = TagHeader::parse(&contents).map_err(Error::from_sgx_error)?;
+    let leaves: Vec<sgx_sha256_hash_t> = integrity::parse_leaves(leaves).collect();
+    let root = header.root(name, &leaves).map_err(Error::from_sgx_error)?;
+    if !ct_eq(&mac(key, &root)?, &header.root_tag) {
+        return Err(Error::from_sgx_error(sgx_status_t::SGX_ERROR_MAC_MISMATCH));
+    }
+    Ok((header.len, leaves))
+}
+
+// The signing key is derived from the key of the file, so that the enclave
+// has no other key to keep. The candidate scalars are CMACs of a counter,
+// the first one in [1, n) is used.
+fn signing_key(key: &sgx_key_128bit_t) -> io::Result<SgxEc256PrivateSecret> {
+    let mut private = SgxEc256PrivateSecret::zeroed();
+    for counter in 0..=u8::MAX {
+        for (half, chunk) in private.expose_mut().r.chunks_mut(16).enumerate() {
+            let mut tag = mac(key, &[SIGNING_KEY, counter, half as u8])?;
+            chunk.copy_from_slice(&tag);
+            zeroize_bytes(&mut tag);
+        }
+        if is_scalar(&private.expose().r) {
+            return Ok(private);
+        }
+    }
+    Err(Error::from_sgx_error(sgx_status_t::SGX_ERROR_UNEXPECTED))
+}
+
+fn is_scalar(r: &[u8; 32]) -> bool {
+    if r.iter().all(|b| *b == 0) {
+        return false;
+    }
+    for (x, n) in r.iter().zip(P256_ORDER.iter()).rev() {
+        if x != n {
+            return x < n;
+        }
+    }
+    false
+}
+
+fn mac(key: &sgx_key_128bit_t, data: &[u8]) -> io::Result<sgx_cmac_128bit_tag_t> {
+    let mut tag = sgx_cmac_128bit_tag_t::default();
+    let status = unsafe {
+        sgx_rijndael128_cmac_msg(
+            key as *const sgx_key_128bit_t,
+            data.as_ptr(),
+            data.len() as u32,
+            &mut tag as *mut sgx_cmac_128bit_tag_t,
+        )
+    };
+    match status {
+        sgx_status_t::SGX_SUCCESS => Ok(tag),
+        status => Err(Error::from_sgx_error(status)),
+    }
+}
+
+fn sign(private: &sgx_ec256_private_t, data: &[u8]) -> io::Result<sgx_ec256_signature_t> {
+    let mut handle: sgx_ecc_state_handle_t = ptr::null_mut();
+    let status = unsafe { sgx_ecc256_open_context(&mut handle) };
+    if status != sgx_status_t::SGX_SUCCESS {
+        return Err(Error::from_sgx_error(status));
+    }
+
+    let mut signature = sgx_ec256_signature_t::default();
+    let status = unsafe {
+        sgx_ecdsa_sign(
+            data.as_ptr(),
+            data.len() as u32,
+            private as *const sgx_ec256_private_t,
+            &mut signature as *mut sgx_ec256_signature_t,
+            handle,
+        )
+    };
+    unsafe { sgx_ecc256_close_context(handle) };
+
+    match status {
+        sgx_status_t::SGX_SUCCESS => Ok(signature),
+        status => Err(Error::from_sgx_error(status)),
+    }
+}
@@ -0,0 +1,69 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//!
+//! The monotonic counter interface shared by the rollback protection of
+//! sealed data in sgx_tseal and of the protected file system in sgx_tstd.
+//!
+
+use crate::error::SgxResult;
+
+///
+/// The identifier of a monotonic counter.
+///
+pub type MonotonicCounterId = [u8; 16];
+
+///
+/// A counter whose value can only grow.
+///
+pub trait MonotonicCounter {
+    ///
+    /// Return the identifier of the counter.
+    ///
+    /// Data bound to a counter is bound to its identifier as well as to its
+    /// value, so that it cannot be replayed against another counter that
+    /// happens to have the same value. Two counters that can be used by the
+    /// same enclave must not share an identifier.
+    ///
+    fn id(&self) -> MonotonicCounterId;
+
+    ///
+    /// Read the current value of the counter.
+    ///
+    fn read(&mut self) -> SgxResult<u64>;
+
+    ///
+    /// Increment the counter and return its new value.
+    ///
+    /// The increment must be durable once this function returns.
+    ///
+    fn increment(&mut self) -> SgxResult<u64>;
+}
+
+impl<'a, C: MonotonicCounter + ?Sized> MonotonicCounter for &'a mut C {
+    fn id(&self) -> MonotonicCounterId {
+        (**self).id()
+    }
+
+    fn read(&mut self) -> SgxResult<u64> {
+        (**self).read()
+    }
+
+    fn increment(&mut self) -> SgxResult<u64> {
+        (**self).increment()
+    }
+}
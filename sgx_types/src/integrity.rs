@@ -0,0 +1,229 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//!
+//! The format of the integrity-only files of the protected file system.
+//!
+//! The files are written in an enclave by sgx_tstd and verified outside of
+//! it by sgx_ucrypto. All integers are little endian:
+//!
+//! ```text
+//! header     magic "SGXIFILE" | version u32 | block size u32 | length u64
+//!            | root tag [16] | root signature [64]
+//! leaves     one SHA-256 hash of 32 bytes per block of the content
+//!
+//! leaf i     SHA-256(0x00 | i u64 | block i)
+//! node       SHA-256(0x01 | left | right), the last node of a level
+//!            without a sibling is carried up unchanged
+//! root       0x02 | header up to the root tag | SHA-256(file name)
+//!            | root node
+//! root tag   AES-CMAC of the root with the key of the file, checked by the
+//!            enclave
+//! signature  ECDSA P-256 signature of the root with the signing key
+//!            derived from the key of the file, checked by untrusted code
+//!            that only holds the public key
+//! ```
+//!
+//! The root node of an empty file is all zeros. The file name is the last
+//! component of the path of the content, so that a file renamed, or swapped
+//! with another file written with the same key, fails verification.
+//!
+
+use crate::error::{sgx_status_t, SgxResult};
+use crate::function::{sgx_sha256_close, sgx_sha256_get_hash, sgx_sha256_init, sgx_sha256_update};
+use crate::types::{sgx_cmac_128bit_tag_t, sgx_ec256_signature_t, sgx_sha256_hash_t, sgx_sha_state_handle_t};
+use core::ptr;
+
+pub const MAGIC: &[u8; 8] = b"SGXIFILE";
+pub const VERSION: u32 = 3;
+pub const BLOCK_SIZE: u64 = 4096;
+pub const HASH_SIZE: usize = 32;
+pub const TAG_SIZE: usize = 16;
+pub const SIGNATURE_SIZE: usize = 64;
+pub const PREFIX_SIZE: usize = 24;
+pub const HEADER_SIZE: usize = PREFIX_SIZE + TAG_SIZE + SIGNATURE_SIZE;
+pub const ROOT_SIZE: usize = 1 + PREFIX_SIZE + HASH_SIZE + HASH_SIZE;
+pub const TAG_SUFFIX: &str = ".mht";
+
+const LEAF: u8 = 0x00;
+const NODE: u8 = 0x01;
+const ROOT: u8 = 0x02;
+
+///
+/// The header of a tag file, which is followed by the leaves.
+///
+#[derive(Clone, Copy, Default)]
+pub struct TagHeader {
+    pub len: u64,
+    pub root_tag: sgx_cmac_128bit_tag_t,
+    pub signature: sgx_ec256_signature_t,
+}
+
+impl TagHeader {
+    ///
+    /// Creates the header of a content of `len` bytes, with an empty root
+    /// tag and signature.
+    ///
+    pub fn new(len: u64) -> TagHeader {
+        TagHeader {
+            len,
+            ..TagHeader::default()
+        }
+    }
+
+    ///
+    /// Checks the layout of a tag file, but not its root tag or signature,
+    /// and returns its header and leaves.
+    ///
+    pub fn parse(contents: &[u8]) -> SgxResult<(TagHeader, &[u8])> {
+        if contents.len() < HEADER_SIZE || &contents[..8] != MAGIC {
+            return Err(sgx_status_t::SGX_ERROR_FILE_NOT_SGX_FILE);
+        }
+        if read_u32(&contents[8..]) != VERSION || read_u32(&contents[12..]) as u64 != BLOCK_SIZE {
+            return Err(sgx_status_t::SGX_ERROR_FILE_NOT_SGX_FILE);
+        }
+        let len = read_u64(&contents[16..]);
+        let count = len / BLOCK_SIZE + (len % BLOCK_SIZE != 0) as u64;
+        if (contents.len() - HEADER_SIZE) as u64 != count.saturating_mul(HASH_SIZE as u64) {
+            return Err(sgx_status_t::SGX_ERROR_MAC_MISMATCH);
+        }
+
+        let mut header = TagHeader::new(len);
+        header
+            .root_tag
+            .copy_from_slice(&contents[PREFIX_SIZE..PREFIX_SIZE + TAG_SIZE]);
+        let words = contents[PREFIX_SIZE + TAG_SIZE..HEADER_SIZE].chunks(4).map(read_u32);
+        for (word, value) in header
+            .signature
+            .x
+            .iter_mut()
+            .chain(header.signature.y.iter_mut())
+            .zip(words)
+        {
+            *word = value;
+        }
+        Ok((header, &contents[HEADER_SIZE..]))
+    }
+
+    ///
+    /// Returns the encoded header, to be followed by the leaves.
+    ///
+    pub fn encode(&self) -> [u8; HEADER_SIZE] {
+        let mut header = [0_u8; HEADER_SIZE];
+        header[..PREFIX_SIZE].copy_from_slice(&self.prefix());
+        header[PREFIX_SIZE..PREFIX_SIZE + TAG_SIZE].copy_from_slice(&self.root_tag);
+        let words = self.signature.x.iter().chain(self.signature.y.iter());
+        for (chunk, word) in header[PREFIX_SIZE + TAG_SIZE..].chunks_mut(4).zip(words) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        header
+    }
+
+    ///
+    /// Returns the message the root tag and the signature are computed over,
+    /// for the file called `name` with the given leaves.
+    ///
+    pub fn root(&self, name: &[u8], leaves: &[sgx_sha256_hash_t]) -> SgxResult<[u8; ROOT_SIZE]> {
+        let mut root = [0_u8; ROOT_SIZE];
+        root[0] = ROOT;
+        root[1..1 + PREFIX_SIZE].copy_from_slice(&self.prefix());
+        root[1 + PREFIX_SIZE..1 + PREFIX_SIZE + HASH_SIZE].copy_from_slice(&hash(&[name])?);
+        root[1 + PREFIX_SIZE + HASH_SIZE..].copy_from_slice(&root_node(leaves)?);
+        Ok(root)
+    }
+
+    fn prefix(&self) -> [u8; PREFIX_SIZE] {
+        let mut prefix = [0_u8; PREFIX_SIZE];
+        prefix[..8].copy_from_slice(MAGIC);
+        prefix[8..12].copy_from_slice(&VERSION.to_le_bytes());
+        prefix[12..16].copy_from_slice(&(BLOCK_SIZE as u32).to_le_bytes());
+        prefix[16..24].copy_from_slice(&self.len.to_le_bytes());
+        prefix
+    }
+}
+
+///
+/// Returns the leaves stored in the `leaves` part of a tag file.
+///
+pub fn parse_leaves(leaves: &[u8]) -> impl Iterator<Item = sgx_sha256_hash_t> + '_ {
+    leaves.chunks(HASH_SIZE).map(|chunk| {
+        let mut leaf = sgx_sha256_hash_t::default();
+        leaf.copy_from_slice(chunk);
+        leaf
+    })
+}
+
+///
+/// Returns the leaf of block `index` of the content.
+///
+pub fn leaf(index: u64, block: &[u8]) -> SgxResult<sgx_sha256_hash_t> {
+    hash(&[&[LEAF], &index.to_le_bytes(), block])
+}
+
+// Carrying the last node of a level up unchanged builds the same tree as
+// splitting the leaves after the largest power of two below their count,
+// which needs no buffer for the levels.
+fn root_node(leaves: &[sgx_sha256_hash_t]) -> SgxResult<sgx_sha256_hash_t> {
+    match leaves.len() {
+        0 => Ok(sgx_sha256_hash_t::default()),
+        1 => Ok(leaves[0]),
+        count => {
+            let split = count.next_power_of_two() / 2;
+            let left = root_node(&leaves[..split])?;
+            let right = root_node(&leaves[split..])?;
+            hash(&[&[NODE], &left, &right])
+        }
+    }
+}
+
+fn hash(parts: &[&[u8]]) -> SgxResult<sgx_sha256_hash_t> {
+    let mut handle: sgx_sha_state_handle_t = ptr::null_mut();
+    let status = unsafe { sgx_sha256_init(&mut handle) };
+    if status != sgx_status_t::SGX_SUCCESS {
+        return Err(status);
+    }
+
+    let mut hash = sgx_sha256_hash_t::default();
+    let mut status = sgx_status_t::SGX_SUCCESS;
+    for part in parts.iter().filter(|part| !part.is_empty()) {
+        status = unsafe { sgx_sha256_update(part.as_ptr(), part.len() as u32, handle) };
+        if status != sgx_status_t::SGX_SUCCESS {
+            break;
+        }
+    }
+    if status == sgx_status_t::SGX_SUCCESS {
+        status = unsafe { sgx_sha256_get_hash(handle, &mut hash as *mut sgx_sha256_hash_t) };
+    }
+    unsafe { sgx_sha256_close(handle) };
+
+    match status {
+        sgx_status_t::SGX_SUCCESS => Ok(hash),
+        status => Err(status),
+    }
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    let mut word = [0_u8; 4];
+    word.copy_from_slice(&bytes[..4]);
+    u32::from_le_bytes(word)
+}
+
+fn read_u64(bytes: &[u8]) -> u64 {
+    let mut word = [0_u8; 8];
+    word.copy_from_slice(&bytes[..8]);
+    u64::from_le_bytes(word)
+}
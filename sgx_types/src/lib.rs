@@ -74,6 +74,16 @@ pub use self::error::*;
 mod function;
 pub use self::function::*;
 
+mod secret;
+pub use self::secret::*;
+
+mod counter;
+pub use self::counter::*;
+
+mod view;
+pub use self::view::*;
+
 pub mod cpu_feature;
+pub mod integrity;
 pub mod marker;
 pub mod metadata;
@@ -0,0 +1,225 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//!
+//! Zeroize-on-drop containers for secret material.
+//!
+//! SgxSecret never prints its contents through Debug and compares in time
+//! that only depends on the size of the wrapped type.
+//!
+
+use crate::marker::ContiguousMemory;
+use crate::types::{
+    sgx_align_ec256_dh_shared_t, sgx_align_ec256_private_t, sgx_align_key_128bit_t,
+    sgx_ec256_dh_shared_t, sgx_ec256_private_t, sgx_ed25519_private_t, sgx_key_128bit_t,
+    sgx_x25519_private_t, sgx_x25519_shared_t,
+};
+use core::fmt;
+use core::mem;
+use core::ops::{Deref, DerefMut};
+use core::ptr;
+use core::slice;
+use core::sync::atomic::{compiler_fence, Ordering};
+
+///
+/// Overwrites a buffer with zeros in a way the optimizer will not elide.
+///
+pub fn zeroize_bytes(buf: &mut [u8]) {
+    for b in buf.iter_mut() {
+        unsafe { ptr::write_volatile(b, 0) };
+    }
+    compiler_fence(Ordering::SeqCst);
+}
+
+///
+/// Compares two byte slices in time that only depends on their lengths.
+///
+/// Returns `true` when both slices have the same length and contents. This is
+/// the comparison behind `sgx_trts::ct::ct_eq` and the crypto primitives of
+/// sgx_tcrypto and sgx_ucrypto, which cannot depend on sgx_trts.
+///
+pub fn ct_eq_bytes(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let diff = a.iter().zip(b.iter()).fold(0_u8, |acc, (x, y)| acc | (x ^ y));
+    unsafe { ptr::read_volatile(&diff) == 0 }
+}
+
+///
+/// A 128-bit key, as returned by EGETKEY or derived by a key exchange.
+///
+pub type SgxKey128Secret = SgxSecret<sgx_key_128bit_t>;
+
+///
+/// A 128-bit key in a 32-byte aligned buffer, as returned by EGETKEY.
+///
+pub type SgxAlignKey128Secret = SgxSecret<sgx_align_key_128bit_t>;
+
+///
+/// A P-256 private key.
+///
+pub type SgxEc256PrivateSecret = SgxSecret<sgx_ec256_private_t>;
+
+///
+/// A P-256 private key in a 64-byte aligned buffer.
+///
+pub type SgxAlignEc256PrivateSecret = SgxSecret<sgx_align_ec256_private_t>;
+
+///
+/// A P-256 Diffie-Hellman shared secret.
+///
+pub type SgxEc256SharedSecret = SgxSecret<sgx_ec256_dh_shared_t>;
+
+///
+/// A P-256 Diffie-Hellman shared secret in a 64-byte aligned buffer.
+///
+pub type SgxAlignEc256SharedSecret = SgxSecret<sgx_align_ec256_dh_shared_t>;
+
+///
+/// An Ed25519 private key.
+///
+pub type SgxEd25519PrivateSecret = SgxSecret<sgx_ed25519_private_t>;
+
+///
+/// An X25519 private key.
+///
+pub type SgxX25519PrivateSecret = SgxSecret<sgx_x25519_private_t>;
+
+///
+/// An X25519 shared secret.
+///
+pub type SgxX25519SharedSecret = SgxSecret<sgx_x25519_shared_t>;
+
+///
+/// SgxSecret owns a value of secret key material and overwrites it with
+/// zeros when dropped.
+///
+/// The wrapped type must be plain data (integers and arrays or structures
+/// of integers), for which the all-zero bit pattern is a valid value.
+///
+pub struct SgxSecret<T: Copy + ContiguousMemory> {
+    inner: T,
+}
+
+impl<T: Copy + ContiguousMemory> SgxSecret<T> {
+    ///
+    /// Takes ownership of `secret`.
+    ///
+    /// The argument is copied into the container; callers holding the
+    /// original value on the stack are responsible for clearing it.
+    ///
+    pub fn new(secret: T) -> SgxSecret<T> {
+        SgxSecret { inner: secret }
+    }
+
+    ///
+    /// Creates a container holding the all-zero value of `T`.
+    ///
+    pub fn zeroed() -> SgxSecret<T> {
+        SgxSecret {
+            inner: unsafe { mem::zeroed() },
+        }
+    }
+
+    ///
+    /// Returns a reference to the secret value.
+    ///
+    pub fn expose(&self) -> &T {
+        &self.inner
+    }
+
+    ///
+    /// Returns a mutable reference to the secret value.
+    ///
+    pub fn expose_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    ///
+    /// Returns the byte representation of the secret value.
+    ///
+    pub fn as_bytes(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(&self.inner as *const T as *const u8, mem::size_of::<T>()) }
+    }
+
+    ///
+    /// Returns the mutable byte representation of the secret value.
+    ///
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+        unsafe { slice::from_raw_parts_mut(&mut self.inner as *mut T as *mut u8, mem::size_of::<T>()) }
+    }
+
+    ///
+    /// Compares two secrets in time that only depends on the size of `T`.
+    ///
+    pub fn ct_eq(&self, other: &SgxSecret<T>) -> bool {
+        ct_eq_bytes(self.as_bytes(), other.as_bytes())
+    }
+}
+
+impl<T: Copy + ContiguousMemory> Default for SgxSecret<T> {
+    fn default() -> SgxSecret<T> {
+        SgxSecret::zeroed()
+    }
+}
+
+impl<T: Copy + ContiguousMemory> From<T> for SgxSecret<T> {
+    fn from(secret: T) -> SgxSecret<T> {
+        SgxSecret::new(secret)
+    }
+}
+
+impl<T: Copy + ContiguousMemory> Deref for SgxSecret<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<T: Copy + ContiguousMemory> DerefMut for SgxSecret<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+}
+
+impl<T: Copy + ContiguousMemory> PartialEq for SgxSecret<T> {
+    fn eq(&self, other: &SgxSecret<T>) -> bool {
+        self.ct_eq(other)
+    }
+}
+
+impl<T: Copy + ContiguousMemory> Eq for SgxSecret<T> {}
+
+impl<T: Copy + ContiguousMemory> fmt::Debug for SgxSecret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SgxSecret([REDACTED])")
+    }
+}
+
+impl<T: Copy + ContiguousMemory> Clone for SgxSecret<T> {
+    fn clone(&self) -> SgxSecret<T> {
+        SgxSecret::new(self.inner)
+    }
+}
+
+impl<T: Copy + ContiguousMemory> Drop for SgxSecret<T> {
+    fn drop(&mut self) {
+        zeroize_bytes(self.as_bytes_mut());
+    }
+}
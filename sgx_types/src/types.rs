@@ -654,6 +654,88 @@ pub type sgx_cmac_128bit_key_t = [uint8_t; SGX_CMAC_KEY_SIZE];
 pub type sgx_cmac_128bit_tag_t = [uint8_t; SGX_CMAC_MAC_SIZE];
 pub type sgx_aes_ctr_128bit_key_t = [uint8_t; SGX_AESCTR_KEY_SIZE];
 
+//
+// Algorithms implemented in Rust by sgx_tcrypto/sgx_ucrypto
+//
+pub const SGX_SHA384_HASH_SIZE: size_t          = 48;
+pub const SGX_SHA512_HASH_SIZE: size_t          = 64;
+pub const SGX_HMAC384_MAC_SIZE: size_t          = 48;
+pub const SGX_HMAC512_MAC_SIZE: size_t          = 64;
+pub const SGX_AESGCM256_KEY_SIZE: size_t        = 32;
+pub const SGX_AESGCM256_MAC_SIZE: size_t        = 16;
+pub const SGX_CHACHA20POLY1305_KEY_SIZE: size_t = 32;
+pub const SGX_CHACHA20POLY1305_IV_SIZE: size_t  = 12;
+pub const SGX_CHACHA20POLY1305_MAC_SIZE: size_t = 16;
+
+pub type sgx_sha384_hash_t = [uint8_t; SGX_SHA384_HASH_SIZE];
+pub type sgx_sha512_hash_t = [uint8_t; SGX_SHA512_HASH_SIZE];
+pub type sgx_hmac_384bit_tag_t = [uint8_t; SGX_HMAC384_MAC_SIZE];
+pub type sgx_hmac_512bit_tag_t = [uint8_t; SGX_HMAC512_MAC_SIZE];
+pub type sgx_aes_gcm_256bit_key_t = [uint8_t; SGX_AESGCM256_KEY_SIZE];
+pub type sgx_aes_gcm_256bit_tag_t = [uint8_t; SGX_AESGCM256_MAC_SIZE];
+pub type sgx_chacha20_poly1305_key_t = [uint8_t; SGX_CHACHA20POLY1305_KEY_SIZE];
+pub type sgx_chacha20_poly1305_tag_t = [uint8_t; SGX_CHACHA20POLY1305_MAC_SIZE];
+
+// Interoperable encodings of P-256 keys and ECDSA signatures
+pub const SGX_EC256_RAW_SIGNATURE_SIZE: size_t  = 2 * SGX_ECP256_KEY_SIZE;
+pub const SGX_EC256_DER_SIGNATURE_MAX_SIZE: size_t = 72;
+pub const SGX_EC256_SEC1_PUBLIC_SIZE: size_t    = 1 + 2 * SGX_ECP256_KEY_SIZE;
+
+pub type sgx_ec256_raw_signature_t = [uint8_t; SGX_EC256_RAW_SIGNATURE_SIZE];
+pub type sgx_ec256_sec1_public_t = [uint8_t; SGX_EC256_SEC1_PUBLIC_SIZE];
+
+impl_enum! {
+    #[repr(u32)]
+    #[derive(Copy, Clone, PartialEq, Eq, Debug)]
+    pub enum sgx_aead_algorithm_t {
+        SGX_AEAD_AES_256_GCM        = 0,
+        SGX_AEAD_CHACHA20_POLY1305  = 1,
+    }
+}
+
+pub const SGX_RSA2048_KEY_SIZE: size_t       = 256;
+pub const SGX_RSA4096_KEY_SIZE: size_t       = 512;
+
+impl_enum! {
+    #[repr(u32)]
+    #[derive(Copy, Clone, PartialEq, Eq, Debug)]
+    pub enum sgx_hash_algorithm_t {
+        SGX_HASH_SHA256     = 0,
+        SGX_HASH_SHA384     = 1,
+        SGX_HASH_SHA512     = 2,
+    }
+}
+
+pub const SGX_ED25519_KEY_SIZE: size_t          = 32;
+pub const SGX_X25519_KEY_SIZE: size_t           = 32;
+
+impl_struct! {
+    pub struct sgx_ed25519_private_t {
+        pub seed: [uint8_t; SGX_ED25519_KEY_SIZE],
+    }
+
+    pub struct sgx_ed25519_public_t {
+        pub key: [uint8_t; SGX_ED25519_KEY_SIZE],
+    }
+
+    pub struct sgx_ed25519_signature_t {
+        pub r: [uint8_t; SGX_ED25519_KEY_SIZE],
+        pub s: [uint8_t; SGX_ED25519_KEY_SIZE],
+    }
+
+    pub struct sgx_x25519_private_t {
+        pub k: [uint8_t; SGX_X25519_KEY_SIZE],
+    }
+
+    pub struct sgx_x25519_public_t {
+        pub u: [uint8_t; SGX_X25519_KEY_SIZE],
+    }
+
+    pub struct sgx_x25519_shared_t {
+        pub s: [uint8_t; SGX_X25519_KEY_SIZE],
+    }
+}
+
 impl_enum! {
     #[repr(u32)]
     #[derive(Copy, Clone, PartialEq, Eq, Debug)]
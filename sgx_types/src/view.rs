@@ -0,0 +1,369 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//!
+//! Zero-copy views of reports and quotes.
+//!
+//! The views borrow the bytes of a report body, an EPID quote or an ECDSA
+//! quote, check their length once, and read each field at its offset. They
+//! avoid copying quotes into packed structures and taking references to
+//! their fields.
+//!
+
+use crate::error::{sgx_status_t, SgxResult};
+use crate::types::*;
+use crate::{size_t, uint16_t, uint32_t};
+use core::mem;
+use core::ptr;
+use core::slice;
+
+pub const SGX_REPORT_BODY_SIZE: size_t = 384;
+pub const SGX_QUOTE_BODY_SIZE: size_t = 432;
+pub const SGX_QUOTE3_HEADER_SIZE: size_t = 48;
+
+impl sgx_attributes_t {
+    ///
+    /// The enclave has been initialized by EINIT.
+    ///
+    pub fn initted(&self) -> bool {
+        self.flags & SGX_FLAGS_INITTED != 0
+    }
+
+    ///
+    /// The enclave is a debug enclave, whose memory can be read by a
+    /// debugger.
+    ///
+    pub fn debug(&self) -> bool {
+        self.flags & SGX_FLAGS_DEBUG != 0
+    }
+
+    ///
+    /// The enclave runs in 64-bit mode.
+    ///
+    pub fn mode64bit(&self) -> bool {
+        self.flags & SGX_FLAGS_MODE64BIT != 0
+    }
+
+    ///
+    /// The enclave has access to the provisioning key.
+    ///
+    pub fn provision_key(&self) -> bool {
+        self.flags & SGX_FLAGS_PROVISION_KEY != 0
+    }
+
+    ///
+    /// The enclave has access to the EINITTOKEN key.
+    ///
+    pub fn einittoken_key(&self) -> bool {
+        self.flags & SGX_FLAGS_EINITTOKEN_KEY != 0
+    }
+
+    ///
+    /// The enclave uses the Key Separation and Sharing feature.
+    ///
+    pub fn kss(&self) -> bool {
+        self.flags & SGX_FLAGS_KSS != 0
+    }
+}
+
+///
+/// A view of the 384 bytes of a report body, as found in a report or a
+/// quote.
+///
+#[derive(Copy, Clone)]
+pub struct SgxReportBody<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> SgxReportBody<'a> {
+    ///
+    /// Views `bytes` as a report body. Fails with
+    /// `SGX_ERROR_INVALID_PARAMETER` unless `bytes` is exactly
+    /// `SGX_REPORT_BODY_SIZE` bytes long.
+    ///
+    pub fn parse(bytes: &'a [u8]) -> SgxResult<SgxReportBody<'a>> {
+        if bytes.len() != SGX_REPORT_BODY_SIZE {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+        Ok(SgxReportBody { bytes })
+    }
+
+    ///
+    /// Views a report body structure, such as the body of a report returned
+    /// by `rsgx_create_report`.
+    ///
+    pub fn from_raw(body: &'a sgx_report_body_t) -> SgxReportBody<'a> {
+        let bytes = unsafe { slice::from_raw_parts(body as *const _ as *const u8, SGX_REPORT_BODY_SIZE) };
+        SgxReportBody { bytes }
+    }
+
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    ///
+    /// Copies the report body into its C structure.
+    ///
+    pub fn to_raw(&self) -> sgx_report_body_t {
+        unsafe { ptr::read_unaligned(self.bytes.as_ptr() as *const sgx_report_body_t) }
+    }
+
+    pub fn cpu_svn(&self) -> &'a sgx_cpu_svn_t {
+        unsafe { cast(self.bytes, 0) }
+    }
+
+    pub fn misc_select(&self) -> sgx_misc_select_t {
+        read_u32(self.bytes, 16)
+    }
+
+    pub fn isv_ext_prod_id(&self) -> &'a sgx_isvext_prod_id_t {
+        unsafe { cast(self.bytes, 32) }
+    }
+
+    pub fn attributes(&self) -> sgx_attributes_t {
+        sgx_attributes_t {
+            flags: read_u64(self.bytes, 48),
+            xfrm: read_u64(self.bytes, 56),
+        }
+    }
+
+    pub fn mr_enclave(&self) -> &'a sgx_measurement_t {
+        unsafe { cast(self.bytes, 64) }
+    }
+
+    pub fn mr_signer(&self) -> &'a sgx_measurement_t {
+        unsafe { cast(self.bytes, 128) }
+    }
+
+    pub fn config_id(&self) -> &'a sgx_config_id_t {
+        unsafe { cast(self.bytes, 192) }
+    }
+
+    pub fn isv_prod_id(&self) -> sgx_prod_id_t {
+        read_u16(self.bytes, 256)
+    }
+
+    pub fn isv_svn(&self) -> sgx_isv_svn_t {
+        read_u16(self.bytes, 258)
+    }
+
+    pub fn config_svn(&self) -> sgx_config_svn_t {
+        read_u16(self.bytes, 260)
+    }
+
+    pub fn isv_family_id(&self) -> &'a sgx_isvfamily_id_t {
+        unsafe { cast(self.bytes, 304) }
+    }
+
+    pub fn report_data(&self) -> &'a sgx_report_data_t {
+        unsafe { cast(self.bytes, 320) }
+    }
+}
+
+impl<'a> From<&'a sgx_report_body_t> for SgxReportBody<'a> {
+    fn from(body: &'a sgx_report_body_t) -> SgxReportBody<'a> {
+        SgxReportBody::from_raw(body)
+    }
+}
+
+///
+/// A view of an EPID quote, as returned by `sgx_get_quote`, or of the quote
+/// body of an IAS attestation verification report (`isvEnclaveQuoteBody`),
+/// which stops before the signature length.
+///
+#[derive(Copy, Clone)]
+pub struct SgxQuote<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> SgxQuote<'a> {
+    ///
+    /// Views `bytes` as an EPID quote. Fails with
+    /// `SGX_ERROR_INVALID_PARAMETER` if `bytes` is shorter than the quote
+    /// body, or if the signature length does not match the remaining bytes.
+    ///
+    pub fn parse(bytes: &'a [u8]) -> SgxResult<SgxQuote<'a>> {
+        let valid = match bytes.len() {
+            SGX_QUOTE_BODY_SIZE => true,
+            len if len >= SGX_QUOTE_BODY_SIZE + 4 => {
+                read_u32(bytes, SGX_QUOTE_BODY_SIZE) as usize == len - SGX_QUOTE_BODY_SIZE - 4
+            }
+            _ => false,
+        };
+        if !valid {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+        Ok(SgxQuote { bytes })
+    }
+
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    pub fn version(&self) -> uint16_t {
+        read_u16(self.bytes, 0)
+    }
+
+    ///
+    /// The signature type, `sgx_quote_sign_type_t::SGX_LINKABLE_SIGNATURE` or
+    /// `SGX_UNLINKABLE_SIGNATURE`.
+    ///
+    pub fn sign_type(&self) -> uint16_t {
+        read_u16(self.bytes, 2)
+    }
+
+    pub fn epid_group_id(&self) -> &'a sgx_epid_group_id_t {
+        unsafe { cast(self.bytes, 4) }
+    }
+
+    pub fn qe_svn(&self) -> sgx_isv_svn_t {
+        read_u16(self.bytes, 8)
+    }
+
+    pub fn pce_svn(&self) -> sgx_isv_svn_t {
+        read_u16(self.bytes, 10)
+    }
+
+    pub fn xeid(&self) -> uint32_t {
+        read_u32(self.bytes, 12)
+    }
+
+    pub fn basename(&self) -> &'a sgx_basename_t {
+        unsafe { cast(self.bytes, 16) }
+    }
+
+    pub fn report_body(&self) -> SgxReportBody<'a> {
+        SgxReportBody {
+            bytes: &self.bytes[48..SGX_QUOTE_BODY_SIZE],
+        }
+    }
+
+    ///
+    /// The EPID signature, empty for a quote body.
+    ///
+    pub fn signature(&self) -> &'a [u8] {
+        self.bytes.get(SGX_QUOTE_BODY_SIZE + 4..).unwrap_or(&[])
+    }
+}
+
+///
+/// A view of a version 3 quote, as returned by `sgx_qe_get_quote`.
+///
+#[derive(Copy, Clone)]
+pub struct SgxQuote3<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> SgxQuote3<'a> {
+    ///
+    /// Views `bytes` as a version 3 quote. Fails with
+    /// `SGX_ERROR_INVALID_PARAMETER` if `bytes` is shorter than the header,
+    /// the report body and the signature data its length announces. Bytes
+    /// after the signature data are ignored.
+    ///
+    pub fn parse(bytes: &'a [u8]) -> SgxResult<SgxQuote3<'a>> {
+        let body_end = SGX_QUOTE3_HEADER_SIZE + SGX_REPORT_BODY_SIZE;
+        if bytes.len() < body_end + 4 {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+        let len = body_end + 4 + read_u32(bytes, body_end) as usize;
+        match bytes.get(..len) {
+            Some(bytes) => Ok(SgxQuote3 { bytes }),
+            None => Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER),
+        }
+    }
+
+    ///
+    /// The bytes of the quote, up to the end of the signature data.
+    ///
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    pub fn version(&self) -> uint16_t {
+        read_u16(self.bytes, 0)
+    }
+
+    ///
+    /// The attestation key type, `sgx_ql_attestation_algorithm_id_t` as an
+    /// integer.
+    ///
+    pub fn att_key_type(&self) -> uint16_t {
+        read_u16(self.bytes, 2)
+    }
+
+    pub fn qe_svn(&self) -> sgx_isv_svn_t {
+        read_u16(self.bytes, 8)
+    }
+
+    pub fn pce_svn(&self) -> sgx_isv_svn_t {
+        read_u16(self.bytes, 10)
+    }
+
+    pub fn qe_vendor_id(&self) -> &'a [u8; 16] {
+        unsafe { cast(self.bytes, 12) }
+    }
+
+    pub fn user_data(&self) -> &'a [u8; 20] {
+        unsafe { cast(self.bytes, 28) }
+    }
+
+    pub fn report_body(&self) -> SgxReportBody<'a> {
+        SgxReportBody {
+            bytes: &self.bytes[SGX_QUOTE3_HEADER_SIZE..SGX_QUOTE3_HEADER_SIZE + SGX_REPORT_BODY_SIZE],
+        }
+    }
+
+    ///
+    /// The header and the report body, which the quote signature covers.
+    ///
+    pub fn signed_bytes(&self) -> &'a [u8] {
+        &self.bytes[..SGX_QUOTE3_HEADER_SIZE + SGX_REPORT_BODY_SIZE]
+    }
+
+    ///
+    /// The signature data, whose layout depends on the attestation key type.
+    ///
+    pub fn signature_data(&self) -> &'a [u8] {
+        &self.bytes[SGX_QUOTE3_HEADER_SIZE + SGX_REPORT_BODY_SIZE + 4..]
+    }
+}
+
+// The structures viewed in place only contain bytes, so that any address is
+// suitably aligned for them.
+unsafe fn cast<T>(bytes: &[u8], offset: usize) -> &T {
+    debug_assert!(mem::align_of::<T>() == 1 && offset + mem::size_of::<T>() <= bytes.len());
+    &*(bytes.as_ptr().add(offset) as *const T)
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+    let mut buf = [0_u8; 2];
+    buf.copy_from_slice(&bytes[offset..offset + 2]);
+    u16::from_le_bytes(buf)
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    let mut buf = [0_u8; 4];
+    buf.copy_from_slice(&bytes[offset..offset + 4]);
+    u32::from_le_bytes(buf)
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> u64 {
+    let mut buf = [0_u8; 8];
+    buf.copy_from_slice(&bytes[offset..offset + 8]);
+    u64::from_le_bytes(buf)
+}
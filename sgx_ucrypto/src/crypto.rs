@@ -18,9 +18,10 @@
 //!
 //! Cryptographic Functions
 //!
+use sgx_crypto_primitive::primitive::rsa::{RsaPrivateKey, RsaPublicKey};
 use sgx_types::marker::ContiguousMemory;
 use sgx_types::*;
-use std::cell::{Cell, RefCell};
+use std::cell::{Cell, Ref, RefCell};
 use std::mem;
 use std::ops::{DerefMut, Drop};
 use std::ptr;
@@ -2090,10 +2091,10 @@ impl SgxEccHandle {
     ///
     /// # Return value
     ///
-    /// **sgx_ec256_private_t**
+    /// **SgxEc256PrivateSecret**
     ///
     /// The private key which is a number that lies in the range of [1, n-1] where n is the order
-    /// of the elliptic curve base point.
+    /// of the elliptic curve base point. It is overwritten with zeros when it is dropped.
     ///
     /// **sgx_ec256_public_t**
     ///
@@ -2119,14 +2120,14 @@ impl SgxEccHandle {
     ///
     /// The key creation process failed due to an internal cryptography library failure.
     ///
-    pub fn create_key_pair(&self) -> SgxResult<(sgx_ec256_private_t, sgx_ec256_public_t)> {
+    pub fn create_key_pair(&self) -> SgxResult<(SgxEc256PrivateSecret, sgx_ec256_public_t)> {
         if !self.initflag.get() {
             return Err(sgx_status_t::SGX_ERROR_INVALID_STATE);
         }
 
-        let mut private = sgx_ec256_private_t::default();
+        let mut private = SgxEc256PrivateSecret::zeroed();
         let mut public = sgx_ec256_public_t::default();
-        let ret = rsgx_ecc256_create_key_pair(&mut private, &mut public, *self.handle.borrow());
+        let ret = rsgx_ecc256_create_key_pair(private.expose_mut(), &mut public, *self.handle.borrow());
 
         match ret {
             sgx_status_t::SGX_SUCCESS => Ok((private, public)),
@@ -2271,6 +2272,7 @@ impl SgxEccHandle {
     /// # Return value
     ///
     /// The secret key generated by this function which is a common point on the elliptic curve.
+    /// It is overwritten with zeros when it is dropped.
     ///
     /// # Errors
     ///
@@ -2294,16 +2296,16 @@ impl SgxEccHandle {
         &self,
         private_b: &sgx_ec256_private_t,
         public_ga: &sgx_ec256_public_t,
-    ) -> SgxResult<sgx_ec256_dh_shared_t> {
+    ) -> SgxResult<SgxEc256SharedSecret> {
         if !self.initflag.get() {
             return Err(sgx_status_t::SGX_ERROR_INVALID_STATE);
         }
 
-        let mut shared_key = sgx_ec256_dh_shared_t::default();
+        let mut shared_key = SgxEc256SharedSecret::zeroed();
         let ret = rsgx_ecc256_compute_shared_dhkey(
             private_b,
             public_ga,
-            &mut shared_key,
+            shared_key.expose_mut(),
             *self.handle.borrow(),
         );
         match ret {
@@ -3104,6 +3106,7 @@ pub struct SgxRsaPrivKey {
     mod_size: Cell<i32>,
     exp_size: Cell<i32>,
     createflag: Cell<bool>,
+    material: RefCell<Option<RsaPrivateKey>>,
 }
 
 impl SgxRsaPrivKey {
@@ -3113,9 +3116,20 @@ impl SgxRsaPrivKey {
             mod_size: Cell::new(0),
             exp_size: Cell::new(0),
             createflag: Cell::new(false),
+            material: RefCell::new(None),
         }
     }
 
+    ///
+    /// The key components kept for the operations implemented in Rust.
+    ///
+    pub(crate) fn material(&self) -> SgxResult<Ref<'_, Option<RsaPrivateKey>>> {
+        if !self.createflag.get() {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_STATE);
+        }
+        Ok(self.material.borrow())
+    }
+
     #[inline]
     pub fn create(
         &self,
@@ -3159,6 +3173,7 @@ impl SgxRsaPrivKey {
         );
         match ret {
             sgx_status_t::SGX_SUCCESS => {
+                *self.material.borrow_mut() = RsaPrivateKey::from_crt(e, p, q, dmp1, dmq1, iqmp);
                 self.mod_size.set(mod_size);
                 self.exp_size.set(exp_size);
                 self.createflag.set(true);
@@ -3192,6 +3207,7 @@ impl SgxRsaPrivKey {
         );
         match ret {
             sgx_status_t::SGX_SUCCESS => {
+                *self.material.borrow_mut() = RsaPrivateKey::from_exponent(n, e, d);
                 self.mod_size.set(mod_size);
                 self.exp_size.set(exp_size);
                 self.createflag.set(true);
@@ -3241,6 +3257,7 @@ impl SgxRsaPrivKey {
             sgx_status_t::SGX_SUCCESS => {
                 self.createflag.set(false);
                 *self.key.borrow_mut() = ptr::null_mut();
+                *self.material.borrow_mut() = None;
                 Ok(())
             }
             _ => Err(ret),
@@ -3265,6 +3282,7 @@ pub struct SgxRsaPubKey {
     mod_size: Cell<i32>,
     exp_size: Cell<i32>,
     createflag: Cell<bool>,
+    material: RefCell<Option<RsaPublicKey>>,
 }
 
 impl SgxRsaPubKey {
@@ -3274,7 +3292,18 @@ impl SgxRsaPubKey {
             mod_size: Cell::new(0),
             exp_size: Cell::new(0),
             createflag: Cell::new(false),
+            material: RefCell::new(None),
+        }
+    }
+
+    ///
+    /// The key components kept for the operations implemented in Rust.
+    ///
+    pub(crate) fn material(&self) -> SgxResult<Ref<'_, Option<RsaPublicKey>>> {
+        if !self.createflag.get() {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_STATE);
         }
+        Ok(self.material.borrow())
     }
 
     pub fn create(&self, mod_size: i32, exp_size: i32, n: &[u8], e: &[u8]) -> SgxError {
@@ -3286,6 +3315,7 @@ impl SgxRsaPubKey {
             rsgx_create_rsa_pub1_key(mod_size, exp_size, n, e, self.key.borrow_mut().deref_mut());
         match ret {
             sgx_status_t::SGX_SUCCESS => {
+                *self.material.borrow_mut() = RsaPublicKey::new(n, e);
                 self.mod_size.set(mod_size);
                 self.exp_size.set(exp_size);
                 self.createflag.set(true);
@@ -3335,6 +3365,7 @@ impl SgxRsaPubKey {
             sgx_status_t::SGX_SUCCESS => {
                 self.createflag.set(false);
                 *self.key.borrow_mut() = ptr::null_mut();
+                *self.material.borrow_mut() = None;
                 Ok(())
             }
             _ => Err(ret),
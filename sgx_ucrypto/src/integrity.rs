@@ -0,0 +1,213 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+// Verifier for the integrity-only files written by sgx_tstd::sgxfs, in the
+// format of sgx_types::integrity. Untrusted code only holds the public key of
+// the file, so it checks the signature of the root and never the AES-CMAC
+// root tag.
+
+use crate::crypto::SgxEccHandle;
+use sgx_types::ct_eq_bytes as ct_eq;
+use sgx_types::integrity::{self, TagHeader, BLOCK_SIZE, TAG_SUFFIX};
+use sgx_types::*;
+use std::cmp;
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::FileExt;
+use std::path::Path;
+
+///
+/// An integrity-only file written by an enclave, opened for verified reads
+/// outside of it.
+///
+/// The content of such a file is plain text and its Merkle tree of SHA-256
+/// hashes is stored next to it in `<path>.mht`. `open` checks the signature
+/// of the root of the tree with the public key of the file, and every read
+/// checks the blocks it touches, so that any offset can be read without
+/// going through the whole file.
+///
+pub struct SgxIntegrityFile {
+    file: File,
+    len: u64,
+    leaves: Vec<sgx_sha256_hash_t>,
+}
+
+impl SgxIntegrityFile {
+    ///
+    /// Opens the integrity-only file at `path` and checks its tags.
+    ///
+    /// # Parameters
+    ///
+    /// **path**
+    ///
+    /// The path of the content of the file. The tags are read from `<path>.mht`.
+    /// The file must keep the name it was written under, the directory does
+    /// not matter.
+    ///
+    /// **public**
+    ///
+    /// The public key of the file, as returned by
+    /// `sgx_tstd::sgxfs::integrity_public_key` in the enclave.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_PARAMETER**
+    ///
+    /// The path does not end in a file name.
+    ///
+    /// **SGX_ERROR_FILE_NOT_SGX_FILE**
+    ///
+    /// The tag file is not in the format of an integrity-only file.
+    ///
+    /// **SGX_ERROR_MAC_MISMATCH**
+    ///
+    /// The signature, the tags, the file name or the length of the content
+    /// do not match the public key.
+    ///
+    /// **SGX_ERROR_UNEXPECTED**
+    ///
+    /// The file or its tags could not be read, or an internal cryptography
+    /// library failure occurred.
+    ///
+    pub fn open<P: AsRef<Path>>(path: P, public: &sgx_ec256_public_t) -> SgxResult<SgxIntegrityFile> {
+        let path = path.as_ref();
+        let name = path.file_name().ok_or(sgx_status_t::SGX_ERROR_INVALID_PARAMETER)?;
+        let file = File::open(path).map_err(io_error)?;
+        let data_len = file.metadata().map_err(io_error)?.len();
+
+        let mut tags = OsString::from(path.as_os_str());
+        tags.push(TAG_SUFFIX);
+        let contents = fs::read(&tags).map_err(io_error)?;
+        let (header, leaves) = TagHeader::parse(&contents)?;
+        let leaves: Vec<sgx_sha256_hash_t> = integrity::parse_leaves(leaves).collect();
+        let root = header.root(name.as_bytes(), &leaves)?;
+
+        let ecc = SgxEccHandle::new();
+        ecc.open()?;
+        if !ecc.ecdsa_verify_slice(&root, public, &header.signature)? || header.len != data_len {
+            return Err(sgx_status_t::SGX_ERROR_MAC_MISMATCH);
+        }
+
+        Ok(SgxIntegrityFile { file, len: header.len, leaves })
+    }
+
+    ///
+    /// Returns the length of the content.
+    ///
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    ///
+    /// Returns true if the content is empty.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    ///
+    /// Reads the content at `offset` into `buf` and checks the blocks it
+    /// comes from.
+    ///
+    /// Returns the number of bytes read, which is less than the length of
+    /// `buf` only at the end of the content.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_MAC_MISMATCH**
+    ///
+    /// A block does not match its hash.
+    ///
+    /// **SGX_ERROR_UNEXPECTED**
+    ///
+    /// The file could not be read, or an internal cryptography library
+    /// failure occurred.
+    ///
+    pub fn read_at(&self, offset: u64, buf: &mut [u8]) -> SgxResult<usize> {
+        let mut read = 0;
+        let mut block = Vec::with_capacity(BLOCK_SIZE as usize);
+        while read < buf.len() {
+            let pos = offset.saturating_add(read as u64);
+            if pos >= self.len {
+                break;
+            }
+            let index = pos / BLOCK_SIZE;
+            self.read_block(index, &mut block)?;
+            let start = (pos % BLOCK_SIZE) as usize;
+            let n = cmp::min(buf.len() - read, block.len() - start);
+            buf[read..read + n].copy_from_slice(&block[start..start + n]);
+            read += n;
+        }
+        Ok(read)
+    }
+
+    ///
+    /// Checks every block of the content.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_MAC_MISMATCH**
+    ///
+    /// A block does not match its hash.
+    ///
+    /// **SGX_ERROR_UNEXPECTED**
+    ///
+    /// The file could not be read, or an internal cryptography library
+    /// failure occurred.
+    ///
+    pub fn verify(&self) -> SgxError {
+        let mut block = Vec::with_capacity(BLOCK_SIZE as usize);
+        for index in 0..self.leaves.len() as u64 {
+            self.read_block(index, &mut block)?;
+        }
+        Ok(())
+    }
+
+    fn read_block(&self, index: u64, block: &mut Vec<u8>) -> SgxError {
+        let start = index * BLOCK_SIZE;
+        let size = cmp::min(BLOCK_SIZE, self.len - start) as usize;
+        block.resize(size, 0);
+        self.file.read_exact_at(block, start).map_err(io_error)?;
+        let leaf = integrity::leaf(index, block)?;
+        if ct_eq(&leaf, &self.leaves[index as usize]) {
+            Ok(())
+        } else {
+            Err(sgx_status_t::SGX_ERROR_MAC_MISMATCH)
+        }
+    }
+}
+
+///
+/// Checks the whole integrity-only file at `path`, as written by an enclave
+/// with the key of `public`.
+///
+/// # Errors
+///
+/// The errors of `SgxIntegrityFile::open` and `SgxIntegrityFile::verify`.
+///
+pub fn rsgx_verify_integrity_file<P: AsRef<Path>>(path: P, public: &sgx_ec256_public_t) -> SgxError {
+    SgxIntegrityFile::open(path, public)?.verify()
+}
+
+fn io_error(e: io::Error) -> sgx_status_t {
+    match e.kind() {
+        io::ErrorKind::UnexpectedEof => sgx_status_t::SGX_ERROR_MAC_MISMATCH,
+        _ => sgx_status_t::SGX_ERROR_UNEXPECTED,
+    }
+}
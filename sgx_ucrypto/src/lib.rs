@@ -28,9 +28,19 @@
 extern crate libc;
 extern crate rand_core;
 extern crate rdrand;
+extern crate sgx_crypto_primitive;
 extern crate sgx_types;
 
 mod util;
 pub use util::*;
 mod crypto;
 pub use self::crypto::*;
+pub use sgx_crypto_primitive::aead::*;
+pub use sgx_crypto_primitive::curve25519::*;
+pub use sgx_crypto_primitive::ecc::*;
+pub use sgx_crypto_primitive::kdf::*;
+pub use sgx_crypto_primitive::sha512::*;
+mod ecc;
+mod integrity;
+pub use self::integrity::*;
+mod rsa;
@@ -0,0 +1,391 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//!
+//! RSA-PSS and PKCS#1 v1.5 signatures, and RSA-OAEP with a selectable hash
+//!
+//! libsgx_tcrypto.a only signs with fixed size 3072-bit keys and only
+//! encrypts with OAEP over SHA-256. SgxRsaPrivKey and SgxRsaPubKey keep a
+//! copy of the key components they are created from, and the methods below
+//! run the RSASSA-PSS, RSASSA-PKCS1-v1_5 and RSAES-OAEP schemes of RFC 8017
+//! that sgx_crypto_primitive implements over them, for 2048, 3072 and
+//! 4096-bit moduli, with SHA-256, SHA-384 or SHA-512 as the message digest
+//! and MGF1 hash.
+//!
+
+use crate::crypto::{SgxRsaPrivKey, SgxRsaPubKey};
+use sgx_crypto_primitive::rsa::{digest, hash_size, MAX_HASH_SIZE};
+use sgx_types::marker::ContiguousMemory;
+use sgx_types::*;
+use std::mem;
+use std::slice;
+
+fn msg_bytes<T>(src: &T) -> SgxResult<&[u8]>
+where
+    T: Copy + ContiguousMemory,
+{
+    let size = mem::size_of::<T>();
+    if size == 0 {
+        return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+    }
+    Ok(unsafe { slice::from_raw_parts(src as *const _ as *const u8, size) })
+}
+
+fn slice_bytes<T>(src: &[T]) -> SgxResult<&[u8]>
+where
+    T: Copy + ContiguousMemory,
+{
+    let size = mem::size_of_val(src);
+    if size == 0 {
+        return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+    }
+    Ok(unsafe { slice::from_raw_parts(src.as_ptr() as *const u8, size) })
+}
+
+impl SgxRsaPrivKey {
+    ///
+    /// sign_pss_msg computes an RSASSA-PSS signature over an input dataset.
+    ///
+    /// # Description
+    ///
+    /// The dataset is hashed with the selected algorithm, which is also used
+    /// for MGF1. The salt is as long as the digest and is read from
+    /// sgx_read_rand. The key must have a 2048, 3072 or 4096-bit modulus.
+    ///
+    /// # Parameters
+    ///
+    /// **hash**
+    ///
+    /// The message digest, SHA-256, SHA-384 or SHA-512.
+    ///
+    /// **src**
+    ///
+    /// The dataset to be signed.
+    ///
+    /// **signature**
+    ///
+    /// Receives the signature. It must be exactly as long as the modulus.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_PARAMETER**
+    ///
+    /// The dataset is empty, the signature buffer has the wrong size or the
+    /// key size is not supported.
+    ///
+    /// **SGX_ERROR_INVALID_STATE**
+    ///
+    /// The key has not been created.
+    ///
+    /// **SGX_ERROR_UNEXPECTED**
+    ///
+    /// The signature could not be computed.
+    ///
+    pub fn sign_pss_msg<T>(&self, hash: sgx_hash_algorithm_t, src: &T, signature: &mut [u8]) -> SgxError
+    where
+        T: Copy + ContiguousMemory,
+    {
+        let mut m_hash = [0_u8; MAX_HASH_SIZE];
+        digest(hash, &[msg_bytes(src)?], &mut m_hash)?;
+        self.sign_pss_digest(hash, &m_hash[..hash_size(hash)], signature)
+    }
+
+    ///
+    /// sign_pss_slice computes an RSASSA-PSS signature over an input dataset.
+    /// See sign_pss_msg.
+    ///
+    pub fn sign_pss_slice<T>(&self, hash: sgx_hash_algorithm_t, src: &[T], signature: &mut [u8]) -> SgxError
+    where
+        T: Copy + ContiguousMemory,
+    {
+        let mut m_hash = [0_u8; MAX_HASH_SIZE];
+        digest(hash, &[slice_bytes(src)?], &mut m_hash)?;
+        self.sign_pss_digest(hash, &m_hash[..hash_size(hash)], signature)
+    }
+
+    ///
+    /// sign_pss_digest computes an RSASSA-PSS signature over a message digest
+    /// that the caller has already computed with the selected algorithm, for
+    /// instance with SgxShaHandle over a stream. See sign_pss_msg.
+    ///
+    pub fn sign_pss_digest(&self, hash: sgx_hash_algorithm_t, digest: &[u8], signature: &mut [u8]) -> SgxError {
+        let material = self.material()?;
+        let key = material.as_ref().ok_or(sgx_status_t::SGX_ERROR_INVALID_PARAMETER)?;
+        key.sign_pss(hash, digest, signature)
+    }
+
+    ///
+    /// sign_pkcs1_v15_msg computes an RSASSA-PKCS1-v1_5 signature over an
+    /// input dataset, hashed with the selected algorithm. The parameters and
+    /// errors are those of sign_pss_msg.
+    ///
+    pub fn sign_pkcs1_v15_msg<T>(&self, hash: sgx_hash_algorithm_t, src: &T, signature: &mut [u8]) -> SgxError
+    where
+        T: Copy + ContiguousMemory,
+    {
+        let mut m_hash = [0_u8; MAX_HASH_SIZE];
+        digest(hash, &[msg_bytes(src)?], &mut m_hash)?;
+        self.sign_pkcs1_v15_digest(hash, &m_hash[..hash_size(hash)], signature)
+    }
+
+    ///
+    /// sign_pkcs1_v15_slice computes an RSASSA-PKCS1-v1_5 signature over an
+    /// input dataset. See sign_pkcs1_v15_msg.
+    ///
+    pub fn sign_pkcs1_v15_slice<T>(&self, hash: sgx_hash_algorithm_t, src: &[T], signature: &mut [u8]) -> SgxError
+    where
+        T: Copy + ContiguousMemory,
+    {
+        let mut m_hash = [0_u8; MAX_HASH_SIZE];
+        digest(hash, &[slice_bytes(src)?], &mut m_hash)?;
+        self.sign_pkcs1_v15_digest(hash, &m_hash[..hash_size(hash)], signature)
+    }
+
+    ///
+    /// sign_pkcs1_v15_digest computes an RSASSA-PKCS1-v1_5 signature over a
+    /// message digest computed by the caller. See sign_pkcs1_v15_msg.
+    ///
+    pub fn sign_pkcs1_v15_digest(&self, hash: sgx_hash_algorithm_t, digest: &[u8], signature: &mut [u8]) -> SgxError {
+        let material = self.material()?;
+        let key = material.as_ref().ok_or(sgx_status_t::SGX_ERROR_INVALID_PARAMETER)?;
+        key.sign_pkcs1_v15(hash, digest, signature)
+    }
+
+    ///
+    /// decrypt_oaep decrypts an RSAES-OAEP ciphertext.
+    ///
+    /// # Description
+    ///
+    /// The selected algorithm is used both to hash the label and for MGF1.
+    /// decrypt_sha256 is the same operation with SHA-256 and an empty label.
+    ///
+    /// # Parameters
+    ///
+    /// **hash**
+    ///
+    /// The OAEP hash, SHA-256, SHA-384 or SHA-512.
+    ///
+    /// **label**
+    ///
+    /// The label associated with the message, usually empty.
+    ///
+    /// **out_data**
+    ///
+    /// Receives the plaintext.
+    ///
+    /// **out_len**
+    ///
+    /// If zero, receives the largest plaintext size the key can carry and
+    /// nothing is decrypted. Otherwise it must be the length of out_data, and
+    /// receives the length of the plaintext.
+    ///
+    /// **in_data**
+    ///
+    /// The ciphertext, as long as the modulus.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_PARAMETER**
+    ///
+    /// The buffers have the wrong size or the key size is not supported.
+    ///
+    /// **SGX_ERROR_INVALID_STATE**
+    ///
+    /// The key has not been created.
+    ///
+    /// **SGX_ERROR_UNEXPECTED**
+    ///
+    /// The ciphertext is not valid for this key and label.
+    ///
+    pub fn decrypt_oaep(
+        &self,
+        hash: sgx_hash_algorithm_t,
+        label: &[u8],
+        out_data: &mut [u8],
+        out_len: &mut usize,
+        in_data: &[u8],
+    ) -> SgxError {
+        let material = self.material()?;
+        let key = material.as_ref().ok_or(sgx_status_t::SGX_ERROR_INVALID_PARAMETER)?;
+        key.decrypt_oaep(hash, label, out_data, out_len, in_data)
+    }
+}
+
+impl SgxRsaPubKey {
+    ///
+    /// verify_pss_msg verifies an RSASSA-PSS signature over an input dataset.
+    ///
+    /// # Description
+    ///
+    /// The dataset is hashed with the selected algorithm, which is also
+    /// expected for MGF1. Any salt length is accepted. The key must have a
+    /// 2048, 3072 or 4096-bit modulus.
+    ///
+    /// # Parameters
+    ///
+    /// **hash**
+    ///
+    /// The message digest, SHA-256, SHA-384 or SHA-512.
+    ///
+    /// **src**
+    ///
+    /// The dataset that was signed.
+    ///
+    /// **signature**
+    ///
+    /// The signature to verify.
+    ///
+    /// # Return value
+    ///
+    /// true if the signature is valid, false otherwise.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_PARAMETER**
+    ///
+    /// The dataset is empty or the key size is not supported.
+    ///
+    /// **SGX_ERROR_INVALID_STATE**
+    ///
+    /// The key has not been created.
+    ///
+    pub fn verify_pss_msg<T>(&self, hash: sgx_hash_algorithm_t, src: &T, signature: &[u8]) -> SgxResult<bool>
+    where
+        T: Copy + ContiguousMemory,
+    {
+        let mut m_hash = [0_u8; MAX_HASH_SIZE];
+        digest(hash, &[msg_bytes(src)?], &mut m_hash)?;
+        self.verify_pss_digest(hash, &m_hash[..hash_size(hash)], signature)
+    }
+
+    ///
+    /// verify_pss_slice verifies an RSASSA-PSS signature over an input
+    /// dataset. See verify_pss_msg.
+    ///
+    pub fn verify_pss_slice<T>(&self, hash: sgx_hash_algorithm_t, src: &[T], signature: &[u8]) -> SgxResult<bool>
+    where
+        T: Copy + ContiguousMemory,
+    {
+        let mut m_hash = [0_u8; MAX_HASH_SIZE];
+        digest(hash, &[slice_bytes(src)?], &mut m_hash)?;
+        self.verify_pss_digest(hash, &m_hash[..hash_size(hash)], signature)
+    }
+
+    ///
+    /// verify_pss_digest verifies an RSASSA-PSS signature over a message
+    /// digest computed by the caller. See verify_pss_msg.
+    ///
+    pub fn verify_pss_digest(&self, hash: sgx_hash_algorithm_t, digest: &[u8], signature: &[u8]) -> SgxResult<bool> {
+        let material = self.material()?;
+        let key = material.as_ref().ok_or(sgx_status_t::SGX_ERROR_INVALID_PARAMETER)?;
+        key.verify_pss(hash, digest, signature)
+    }
+
+    ///
+    /// verify_pkcs1_v15_msg verifies an RSASSA-PKCS1-v1_5 signature over an
+    /// input dataset, hashed with the selected algorithm. The parameters,
+    /// return value and errors are those of verify_pss_msg.
+    ///
+    pub fn verify_pkcs1_v15_msg<T>(&self, hash: sgx_hash_algorithm_t, src: &T, signature: &[u8]) -> SgxResult<bool>
+    where
+        T: Copy + ContiguousMemory,
+    {
+        let mut m_hash = [0_u8; MAX_HASH_SIZE];
+        digest(hash, &[msg_bytes(src)?], &mut m_hash)?;
+        self.verify_pkcs1_v15_digest(hash, &m_hash[..hash_size(hash)], signature)
+    }
+
+    ///
+    /// verify_pkcs1_v15_slice verifies an RSASSA-PKCS1-v1_5 signature over an
+    /// input dataset. See verify_pkcs1_v15_msg.
+    ///
+    pub fn verify_pkcs1_v15_slice<T>(&self, hash: sgx_hash_algorithm_t, src: &[T], signature: &[u8]) -> SgxResult<bool>
+    where
+        T: Copy + ContiguousMemory,
+    {
+        let mut m_hash = [0_u8; MAX_HASH_SIZE];
+        digest(hash, &[slice_bytes(src)?], &mut m_hash)?;
+        self.verify_pkcs1_v15_digest(hash, &m_hash[..hash_size(hash)], signature)
+    }
+
+    ///
+    /// verify_pkcs1_v15_digest verifies an RSASSA-PKCS1-v1_5 signature over a
+    /// message digest computed by the caller. See verify_pkcs1_v15_msg.
+    ///
+    pub fn verify_pkcs1_v15_digest(&self, hash: sgx_hash_algorithm_t, digest: &[u8], signature: &[u8]) -> SgxResult<bool> {
+        let material = self.material()?;
+        let key = material.as_ref().ok_or(sgx_status_t::SGX_ERROR_INVALID_PARAMETER)?;
+        key.verify_pkcs1_v15(hash, digest, signature)
+    }
+
+    ///
+    /// encrypt_oaep encrypts a message with RSAES-OAEP.
+    ///
+    /// # Description
+    ///
+    /// The selected algorithm is used both to hash the label and for MGF1.
+    /// encrypt_sha256 is the same operation with SHA-256 and an empty label.
+    ///
+    /// # Parameters
+    ///
+    /// **hash**
+    ///
+    /// The OAEP hash, SHA-256, SHA-384 or SHA-512.
+    ///
+    /// **label**
+    ///
+    /// The label associated with the message, usually empty.
+    ///
+    /// **out_data**
+    ///
+    /// Receives the ciphertext.
+    ///
+    /// **out_len**
+    ///
+    /// If zero, receives the ciphertext size, which is the modulus size, and
+    /// nothing is encrypted. Otherwise it must be the length of out_data, and
+    /// receives the length of the ciphertext.
+    ///
+    /// **in_data**
+    ///
+    /// The message. It can be at most the modulus size minus twice the hash
+    /// size minus two bytes long.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_PARAMETER**
+    ///
+    /// The buffers have the wrong size, the message is too long or the key
+    /// size is not supported.
+    ///
+    /// **SGX_ERROR_INVALID_STATE**
+    ///
+    /// The key has not been created.
+    ///
+    pub fn encrypt_oaep(
+        &self,
+        hash: sgx_hash_algorithm_t,
+        label: &[u8],
+        out_data: &mut [u8],
+        out_len: &mut usize,
+        in_data: &[u8],
+    ) -> SgxError {
+        let material = self.material()?;
+        let key = material.as_ref().ok_or(sgx_status_t::SGX_ERROR_INVALID_PARAMETER)?;
+        key.encrypt_oaep(hash, label, out_data, out_len, in_data)
+    }
+}
@@ -30,6 +30,7 @@ pub mod mem;
 pub mod net;
 pub mod pipe;
 pub mod process;
+pub mod ra;
 pub mod signal;
 pub mod socket;
 pub mod sys;
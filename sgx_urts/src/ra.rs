@@ -0,0 +1,355 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License..
+
+//! Untrusted side of the remote attestation and key exchange.
+//!
+//! The enclave owns the key exchange context (`sgx_tkey_exchange::RaSession`);
+//! these functions produce msg1 and turn the msg2 of the service provider into
+//! msg3, calling into the enclave through the ECALL proxies generated from
+//! `sgx_tkey_exchange.edl`. The application must link `libsgx_ukey_exchange`.
+
+use libc;
+use sgx_types::*;
+use std::mem;
+use std::ptr;
+use std::slice;
+
+const MSG2_SIZE: usize = mem::size_of::<sgx_ra_msg2_t>();
+const MSG3_SIZE: usize = mem::size_of::<sgx_ra_msg3_t>();
+
+///
+/// A msg2 received from the service provider: `sgx_ra_msg2_t` followed by
+/// `sig_rl_size` bytes of signature revocation list.
+///
+pub struct RaMsg2 {
+    bytes: Vec<u8>,
+}
+
+impl RaMsg2 {
+    ///
+    /// Checks that `bytes` holds a msg2 and its whole revocation list.
+    ///
+    /// # Errors
+    ///
+    /// **SGX_ERROR_INVALID_PARAMETER**
+    ///
+    /// `bytes` is shorter than `sgx_ra_msg2_t`, or its length does not match
+    /// `sig_rl_size`.
+    ///
+    pub fn parse(bytes: &[u8]) -> SgxResult<RaMsg2> {
+        if bytes.len() < MSG2_SIZE {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+        let msg2 = RaMsg2 { bytes: bytes.to_vec() };
+        if msg2.header().sig_rl_size as usize != bytes.len() - MSG2_SIZE {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+        Ok(msg2)
+    }
+
+    ///
+    /// The fixed part of msg2.
+    ///
+    pub fn header(&self) -> sgx_ra_msg2_t {
+        unsafe { ptr::read_unaligned(self.bytes.as_ptr() as *const sgx_ra_msg2_t) }
+    }
+
+    pub fn g_b(&self) -> sgx_ec256_public_t {
+        self.header().g_b
+    }
+
+    pub fn spid(&self) -> sgx_spid_t {
+        self.header().spid
+    }
+
+    pub fn quote_type(&self) -> uint16_t {
+        self.header().quote_type
+    }
+
+    pub fn kdf_id(&self) -> uint16_t {
+        self.header().kdf_id
+    }
+
+    pub fn sig_rl(&self) -> &[u8] {
+        &self.bytes[MSG2_SIZE..]
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    fn size(&self) -> SgxResult<uint32_t> {
+        if self.bytes.len() > uint32_t::max_value() as usize {
+            return Err(sgx_status_t::SGX_ERROR_INVALID_PARAMETER);
+        }
+        Ok(self.bytes.len() as uint32_t)
+    }
+}
+
+///
+/// A msg3 generated by the enclave, to send to the service provider:
+/// `sgx_ra_msg3_t` followed by the quote. The buffer allocated by
+/// `sgx_ra_proc_msg2` is freed when the message is dropped.
+///
+pub struct RaMsg3 {
+    msg3: *mut sgx_ra_msg3_t,
+    size: usize,
+}
+
+impl RaMsg3 {
+    pub fn as_bytes(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.msg3 as *const u8, self.size) }
+    }
+
+    ///
+    /// The fixed part of msg3.
+    ///
+    pub fn header(&self) -> sgx_ra_msg3_t {
+        unsafe { ptr::read_unaligned(self.msg3) }
+    }
+
+    pub fn mac(&self) -> sgx_mac_t {
+        self.header().mac
+    }
+
+    pub fn g_a(&self) -> sgx_ec256_public_t {
+        self.header().g_a
+    }
+
+    ///
+    /// The quote of the enclave, which binds the key exchange into its report
+    /// data.
+    ///
+    pub fn quote(&self) -> &[u8] {
+        &self.as_bytes()[MSG3_SIZE..]
+    }
+}
+
+impl Drop for RaMsg3 {
+    fn drop(&mut self) {
+        unsafe { libc::free(self.msg3 as *mut libc::c_void) }
+    }
+}
+
+///
+/// Generates msg1 for the session `context` of the enclave `eid`, with the
+/// ECALL proxy of `sgx_ra_get_ga`.
+///
+pub fn rsgx_ra_get_msg1(
+    context: sgx_ra_context_t,
+    eid: sgx_enclave_id_t,
+    get_ga: sgx_ecall_get_ga_trusted_t,
+) -> SgxResult<sgx_ra_msg1_t> {
+    let mut msg1 = sgx_ra_msg1_t::default();
+    let ret = unsafe { sgx_ra_get_msg1(context, eid, get_ga, &mut msg1 as *mut sgx_ra_msg1_t) };
+    match ret {
+        sgx_status_t::SGX_SUCCESS => Ok(msg1),
+        _ => Err(ret),
+    }
+}
+
+///
+/// Generates msg1 with the attestation key `att_key_id`.
+///
+pub fn rsgx_ra_get_msg1_ex(
+    att_key_id: &sgx_att_key_id_t,
+    context: sgx_ra_context_t,
+    eid: sgx_enclave_id_t,
+    get_ga: sgx_ecall_get_ga_trusted_t,
+) -> SgxResult<sgx_ra_msg1_t> {
+    let mut msg1 = sgx_ra_msg1_t::default();
+    let ret = unsafe {
+        sgx_ra_get_msg1_ex(
+            att_key_id as *const sgx_att_key_id_t,
+            context,
+            eid,
+            get_ga,
+            &mut msg1 as *mut sgx_ra_msg1_t,
+        )
+    };
+    match ret {
+        sgx_status_t::SGX_SUCCESS => Ok(msg1),
+        _ => Err(ret),
+    }
+}
+
+///
+/// Processes `msg2` in the enclave `eid` and returns msg3, with the ECALL
+/// proxies of `sgx_ra_proc_msg2_trusted` and `sgx_ra_get_msg3_trusted`.
+///
+/// # Errors
+///
+/// **SGX_ERROR_INVALID_SIGNATURE**
+///
+/// msg2 is not signed by the service provider key of the session.
+///
+/// **SGX_ERROR_MAC_MISMATCH**
+///
+/// The MAC of msg2 is invalid.
+///
+/// **SGX_ERROR_INVALID_STATE**
+///
+/// msg1 has not been generated for the session.
+///
+pub fn rsgx_ra_proc_msg2(
+    context: sgx_ra_context_t,
+    eid: sgx_enclave_id_t,
+    proc_msg2: sgx_ecall_proc_msg2_trusted_t,
+    get_msg3: sgx_ecall_get_msg3_trusted_t,
+    msg2: &RaMsg2,
+) -> SgxResult<RaMsg3> {
+    let mut msg3: *mut sgx_ra_msg3_t = ptr::null_mut();
+    let mut size: uint32_t = 0;
+    let ret = unsafe {
+        sgx_ra_proc_msg2(
+            context,
+            eid,
+            proc_msg2,
+            get_msg3,
+            msg2.as_bytes().as_ptr() as *const sgx_ra_msg2_t,
+            msg2.size()?,
+            &mut msg3 as *mut *mut sgx_ra_msg3_t,
+            &mut size as *mut uint32_t,
+        )
+    };
+    msg3_result(ret, msg3, size)
+}
+
+///
+/// Processes `msg2` and returns msg3, with the attestation key `att_key_id`.
+///
+pub fn rsgx_ra_proc_msg2_ex(
+    att_key_id: &sgx_att_key_id_t,
+    context: sgx_ra_context_t,
+    eid: sgx_enclave_id_t,
+    proc_msg2: sgx_ecall_proc_msg2_trusted_t,
+    get_msg3: sgx_ecall_get_msg3_trusted_t,
+    msg2: &RaMsg2,
+) -> SgxResult<RaMsg3> {
+    let mut msg3: *mut sgx_ra_msg3_t = ptr::null_mut();
+    let mut size: uint32_t = 0;
+    let ret = unsafe {
+        sgx_ra_proc_msg2_ex(
+            att_key_id as *const sgx_att_key_id_t,
+            context,
+            eid,
+            proc_msg2,
+            get_msg3,
+            msg2.as_bytes().as_ptr() as *const sgx_ra_msg2_t,
+            msg2.size()?,
+            &mut msg3 as *mut *mut sgx_ra_msg3_t,
+            &mut size as *mut uint32_t,
+        )
+    };
+    msg3_result(ret, msg3, size)
+}
+
+fn msg3_result(ret: sgx_status_t, msg3: *mut sgx_ra_msg3_t, size: uint32_t) -> SgxResult<RaMsg3> {
+    let msg3 = RaMsg3 {
+        msg3,
+        size: size as usize,
+    };
+    match ret {
+        sgx_status_t::SGX_SUCCESS if !msg3.msg3.is_null() && msg3.size >= MSG3_SIZE => Ok(msg3),
+        sgx_status_t::SGX_SUCCESS => Err(sgx_status_t::SGX_ERROR_UNEXPECTED),
+        _ => Err(ret),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn msg2(sig_rl_size: uint32_t, sig_rl: &[u8]) -> Vec<u8> {
+        let mut header = sgx_ra_msg2_t::default();
+        header.g_b.gx = [0x11; 32];
+        header.spid.id = [0x22; 16];
+        header.quote_type = 1;
+        header.kdf_id = 1;
+        header.sig_rl_size = sig_rl_size;
+        let mut bytes = unsafe { slice::from_raw_parts(&header as *const sgx_ra_msg2_t as *const u8, MSG2_SIZE) }.to_vec();
+        bytes.extend_from_slice(sig_rl);
+        bytes
+    }
+
+    // A msg3 in a buffer allocated as sgx_ra_proc_msg2 allocates it.
+    fn alloc_msg3(g_a: u8, quote: &[u8]) -> (*mut sgx_ra_msg3_t, uint32_t) {
+        let size = MSG3_SIZE + quote.len();
+        unsafe {
+            let msg3 = libc::malloc(size) as *mut u8;
+            assert!(!msg3.is_null());
+            ptr::write_bytes(msg3, 0, MSG3_SIZE);
+            ptr::write_bytes(msg3.add(mem::size_of::<sgx_mac_t>()), g_a, mem::size_of::<sgx_ec256_public_t>());
+            ptr::copy_nonoverlapping(quote.as_ptr(), msg3.add(MSG3_SIZE), quote.len());
+            (msg3 as *mut sgx_ra_msg3_t, size as uint32_t)
+        }
+    }
+
+    #[test]
+    fn parse_msg2() {
+        let msg2 = RaMsg2::parse(&msg2(3, &[1, 2, 3])).unwrap();
+        assert_eq!(msg2.g_b().gx, [0x11; 32]);
+        assert_eq!(msg2.spid().id, [0x22; 16]);
+        assert_eq!(msg2.quote_type(), 1);
+        assert_eq!(msg2.kdf_id(), 1);
+        assert_eq!(msg2.sig_rl(), &[1, 2, 3]);
+        assert_eq!(msg2.size(), Ok(MSG2_SIZE as uint32_t + 3));
+    }
+
+    #[test]
+    fn parse_msg2_checks_the_revocation_list() {
+        let bytes = msg2(3, &[1, 2, 3]);
+        for &len in &[0, MSG2_SIZE - 1, MSG2_SIZE, MSG2_SIZE + 2] {
+            assert_eq!(
+                RaMsg2::parse(&bytes[..len]).err(),
+                Some(sgx_status_t::SGX_ERROR_INVALID_PARAMETER)
+            );
+        }
+        assert_eq!(
+            RaMsg2::parse(&msg2(2, &[1, 2, 3])).err(),
+            Some(sgx_status_t::SGX_ERROR_INVALID_PARAMETER)
+        );
+        assert!(RaMsg2::parse(&msg2(0, &[])).unwrap().sig_rl().is_empty());
+    }
+
+    #[test]
+    fn msg3_of_proc_msg2() {
+        let (msg3, size) = alloc_msg3(0x33, &[4, 5, 6, 7]);
+        let msg3 = msg3_result(sgx_status_t::SGX_SUCCESS, msg3, size).unwrap();
+        assert_eq!(msg3.g_a().gx, [0x33; 32]);
+        assert_eq!(msg3.mac(), [0; 16]);
+        assert_eq!(msg3.quote(), &[4, 5, 6, 7]);
+        assert_eq!(msg3.as_bytes().len(), MSG3_SIZE + 4);
+    }
+
+    #[test]
+    fn msg3_of_failed_proc_msg2() {
+        assert_eq!(
+            msg3_result(sgx_status_t::SGX_ERROR_MAC_MISMATCH, ptr::null_mut(), 0).err(),
+            Some(sgx_status_t::SGX_ERROR_MAC_MISMATCH)
+        );
+        assert_eq!(
+            msg3_result(sgx_status_t::SGX_SUCCESS, ptr::null_mut(), 0).err(),
+            Some(sgx_status_t::SGX_ERROR_UNEXPECTED)
+        );
+        let (msg3, size) = alloc_msg3(0x33, &[]);
+        assert_eq!(
+            msg3_result(sgx_status_t::SGX_SUCCESS, msg3, size - 1).err(),
+            Some(sgx_status_t::SGX_ERROR_UNEXPECTED)
+        );
+    }
+}